@@ -1,9 +1,9 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use poc_kokkos_rs::{
-    functor::KernelArgs,
+    blas::axpy,
     routines::{
-        parallel_for,
-        parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        bench::{bench_prep, BenchPrep},
+        parameters::{ExecutionSpace, Schedule},
     },
     view::{parameters::Layout, ViewOwned},
 };
@@ -12,6 +12,7 @@ use rand::{
     rngs::SmallRng,
     SeedableRng,
 };
+use std::hint::black_box;
 
 // Serial AXPY
 fn f1(x_init: Vec<f64>, y_init: Vec<f64>, alpha: f64) {
@@ -21,22 +22,8 @@ fn f1(x_init: Vec<f64>, y_init: Vec<f64>, alpha: f64) {
     black_box(&mut x);
     black_box(&mut y);
 
-    let execp = ExecutionPolicy {
-        space: ExecutionSpace::Serial,
-        range: RangePolicy::RangePolicy(0..length),
-        schedule: Schedule::Static,
-    };
-
     // y = alpha * x + y
-    let axpy_kernel = |arg: KernelArgs<1>| match arg {
-        KernelArgs::Index1D(i) => {
-            let val = alpha * x.get([i]) + y.get([i]);
-            y.set([i], val);
-        }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
-    };
-    parallel_for(execp, axpy_kernel).unwrap();
+    axpy(ExecutionSpace::Serial, Schedule::Static, alpha, &x, &mut y).unwrap();
     black_box(&y);
 }
 
@@ -48,27 +35,22 @@ fn f2(x_init: Vec<f64>, y_init: Vec<f64>, alpha: f64) {
     black_box(&mut x);
     black_box(&mut y);
 
-    let execp = ExecutionPolicy {
-        space: ExecutionSpace::DeviceCPU,
-        range: RangePolicy::RangePolicy(0..length),
-        schedule: Schedule::Static,
-    };
-
     // y = alpha * x + y
-    let axpy_kernel = |arg: KernelArgs<1>| match arg {
-        KernelArgs::Index1D(i) => {
-            let val = alpha * x.get([i]) + y.get([i]);
-            y.set([i], val);
-        }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
-    };
-
-    parallel_for(execp, axpy_kernel).unwrap();
+    axpy(
+        ExecutionSpace::DeviceCPU,
+        Schedule::Static,
+        alpha,
+        &x,
+        &mut y,
+    )
+    .unwrap();
     black_box(&y);
 }
 
 pub fn criterion_benchmark(c: &mut Criterion) {
+    // Warm-up the backend
+    bench_prep(&BenchPrep::default()).unwrap();
+
     // Generate/Define the input
     const DATA_SIZE: u32 = 20;
     let length = 2_usize.pow(DATA_SIZE);
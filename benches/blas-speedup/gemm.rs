@@ -1,9 +1,9 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use poc_kokkos_rs::{
-    functor::KernelArgs,
+    blas::gemm,
     routines::{
-        parallel_for,
-        parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        bench::{bench_prep, BenchPrep},
+        parameters::{ExecutionSpace, Schedule},
     },
     view::{parameters::Layout, ViewOwned},
 };
@@ -12,6 +12,7 @@ use rand::{
     rngs::SmallRng,
     SeedableRng,
 };
+use std::hint::black_box;
 
 // Serial GEMM
 fn f1(
@@ -29,28 +30,17 @@ fn f1(
     black_box(&mut bb);
     black_box(&mut cc);
 
-    let execp = ExecutionPolicy {
-        space: ExecutionSpace::Serial,
-        range: RangePolicy::RangePolicy(0..length),
-        schedule: Schedule::Static,
-    };
-
     // C = alpha * A * B + beta * C
-    let gemm_kernel = |arg: KernelArgs<1>| match arg {
-        // lines
-        KernelArgs::Index1D(i) => {
-            // cols
-            for j in 0..length {
-                // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
-                let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
-                let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
-                cc.set([i, j], val);
-            }
-        }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
-    };
-    parallel_for(execp, gemm_kernel).unwrap();
+    gemm(
+        ExecutionSpace::Serial,
+        Schedule::Static,
+        alpha,
+        &aa,
+        &bb,
+        beta,
+        &mut cc,
+    )
+    .unwrap();
     black_box(&cc);
 }
 
@@ -70,32 +60,24 @@ fn f2(
     black_box(&mut bb);
     black_box(&mut cc);
 
-    let execp = ExecutionPolicy {
-        space: ExecutionSpace::DeviceCPU,
-        range: RangePolicy::RangePolicy(0..length),
-        schedule: Schedule::Static,
-    };
-
     // C = alpha * A * B + beta * C
-    let gemm_kernel = |arg: KernelArgs<1>| match arg {
-        // lines
-        KernelArgs::Index1D(i) => {
-            // cols
-            for j in 0..length {
-                // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
-                let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
-                let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
-                cc.set([i, j], val);
-            }
-        }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
-    };
-    parallel_for(execp, gemm_kernel).unwrap();
+    gemm(
+        ExecutionSpace::DeviceCPU,
+        Schedule::Static,
+        alpha,
+        &aa,
+        &bb,
+        beta,
+        &mut cc,
+    )
+    .unwrap();
     black_box(&cc);
 }
 
 pub fn criterion_benchmark(c: &mut Criterion) {
+    // Warm-up the backend
+    bench_prep(&BenchPrep::default()).unwrap();
+
     // Generate/Define the input
     const DATA_SIZE: u32 = 10;
     let length = 2_usize.pow(DATA_SIZE);
@@ -1,9 +1,9 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use poc_kokkos_rs::{
-    functor::KernelArgs,
+    blas::gemv,
     routines::{
-        parallel_for,
-        parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        bench::{bench_prep, BenchPrep},
+        parameters::{ExecutionSpace, Schedule},
     },
     view::{parameters::Layout, ViewOwned},
 };
@@ -12,6 +12,7 @@ use rand::{
     rngs::SmallRng,
     SeedableRng,
 };
+use std::hint::black_box;
 
 // Serial GEMV
 fn f1(aa_init: Vec<f64>, x_init: Vec<f64>, y_init: Vec<f64>, alpha: f64, beta: f64) {
@@ -23,23 +24,17 @@ fn f1(aa_init: Vec<f64>, x_init: Vec<f64>, y_init: Vec<f64>, alpha: f64, beta: f
     black_box(&mut x);
     black_box(&mut y);
 
-    let execp = ExecutionPolicy {
-        space: ExecutionSpace::Serial,
-        range: RangePolicy::RangePolicy(0..length),
-        schedule: Schedule::Static,
-    };
-
     // y = alpha * A * x + beta * y
-    let gemv_kernel = |arg: KernelArgs<1>| match arg {
-        KernelArgs::Index1D(i) => {
-            let ax_i: f64 = (0..length).map(|j| aa.get([i, j]) * x.get([j])).sum();
-            let val = alpha * ax_i + beta * y.get([i]);
-            y.set([i], val);
-        }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
-    };
-    parallel_for(execp, gemv_kernel).unwrap();
+    gemv(
+        ExecutionSpace::Serial,
+        Schedule::Static,
+        alpha,
+        &aa,
+        &x,
+        beta,
+        &mut y,
+    )
+    .unwrap();
     black_box(&y);
 }
 
@@ -53,27 +48,24 @@ fn f2(aa_init: Vec<f64>, x_init: Vec<f64>, y_init: Vec<f64>, alpha: f64, beta: f
     black_box(&mut x);
     black_box(&mut y);
 
-    let execp = ExecutionPolicy {
-        space: ExecutionSpace::DeviceCPU,
-        range: RangePolicy::RangePolicy(0..length),
-        schedule: Schedule::Static,
-    };
-
     // y = alpha * A * x + beta * y
-    let gemv_kernel = |arg: KernelArgs<1>| match arg {
-        KernelArgs::Index1D(i) => {
-            let ax_i: f64 = (0..length).map(|j| aa.get([i, j]) * x.get([j])).sum();
-            let val = alpha * ax_i + beta * y.get([i]);
-            y.set([i], val);
-        }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
-    };
-    parallel_for(execp, gemv_kernel).unwrap();
+    gemv(
+        ExecutionSpace::DeviceCPU,
+        Schedule::Static,
+        alpha,
+        &aa,
+        &x,
+        beta,
+        &mut y,
+    )
+    .unwrap();
     black_box(&y);
 }
 
 pub fn criterion_benchmark(c: &mut Criterion) {
+    // Warm-up the backend
+    bench_prep(&BenchPrep::default()).unwrap();
+
     // Generate/Define the input
     const DATA_SIZE: u32 = 12;
     let length = 2_usize.pow(DATA_SIZE);
@@ -1,5 +1,6 @@
 use atomic::Atomic;
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
 
 use rand::{
     distributions::{Distribution, Uniform},
@@ -1,9 +1,8 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use poc_kokkos_rs::{
-    functor::KernelArgs,
     routines::{
-        parallel_for,
-        parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        parameters::{ExecutionSpace, Range1D, Schedule, TypedExecutionPolicy},
+        typed::parallel_for,
     },
     view::{parameters::Layout, ViewOwned},
 };
@@ -12,6 +11,7 @@ use rand::{
     rngs::SmallRng,
     SeedableRng,
 };
+use std::hint::black_box;
 
 // GEMM - worst case layout
 fn f1(
@@ -32,26 +32,23 @@ fn f1(
     black_box(&mut bb);
     black_box(&mut cc);
 
-    let execp = ExecutionPolicy {
+    let execp = TypedExecutionPolicy {
         space: ExecutionSpace::DeviceCPU,
-        range: RangePolicy::RangePolicy(0..length),
+        policy: Range1D(0..length),
         schedule: Schedule::Static,
+        chunk_size: None,
+        chunk_predicate: None,
     };
 
     // C = alpha * A * B + beta * C
-    let gemm_kernel = |arg: KernelArgs<1>| match arg {
-        // lines
-        KernelArgs::Index1D(i) => {
-            // cols
-            for j in 0..length {
-                // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
-                let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
-                let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
-                cc.set([i, j], val);
-            }
+    let gemm_kernel = |i: usize| {
+        // cols
+        for j in 0..length {
+            // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
+            let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
+            let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
+            cc.set([i, j], val);
         }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
     };
     parallel_for(execp, gemm_kernel).unwrap();
     black_box(&cc);
@@ -76,26 +73,23 @@ fn f2(
     black_box(&mut bb);
     black_box(&mut cc);
 
-    let execp = ExecutionPolicy {
+    let execp = TypedExecutionPolicy {
         space: ExecutionSpace::DeviceCPU,
-        range: RangePolicy::RangePolicy(0..length),
+        policy: Range1D(0..length),
         schedule: Schedule::Static,
+        chunk_size: None,
+        chunk_predicate: None,
     };
 
     // C = alpha * A * B + beta * C
-    let gemm_kernel = |arg: KernelArgs<1>| match arg {
-        // lines
-        KernelArgs::Index1D(i) => {
-            // cols
-            for j in 0..length {
-                // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
-                let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
-                let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
-                cc.set([i, j], val);
-            }
+    let gemm_kernel = |i: usize| {
+        // cols
+        for j in 0..length {
+            // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
+            let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
+            let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
+            cc.set([i, j], val);
         }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
     };
     parallel_for(execp, gemm_kernel).unwrap();
     black_box(&cc);
@@ -117,26 +111,23 @@ fn f3(
     black_box(&mut bb);
     black_box(&mut cc);
 
-    let execp = ExecutionPolicy {
+    let execp = TypedExecutionPolicy {
         space: ExecutionSpace::DeviceCPU,
-        range: RangePolicy::RangePolicy(0..length),
+        policy: Range1D(0..length),
         schedule: Schedule::Static,
+        chunk_size: None,
+        chunk_predicate: None,
     };
 
     // C = alpha * A * B + beta * C
-    let gemm_kernel = |arg: KernelArgs<1>| match arg {
-        // lines
-        KernelArgs::Index1D(i) => {
-            // cols
-            for j in 0..length {
-                // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
-                let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
-                let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
-                cc.set([i, j], val);
-            }
+    let gemm_kernel = |i: usize| {
+        // cols
+        for j in 0..length {
+            // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
+            let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
+            let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
+            cc.set([i, j], val);
         }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
     };
     parallel_for(execp, gemm_kernel).unwrap();
     black_box(&cc);
@@ -0,0 +1,72 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use poc_kokkos_rs::{
+    routines::{
+        iter::MDIndexIter,
+        parameters::{ExecutionSpace, MDRange, Schedule, TypedExecutionPolicy},
+        typed::parallel_for,
+    },
+    view::{parameters::Layout, ViewOwned},
+};
+use std::hint::black_box;
+
+// this bench is used to evaluate the cost of iterating over a MDRangePolicy
+
+// hardcoded nested loops
+fn f1(length: usize) {
+    let mut v_y: ViewOwned<'_, 3, f64> = ViewOwned::new(Layout::Right, [length, length, length]);
+    black_box(&mut v_y);
+    for i in 0..length {
+        for j in 0..length {
+            for k in 0..length {
+                v_y.set([i, j, k], (i + j + k) as f64);
+            }
+        }
+    }
+    black_box(&v_y);
+}
+
+// index iterator
+fn f1_b(length: usize) {
+    let mut v_y: ViewOwned<'_, 3, f64> = ViewOwned::new(Layout::Right, [length, length, length]);
+    black_box(&mut v_y);
+    MDIndexIter::new([0..length, 0..length, 0..length])
+        .for_each(|[i, j, k]| v_y.set([i, j, k], (i + j + k) as f64));
+    black_box(&v_y);
+}
+
+// serial parallel_for
+fn f1_bb(length: usize) {
+    let mut v_y: ViewOwned<'_, 3, f64> = ViewOwned::new(Layout::Right, [length, length, length]);
+    black_box(&mut v_y);
+    let execp = TypedExecutionPolicy {
+        space: ExecutionSpace::Serial,
+        policy: MDRange([0..length, 0..length, 0..length]),
+        schedule: Schedule::Static,
+        chunk_size: None,
+        chunk_predicate: None,
+    };
+    let kernel = |[i, j, k]: [usize; 3]| v_y.set([i, j, k], (i + j + k) as f64);
+    parallel_for(execp, kernel).unwrap();
+    black_box(&v_y);
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    // Generate/Define the input
+    let data_size: u32 = 7; // 128*128*128 elements
+
+    let mut group = c.benchmark_group("mdrange-populate");
+    let length = 2_usize.pow(data_size);
+    group.bench_with_input(BenchmarkId::new("nested-loops", ""), &length, |b, &n| {
+        b.iter(|| f1(n))
+    });
+    group.bench_with_input(BenchmarkId::new("index-iter", ""), &length, |b, &n| {
+        b.iter(|| f1_b(n))
+    });
+    group.bench_with_input(BenchmarkId::new("parallel-for", ""), &length, |b, &n| {
+        b.iter(|| f1_bb(n))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -1,9 +1,11 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
 use rand::prelude::*;
+use std::hint::black_box;
 
 // this bench is used to evaluate the cost of accessing views' data
 // all benched functions contain 10^3 accesses.
+// run with `--features index-u32` to compare index types of views.
 
 // 1D vector access
 fn f1(length: usize, indices: &[usize]) {
@@ -28,6 +30,18 @@ fn f1_b(length: usize, indices: &[usize]) {
     })
 }
 
+// 1D view access without bounds checks
+fn f1_c(length: usize, indices: &[usize]) {
+    let v_y: ViewOwned<'_, 1, f64> =
+        ViewOwned::new_from_data(vec![0.0; length], Layout::Right, [length]);
+    let idx = &indices[0..length];
+
+    idx.iter().for_each(|i| {
+        let tmp = unsafe { v_y.get_unchecked([*i]) };
+        black_box(tmp);
+    })
+}
+
 // 2D vector access
 fn f2(length: usize, indices: &[(usize, usize)]) {
     let y: Vec<f64> = vec![0.0; length * length];
@@ -114,9 +128,14 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     );
     group1.bench_with_input(
         BenchmarkId::new("view", ""),
-        &(length, indices1),
+        &(length, indices1.clone()),
         |b, (n, i)| b.iter(|| f1_b(*n, i)),
     );
+    group1.bench_with_input(
+        BenchmarkId::new("view-unchecked", ""),
+        &(length, indices1),
+        |b, (n, i)| b.iter(|| f1_c(*n, i)),
+    );
     group1.finish();
 
     let mut group2 = c.benchmark_group("access-overhead-2D");
@@ -1,5 +1,6 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+use std::hint::black_box;
 
 // this bench is used to evaluate the cost of creating views
 
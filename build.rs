@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, path::Path, process::Command};
 
 fn main() {
     // need to find a good default value for the compiler
@@ -18,10 +18,19 @@ fn main() {
     cxx_build::bridge("src/lib.rs")
         .compiler(compiler)
         .file("src/cpp/hello.cpp")
+        .file("src/cpp/views.cpp")
         .flag_if_supported("-std=c++20")
         .flag(ompflags) // clang
         .compile("poc-cc");
 
+    // --- build information ---
+    // rustc version & git hash are exposed to the crate through env variables
+    let rustc = env::var("RUSTC").unwrap_or("rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]);
+    let git_hash = command_output("git", &["rev-parse", "--short", "HEAD"]);
+    println!("cargo:rustc-env=KOKKOS_RS_RUSTC_VERSION={rustc_version}");
+    println!("cargo:rustc-env=KOKKOS_RS_GIT_HASH={git_hash}");
+
     // --- linking shenanigans ---
     match env::consts::OS {
         "macos" => {
@@ -40,6 +49,24 @@ fn main() {
     println!("cargo:rerun-if-changed=src/main.rs");
     // cpp files
     println!("cargo:rerun-if-changed=src/cpp/hello.cpp");
+    println!("cargo:rerun-if-changed=src/cpp/views.cpp");
     // header files
     println!("cargo:rerun-if-changed=src/include/hello.hpp");
+    println!("cargo:rerun-if-changed=src/include/views.hpp");
+    // git hash; the file does not exist if the crate isn't built from the repository
+    if Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+    }
+}
+
+/// Run a command & return its trimmed output, or "unknown" if it failed.
+fn command_output(cmd: &str, args: &[&str]) -> String {
+    Command::new(cmd)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .unwrap_or("unknown".to_string())
 }
@@ -0,0 +1,50 @@
+use poc_kokkos_rs::{
+    functor::KernelArgs,
+    routines::{
+        parallel_for_skip_chunks,
+        parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        tune::{save_best, sweep, write_csv, SweepParams},
+    },
+    view::{parameters::Layout, ViewOwned},
+};
+
+// Sweep the chunk size of a sparse 1D update; only a tenth of the range is active.
+// Results are printed as CSV; the best chunk size is saved in `best_chunk_size.txt`.
+fn main() {
+    const LENGTH: usize = 1 << 22;
+    let active = 0..LENGTH / 10;
+
+    let candidates: Vec<usize> = (4..=16).step_by(2).map(|p| 1 << p).collect();
+    let params = SweepParams {
+        warmup: 2,
+        repetitions: 10,
+    };
+
+    #[allow(unused_mut)]
+    let mut view: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [LENGTH]);
+
+    let results = sweep(&candidates, &params, |chunk_size| {
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::RangePolicy(0..LENGTH),
+            schedule: Schedule::Static,
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let predicate = |bounds: std::ops::Range<usize>| bounds.start < active.end;
+        let kernel = |arg: KernelArgs<1>| match arg {
+            KernelArgs::Index1D(i) => {
+                if active.contains(&i) {
+                    view.set([i], i as f64)
+                }
+            }
+            KernelArgs::IndexND(_) => unimplemented!(),
+            KernelArgs::Handle(_) => unimplemented!(),
+        };
+        parallel_for_skip_chunks(execp, *chunk_size, predicate, kernel)
+    })
+    .unwrap();
+
+    write_csv(std::io::stdout(), &results).unwrap();
+    save_best("best_chunk_size.txt", &results).unwrap();
+}
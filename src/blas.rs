@@ -0,0 +1,338 @@
+//! BLAS-like kernel related code
+//!
+//! This module contains reference implementations of common dense linear algebra
+//! operations on views, executed using the statements of the
+//! [typed][crate::routines::typed] module:
+//!
+//! - [`axpy`]: `y = alpha * x + y`, using 1D views.
+//! - [`dot`]: `x . y`, using 1D views.
+//! - [`gemv`]: `y = alpha * a * x + beta * y`, `a` being a 2D view.
+//! - [`gemm`]: `c = alpha * a * b + beta * c`, using 2D views.
+//!
+//! Operations are generic over their element type, see [BlasScalar]; `alpha` & `beta`
+//! coefficients are applied using [Scalar::scale], hence [`axpy`] can be used on views
+//! of newtypes declared with [impl_scalar_newtype][crate::impl_scalar_newtype]. Other
+//! operations additionally require elements to be multiplied together.
+//!
+//! All operations take the [ExecutionSpace] & [Schedule] of their dispatch. They accept
+//! views of any layout; [`gemm`] iterates over the output following its layout, and
+//! [`gemv`] processes `a` row by row, or by blocks of [GEMV_ROW_BLOCK] rows read column
+//! by column if `a` uses [Layout::Left][crate::view::parameters::Layout::Left].
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     blas::{dot, gemv},
+//!     routines::parameters::{ExecutionSpace, Schedule},
+//!     view::{parameters::Layout, ViewOwned},
+//! };
+//!
+//! let (space, schedule) = (ExecutionSpace::DeviceCPU, Schedule::Static);
+//! let a: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data(vec![1.0, 2.0, 3.0, 4.0], Layout::Right, [2, 2]);
+//! let x: ViewOwned<'_, 1, f64> = ViewOwned::new_from_data(vec![1.0, 1.0], Layout::Right, [2]);
+//! let mut y: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [2]);
+//!
+//! gemv(space, schedule.clone(), 1.0, &a, &x, 0.0, &mut y).unwrap();
+//! assert_eq!(y.get([0]), 3.0);
+//! assert_eq!(y.get([1]), 7.0);
+//! assert_eq!(dot(space, schedule, &x, &y).unwrap(), 10.0);
+//! ```
+
+use std::ops::Mul;
+
+use crate::{
+    routines::{
+        parameters::{ExecutionSpace, Iterate, Range1D, ReduceOp, Schedule, TypedExecutionPolicy},
+        typed::{parallel_for, parallel_reduce},
+        StatementError,
+    },
+    view::{
+        parameters::{ReductionIdentity, Scalar},
+        ViewBase,
+    },
+};
+
+/// Element types supported by the operations of this module. [`dot`], [`gemv`] &
+/// [`gemm`] additionally require elements to implement [Mul].
+///
+/// This trait is implemented for all types satisfying its supertraits.
+pub trait BlasScalar: Scalar + Send + Sync {}
+
+impl<T> BlasScalar for T where T: Scalar + Send + Sync {}
+
+/// Number of rows of `a` processed together by [`gemv`] when it is read column by column.
+pub const GEMV_ROW_BLOCK: usize = 64;
+
+// Statements
+
+/// Compute `y = alpha * x + y`.
+///
+/// A [StatementError::DimensionMismatch] error is returned if views have different
+/// lengths.
+pub fn axpy<T: BlasScalar>(
+    space: ExecutionSpace,
+    schedule: Schedule,
+    alpha: f64,
+    x: &ViewBase<'_, 1, T>,
+    y: &mut ViewBase<'_, 1, T>,
+) -> Result<(), StatementError> {
+    if x.dims() != y.dims() {
+        return Err(StatementError::DimensionMismatch);
+    }
+    let [len] = y.dims();
+    let execp = TypedExecutionPolicy {
+        space,
+        policy: Range1D(0..len),
+        schedule,
+        chunk_size: None,
+        chunk_predicate: None,
+    };
+    parallel_for(execp, |i| y.set([i], x.get([i]).scale(alpha) + y.get([i])))
+}
+
+/// Compute the dot product of `x` & `y`.
+///
+/// A [StatementError::DimensionMismatch] error is returned if views have different
+/// lengths.
+pub fn dot<T: BlasScalar + Mul<Output = T> + ReductionIdentity>(
+    space: ExecutionSpace,
+    schedule: Schedule,
+    x: &ViewBase<'_, 1, T>,
+    y: &ViewBase<'_, 1, T>,
+) -> Result<T, StatementError> {
+    if x.dims() != y.dims() {
+        return Err(StatementError::DimensionMismatch);
+    }
+    let [len] = y.dims();
+    let execp = TypedExecutionPolicy {
+        space,
+        policy: Range1D(0..len),
+        schedule,
+        chunk_size: None,
+        chunk_predicate: None,
+    };
+    parallel_reduce(execp, ReduceOp::Sum, |i| x.get([i]) * y.get([i]))
+}
+
+/// Compute `y = alpha * a * x + beta * y`.
+///
+/// Rows of `a` are distributed over computational ressources if its layout iterates over
+/// the last index the fastest, see [Iterate]. Otherwise, blocks of [GEMV_ROW_BLOCK] rows
+/// are distributed, each block being read column by column. A
+/// [StatementError::DimensionMismatch] error is returned if dimensions of the views are
+/// not compatible.
+pub fn gemv<T: BlasScalar + Mul<Output = T>>(
+    space: ExecutionSpace,
+    schedule: Schedule,
+    alpha: f64,
+    a: &ViewBase<'_, 2, T>,
+    x: &ViewBase<'_, 1, T>,
+    beta: f64,
+    y: &mut ViewBase<'_, 1, T>,
+) -> Result<(), StatementError> {
+    let [m, n] = a.dims();
+    if x.dims() != [n] || y.dims() != [m] {
+        return Err(StatementError::DimensionMismatch);
+    }
+    match Iterate::from(&a.layout) {
+        Iterate::Right => {
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: Range1D(0..m),
+                schedule,
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            parallel_for(execp, |i| {
+                let ax_i = (0..n).fold(T::zero(), |acc, j| acc + a.get([i, j]) * x.get([j]));
+                y.set([i], ax_i.scale(alpha) + y.get([i]).scale(beta))
+            })
+        }
+        Iterate::Left => {
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: Range1D(0..m.div_ceil(GEMV_ROW_BLOCK)),
+                schedule,
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            parallel_for(execp, |block| {
+                let rows = block * GEMV_ROW_BLOCK..((block + 1) * GEMV_ROW_BLOCK).min(m);
+                let mut ax = vec![T::zero(); rows.len()];
+                (0..n).for_each(|j| {
+                    let x_j = x.get([j]);
+                    rows.clone()
+                        .zip(ax.iter_mut())
+                        .for_each(|(i, acc)| *acc = *acc + a.get([i, j]) * x_j);
+                });
+                rows.zip(ax)
+                    .for_each(|(i, ax_i)| y.set([i], ax_i.scale(alpha) + y.get([i]).scale(beta)))
+            })
+        }
+    }
+}
+
+/// Compute `c = alpha * a * b + beta * c`.
+///
+/// Rows (resp. columns) of `c` are distributed over computational ressources if its
+/// layout iterates over the last (resp. first) index the fastest, see [Iterate]. A
+/// [StatementError::DimensionMismatch] error is returned if dimensions of the views are
+/// not compatible.
+pub fn gemm<T: BlasScalar + Mul<Output = T>>(
+    space: ExecutionSpace,
+    schedule: Schedule,
+    alpha: f64,
+    a: &ViewBase<'_, 2, T>,
+    b: &ViewBase<'_, 2, T>,
+    beta: f64,
+    c: &mut ViewBase<'_, 2, T>,
+) -> Result<(), StatementError> {
+    let ([m, k], [kb, n]) = (a.dims(), b.dims());
+    if k != kb || c.dims() != [m, n] {
+        return Err(StatementError::DimensionMismatch);
+    }
+    let ab =
+        |i: usize, j: usize| (0..k).fold(T::zero(), |acc, p| acc + a.get([i, p]) * b.get([p, j]));
+    match Iterate::from(&c.layout) {
+        Iterate::Right => {
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: Range1D(0..m),
+                schedule,
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            parallel_for(execp, |i| {
+                (0..n)
+                    .for_each(|j| c.set([i, j], ab(i, j).scale(alpha) + c.get([i, j]).scale(beta)))
+            })
+        }
+        Iterate::Left => {
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: Range1D(0..n),
+                schedule,
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            parallel_for(execp, |j| {
+                (0..m)
+                    .for_each(|i| c.set([i, j], ab(i, j).scale(alpha) + c.get([i, j]).scale(beta)))
+            })
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        routines::iter::MDIndexIter,
+        view::{parameters::Layout, subview::SliceArg, ViewOwned},
+    };
+
+    const SPACES: [ExecutionSpace; 2] = [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU];
+
+    #[test]
+    fn level1() {
+        let len = 1000;
+        let x: ViewOwned<'_, 1, f64> =
+            ViewOwned::new_from_data((0..len).map(|i| i as f64).collect(), Layout::Right, [len]);
+        for space in SPACES {
+            for schedule in [Schedule::Static, Schedule::Dynamic] {
+                let mut y: ViewOwned<'_, 1, f64> =
+                    ViewOwned::new_from_data(vec![1.0; len], Layout::Right, [len]);
+                axpy(space, schedule.clone(), 2.0, &x, &mut y).unwrap();
+                assert!((0..len).all(|i| y.get([i]) == 2.0 * i as f64 + 1.0));
+
+                let ones: ViewOwned<'_, 1, f64> =
+                    ViewOwned::new_from_data(vec![1.0; len], Layout::Right, [len]);
+                assert_eq!(dot(space, schedule, &x, &ones).unwrap(), 499500.0);
+            }
+        }
+
+        let mut short: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [3]);
+        assert!(matches!(
+            axpy(
+                ExecutionSpace::Serial,
+                Schedule::Static,
+                1.0,
+                &x,
+                &mut short
+            ),
+            Err(StatementError::DimensionMismatch)
+        ));
+        assert!(matches!(
+            dot(ExecutionSpace::Serial, Schedule::Static, &x, &short),
+            Err(StatementError::DimensionMismatch)
+        ));
+    }
+
+    #[test]
+    fn level2_3() {
+        // several blocks of rows, a partial one included
+        let (m, k, n) = (GEMV_ROW_BLOCK * 2 + 13, 7, 21);
+        let a_val = |i: usize, p: usize| (i + 2 * p) as f64 * 0.5;
+        let b_val = |p: usize, j: usize| (p * j % 5) as f64 - 1.0;
+        let dot_ab = |i: usize, j: usize| (0..k).map(|p| a_val(i, p) * b_val(p, j)).sum::<f64>();
+        for space in SPACES {
+            for layout in [Layout::Right, Layout::Left] {
+                #[allow(unused_mut)]
+                let mut a: ViewOwned<'_, 2, f64> = ViewOwned::new(layout, [m, k]);
+                #[allow(unused_mut)]
+                let mut b: ViewOwned<'_, 2, f64> = ViewOwned::new(layout, [k, n]);
+                MDIndexIter::new([0..m, 0..k]).for_each(|[i, p]| a.set([i, p], a_val(i, p)));
+                MDIndexIter::new([0..k, 0..n]).for_each(|[p, j]| b.set([p, j], b_val(p, j)));
+
+                let mut c: ViewOwned<'_, 2, f64> =
+                    ViewOwned::new_from_data(vec![1.0; m * n], layout, [m, n]);
+                gemm(space, Schedule::Static, 2.0, &a, &b, 3.0, &mut c).unwrap();
+                MDIndexIter::new([0..m, 0..n])
+                    .for_each(|[i, j]| assert_eq!(c.get([i, j]), 2.0 * dot_ab(i, j) + 3.0));
+
+                // first column of b
+                let x = b.subview::<1>([SliceArg::All, SliceArg::Index(0)]).unwrap();
+                let mut y: ViewOwned<'_, 1, f64> =
+                    ViewOwned::new_from_data(vec![1.0; m], Layout::Right, [m]);
+                gemv(space, Schedule::Dynamic, 2.0, &a, &x, -1.0, &mut y).unwrap();
+                (0..m).for_each(|i| assert_eq!(y.get([i]), 2.0 * dot_ab(i, 0) - 1.0));
+
+                assert!(matches!(
+                    gemm(space, Schedule::Static, 1.0, &b, &a, 0.0, &mut c),
+                    Err(StatementError::DimensionMismatch)
+                ));
+                let mut z: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [n]);
+                assert!(matches!(
+                    gemv(space, Schedule::Static, 1.0, &a, &x, 0.0, &mut z),
+                    Err(StatementError::DimensionMismatch)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn scalar_newtype() {
+        #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+        struct Meters(f64);
+
+        crate::impl_scalar_newtype!(Meters);
+
+        let len = 100;
+        let x: ViewOwned<'_, 1, Meters> = ViewOwned::new_from_data(
+            (0..len).map(|i| Meters(i as f64)).collect(),
+            Layout::Right,
+            [len],
+        );
+        for space in SPACES {
+            let mut y: ViewOwned<'_, 1, Meters> =
+                ViewOwned::new_from_data(vec![Meters(1.0); len], Layout::Right, [len]);
+            axpy(space, Schedule::Static, 0.5, &x, &mut y).unwrap();
+            assert!((0..len).all(|i| y.get([i]) == Meters(0.5 * i as f64 + 1.0)));
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! bitset related code
+//!
+//! This module contains [Bitset], the equivalent of Kokkos' `Bitset`: a fixed-size set
+//! of bits stored in a view of `u64` words. Bits can be set, reset & tested from parallel
+//! kernels using atomic operations; bulk operations are done from the host.
+//!
+//! Writing methods take a shared reference when a parallelization feature is enabled, a
+//! mutable one otherwise.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     containers::bitset::Bitset,
+//!     routines::{
+//!         parameters::{Range1D, TypedExecutionPolicy},
+//!         typed::parallel_for,
+//!     },
+//! };
+//!
+//! #[allow(unused_mut)]
+//! let mut bitset = Bitset::new(100);
+//!
+//! // mark multiples of 3
+//! let execp = TypedExecutionPolicy::new(Range1D(0..100));
+//! parallel_for(execp, |i| {
+//!     if i % 3 == 0 {
+//!         bitset.set(i);
+//!     }
+//! })
+//! .unwrap();
+//!
+//! assert!(bitset.test(99));
+//! assert_eq!(bitset.count(), 34);
+//! ```
+
+use std::sync::atomic::Ordering;
+
+use crate::view::{parameters::Layout, ViewError, ViewOwned};
+
+/// Number of bits of a word.
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Set of bits with a fixed size, stored in a view.
+#[derive(Debug)]
+pub struct Bitset {
+    words: ViewOwned<'static, 1, u64>,
+    size: usize,
+}
+
+impl Bitset {
+    /// Create a set of `size` bits, all reset.
+    ///
+    /// Panics if the view cannot be created, see [Bitset::try_new].
+    pub fn new(size: usize) -> Self {
+        Self::try_new(size).unwrap_or_else(|e| panic!("could not create bitset: {e:?}"))
+    }
+
+    /// Fallible constructor. Returns the same errors as
+    /// [ViewBase::try_new][crate::view::ViewBase::try_new].
+    pub fn try_new(size: usize) -> Result<Self, ViewError<'static>> {
+        Ok(Self {
+            words: ViewOwned::try_new(Layout::Right, [size.div_ceil(WORD_BITS)])?,
+            size,
+        })
+    }
+
+    /// Returns the number of bits of the set.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the word holding bit `i` & the mask selecting it.
+    ///
+    /// Panics if `i` is out of bounds.
+    fn locate(&self, i: usize) -> (usize, u64) {
+        assert!(i < self.size, "bit index {i} is out of bounds");
+        (i / WORD_BITS, 1 << (i % WORD_BITS))
+    }
+
+    /// Returns the value of bit `i`.
+    pub fn test(&self, i: usize) -> bool {
+        let (word, mask) = self.locate(i);
+        self.words.get([word]) & mask != 0
+    }
+
+    /// Returns the number of set bits. This is a host operation.
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Reset all bits. This is a host operation.
+    pub fn clear(&mut self) {
+        self.words = ViewOwned::new(Layout::Right, self.words.dims());
+    }
+
+    /// Set all bits. This is a host operation.
+    pub fn set_all(&mut self) {
+        let n_words = self.words.dims()[0];
+        let data = (0..n_words)
+            .map(|w| match self.size - w * WORD_BITS {
+                n if n >= WORD_BITS => u64::MAX,
+                n => (1 << n) - 1,
+            })
+            .collect();
+        self.words = ViewOwned::new_from_data(data, Layout::Right, [n_words]);
+    }
+}
+
+// writing methods only differ by their receiver, depending on enabled features
+macro_rules! impl_bitset_writes {
+    ($version: literal, $($recv: tt)+) => {
+        impl Bitset {
+            /// Set bit `i`, returning its previous value.
+            ///
+            #[doc = $version]
+            pub fn set($($recv)+ self, i: usize) -> bool {
+                let (word, mask) = self.locate(i);
+                self.update(word, |w| w | mask) & mask != 0
+            }
+
+            /// Reset bit `i`, returning its previous value.
+            ///
+            #[doc = $version]
+            pub fn reset($($recv)+ self, i: usize) -> bool {
+                let (word, mask) = self.locate(i);
+                self.update(word, |w| w & !mask) & mask != 0
+            }
+
+            /// Apply `op` to a word atomically, returning its previous value.
+            fn update($($recv)+ self, word: usize, op: impl Fn(u64) -> u64) -> u64 {
+                let mut current = self.words.get([word]);
+                loop {
+                    match self.words.compare_exchange(
+                        [word],
+                        current,
+                        op(current),
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    ) {
+                        Ok(prev) => return prev,
+                        Err(prev) => current = prev,
+                    }
+                }
+            }
+        }
+    };
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))] {
+        impl_bitset_writes!("**Current version**: thread-safe", &);
+    } else {
+        impl_bitset_writes!("**Current version**: no feature", &mut);
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::{
+        parameters::{ExecutionSpace, Range1D, Schedule, TypedExecutionPolicy},
+        typed::parallel_for,
+    };
+
+    #[test]
+    fn set_reset() {
+        #[allow(unused_mut)]
+        let mut bitset = Bitset::new(70);
+        assert_eq!(bitset.size(), 70);
+        assert!(!bitset.set(64));
+        assert!(bitset.set(64));
+        assert!(bitset.test(64) && !bitset.test(63));
+        assert!(bitset.reset(64));
+        assert!(!bitset.reset(64));
+        assert_eq!(bitset.count(), 0);
+
+        bitset.set_all();
+        assert_eq!(bitset.count(), 70);
+        bitset.clear();
+        assert_eq!(bitset.count(), 0);
+        assert!(std::panic::catch_unwind(|| Bitset::new(70).test(70)).is_err());
+    }
+
+    #[test]
+    fn parallel_set() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            #[allow(unused_mut)]
+            let mut bitset = Bitset::new(256);
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: Range1D(0..1024),
+                schedule: Schedule::Dynamic,
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            // many iterations update the same words
+            parallel_for(execp, |i| {
+                bitset.set(i % 256);
+            })
+            .unwrap();
+            assert_eq!(bitset.count(), 256);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! dynamic rank view related code
+//!
+//! This module contains [DynRankView], the equivalent of Kokkos' `DynRankView`: a view
+//! whose rank is chosen at runtime, up to [MAX_VIEW_DEPTH]. It is meant to ease the
+//! porting of codes whose data rank depends on their input.
+//!
+//! The view is stored as a view of depth [MAX_VIEW_DEPTH] whose trailing dimensions are
+//! of size 1. Indices are passed as slices, whose length must match the rank of the view.
+//! Views of known rank should be preferred whenever possible, since their indices are
+//! checked at compile time.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{containers::dyn_rank_view::DynRankView, view::parameters::Layout};
+//!
+//! // rank read from the input
+//! let dims = vec![2, 3, 4];
+//! #[allow(unused_mut)]
+//! let mut view: DynRankView<f64> = DynRankView::new(Layout::Left, &dims);
+//! assert_eq!(view.rank(), 3);
+//!
+//! view.set(&[1, 2, 3], 1.0);
+//! assert_eq!(view.get(&[1, 2, 3]), 1.0);
+//! ```
+
+use crate::view::{
+    parameters::{DataTraits, Layout, MAX_VIEW_DEPTH},
+    ViewError, ViewOwned,
+};
+
+/// View whose rank is chosen at runtime.
+#[derive(Debug)]
+pub struct DynRankView<T>
+where
+    T: DataTraits + 'static,
+{
+    view: ViewOwned<'static, MAX_VIEW_DEPTH, T>,
+    rank: usize,
+}
+
+impl<T> DynRankView<T>
+where
+    T: DataTraits + 'static,
+{
+    /// Create a view of dimensions `dims`, whose length is the rank of the view.
+    ///
+    /// Panics if the view cannot be created, see [DynRankView::try_new].
+    pub fn new(layout: Layout<MAX_VIEW_DEPTH>, dims: &[usize]) -> Self {
+        Self::try_new(layout, dims).unwrap_or_else(|e| panic!("could not create view: {e:?}"))
+    }
+
+    /// Fallible constructor. Strides of a [Layout::Stride] layout beyond the rank of the
+    /// view are ignored.
+    ///
+    /// An error is returned if the rank is 0 or exceeds [MAX_VIEW_DEPTH], as well as the
+    /// errors of [ViewBase::try_new][crate::view::ViewBase::try_new].
+    pub fn try_new(
+        layout: Layout<MAX_VIEW_DEPTH>,
+        dims: &[usize],
+    ) -> Result<Self, ViewError<'static>> {
+        if dims.is_empty() || dims.len() > MAX_VIEW_DEPTH {
+            return Err(ViewError::ValueError(
+                "View rank must be between 1 & MAX_VIEW_DEPTH",
+            ));
+        }
+        let mut dim = [1; MAX_VIEW_DEPTH];
+        dim[..dims.len()].copy_from_slice(dims);
+        Ok(Self {
+            view: ViewOwned::try_new(layout, dim)?,
+            rank: dims.len(),
+        })
+    }
+
+    /// Returns the rank of the view.
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    /// Returns the dimensions of the view.
+    pub fn dims(&self) -> Vec<usize> {
+        self.view.dims()[..self.rank].to_vec()
+    }
+
+    /// Returns the extent of dimension `dim`, or 1 if `dim` exceeds the rank, following
+    /// Kokkos.
+    pub fn extent(&self, dim: usize) -> usize {
+        if dim < self.rank {
+            self.view.dims()[dim]
+        } else {
+            1
+        }
+    }
+
+    /// Returns the underlying view, of depth [MAX_VIEW_DEPTH].
+    pub fn view(&self) -> &ViewOwned<'static, MAX_VIEW_DEPTH, T> {
+        &self.view
+    }
+
+    /// Returns the index of the underlying view matching `index`.
+    ///
+    /// Panics if the length of `index` does not match the rank of the view.
+    fn full_index(&self, index: &[usize]) -> [usize; MAX_VIEW_DEPTH] {
+        assert_eq!(
+            index.len(),
+            self.rank,
+            "index length does not match the rank of the view"
+        );
+        let mut full = [0; MAX_VIEW_DEPTH];
+        full[..self.rank].copy_from_slice(index);
+        full
+    }
+
+    /// Read the element at `index`.
+    pub fn get(&self, index: &[usize]) -> T {
+        self.view.get(self.full_index(index))
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))] {
+        impl<T> DynRankView<T>
+        where
+            T: DataTraits + 'static,
+        {
+            /// Write `val` at `index`.
+            ///
+            /// **Current version**: thread-safe
+            pub fn set(&self, index: &[usize], val: T) {
+                self.view.set(self.full_index(index), val);
+            }
+        }
+    } else {
+        impl<T> DynRankView<T>
+        where
+            T: DataTraits + 'static,
+        {
+            /// Write `val` at `index`.
+            ///
+            /// **Current version**: no feature
+            pub fn set(&mut self, index: &[usize], val: T) {
+                let index = self.full_index(index);
+                self.view.set(index, val);
+            }
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dyn_rank() {
+        for layout in [Layout::Right, Layout::Left] {
+            #[allow(unused_mut)]
+            let mut view: DynRankView<i32> = DynRankView::new(layout, &[2, 3]);
+            assert_eq!(view.rank(), 2);
+            assert_eq!(view.dims(), vec![2, 3]);
+            assert_eq!((view.extent(1), view.extent(5)), (3, 1));
+
+            view.set(&[1, 2], 7);
+            assert_eq!(view.get(&[1, 2]), 7);
+            assert_eq!(view.view().iter().filter(|v| *v == 7).count(), 1);
+        }
+        assert!(DynRankView::<i32>::try_new(Layout::Right, &[]).is_err());
+        assert!(DynRankView::<i32>::try_new(Layout::Right, &[1; MAX_VIEW_DEPTH + 1]).is_err());
+        let wrong_rank = || DynRankView::<i32>::new(Layout::Right, &[2, 3]).get(&[1]);
+        assert!(std::panic::catch_unwind(wrong_rank).is_err());
+    }
+}
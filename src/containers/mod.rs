@@ -0,0 +1,12 @@
+//! container related code
+//!
+//! This module contains data structures built on top of views, following the Kokkos
+//! containers subpackage:
+//!
+//! - [`unordered_map`]: concurrent hash map, usable inside parallel kernels.
+//! - [`bitset`]: set of bits that can be updated atomically inside parallel kernels.
+//! - [`dyn_rank_view`]: view whose rank is chosen at runtime.
+
+pub mod bitset;
+pub mod dyn_rank_view;
+pub mod unordered_map;
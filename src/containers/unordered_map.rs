@@ -0,0 +1,357 @@
+//! unordered map related code
+//!
+//! This module contains [UnorderedMap], the equivalent of Kokkos' `UnorderedMap`: a
+//! fixed-capacity hash map using open addressing, whose entries are stored in views.
+//! Insertions, lookups & removals can be done from parallel kernels; capacity is managed
+//! from the host:
+//!
+//! - insertions never overwrite the value of an existing key, and fail if the map is full.
+//!   Failures are recorded & can be checked using [UnorderedMap::failed_insert],
+//! - [UnorderedMap::rehash] reallocates the map with a new capacity, keeping its entries.
+//!
+//! Slots of erased entries are not reused before the next rehash.
+//!
+//! Like other views, the element types must implement [DataTraits]. Writing methods
+//! take a shared reference when a parallelization feature is enabled, a mutable one
+//! otherwise.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     containers::unordered_map::{InsertResult, UnorderedMap},
+//!     routines::{
+//!         parameters::{Range1D, TypedExecutionPolicy},
+//!         typed::parallel_for,
+//!     },
+//! };
+//!
+//! #[allow(unused_mut)]
+//! let mut map: UnorderedMap<u64, f64> = UnorderedMap::new(8);
+//!
+//! // 16 distinct keys do not fit
+//! let execp = TypedExecutionPolicy::new(Range1D(0..16));
+//! parallel_for(execp, |i| {
+//!     map.insert(i as u64, i as f64);
+//! })
+//! .unwrap();
+//! assert!(map.failed_insert());
+//!
+//! // grow the map on the host & insert again
+//! map.rehash(32).unwrap();
+//! let execp = TypedExecutionPolicy::new(Range1D(0..16));
+//! parallel_for(execp, |i| {
+//!     map.insert(i as u64, i as f64);
+//! })
+//! .unwrap();
+//! assert!(!map.failed_insert());
+//! assert_eq!(map.size(), 16);
+//! assert_eq!(map.find(&3), Some(3.0));
+//! ```
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::atomic::{fence, AtomicBool, Ordering},
+};
+
+use crate::view::{
+    parameters::{DataTraits, Layout},
+    ViewError, ViewOwned,
+};
+
+/// State of a slot that has never been used.
+const EMPTY: u32 = 0;
+/// State of a slot whose entry is being written.
+const BUSY: u32 = 1;
+/// State of a slot holding an entry.
+const FULL: u32 = 2;
+/// State of a slot whose entry was erased.
+const ERASED: u32 = 3;
+
+/// Outcome of [UnorderedMap::insert].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertResult {
+    /// The entry was inserted in the slot of given index.
+    Success(usize),
+    /// The key already exists in the slot of given index. Its value was not updated.
+    Existing(usize),
+    /// The map is full.
+    Failed,
+}
+
+/// Concurrent hash map with a fixed capacity, stored in views.
+#[derive(Debug)]
+pub struct UnorderedMap<K, V>
+where
+    K: DataTraits + Hash + Eq + 'static,
+    V: DataTraits + 'static,
+{
+    keys: ViewOwned<'static, 1, K>,
+    values: ViewOwned<'static, 1, V>,
+    states: ViewOwned<'static, 1, u32>,
+    failed: AtomicBool,
+}
+
+impl<K, V> UnorderedMap<K, V>
+where
+    K: DataTraits + Hash + Eq + 'static,
+    V: DataTraits + 'static,
+{
+    /// Create an empty map able to hold `capacity` entries, or one if `capacity` is 0.
+    ///
+    /// Panics if the views cannot be created, see [UnorderedMap::try_new].
+    pub fn new(capacity: usize) -> Self {
+        Self::try_new(capacity).unwrap_or_else(|e| panic!("could not create map: {e:?}"))
+    }
+
+    /// Fallible constructor. Returns the same errors as
+    /// [ViewBase::try_new][crate::view::ViewBase::try_new].
+    pub fn try_new(capacity: usize) -> Result<Self, ViewError<'static>> {
+        let capacity = capacity.max(1);
+        Ok(Self {
+            keys: ViewOwned::try_new(Layout::Right, [capacity])?,
+            values: ViewOwned::try_new(Layout::Right, [capacity])?,
+            states: ViewOwned::try_new(Layout::Right, [capacity])?,
+            failed: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns the maximum number of entries of the map.
+    pub fn capacity(&self) -> usize {
+        self.states.dims()[0]
+    }
+
+    /// Returns the number of entries of the map. This is a host operation.
+    pub fn size(&self) -> usize {
+        self.states.iter().filter(|s| *s == FULL).count()
+    }
+
+    /// Returns `true` if an insertion failed since the creation of the map or its last
+    /// rehash.
+    pub fn failed_insert(&self) -> bool {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the state of a slot, synchronizing with the write of its entry.
+    fn state(&self, slot: usize) -> u32 {
+        let state = self.states.get([slot]);
+        fence(Ordering::Acquire);
+        state
+    }
+
+    /// Returns the slot where the probing sequence of `key` starts.
+    fn first_slot(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.capacity() as u64) as usize
+    }
+
+    /// Returns the slot holding `key`, if any.
+    pub fn find_slot(&self, key: &K) -> Option<usize> {
+        let first = self.first_slot(key);
+        let capacity = self.capacity();
+        let mut probe = 0;
+        while probe < capacity {
+            let slot = (first + probe) % capacity;
+            match self.state(slot) {
+                EMPTY => return None,
+                // the entry is being written, it may be the key
+                BUSY => {
+                    std::hint::spin_loop();
+                    continue;
+                }
+                FULL if self.keys.get([slot]) == *key => return Some(slot),
+                _ => {}
+            }
+            probe += 1;
+        }
+        None
+    }
+
+    /// Returns the value associated to `key`, if any.
+    pub fn find(&self, key: &K) -> Option<V> {
+        self.find_slot(key).map(|slot| self.values.get([slot]))
+    }
+
+    /// Returns `true` if the map holds `key`.
+    pub fn exists(&self, key: &K) -> bool {
+        self.find_slot(key).is_some()
+    }
+
+    /// Reallocate the map with a new capacity, reinserting its entries. The record of
+    /// failed insertions is cleared.
+    ///
+    /// An error is returned if the entries do not fit in the new capacity; the map is
+    /// left unchanged.
+    pub fn rehash(&mut self, capacity: usize) -> Result<(), ViewError<'static>> {
+        if capacity < self.size() {
+            return Err(ViewError::ValueError(
+                "New capacity is smaller than the number of entries",
+            ));
+        }
+        #[allow(unused_mut)]
+        let mut map = Self::try_new(capacity)?;
+        (0..self.capacity())
+            .filter(|slot| self.state(*slot) == FULL)
+            .for_each(|slot| {
+                map.insert(self.keys.get([slot]), self.values.get([slot]));
+            });
+        *self = map;
+        Ok(())
+    }
+
+    /// Remove all entries of the map, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.states = ViewOwned::new(Layout::Right, [self.capacity()]);
+        self.failed.store(false, Ordering::Relaxed);
+    }
+}
+
+// writing methods only differ by their receiver, depending on enabled features
+macro_rules! impl_map_writes {
+    ($version: literal, $($recv: tt)+) => {
+        impl<K, V> UnorderedMap<K, V>
+        where
+            K: DataTraits + Hash + Eq + 'static,
+            V: DataTraits + 'static,
+        {
+            /// Insert an entry in the map, if `key` is not already in it.
+            ///
+            #[doc = $version]
+            pub fn insert($($recv)+ self, key: K, value: V) -> InsertResult {
+                let first = self.first_slot(&key);
+                let capacity = self.capacity();
+                let mut probe = 0;
+                while probe < capacity {
+                    let slot = (first + probe) % capacity;
+                    match self.state(slot) {
+                        EMPTY => {
+                            // claim the slot, or check it again if another thread did
+                            let claim = self.states.compare_exchange(
+                                [slot],
+                                EMPTY,
+                                BUSY,
+                                Ordering::Acquire,
+                                Ordering::Relaxed,
+                            );
+                            if claim.is_ok() {
+                                self.keys.set([slot], key);
+                                self.values.set([slot], value);
+                                // publish the entry
+                                let _ = self.states.compare_exchange(
+                                    [slot],
+                                    BUSY,
+                                    FULL,
+                                    Ordering::Release,
+                                    Ordering::Relaxed,
+                                );
+                                return InsertResult::Success(slot);
+                            }
+                            continue;
+                        }
+                        BUSY => {
+                            std::hint::spin_loop();
+                            continue;
+                        }
+                        FULL if self.keys.get([slot]) == key => return InsertResult::Existing(slot),
+                        _ => {}
+                    }
+                    probe += 1;
+                }
+                self.failed.store(true, Ordering::Relaxed);
+                InsertResult::Failed
+            }
+
+            /// Remove the entry of `key` from the map. Returns `true` if it was found.
+            ///
+            #[doc = $version]
+            pub fn erase($($recv)+ self, key: &K) -> bool {
+                match self.find_slot(key) {
+                    Some(slot) => self
+                        .states
+                        .compare_exchange(
+                            [slot],
+                            FULL,
+                            ERASED,
+                            Ordering::Relaxed,
+                            Ordering::Relaxed,
+                        )
+                        .is_ok(),
+                    None => false,
+                }
+            }
+        }
+    };
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))] {
+        impl_map_writes!("**Current version**: thread-safe", &);
+    } else {
+        impl_map_writes!("**Current version**: no feature", &mut);
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::{
+        parameters::{ExecutionSpace, Range1D, Schedule, TypedExecutionPolicy},
+        typed::parallel_for,
+    };
+
+    #[test]
+    fn insert_find_erase() {
+        #[allow(unused_mut)]
+        let mut map: UnorderedMap<i32, f64> = UnorderedMap::new(4);
+        assert_eq!(map.capacity(), 4);
+        assert!(matches!(map.insert(1, 1.0), InsertResult::Success(_)));
+        assert!(matches!(map.insert(1, 2.0), InsertResult::Existing(_)));
+        assert_eq!(map.find(&1), Some(1.0));
+        assert_eq!(map.find(&2), None);
+
+        assert!(map.erase(&1));
+        assert!(!map.erase(&1));
+        assert!(!map.exists(&1));
+        // erased slots are not reused before a rehash
+        (2..5).for_each(|k| assert!(matches!(map.insert(k, 0.0), InsertResult::Success(_))));
+        assert_eq!(map.insert(5, 0.0), InsertResult::Failed);
+        assert!(map.failed_insert());
+
+        map.rehash(3).unwrap();
+        assert!(!map.failed_insert());
+        assert_eq!((map.capacity(), map.size()), (3, 3));
+        assert!((2..5).all(|k| map.exists(&k)));
+        assert!(map.rehash(2).is_err());
+
+        map.clear();
+        assert_eq!(map.size(), 0);
+        assert_eq!(UnorderedMap::<i32, f64>::new(0).capacity(), 1);
+    }
+
+    #[test]
+    fn parallel_insert() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            #[allow(unused_mut)]
+            let mut map: UnorderedMap<usize, usize> = UnorderedMap::new(64);
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: Range1D(0..1000),
+                schedule: Schedule::Dynamic,
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            // many iterations insert the same keys
+            parallel_for(execp, |i| {
+                map.insert(i % 50, i % 50);
+            })
+            .unwrap();
+            assert!(!map.failed_insert());
+            assert_eq!(map.size(), 50);
+            assert!((0..50).all(|k| map.find(&k) == Some(k)));
+        }
+    }
+}
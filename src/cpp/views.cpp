@@ -0,0 +1,27 @@
+#include "poc-kokkos-rs/src/include/views.hpp"
+#include "poc-kokkos-rs/src/lib.rs.h"
+
+#include <vector>
+
+void fill_iota(ViewHandle &view) {
+  // only double views are handled
+  if (view.element_type() != ElementType::F64) {
+    return;
+  }
+  double *data = reinterpret_cast<double *>(view.data());
+  size_t rank = view.rank();
+  size_t len = 1;
+  for (size_t d = 0; d < rank; ++d) {
+    len *= view.extent(d);
+  }
+  // unflatten the row-major rank of each element & use the strides of the handle
+  for (size_t k = 0; k < len; ++k) {
+    size_t offset = 0;
+    size_t rem = k;
+    for (size_t d = rank; d-- > 0;) {
+      offset += (rem % view.extent(d)) * view.stride(d);
+      rem /= view.extent(d);
+    }
+    data[offset] = static_cast<double>(k);
+  }
+}
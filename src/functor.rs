@@ -11,8 +11,21 @@
 //! In order to have actual closures match the required trait implementation,
 //! the same mechanism is used to define operations on [`Views`][crate::view].
 
+use std::{
+    ops::{Add, Range},
+    sync::Arc,
+};
+
 #[cfg(doc)]
 use crate::routines::parameters::RangePolicy;
+use crate::{
+    routines::{
+        parameters::{Reducer, ScanMode},
+        scratch::{ScratchArena, TeamScratch, SCRATCH_LEVELS},
+        team::TeamSync,
+    },
+    view::parameters::DataTraits,
+};
 
 /// Kernel argument enum
 ///
@@ -22,6 +35,11 @@ use crate::routines::parameters::RangePolicy;
 /// due to downcasting, the solution was to define kernel arguments as a
 /// struct-like enum.
 ///
+/// **Deprecated**: kernels written this way must handle variants that their policy
+/// never yields. Statements of the [typed][crate::routines::typed] module pass the
+/// concrete argument of the policy instead, and should be preferred in new code. The
+/// enum remains the representation used by dispatch routines.
+///
 /// ### Example
 ///
 /// One-dimensional kernel:
@@ -35,7 +53,7 @@ use crate::routines::parameters::RangePolicy;
 ///             println!("Hello from iteration {i}")
 ///         },
 ///         KernelArgs::IndexND(_) => unimplemented!(),
-///         KernelArgs::Handle => unimplemented!(),
+///         KernelArgs::Handle(_) => unimplemented!(),
 ///     };
 /// ```
 ///
@@ -50,7 +68,7 @@ use crate::routines::parameters::RangePolicy;
 ///             // body of the kernel
 ///             println!("Hello from iteration {idx:?}")
 ///         },
-///         KernelArgs::Handle => unimplemented!(),
+///         KernelArgs::Handle(_) => unimplemented!(),
 ///     };
 ///
 /// // Decompose the array
@@ -60,7 +78,7 @@ use crate::routines::parameters::RangePolicy;
 ///             // body of the kernel
 ///             println!("Hello from iteration {i},{j},{k}");
 ///         },
-///         KernelArgs::Handle => unimplemented!(),
+///         KernelArgs::Handle(_) => unimplemented!(),
 ///     };
 /// ```
 pub enum KernelArgs<const N: usize> {
@@ -68,8 +86,215 @@ pub enum KernelArgs<const N: usize> {
     Index1D(usize),
     /// Arguments of a `N`-dimensionnal kernel (e.g. a [MDRangePolicy][RangePolicy::MDRangePolicy]).
     IndexND([usize; N]),
-    /// Arguments of a team-based kernel.
-    Handle,
+    /// Arguments of a team-based kernel (e.g. a [TeamPolicy][RangePolicy::TeamPolicy]).
+    Handle(TeamHandle),
+}
+
+/// Team handle struct
+///
+/// Handle passed to the kernels of team-based statements. It identifies the team member
+/// executing the kernel, and is used to build nested policies such as
+/// [TeamThreadRange][RangePolicy::TeamThreadRange], [PerTeam][RangePolicy::PerTeam], or
+/// vector policies such as [ThreadVectorRange][RangePolicy::ThreadVectorRange].
+///
+/// Members of a team can synchronize using collectives, e.g. [TeamHandle::team_barrier],
+/// provided they are executed concurrently, see the [team][crate::routines::team] module.
+///
+/// Members of a team share its scratch memory, see [TeamHandle::team_scratch].
+///
+/// ### Example
+///
+/// ```
+/// use poc_kokkos_rs::functor::KernelArgs;
+///
+/// let kern = |arg: KernelArgs<1>| match arg {
+///         KernelArgs::Index1D(_) => unimplemented!(),
+///         KernelArgs::IndexND(_) => unimplemented!(),
+///         KernelArgs::Handle(handle) => {
+///             println!(
+///                 "Hello from thread {} of team {}",
+///                 handle.team_rank(),
+///                 handle.league_rank(),
+///             )
+///         },
+///     };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamHandle {
+    league_rank: usize,
+    league_size: usize,
+    team_rank: usize,
+    team_size: usize,
+    vector_size: usize,
+    /// Scratch memory of the statement, if requested.
+    scratch: Option<Arc<ScratchArena>>,
+    /// Synchronization state of the team, if members are executed concurrently.
+    sync: Option<Arc<TeamSync>>,
+}
+
+impl TeamHandle {
+    /// Build the handle of a team member from its flat index in the league, i.e.
+    /// `league_rank * team_size + team_rank`.
+    pub(crate) fn from_flat(
+        idx: usize,
+        league_size: usize,
+        team_size: usize,
+        vector_size: usize,
+    ) -> Self {
+        Self {
+            league_rank: idx / team_size,
+            league_size,
+            team_rank: idx % team_size,
+            team_size,
+            vector_size,
+            scratch: None,
+            sync: None,
+        }
+    }
+
+    /// Attach the scratch memory of the statement to the handle.
+    pub(crate) fn with_scratch(mut self, scratch: &Option<Arc<ScratchArena>>) -> Self {
+        self.scratch = scratch.clone();
+        self
+    }
+
+    /// Attach the synchronization state of the team to the handle.
+    #[cfg(any(feature = "threads", feature = "rayon"))]
+    pub(crate) fn with_sync(mut self, sync: &Option<Arc<TeamSync>>) -> Self {
+        self.sync = sync.clone();
+        self
+    }
+
+    /// Returns the index of the team of the member.
+    pub fn league_rank(&self) -> usize {
+        self.league_rank
+    }
+
+    /// Returns the number of teams.
+    pub fn league_size(&self) -> usize {
+        self.league_size
+    }
+
+    /// Returns the index of the member in its team.
+    pub fn team_rank(&self) -> usize {
+        self.team_rank
+    }
+
+    /// Returns the number of members per team.
+    pub fn team_size(&self) -> usize {
+        self.team_size
+    }
+
+    /// Returns the vector length of the team.
+    pub fn vector_size(&self) -> usize {
+        self.vector_size
+    }
+
+    /// Returns the part of `range` assigned to the member by a
+    /// [TeamThreadRange][RangePolicy::TeamThreadRange] policy. Ranges of the members
+    /// of a team form a contiguous partition of `range`.
+    pub fn thread_range(&self, range: &Range<usize>) -> Range<usize> {
+        let chunk_size = range.len().div_ceil(self.team_size);
+        let start = (range.start + self.team_rank * chunk_size).min(range.end);
+        start..(start + chunk_size).min(range.end)
+    }
+
+    /// Returns the chunks of `range` executed by vector policies, e.g.
+    /// [ThreadVectorRange][RangePolicy::ThreadVectorRange]: consecutive chunks of
+    /// `vector_size` indices, one per lane, the last one being possibly shorter. A vector
+    /// size of 0 is treated as 1.
+    ///
+    /// Lanes of a chunk are executed by the calling thread, one after the other. Explicit
+    /// SIMD is not used since kernels are called through trait objects, which prevents
+    /// vectorizing them across lanes.
+    pub fn vector_chunks(&self, range: Range<usize>) -> impl Iterator<Item = Range<usize>> {
+        let (end, lanes) = (range.end, self.vector_size.max(1));
+        range
+            .step_by(lanes)
+            .map(move |start| start..(start + lanes).min(end))
+    }
+
+    /// Returns the scratch memory of the team at `level`, whose size is requested by the
+    /// policy. Memory is empty if the policy did not request any.
+    ///
+    /// Panics if `level` is not lower than [SCRATCH_LEVELS].
+    pub fn team_scratch(&self, level: usize) -> TeamScratch<'_> {
+        assert!(
+            level < SCRATCH_LEVELS,
+            "scratch level {level} does not exist"
+        );
+        match &self.scratch {
+            Some(arena) => arena.team(level, self.league_rank),
+            None => TeamScratch::empty(),
+        }
+    }
+
+    /// Exchange `value` with the other members of the team.
+    fn gather<T: Clone + Send + 'static>(&self, value: T) -> Vec<T> {
+        match &self.sync {
+            Some(sync) => sync.gather(self.team_rank, value),
+            None if self.team_size == 1 => vec![value],
+            None => panic!("team collectives require members to be executed concurrently"),
+        }
+    }
+
+    /// Block until all members of the team reach the barrier.
+    ///
+    /// Panics if members are not executed concurrently, see the
+    /// [team][crate::routines::team] module.
+    pub fn team_barrier(&self) {
+        match &self.sync {
+            Some(sync) => sync.barrier(),
+            None => {
+                self.gather(());
+            }
+        }
+    }
+
+    /// Returns the `value` of member `root` to all members of the team.
+    ///
+    /// Panics if `root` is not a rank of the team, or if members are not executed
+    /// concurrently.
+    pub fn team_broadcast<T: Clone + Send + 'static>(&self, value: T, root: usize) -> T {
+        assert!(
+            root < self.team_size,
+            "root {root} is not a member of the team"
+        );
+        self.gather(value).swap_remove(root)
+    }
+
+    /// Returns the reduction of the values of all members of the team, combined in rank
+    /// order, to all of them. The final step of the reducer is applied.
+    ///
+    /// Panics if members are not executed concurrently.
+    pub fn team_reduce<R>(&self, value: R::Value, reducer: &R) -> R::Value
+    where
+        R: Reducer,
+        R::Value: Send + 'static,
+    {
+        let values = self.gather(value);
+        let total = reducer
+            .reduce(values.into_iter())
+            .expect("teams have members");
+        reducer.finalize(total)
+    }
+
+    /// Returns the sum of the values of members of lower rank, including the value of the
+    /// calling member in [ScanMode::Inclusive] mode.
+    ///
+    /// Panics if members are not executed concurrently.
+    pub fn team_scan<T>(&self, value: T, mode: ScanMode) -> T
+    where
+        T: DataTraits + Add<Output = T> + Send + 'static,
+    {
+        let end = match mode {
+            ScanMode::Inclusive => self.team_rank + 1,
+            ScanMode::Exclusive => self.team_rank,
+        };
+        self.gather(value)[..end]
+            .iter()
+            .fold(T::default(), |acc, val| acc + *val)
+    }
 }
 
 cfg_if::cfg_if! {
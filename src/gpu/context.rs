@@ -0,0 +1,218 @@
+//! GPU execution context related code
+//!
+//! This module contains the device context used by GPU kernels. The context is created
+//! on first use and lives until the end of the program; it holds the `wgpu` device &
+//! queue, as well as compiled pipelines of built-in kernels. Device memory is managed
+//! through [DeviceView][super::view::DeviceView].
+//!
+//! Creating the context fails if no adapter is available on the machine. Software
+//! adapters (e.g. `llvmpipe`) are accepted, which makes it possible to run GPU code
+//! paths on machines without a GPU.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use wgpu::util::DeviceExt;
+
+use super::GpuScalar;
+use crate::routines::dispatch::DispatchError;
+
+/// Size of the workgroups of 1D kernels. Must match the `workgroup_size` attribute
+/// of the corresponding shaders.
+pub(crate) const WORKGROUP_SIZE_1D: u32 = 64;
+
+/// Size of the workgroups of 2D kernels, along each dimension. Must match the
+/// `workgroup_size` attribute of the corresponding shaders.
+pub(crate) const WORKGROUP_SIZE_2D: u32 = 8;
+
+/// Device context of GPU kernels.
+pub struct GpuContext {
+    adapter_info: wgpu::AdapterInfo,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    /// Compiled pipelines, indexed by kernel name and element type.
+    pipelines: Mutex<HashMap<(&'static str, &'static str), wgpu::ComputePipeline>>,
+}
+
+static CONTEXT: OnceLock<Result<GpuContext, &'static str>> = OnceLock::new();
+
+/// Returns the GPU context, creating it if necessary.
+pub fn context() -> Result<&'static GpuContext, DispatchError> {
+    CONTEXT
+        .get_or_init(GpuContext::new)
+        .as_ref()
+        .map_err(|desc| DispatchError::GPU(desc))
+}
+
+impl GpuContext {
+    fn new() -> Result<Self, &'static str> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .map_err(|_| "no GPU adapter available")?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("kokkos-rs device"),
+            required_limits: adapter.limits(),
+            ..Default::default()
+        }))
+        .map_err(|_| "could not create the GPU device")?;
+        Ok(Self {
+            adapter_info: adapter.get_info(),
+            device,
+            queue,
+            pipelines: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns information about the adapter used by the context.
+    pub fn adapter_info(&self) -> &wgpu::AdapterInfo {
+        &self.adapter_info
+    }
+
+    /// Returns the maximum number of workgroups of a dispatch, along each dimension.
+    pub(crate) fn max_workgroups(&self) -> u32 {
+        self.device.limits().max_compute_workgroups_per_dimension
+    }
+
+    /// Returns the pipeline of a kernel for elements of type `T`, compiling it on first
+    /// use. `source` is the WGSL code of the kernel, using `ELEM` as the element type.
+    pub(crate) fn pipeline<T: GpuScalar>(
+        &self,
+        name: &'static str,
+        source: &str,
+    ) -> wgpu::ComputePipeline {
+        let mut pipelines = self.pipelines.lock().unwrap_or_else(|e| e.into_inner());
+        pipelines
+            .entry((name, T::WGSL_TYPE))
+            .or_insert_with(|| {
+                let module = self
+                    .device
+                    .create_shader_module(wgpu::ShaderModuleDescriptor {
+                        label: Some(name),
+                        source: wgpu::ShaderSource::Wgsl(
+                            source.replace("ELEM", T::WGSL_TYPE).into(),
+                        ),
+                    });
+                self.device
+                    .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                        label: Some(name),
+                        layout: None,
+                        module: &module,
+                        entry_point: Some("main"),
+                        compilation_options: Default::default(),
+                        cache: None,
+                    })
+            })
+            .clone()
+    }
+
+    /// Create a uniform buffer holding `bytes`, padded to a multiple of 16 bytes.
+    pub(crate) fn uniform(&self, bytes: &[u8]) -> wgpu::Buffer {
+        let mut contents = bytes.to_vec();
+        contents.resize(bytes.len().div_ceil(16) * 16, 0);
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &contents,
+                usage: wgpu::BufferUsages::UNIFORM,
+            })
+    }
+
+    /// Create a zero-initialized storage buffer holding `len` elements of type `T`.
+    ///
+    /// Bindings cannot be empty, so room for a single element is allocated if `len` is 0.
+    pub(crate) fn storage<T: GpuScalar>(&self, len: usize) -> wgpu::Buffer {
+        self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (len.max(1) * std::mem::size_of::<T>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_SRC
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Write `data` at the start of `buffer`. The write is visible to kernels executed
+    /// afterward.
+    pub(crate) fn write<T: GpuScalar>(&self, buffer: &wgpu::Buffer, data: &[T]) {
+        if !data.is_empty() {
+            self.queue
+                .write_buffer(buffer, 0, bytemuck::cast_slice(data));
+        }
+    }
+
+    /// Execute `pipeline` over `workgroups`, binding `buffers` in order.
+    pub(crate) fn run(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        buffers: &[&wgpu::Buffer],
+        workgroups: [u32; 2],
+    ) {
+        let entries: Vec<_> = buffers
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &entries,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(workgroups[0], workgroups[1], 1);
+        }
+        self.queue.submit([encoder.finish()]);
+    }
+
+    /// Read back the first `len` elements of `buffer`, waiting for previously submitted
+    /// kernels to complete.
+    pub(crate) fn read<T: GpuScalar>(
+        &self,
+        buffer: &wgpu::Buffer,
+        len: usize,
+    ) -> Result<Vec<T>, DispatchError> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        let size = (len * std::mem::size_of::<T>()) as u64;
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        // wait for the results
+        let slice = staging.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |res| {
+            let _ = sender.send(res);
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|_| DispatchError::GPU("device was lost during execution"))?;
+        match receiver.recv() {
+            Ok(Ok(())) => {}
+            _ => return Err(DispatchError::GPU("could not read back results")),
+        }
+        let view = slice
+            .get_mapped_range()
+            .map_err(|_| DispatchError::GPU("could not read back results"))?;
+        Ok(bytemuck::cast_slice(&view).to_vec())
+    }
+}
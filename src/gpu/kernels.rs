@@ -0,0 +1,123 @@
+//! device kernel related code
+//!
+//! This module contains the built-in kernels operating on [DeviceView]s. Kernels are
+//! submitted to the device queue and executed in submission order; functions return
+//! without waiting for completion. Results are obtained using
+//! [deep_copy_to_host][super::view::deep_copy_to_host].
+
+use super::{
+    context::{self, GpuContext, WORKGROUP_SIZE_1D, WORKGROUP_SIZE_2D},
+    view::DeviceView,
+    GpuScalar,
+};
+use crate::routines::{dispatch::DispatchError, StatementError};
+
+const FILL_SRC: &str = include_str!("shaders/fill.wgsl");
+const AXPY_SRC: &str = include_str!("shaders/axpy.wgsl");
+const GEMM_SRC: &str = include_str!("shaders/gemm.wgsl");
+
+/// Set all elements of `dst` to `val`.
+pub fn fill<const N: usize, T: GpuScalar>(
+    dst: &mut DeviceView<N, T>,
+    val: T,
+) -> Result<(), StatementError> {
+    let ctx = context::context()?;
+    if dst.is_empty() {
+        return Ok(());
+    }
+    let mut params = u32_param(dst.len())?.to_le_bytes().to_vec();
+    params.extend_from_slice(bytemuck::bytes_of(&val));
+    let workgroups = grid_1d(ctx, dst.len())?;
+    ctx.run(
+        &ctx.pipeline::<T>("fill", FILL_SRC),
+        &[&ctx.uniform(&params), &dst.buffer],
+        workgroups,
+    );
+    Ok(())
+}
+
+/// Compute `y = alpha * x + y`.
+///
+/// A [StatementError::DimensionMismatch] error is returned if views have different
+/// lengths.
+pub fn axpy<T: GpuScalar>(
+    alpha: f64,
+    x: &DeviceView<1, T>,
+    y: &mut DeviceView<1, T>,
+) -> Result<(), StatementError> {
+    if x.dims() != y.dims() {
+        return Err(StatementError::DimensionMismatch);
+    }
+    let ctx = context::context()?;
+    if y.is_empty() {
+        return Ok(());
+    }
+    let mut params = u32_param(y.len())?.to_le_bytes().to_vec();
+    params.extend_from_slice(bytemuck::bytes_of(&T::from_coefficient(alpha)));
+    let workgroups = grid_1d(ctx, y.len())?;
+    ctx.run(
+        &ctx.pipeline::<T>("axpy", AXPY_SRC),
+        &[&ctx.uniform(&params), &x.buffer, &y.buffer],
+        workgroups,
+    );
+    Ok(())
+}
+
+/// Compute `c = alpha * a * b + beta * c`.
+///
+/// A [StatementError::DimensionMismatch] error is returned if dimensions of the views
+/// are not compatible.
+pub fn gemm<T: GpuScalar>(
+    alpha: f64,
+    a: &DeviceView<2, T>,
+    b: &DeviceView<2, T>,
+    beta: f64,
+    c: &mut DeviceView<2, T>,
+) -> Result<(), StatementError> {
+    let ([m, k], [kb, n]) = (a.dims(), b.dims());
+    if k != kb || c.dims() != [m, n] {
+        return Err(StatementError::DimensionMismatch);
+    }
+    let ctx = context::context()?;
+    if c.is_empty() {
+        return Ok(());
+    }
+    let mut params = Vec::new();
+    for dim in [m, n, k] {
+        params.extend_from_slice(&u32_param(dim)?.to_le_bytes());
+    }
+    params.extend_from_slice(bytemuck::bytes_of(&T::from_coefficient(alpha)));
+    params.extend_from_slice(bytemuck::bytes_of(&T::from_coefficient(beta)));
+    let workgroups = [
+        u32_param(n.div_ceil(WORKGROUP_SIZE_2D as usize))?,
+        u32_param(m.div_ceil(WORKGROUP_SIZE_2D as usize))?,
+    ];
+    if workgroups.iter().any(|w| *w > ctx.max_workgroups()) {
+        return Err(DispatchError::GPU("matrices are too large to be dispatched").into());
+    }
+    ctx.run(
+        &ctx.pipeline::<T>("gemm", GEMM_SRC),
+        &[&ctx.uniform(&params), &a.buffer, &b.buffer, &c.buffer],
+        workgroups,
+    );
+    Ok(())
+}
+
+// Helpers
+
+/// Convert a size to a `u32` kernel parameter.
+fn u32_param(val: usize) -> Result<u32, DispatchError> {
+    u32::try_from(val).map_err(|_| DispatchError::GPU("views are too large to be dispatched"))
+}
+
+/// Returns the workgroup grid of a 1D kernel over `len` elements. Large ranges are
+/// dispatched over a second dimension.
+fn grid_1d(ctx: &GpuContext, len: usize) -> Result<[u32; 2], DispatchError> {
+    let groups = u32_param(len.div_ceil(WORKGROUP_SIZE_1D as usize))?;
+    let x = groups.min(ctx.max_workgroups());
+    let y = groups.div_ceil(x);
+    if y > ctx.max_workgroups() {
+        return Err(DispatchError::GPU("views are too large to be dispatched"));
+    }
+    Ok([x, y])
+}
@@ -0,0 +1,264 @@
+//! GPU related code
+//!
+//! This module contains built-in kernels that can be executed on a GPU using
+//! [`wgpu`](https://wgpu.rs) compute shaders. It is only defined when the `gpu` feature
+//! is enabled.
+//!
+//! Rust closures cannot be compiled to shaders, hence kernels passed to statements such
+//! as [`parallel_for`] cannot be executed on the device. Instead, this module provides
+//! a small set of common operations written in WGSL:
+//!
+//! - [`fill`]: set all elements of a view to a value.
+//! - [`axpy`]: `y = alpha * x + y`, using 1D views.
+//! - [`gemm`]: `c = alpha * a * b + beta * c`, using 2D views.
+//!
+//! Functions of this module operate on host views and take an [`ExecutionSpace`]. Using
+//! [`ExecutionSpace::DeviceGPU`], view data is copied to the device, the kernel is
+//! executed, and results are copied back into the host view. Other spaces execute the
+//! operation on the host using [`parallel_for`] or the [blas] module. Data
+//! is transferred in logical
+//! (row-major) order, so views of any layout can be used.
+//!
+//! To avoid transfers between consecutive kernels, data can be kept on the device
+//! using the [`view::DeviceView`] type & the [`kernels`] module.
+//!
+//! The device is initialized on first use, see the [`context`] module.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     gpu::{axpy, context::context},
+//!     routines::parameters::ExecutionSpace,
+//!     view::{parameters::Layout, ViewOwned},
+//! };
+//!
+//! // machines without any adapter can still use host spaces
+//! let space = match context() {
+//!     Ok(_) => ExecutionSpace::DeviceGPU,
+//!     Err(_) => ExecutionSpace::Serial,
+//! };
+//!
+//! let x: ViewOwned<'_, 1, f32> = ViewOwned::new_from_data(vec![1.0, 2.0], Layout::Right, [2]);
+//! let mut y: ViewOwned<'_, 1, f32> = ViewOwned::new_from_data(vec![0.5, 0.5], Layout::Right, [2]);
+//!
+//! axpy(space, 2.0, &x, &mut y).unwrap();
+//!
+//! assert_eq!(y.get([0]), 2.5);
+//! assert_eq!(y.get([1]), 4.5);
+//! ```
+
+pub mod context;
+pub mod kernels;
+pub mod view;
+
+use std::ops::Mul;
+
+use crate::{
+    blas::{self, BlasScalar},
+    routines::{
+        iter::MDIndexIter,
+        parameters::{ExecutionSpace, MDRange, Schedule, TypedExecutionPolicy},
+        typed::parallel_for,
+        StatementError,
+    },
+    view::ViewBase,
+};
+
+use self::view::{deep_copy_to_device, deep_copy_to_host, DeviceView};
+
+/// Element types supported by GPU kernels.
+///
+/// Types must have a WGSL equivalent of the same size. `f64` is not supported since
+/// most adapters lack double precision support.
+pub trait GpuScalar: BlasScalar + Mul<Output = Self> + bytemuck::Pod {
+    /// Name of the equivalent type in WGSL.
+    const WGSL_TYPE: &'static str;
+
+    /// Convert a coefficient of an operation to the type, e.g. `alpha` of [`axpy`].
+    fn from_coefficient(val: f64) -> Self;
+}
+
+impl GpuScalar for f32 {
+    const WGSL_TYPE: &'static str = "f32";
+
+    fn from_coefficient(val: f64) -> Self {
+        val as f32
+    }
+}
+
+// Statements
+
+/// Set all elements of `dst` to `val`.
+pub fn fill<const N: usize, T: GpuScalar>(
+    space: ExecutionSpace,
+    dst: &mut ViewBase<'_, N, T>,
+    val: T,
+) -> Result<(), StatementError> {
+    match space {
+        ExecutionSpace::DeviceGPU => {
+            let mut d_dst = DeviceView::new(dst.dims())?;
+            kernels::fill(&mut d_dst, val)?;
+            deep_copy_to_host(dst, &d_dst)
+        }
+        _ => {
+            let dst = &*dst;
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: MDRange::from_dims(dst.dims()),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            parallel_for(execp, |idx| dst.set(idx, val))
+        }
+    }
+}
+
+/// Compute `y = alpha * x + y`.
+///
+/// A [StatementError::DimensionMismatch] error is returned if views have different
+/// lengths.
+pub fn axpy<T: GpuScalar>(
+    space: ExecutionSpace,
+    alpha: f64,
+    x: &ViewBase<'_, 1, T>,
+    y: &mut ViewBase<'_, 1, T>,
+) -> Result<(), StatementError> {
+    if x.dims() != y.dims() {
+        return Err(StatementError::DimensionMismatch);
+    }
+    let [len] = y.dims();
+    match space {
+        ExecutionSpace::DeviceGPU => {
+            let (mut d_x, mut d_y) = (DeviceView::new([len])?, DeviceView::new([len])?);
+            deep_copy_to_device(&mut d_x, x)?;
+            deep_copy_to_device(&mut d_y, y)?;
+            kernels::axpy(alpha, &d_x, &mut d_y)?;
+            deep_copy_to_host(y, &d_y)
+        }
+        _ => blas::axpy(space, Schedule::default(), alpha, x, y),
+    }
+}
+
+/// Compute `c = alpha * a * b + beta * c`.
+///
+/// A [StatementError::DimensionMismatch] error is returned if dimensions of the views
+/// are not compatible.
+pub fn gemm<T: GpuScalar>(
+    space: ExecutionSpace,
+    alpha: f64,
+    a: &ViewBase<'_, 2, T>,
+    b: &ViewBase<'_, 2, T>,
+    beta: f64,
+    c: &mut ViewBase<'_, 2, T>,
+) -> Result<(), StatementError> {
+    let ([m, k], [kb, n]) = (a.dims(), b.dims());
+    if k != kb || c.dims() != [m, n] {
+        return Err(StatementError::DimensionMismatch);
+    }
+    match space {
+        ExecutionSpace::DeviceGPU => {
+            let mut d_a = DeviceView::new([m, k])?;
+            let mut d_b = DeviceView::new([k, n])?;
+            let mut d_c = DeviceView::new([m, n])?;
+            deep_copy_to_device(&mut d_a, a)?;
+            deep_copy_to_device(&mut d_b, b)?;
+            deep_copy_to_device(&mut d_c, c)?;
+            kernels::gemm(alpha, &d_a, &d_b, beta, &mut d_c)?;
+            deep_copy_to_host(c, &d_c)
+        }
+        _ => blas::gemm(space, Schedule::default(), alpha, a, b, beta, c),
+    }
+}
+
+// Helpers
+
+/// Returns the values of `view` in logical (row-major) order.
+fn host_values<const N: usize, T: GpuScalar>(view: &ViewBase<'_, N, T>) -> Vec<T> {
+    MDIndexIter::new(view.dims().map(|d| 0..d))
+        .map(|idx| view.get(idx))
+        .collect()
+}
+
+/// Write `values`, given in logical (row-major) order, into `view`.
+fn store<const N: usize, T: GpuScalar>(view: &ViewBase<'_, N, T>, values: Vec<T>) {
+    MDIndexIter::new(view.dims().map(|d| 0..d))
+        .zip(values)
+        .for_each(|(idx, val)| view.set(idx, val));
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::{parameters::Layout, ViewOwned};
+
+    /// Returns the spaces to test: host spaces, and the device if an adapter is available.
+    fn spaces() -> Vec<ExecutionSpace> {
+        let mut spaces = vec![ExecutionSpace::Serial, ExecutionSpace::DeviceCPU];
+        match context::context() {
+            Ok(_) => spaces.push(ExecutionSpace::DeviceGPU),
+            Err(e) => eprintln!("skipping device tests: {e}"),
+        }
+        spaces
+    }
+
+    #[test]
+    fn fill_views() {
+        for space in spaces() {
+            let mut view: ViewOwned<'_, 3, f32> = ViewOwned::new(Layout::Left, [3, 40, 5]);
+            fill(space, &mut view, 1.5).unwrap();
+            assert!(host_values(&view).iter().all(|v| *v == 1.5));
+        }
+    }
+
+    #[test]
+    fn axpy_large() {
+        // more than 64 * 65535 elements, to exercise the 2D grid of workgroups
+        let len = 5_000_000;
+        let x: ViewOwned<'_, 1, f32> = ViewOwned::new_from_data(
+            (0..len).map(|i| (i % 7) as f32).collect(),
+            Layout::Right,
+            [len],
+        );
+        for space in spaces() {
+            let mut y: ViewOwned<'_, 1, f32> =
+                ViewOwned::new_from_data(vec![1.0; len], Layout::Right, [len]);
+            axpy(space, 2.0, &x, &mut y).unwrap();
+            assert!((0..len).all(|i| y.get([i]) == 2.0 * (i % 7) as f32 + 1.0));
+        }
+
+        let mut short: ViewOwned<'_, 1, f32> = ViewOwned::new(Layout::Right, [3]);
+        assert!(matches!(
+            axpy(ExecutionSpace::DeviceGPU, 1.0, &x, &mut short),
+            Err(StatementError::DimensionMismatch)
+        ));
+    }
+
+    #[test]
+    fn gemm_layouts() {
+        let (m, k, n) = (13, 7, 21);
+        let a_val = |i: usize, p: usize| (i + 2 * p) as f32 * 0.5;
+        let b_val = |p: usize, j: usize| (p * j % 5) as f32 - 1.0;
+        for space in spaces() {
+            for layout in [Layout::Right, Layout::Left] {
+                let a: ViewOwned<'_, 2, f32> = ViewOwned::new(layout, [m, k]);
+                let b: ViewOwned<'_, 2, f32> = ViewOwned::new(layout, [k, n]);
+                let mut c: ViewOwned<'_, 2, f32> = ViewOwned::new(layout, [m, n]);
+                MDIndexIter::new([0..m, 0..k]).for_each(|[i, p]| a.set([i, p], a_val(i, p)));
+                MDIndexIter::new([0..k, 0..n]).for_each(|[p, j]| b.set([p, j], b_val(p, j)));
+                fill(space, &mut c, 1.0).unwrap();
+
+                gemm(space, 2.0, &a, &b, 3.0, &mut c).unwrap();
+
+                MDIndexIter::new([0..m, 0..n]).for_each(|[i, j]| {
+                    let dot: f32 = (0..k).map(|p| a_val(i, p) * b_val(p, j)).sum();
+                    assert_eq!(c.get([i, j]), 2.0 * dot + 3.0);
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,24 @@
+// y[i] = alpha * x[i] + y[i]
+//
+// ELEM is replaced by the WGSL type of the elements when building the pipeline.
+
+struct Params {
+    n: u32,
+    alpha: ELEM,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> x: array<ELEM>;
+@group(0) @binding(2) var<storage, read_write> y: array<ELEM>;
+
+@compute @workgroup_size(64)
+fn main(
+    @builtin(global_invocation_id) gid: vec3<u32>,
+    @builtin(num_workgroups) nwg: vec3<u32>,
+) {
+    // large ranges are dispatched over a 2D grid of workgroups
+    let i = gid.y * nwg.x * 64u + gid.x;
+    if (i < params.n) {
+        y[i] = params.alpha * x[i] + y[i];
+    }
+}
@@ -0,0 +1,32 @@
+// c = alpha * a * b + beta * c
+//
+// Matrices are stored in row-major order: a is m x k, b is k x n, c is m x n.
+// ELEM is replaced by the WGSL type of the elements when building the pipeline.
+
+struct Params {
+    m: u32,
+    n: u32,
+    k: u32,
+    alpha: ELEM,
+    beta: ELEM,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> a: array<ELEM>;
+@group(0) @binding(2) var<storage, read> b: array<ELEM>;
+@group(0) @binding(3) var<storage, read_write> c: array<ELEM>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let row = gid.y;
+    let col = gid.x;
+    if (row >= params.m || col >= params.n) {
+        return;
+    }
+    var acc = ELEM(0);
+    for (var p = 0u; p < params.k; p++) {
+        acc += a[row * params.k + p] * b[p * params.n + col];
+    }
+    let idx = row * params.n + col;
+    c[idx] = params.alpha * acc + params.beta * c[idx];
+}
@@ -0,0 +1,170 @@
+//! device view related code
+//!
+//! This module contains [DeviceView], a view whose data resides in device memory, and
+//! the functions used to move data between host & device. Together, they make it
+//! possible to express the usual Kokkos host-mirror workflow:
+//!
+//! 1. allocate a view on the device,
+//! 2. create a host mirror using [DeviceView::create_mirror_view] & fill it,
+//! 3. copy it to the device using [deep_copy_to_device],
+//! 4. execute kernels of the [kernels][super::kernels] module,
+//! 5. copy results back using [deep_copy_to_host].
+//!
+//! Data is never moved implicitly: device views cannot be read or written from the host.
+//!
+//! Device data is stored in row-major order, i.e. using [Layout::Right]. Deep copies
+//! convert the layout of host views as needed.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::gpu::{
+//!     context::context,
+//!     kernels,
+//!     view::{deep_copy_to_device, deep_copy_to_host, DeviceView},
+//! };
+//!
+//! // device code paths require an adapter
+//! if context().is_ok() {
+//!     let mut x: DeviceView<1, f32> = DeviceView::new([4]).unwrap();
+//!     let mut y: DeviceView<1, f32> = DeviceView::new([4]).unwrap();
+//!
+//!     let mut h_x = x.create_mirror_view();
+//!     (0..4).for_each(|i| h_x.set([i], i as f32));
+//!     deep_copy_to_device(&mut x, &h_x).unwrap();
+//!
+//!     // data stays on the device between kernels
+//!     kernels::fill(&mut y, 1.0).unwrap();
+//!     kernels::axpy(2.0, &x, &mut y).unwrap();
+//!     kernels::axpy(2.0, &x, &mut y).unwrap();
+//!
+//!     let mut h_y = y.create_mirror_view();
+//!     deep_copy_to_host(&mut h_y, &y).unwrap();
+//!     assert_eq!(h_y.get([3]), 13.0);
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use super::{context, host_values, store, GpuScalar};
+use crate::{
+    routines::{dispatch::DispatchError, StatementError},
+    view::{parameters::Layout, ViewBase, ViewOwned},
+};
+
+/// View whose data resides in device memory.
+///
+/// Elements are zero-initialized on allocation. The allocation is released when the
+/// view is dropped.
+pub struct DeviceView<const N: usize, T: GpuScalar> {
+    pub(crate) buffer: wgpu::Buffer,
+    dim: [usize; N],
+    _elem: PhantomData<T>,
+}
+
+impl<const N: usize, T: GpuScalar> DeviceView<N, T> {
+    /// Allocate a view of dimensions `dim` on the device.
+    pub fn new(dim: [usize; N]) -> Result<Self, DispatchError> {
+        let ctx = context::context()?;
+        Ok(Self {
+            buffer: ctx.storage::<T>(dim.iter().product()),
+            dim,
+            _elem: PhantomData,
+        })
+    }
+
+    /// Returns the dimensions of the view.
+    pub fn dims(&self) -> [usize; N] {
+        self.dim
+    }
+
+    /// Returns the number of elements of the view.
+    pub fn len(&self) -> usize {
+        self.dim.iter().product()
+    }
+
+    /// Returns `true` if the view has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Create a host view matching the dimensions & layout of `self`.
+    ///
+    /// The mirror is a new allocation and its content is not copied; use
+    /// [deep_copy_to_host] to do so. Unlike Kokkos, the mirror of a host view is
+    /// obtained using [ViewBase::create_mirror].
+    pub fn create_mirror_view(&self) -> ViewOwned<'static, N, T> {
+        ViewOwned::new(Layout::Right, self.dim)
+    }
+}
+
+/// Copy the content of `src` into `dst`, which resides on the device.
+///
+/// A [StatementError::DimensionMismatch] error is returned if views have different
+/// dimensions.
+pub fn deep_copy_to_device<const N: usize, T: GpuScalar>(
+    dst: &mut DeviceView<N, T>,
+    src: &ViewBase<'_, N, T>,
+) -> Result<(), StatementError> {
+    if dst.dims() != src.dims() {
+        return Err(StatementError::DimensionMismatch);
+    }
+    context::context()?.write(&dst.buffer, &host_values(src));
+    Ok(())
+}
+
+/// Copy the content of `src`, which resides on the device, into `dst`. Kernels using
+/// `src` are completed beforehand.
+///
+/// A [StatementError::DimensionMismatch] error is returned if views have different
+/// dimensions.
+pub fn deep_copy_to_host<const N: usize, T: GpuScalar>(
+    dst: &mut ViewBase<'_, N, T>,
+    src: &DeviceView<N, T>,
+) -> Result<(), StatementError> {
+    let dst = &*dst;
+    if dst.dims() != src.dims() {
+        return Err(StatementError::DimensionMismatch);
+    }
+    let values = context::context()?.read::<T>(&src.buffer, src.len())?;
+    store(dst, values);
+    Ok(())
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::iter::MDIndexIter;
+
+    #[test]
+    fn round_trip() {
+        if context::context().is_err() {
+            eprintln!("skipping device tests: no adapter");
+            return;
+        }
+        let src: ViewOwned<'_, 3, f32> = ViewOwned::new(Layout::Left, [3, 4, 5]);
+        MDIndexIter::new([0..3, 0..4, 0..5])
+            .for_each(|[i, j, k]| src.set([i, j, k], (100 * i + 10 * j + k) as f32));
+
+        let mut dev: DeviceView<3, f32> = DeviceView::new([3, 4, 5]).unwrap();
+        deep_copy_to_device(&mut dev, &src).unwrap();
+        let mut mirror = dev.create_mirror_view();
+        assert_eq!(mirror.layout, Layout::Right);
+        deep_copy_to_host(&mut mirror, &dev).unwrap();
+        MDIndexIter::new([0..3, 0..4, 0..5])
+            .for_each(|idx| assert_eq!(mirror.get(idx), src.get(idx)));
+
+        let mut other: ViewOwned<'_, 3, f32> = ViewOwned::new(Layout::Right, [3, 4, 4]);
+        assert!(matches!(
+            deep_copy_to_host(&mut other, &dev),
+            Err(StatementError::DimensionMismatch)
+        ));
+        assert!(matches!(
+            deep_copy_to_device(&mut dev, &other),
+            Err(StatementError::DimensionMismatch)
+        ));
+    }
+}
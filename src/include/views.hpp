@@ -0,0 +1,6 @@
+#pragma once
+#include "rust/cxx.h"
+
+struct ViewHandle;
+
+void fill_iota(ViewHandle &view);
@@ -0,0 +1,84 @@
+//! build information related code
+//!
+//! This module contains code used to report how the crate was compiled. This is
+//! meant to be printed alongside benchmark results, so that numbers gathered using
+//! differently-compiled binaries can be told apart.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::info::build_info;
+//!
+//! println!("{}", build_info());
+//! ```
+
+use std::fmt::Display;
+
+/// Compilation information of the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// Version of the crate.
+    pub version: &'static str,
+    /// Enabled features among the ones changing the behavior of the crate.
+    pub features: Vec<&'static str>,
+    /// Output of `rustc --version` for the compiler used, `"unknown"` if it could not
+    /// be determined by the build script.
+    pub rustc_version: &'static str,
+    /// Short hash of the git commit the crate was built from, `"unknown"` if it could
+    /// not be determined by the build script.
+    pub git_hash: &'static str,
+}
+
+/// Returns the compilation information of the crate.
+pub fn build_info() -> BuildInfo {
+    let mut features = Vec::new();
+    if cfg!(feature = "rayon") {
+        features.push("rayon");
+    }
+    if cfg!(feature = "threads") {
+        features.push("threads");
+    }
+    if cfg!(feature = "gpu") {
+        features.push("gpu");
+    }
+    if cfg!(feature = "access-stats") {
+        features.push("access-stats");
+    }
+
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        features,
+        rustc_version: env!("KOKKOS_RS_RUSTC_VERSION"),
+        git_hash: env!("KOKKOS_RS_GIT_HASH"),
+    }
+}
+
+impl Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let features = if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(", ")
+        };
+        writeln!(f, "poc-kokkos-rs {} ({})", self.version, self.git_hash)?;
+        writeln!(f, "features: {features}")?;
+        write!(f, "compiler: {}", self.rustc_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enabled_features() {
+        let info = build_info();
+        assert_eq!(info.features.contains(&"rayon"), cfg!(feature = "rayon"));
+        assert_eq!(
+            info.features.contains(&"threads"),
+            cfg!(feature = "threads")
+        );
+        assert!(!info.rustc_version.is_empty());
+        assert!(info.to_string().starts_with("poc-kokkos-rs"));
+    }
+}
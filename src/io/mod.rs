@@ -0,0 +1,11 @@
+//! output related code
+//!
+//! This module contains writers used to dump the content of views into files that can
+//! be inspected using standard visualization tools:
+//!
+//! - [`vtk`]: legacy VTK files of 2D & 3D views, e.g. for ParaView or VisIt.
+//! - `png`: heatmap images of 2D views. Only defined when the `image` feature is enabled.
+
+#[cfg(feature = "image")]
+pub mod png;
+pub mod vtk;
@@ -0,0 +1,75 @@
+//! PNG heatmap output code
+//!
+//! This module contains code used to write 2D scalar views as grayscale heatmaps. It is
+//! only compiled when the `image` feature is enabled.
+//!
+//! The first index of the view is mapped to the horizontal axis, the second one to the
+//! vertical axis. Values are linearly mapped from the `[min, max]` range of the view to
+//! pixel intensities, the minimum being black.
+//!
+//! ### Example
+//!
+//! ```rust,no_run
+//! use poc_kokkos_rs::{
+//!     io::png::export_png,
+//!     view::{parameters::Layout, ViewOwned},
+//! };
+//!
+//! let field: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data(vec![0.0, 1.0, 2.0, 3.0], Layout::Left, [2, 2]);
+//!
+//! export_png("field.png", &field).unwrap();
+//! ```
+
+use std::path::Path;
+
+use image::{GrayImage, ImageResult, Luma};
+
+use crate::view::{parameters::DataTraits, ViewBase};
+
+/// Build a grayscale heatmap of a 2D view.
+pub fn heatmap<T>(view: &ViewBase<'_, 2, T>) -> GrayImage
+where
+    T: DataTraits + Into<f64>,
+{
+    let [nx, ny] = view.dims();
+    let (min, max) = view
+        .values()
+        .map(Into::into)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), val| {
+            (min.min(val), max.max(val))
+        });
+    let range = if max > min { max - min } else { 1.0 };
+    GrayImage::from_fn(nx as u32, ny as u32, |x, y| {
+        let val: f64 = view.get([x as usize, y as usize]).into();
+        Luma([((val - min) / range * 255.0).round() as u8])
+    })
+}
+
+/// Write a 2D view as a grayscale heatmap into the PNG file at `path`.
+pub fn export_png<P: AsRef<Path>, T>(path: P, view: &ViewBase<'_, 2, T>) -> ImageResult<()>
+where
+    T: DataTraits + Into<f64>,
+{
+    heatmap(view).save_with_format(path, image::ImageFormat::Png)
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::{parameters::Layout, ViewOwned};
+
+    #[test]
+    fn intensities() {
+        let view: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data(vec![-1.0, 0.0, 0.0, 1.0], Layout::Right, [2, 2]);
+        let img = heatmap(&view);
+        assert_eq!(img.dimensions(), (2, 2));
+        assert_eq!(img.get_pixel(0, 0), &Luma([0]));
+        assert_eq!(img.get_pixel(1, 0), &Luma([128]));
+        assert_eq!(img.get_pixel(1, 1), &Luma([255]));
+    }
+}
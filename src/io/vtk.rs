@@ -0,0 +1,140 @@
+//! legacy VTK output code
+//!
+//! This module contains code used to write 2D & 3D scalar views as legacy VTK
+//! `STRUCTURED_POINTS` datasets. Values are written in ASCII, as double precision
+//! floating-point numbers.
+//!
+//! The first index of the view is mapped to the `x` axis, the second to `y` and the
+//! third to `z`. The output does not depend on the layout of the view.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     io::vtk::write_vtk,
+//!     view::{parameters::Layout, ViewOwned},
+//! };
+//!
+//! let field: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data(vec![0.0, 1.0, 2.0, 3.0], Layout::Left, [2, 2]);
+//!
+//! let mut out: Vec<u8> = Vec::new();
+//! write_vtk(&mut out, &field, "temperature").unwrap();
+//! ```
+
+use std::{
+    fs::File,
+    io::{BufWriter, Error, ErrorKind, Write},
+    path::Path,
+};
+
+use crate::view::{parameters::DataTraits, ViewBase};
+
+/// Write a 2D or 3D view as a legacy VTK dataset into `out`.
+///
+/// `name` is used as the name of the scalar field; it must not contain whitespaces.
+/// Returns an [ErrorKind::InvalidInput] error if the view is not 2D or 3D, or if the
+/// name is invalid.
+pub fn write_vtk<W: Write, const N: usize, T>(
+    mut out: W,
+    view: &ViewBase<'_, N, T>,
+    name: &str,
+) -> std::io::Result<()>
+where
+    T: DataTraits + Into<f64>,
+{
+    if !(2..=3).contains(&N) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "only 2D & 3D views can be written as VTK",
+        ));
+    }
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "field name must be non-empty & contain no whitespace",
+        ));
+    }
+    let dims = view.dims();
+    let [nx, ny, nz] = [dims[0], dims[1], if N == 3 { dims[2] } else { 1 }];
+
+    writeln!(out, "# vtk DataFile Version 3.0")?;
+    writeln!(out, "{name}")?;
+    writeln!(out, "ASCII")?;
+    writeln!(out, "DATASET STRUCTURED_POINTS")?;
+    writeln!(out, "DIMENSIONS {nx} {ny} {nz}")?;
+    writeln!(out, "ORIGIN 0 0 0")?;
+    writeln!(out, "SPACING 1 1 1")?;
+    writeln!(out, "POINT_DATA {}", nx * ny * nz)?;
+    writeln!(out, "SCALARS {name} double 1")?;
+    writeln!(out, "LOOKUP_TABLE default")?;
+    // VTK expects x to vary fastest
+    let mut index = [0; N];
+    for k in 0..nz {
+        for j in 0..ny {
+            for i in 0..nx {
+                index[0] = i;
+                index[1] = j;
+                if N == 3 {
+                    index[2] = k;
+                }
+                writeln!(out, "{}", view.get(index).into())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write a 2D or 3D view as a legacy VTK dataset into the file at `path`.
+pub fn export_vtk<P: AsRef<Path>, const N: usize, T>(
+    path: P,
+    view: &ViewBase<'_, N, T>,
+    name: &str,
+) -> std::io::Result<()>
+where
+    T: DataTraits + Into<f64>,
+{
+    let mut file = BufWriter::new(File::create(path)?);
+    write_vtk(&mut file, view, name)?;
+    file.flush()
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::{parameters::Layout, ViewOwned};
+
+    #[test]
+    fn layout_independent() {
+        let data: Vec<f32> = (0..12).map(|x| x as f32).collect();
+        let right: ViewOwned<'_, 3, f32> =
+            ViewOwned::new_from_data(data.clone(), Layout::Right, [2, 3, 2]);
+        let mut left: ViewOwned<'_, 3, f32> =
+            ViewOwned::new_from_data(data, Layout::Right, [2, 3, 2]);
+        left.convert_layout_in_place(Layout::Left).unwrap();
+        let (mut out_r, mut out_l): (Vec<u8>, Vec<u8>) = (Vec::new(), Vec::new());
+        write_vtk(&mut out_r, &right, "f").unwrap();
+        write_vtk(&mut out_l, &left, "f").unwrap();
+        assert_eq!(out_r, out_l);
+
+        let out = String::from_utf8(out_r).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[4], "DIMENSIONS 2 3 2");
+        // x fastest: (0,0,0), (1,0,0), (0,1,0)
+        assert_eq!(&lines[10..13], &["0", "6", "2"]);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let view: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [4]);
+        let err = write_vtk(Vec::new(), &view, "f").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [4, 4]);
+        let err = write_vtk(Vec::new(), &view, "my field").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
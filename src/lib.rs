@@ -30,7 +30,8 @@
 //! cargo doc --no-deps --open
 //! ```
 //!
-//! Note that some elements of the documentation are feature specific.
+//! Note that some elements of the documentation are feature specific. Enabled features
+//! can be checked at runtime using [`info::build_info`].
 //!
 //! ## Compilation
 //!
@@ -47,13 +48,23 @@
 //!
 //! - `rayon`: Uses the [rayon][2] crate to handle parallelization on CPU.
 //! - `threads` : Uses [`std::thread`] methods to handle parallelization on CPU.
-//! - `gpu`: Currently used as a way to gate GPU usage as this cannot be done in pure Rust.
+//! - `gpu`: Enable built-in GPU kernels, executed using [wgpu][3] compute shaders. See the
+//!   `gpu` module for more information. Rust closures cannot be executed on the device.
+//! - `access-stats`: Count reads & writes made to each view. See the [stats][view::stats]
+//!   module for more information.
+//! - `index-u32`: Use `u32` strides & offsets in views. See [IndexType][view::parameters::IndexType].
+//! - `image`: Enable PNG heatmap output of 2D views in the [io] module.
+//! - `chrome-trace`: Enable the export of profiling records in the Chrome trace event
+//!   format. See the [profiling] module.
+//! - `ndarray`: Enable conversions between views & [ndarray][4] arrays. See the
+//!   [interop][view::interop] module.
+//! - `complex`: Enable views of [num-complex][5] `Complex<f32>` & `Complex<f64>` elements.
 //!
 //! ### C++ Interoperability
 //!
 //! The build script will read the `CXX` environment variable to choose which C++ compiler to use
 //! for Rust/C++ interop. Note that the crate itself does not currently use C++ code, only examples
-//! do.
+//! do. Views can be handed to C++ code using the handles of the [view::handle] module.
 //!
 //! #### Known issues
 //!
@@ -66,22 +77,71 @@
 //!
 //! [1]: https://kokkos.github.io/kokkos-core-wiki/index.html
 //! [2]: https://docs.rs/rayon/latest/rayon/
+//! [3]: https://wgpu.rs
+//! [4]: https://docs.rs/ndarray/latest/ndarray/
+//! [5]: https://docs.rs/num-complex/latest/num_complex/
 
 //#![feature(type_alias_impl_trait)]
 
 #[cxx::bridge(namespace = "")]
 /// C++ inter-op code
 pub mod ffi {
+    /// Element type of a view shared with C++. See [ViewHandle].
+    #[derive(Debug)]
+    enum ElementType {
+        /// `double`
+        F64,
+        /// `float`
+        F32,
+        /// `int32_t`
+        I32,
+        /// `int64_t`
+        I64,
+    }
+
     // C++ types and signatures exposed to Rust.
     unsafe extern "C++" {
         include!("poc-kokkos-rs/src/include/hello.hpp");
+        include!("poc-kokkos-rs/src/include/views.hpp");
 
         fn say_hello();
 
         fn say_many_hello();
+
+        /// Set each element of a `double` view to its row-major rank.
+        fn fill_iota(view: &mut ViewHandle);
+    }
+
+    // Rust types and signatures exposed to C++.
+    extern "Rust" {
+        /// Opaque handle to a view. See [view::handle][crate::view::handle].
+        type ViewHandle;
+
+        fn new_view_handle(element: ElementType, dims: &[usize]) -> Result<Box<ViewHandle>>;
+
+        fn element_type(self: &ViewHandle) -> ElementType;
+
+        fn rank(self: &ViewHandle) -> usize;
+
+        fn extent(self: &ViewHandle, dim: usize) -> usize;
+
+        fn stride(self: &ViewHandle, dim: usize) -> usize;
+
+        fn data(self: &mut ViewHandle) -> *mut u8;
     }
 }
 
+use view::handle::{new_view_handle, ViewHandle};
+
+pub mod blas;
+pub mod containers;
 pub mod functor;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod info;
+pub mod io;
+pub mod profiling;
 pub mod routines;
+pub mod runtime;
+pub mod sparse;
 pub mod view;
@@ -3,10 +3,9 @@
 use std::hint::black_box;
 
 use poc_kokkos_rs::{
-    functor::KernelArgs,
     routines::{
-        parallel_for,
-        parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        parameters::{ExecutionSpace, Range1D, Schedule, TypedExecutionPolicy},
+        typed::parallel_for,
     },
     view::{parameters::Layout, ViewOwned},
 };
@@ -43,26 +42,23 @@ fn main() {
     black_box(&mut bb);
     black_box(&mut cc);
 
-    let execp = ExecutionPolicy {
+    let execp = TypedExecutionPolicy {
         space: ExecutionSpace::DeviceCPU,
-        range: RangePolicy::RangePolicy(0..length),
+        policy: Range1D(0..length),
         schedule: Schedule::Static,
+        chunk_size: None,
+        chunk_predicate: None,
     };
 
     // C = alpha * A * B + beta * C
-    let gemm_kernel = |arg: KernelArgs<1>| match arg {
-        // lines
-        KernelArgs::Index1D(i) => {
-            // cols
-            for j in 0..length {
-                // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
-                let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
-                let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
-                cc.set([i, j], val);
-            }
+    let gemm_kernel = |i: usize| {
+        // cols
+        for j in 0..length {
+            // all b[k, j] for k values are adjacent in memory thanks to the LayoutLeft
+            let ab_ij: f64 = (0..length).map(|k| aa.get([i, k]) * bb.get([k, j])).sum();
+            let val: f64 = alpha * ab_ij + beta * cc.get([i, j]);
+            cc.set([i, j], val);
         }
-        KernelArgs::IndexND(_) => unimplemented!(),
-        KernelArgs::Handle => unimplemented!(),
     };
     parallel_for(execp, gemm_kernel).unwrap();
 }
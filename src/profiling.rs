@@ -0,0 +1,670 @@
+//! profiling related code
+//!
+//! This module contains code used to collect and export performance metrics of
+//! parallel statements. Collection is disabled by default and can be toggled at runtime
+//! using [`enable`] and [`disable`]. When enabled, each statement pushes a
+//! [`StatementRecord`] into a global store that can be fetched using [`records`].
+//!
+//! Statements are named using [`labeled`]: statements executed by its closure are
+//! recorded under the given label, akin to the label argument of Kokkos statements.
+//! Using [`labeled_with_bytes`], records also carry the number of bytes moved by each
+//! statement, from which the achieved bandwidth is computed.
+//!
+//! Records can then be exported to CSV using [`write_csv`] or [`export_csv`], for
+//! analysis using dataframe libraries (pandas, polars, ...), aggregated per label using
+//! [`summary`], or exported as a Chrome trace (`chrome://tracing`, Perfetto) using
+//! `write_chrome_trace` when the `chrome-trace` feature is enabled.
+//!
+//! External tools can be notified of each statement, Kokkos-Tools style, by registering
+//! a [`Tool`] using [`register_tool`]. Tools are notified even if record collection is
+//! disabled.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::profiling::{self, StatementRecord};
+//! use std::time::Duration;
+//!
+//! let record = StatementRecord {
+//!     label: "axpy".to_string(),
+//!     policy: "RangePolicy",
+//!     extents: vec![1024],
+//!     start: Duration::ZERO,
+//!     duration: Duration::from_micros(10),
+//!     bytes: Some(3 * 1024 * 8),
+//! };
+//!
+//! let mut out: Vec<u8> = Vec::new();
+//! profiling::write_csv(&mut out, &[record]).unwrap();
+//! ```
+//!
+//! Label statements and find the ones dominating runtime:
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     profiling,
+//!     routines::{
+//!         parameters::{Range1D, TypedExecutionPolicy},
+//!         typed::parallel_for,
+//!     },
+//! };
+//!
+//! profiling::enable();
+//! profiling::labeled("init", || {
+//!     parallel_for(TypedExecutionPolicy::new(Range1D(0..64)), |_| {}).unwrap()
+//! });
+//! profiling::disable();
+//!
+//! let summary = profiling::summary(&profiling::take_records());
+//! assert_eq!(summary[0].label, "init");
+//! assert_eq!(summary[0].count, 1);
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::routines::parameters::RangePolicy;
+
+// Records
+
+/// Metrics of a single statement execution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatementRecord {
+    /// Label of the statement. Empty if the statement was not labelled.
+    pub label: String,
+    /// Name of the range policy used by the statement.
+    pub policy: &'static str,
+    /// Extents of the iteration space. The meaning of each value depends on the policy,
+    /// e.g. `[league_size, team_size, vector_size]` for a team policy.
+    pub extents: Vec<usize>,
+    /// Start time of the statement, relative to the first measure of the process.
+    pub start: Duration,
+    /// Execution time of the statement, dispatch included.
+    pub duration: Duration,
+    /// Number of bytes moved by the statement, if known. Used to compute the bandwidth.
+    pub bytes: Option<usize>,
+}
+
+impl StatementRecord {
+    /// Returns the achieved bandwidth in GB/s, if the number of moved bytes is known.
+    pub fn bandwidth(&self) -> Option<f64> {
+        self.bytes
+            .map(|b| b as f64 / self.duration.as_secs_f64() / 1.0e9)
+    }
+}
+
+// Global store
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECORDS: Mutex<Vec<StatementRecord>> = Mutex::new(Vec::new());
+
+/// Start collecting statement records.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Stop collecting statement records. Already collected records are kept.
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Returns `true` if statement records are currently collected.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Push a record into the global store. This can be used to add records of
+/// user-timed sections. The record is pushed even if collection is disabled.
+pub fn record(rec: StatementRecord) {
+    RECORDS.lock().unwrap_or_else(|e| e.into_inner()).push(rec);
+}
+
+/// Returns a copy of all collected records.
+pub fn records() -> Vec<StatementRecord> {
+    RECORDS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Remove and return all collected records.
+pub fn take_records() -> Vec<StatementRecord> {
+    std::mem::take(&mut *RECORDS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+// Labels
+
+thread_local! {
+    static LABEL: RefCell<String> = const { RefCell::new(String::new()) };
+    static BYTES: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Execute `body`, statements it executes on the calling thread being labelled using
+/// `label`. Labels do not nest: the innermost one is used.
+pub fn labeled<R>(label: &str, body: impl FnOnce() -> R) -> R {
+    scoped(label, None, body)
+}
+
+/// Execute `body` like [`labeled`], each statement it executes being recorded as moving
+/// `bytes` bytes, e.g. `3 * n * size_of::<f64>()` for an axpy over `n` elements. This
+/// fills the `bytes` & `bandwidth_gbs` fields of the records.
+pub fn labeled_with_bytes<R>(label: &str, bytes: usize, body: impl FnOnce() -> R) -> R {
+    scoped(label, Some(bytes), body)
+}
+
+fn scoped<R>(label: &str, bytes: Option<usize>, body: impl FnOnce() -> R) -> R {
+    /// Restores the previous scope when dropped, even if `body` panics.
+    struct Restore(String, Option<usize>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            LABEL.with(|l| std::mem::swap(&mut *l.borrow_mut(), &mut self.0));
+            BYTES.with(|b| b.set(self.1));
+        }
+    }
+
+    let previous = LABEL.with(|l| l.replace(label.to_string()));
+    let _restore = Restore(previous, BYTES.with(|b| b.replace(bytes)));
+    body()
+}
+
+/// Returns the label applied to statements of the calling thread. Empty if no label is
+/// set.
+pub fn current_label() -> String {
+    LABEL.with(|l| l.borrow().clone())
+}
+
+/// Returns the number of bytes attributed to statements of the calling thread, if set
+/// using [`labeled_with_bytes`].
+pub fn current_bytes() -> Option<usize> {
+    BYTES.with(|b| b.get())
+}
+
+// Tools
+
+/// Description of a statement, passed to [`Tool`] callbacks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatementInfo {
+    /// Identifier of the statement, unique during the execution of the program.
+    pub id: u64,
+    /// Label of the statement. Empty if the statement was not labelled.
+    pub label: String,
+    /// Name of the range policy used by the statement.
+    pub policy: &'static str,
+    /// Extents of the iteration space. See [`StatementRecord::extents`].
+    pub extents: Vec<usize>,
+}
+
+/// Callbacks notified at the beginning & end of each statement.
+///
+/// Callbacks are executed synchronously by the thread executing the statement, hence
+/// they should be short.
+pub trait Tool: Send + Sync {
+    /// Called before the statement is dispatched.
+    fn begin_statement(&self, _info: &StatementInfo) {}
+
+    /// Called once the statement is complete, `duration` being its execution time.
+    fn end_statement(&self, _info: &StatementInfo, _duration: Duration) {}
+}
+
+static TOOLS: RwLock<Vec<Arc<dyn Tool>>> = RwLock::new(Vec::new());
+static HAS_TOOLS: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Register a tool notified of all subsequent statements.
+pub fn register_tool(tool: Arc<dyn Tool>) {
+    let mut tools = TOOLS.write().unwrap_or_else(|e| e.into_inner());
+    tools.push(tool);
+    HAS_TOOLS.store(true, Ordering::Relaxed);
+}
+
+/// Unregister all tools.
+pub fn clear_tools() {
+    let mut tools = TOOLS.write().unwrap_or_else(|e| e.into_inner());
+    tools.clear();
+    HAS_TOOLS.store(false, Ordering::Relaxed);
+}
+
+fn for_each_tool(f: impl Fn(&dyn Tool)) {
+    TOOLS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .for_each(|tool| f(tool.as_ref()));
+}
+
+// Statement instrumentation
+
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// Ongoing measure of a statement. Created by [`begin`], and consumed by
+/// [`ActiveStatement::end`] to push the corresponding record.
+pub struct ActiveStatement {
+    info: StatementInfo,
+    bytes: Option<usize>,
+    start: Instant,
+}
+
+/// Start measuring a statement using the given range policy. Returns `None` if
+/// collection is disabled and no tool is registered.
+pub fn begin<const N: usize>(range: &RangePolicy<N>) -> Option<ActiveStatement> {
+    let has_tools = HAS_TOOLS.load(Ordering::Relaxed);
+    if !is_enabled() && !has_tools {
+        return None;
+    }
+    let info = StatementInfo {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        label: current_label(),
+        policy: range.kind().name(),
+        extents: extents(range),
+    };
+    if has_tools {
+        for_each_tool(|tool| tool.begin_statement(&info));
+    }
+    let start = Instant::now();
+    EPOCH.get_or_init(|| start);
+    Some(ActiveStatement {
+        info,
+        bytes: current_bytes(),
+        start,
+    })
+}
+
+impl ActiveStatement {
+    /// Stop the measure, notify tools and push the resulting record into the global
+    /// store if collection is enabled.
+    pub fn end(self) {
+        let duration = self.start.elapsed();
+        if HAS_TOOLS.load(Ordering::Relaxed) {
+            for_each_tool(|tool| tool.end_statement(&self.info, duration));
+        }
+        if is_enabled() {
+            let epoch = *EPOCH.get_or_init(|| self.start);
+            record(StatementRecord {
+                label: self.info.label,
+                policy: self.info.policy,
+                extents: self.info.extents,
+                start: self.start.saturating_duration_since(epoch),
+                duration,
+                bytes: self.bytes,
+            })
+        }
+    }
+}
+
+// Timers
+
+/// Execution times of all statements sharing a label.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KernelSummary {
+    /// Label of the statements.
+    pub label: String,
+    /// Number of executions.
+    pub count: usize,
+    /// Cumulated execution time.
+    pub total: Duration,
+    /// Shortest execution time.
+    pub min: Duration,
+    /// Longest execution time.
+    pub max: Duration,
+}
+
+impl KernelSummary {
+    /// Returns the mean execution time.
+    pub fn mean(&self) -> Duration {
+        self.total / self.count as u32
+    }
+}
+
+/// Aggregate records per label, sorted by decreasing cumulated execution time.
+pub fn summary(records: &[StatementRecord]) -> Vec<KernelSummary> {
+    let mut per_label: HashMap<&str, KernelSummary> = HashMap::new();
+    for rec in records {
+        per_label
+            .entry(&rec.label)
+            .and_modify(|s| {
+                s.count += 1;
+                s.total += rec.duration;
+                s.min = s.min.min(rec.duration);
+                s.max = s.max.max(rec.duration);
+            })
+            .or_insert_with(|| KernelSummary {
+                label: rec.label.clone(),
+                count: 1,
+                total: rec.duration,
+                min: rec.duration,
+                max: rec.duration,
+            });
+    }
+    let mut res: Vec<KernelSummary> = per_label.into_values().collect();
+    res.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.label.cmp(&b.label)));
+    res
+}
+
+/// Returns the extents of a range policy.
+fn extents<const N: usize>(range: &RangePolicy<N>) -> Vec<usize> {
+    match range {
+        RangePolicy::RangePolicy(r) => vec![r.len()],
+        RangePolicy::MDRangePolicy(rs) | RangePolicy::TiledMDRangePolicy { ranges: rs, .. } => {
+            rs.iter().map(|r| r.len()).collect()
+        }
+        RangePolicy::TeamPolicy {
+            league_size,
+            team_size,
+            vector_size,
+            ..
+        } => vec![*league_size, *team_size, *vector_size],
+        _ => Vec::new(),
+    }
+}
+
+// Exporters
+
+/// Header line of the CSV output.
+pub const CSV_HEADER: &str = "label,policy,extents,duration_ns,bytes,bandwidth_gbs";
+
+/// Write records as CSV into `out`, header included.
+///
+/// Extents are written as a single `x`-separated field (e.g. `64x32`). The `bytes`
+/// and `bandwidth_gbs` fields are left empty when the number of moved bytes is unknown.
+pub fn write_csv<W: Write>(mut out: W, records: &[StatementRecord]) -> std::io::Result<()> {
+    writeln!(out, "{CSV_HEADER}")?;
+    for rec in records {
+        let extents = rec
+            .extents
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<String>>()
+            .join("x");
+        let bytes = rec.bytes.map(|b| b.to_string()).unwrap_or_default();
+        let bandwidth = rec.bandwidth().map(|b| b.to_string()).unwrap_or_default();
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            escape_csv(&rec.label),
+            rec.policy,
+            extents,
+            rec.duration.as_nanos(),
+            bytes,
+            bandwidth,
+        )?;
+    }
+    Ok(())
+}
+
+/// Write all collected records as CSV into the file at `path`.
+pub fn export_csv<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    write_csv(file, &records())
+}
+
+#[cfg(feature = "chrome-trace")]
+/// Write records in the Chrome trace event format into `out`. The output can be loaded
+/// using `chrome://tracing` or Perfetto.
+///
+/// Each record is a complete event named after its label (or its policy if it is not
+/// labelled); timestamps are in microseconds.
+///
+/// Only defined when the `chrome-trace` feature is enabled.
+pub fn write_chrome_trace<W: Write>(
+    mut out: W,
+    records: &[StatementRecord],
+) -> std::io::Result<()> {
+    writeln!(out, "[")?;
+    for (i, rec) in records.iter().enumerate() {
+        let name = if rec.label.is_empty() {
+            rec.policy
+        } else {
+            &rec.label
+        };
+        let extents = rec
+            .extents
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        let sep = if i + 1 < records.len() { "," } else { "" };
+        writeln!(
+            out,
+            "{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":0,\"tid\":0,\"args\":{{\"extents\":[{}]}}}}{sep}",
+            escape_json(name),
+            rec.policy,
+            rec.start.as_secs_f64() * 1.0e6,
+            rec.duration.as_secs_f64() * 1.0e6,
+            extents,
+        )?;
+    }
+    writeln!(out, "]")
+}
+
+#[cfg(feature = "chrome-trace")]
+/// Write all collected records in the Chrome trace event format into the file at `path`.
+///
+/// Only defined when the `chrome-trace` feature is enabled.
+pub fn export_chrome_trace<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    write_chrome_trace(file, &records())
+}
+
+#[cfg(feature = "chrome-trace")]
+/// Escape a JSON string.
+fn escape_json(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            c if (c as u32) < 0x20 => format!("\\u{:04x}", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Quote a CSV field if it contains a delimiter, a quote or a line break.
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        routines::{
+            parameters::{MDRange, Range1D, TypedExecutionPolicy},
+            typed::parallel_for,
+        },
+        runtime::{self, RuntimeConfig},
+    };
+    use std::sync::atomic::AtomicUsize;
+
+    /// Record of a statement labelled `label`, lasting `us` microseconds.
+    fn timed(label: &str, us: u64) -> StatementRecord {
+        StatementRecord {
+            label: label.to_string(),
+            policy: "RangePolicy",
+            extents: vec![8],
+            start: Duration::ZERO,
+            duration: Duration::from_micros(us),
+            bytes: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        begins: AtomicUsize,
+        ends: AtomicUsize,
+        labels: Mutex<Vec<String>>,
+    }
+
+    // other tests may execute statements concurrently: only ours are labelled
+    const PREFIX: &str = "tools:";
+
+    impl Tool for Counter {
+        fn begin_statement(&self, info: &StatementInfo) {
+            if info.label.starts_with(PREFIX) {
+                self.begins.fetch_add(1, Ordering::Relaxed);
+                self.labels.lock().unwrap().push(info.label.clone());
+            }
+        }
+
+        fn end_statement(&self, info: &StatementInfo, _duration: Duration) {
+            if info.label.starts_with(PREFIX) {
+                self.ends.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn own_records() -> Vec<StatementRecord> {
+        take_records()
+            .into_iter()
+            .filter(|rec| rec.label.starts_with(PREFIX))
+            .collect()
+    }
+
+    #[test]
+    fn labels_and_tools() {
+        let counter = Arc::new(Counter::default());
+        let tool = counter.clone();
+        runtime::scoped(RuntimeConfig::default(), move || {
+            register_tool(tool);
+            // tools are notified while collection is disabled
+            labeled("tools:outer", || {
+                parallel_for(TypedExecutionPolicy::new(Range1D(0..4)), |_| {}).unwrap();
+                labeled("tools:inner", || {
+                    parallel_for(TypedExecutionPolicy::new(MDRange([0..2, 0..2])), |_| {}).unwrap()
+                });
+                assert_eq!(current_label(), "tools:outer");
+            });
+            assert_eq!(current_label(), "");
+            assert!(own_records().is_empty());
+
+            enable();
+            for label in ["tools:a", "tools:b"] {
+                labeled(label, || {
+                    parallel_for(TypedExecutionPolicy::new(Range1D(0..4)), |_| {}).unwrap()
+                });
+            }
+            labeled_with_bytes("tools:c", 64 * 8, || {
+                parallel_for(TypedExecutionPolicy::new(Range1D(0..64)), |_| {}).unwrap()
+            });
+            assert_eq!(current_bytes(), None);
+            let recs = own_records();
+            assert_eq!(recs.len(), 3);
+            assert_eq!(recs[0].label, "tools:a");
+            assert_eq!(recs[0].extents, vec![4]);
+            assert_eq!(recs[0].bytes, None);
+            assert!(recs[0].start <= recs[1].start);
+            assert_eq!(recs[2].bytes, Some(512));
+
+            // bandwidth is written for statements with a byte count
+            let mut out: Vec<u8> = Vec::new();
+            write_csv(&mut out, &recs[2..]).unwrap();
+            let out = String::from_utf8(out).unwrap();
+            let fields: Vec<&str> = out.lines().nth(1).unwrap().split(',').collect();
+            assert_eq!(fields[4], "512");
+            assert!(fields[5].parse::<f64>().unwrap() > 0.0);
+        })
+        .unwrap();
+
+        assert_eq!(counter.begins.load(Ordering::Relaxed), 5);
+        assert_eq!(counter.ends.load(Ordering::Relaxed), 5);
+        assert_eq!(
+            *counter.labels.lock().unwrap(),
+            vec![
+                "tools:outer",
+                "tools:inner",
+                "tools:a",
+                "tools:b",
+                "tools:c"
+            ]
+        );
+        // tools are cleared by finalization
+        assert!(!HAS_TOOLS.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn kernel_summary() {
+        let records = [timed("a", 10), timed("b", 30), timed("a", 20), timed("", 5)];
+        let summary = summary(&records);
+        let labels: Vec<&str> = summary.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, vec!["a", "b", ""]);
+        assert_eq!(summary[0].count, 2);
+        assert_eq!(summary[0].total, Duration::from_micros(30));
+        assert_eq!(summary[0].min, Duration::from_micros(10));
+        assert_eq!(summary[0].max, Duration::from_micros(20));
+        assert_eq!(summary[0].mean(), Duration::from_micros(15));
+        assert!(super::summary(&[]).is_empty());
+    }
+
+    #[cfg(feature = "chrome-trace")]
+    #[test]
+    fn chrome_trace() {
+        let mut rec = timed("say \"hi\"", 2);
+        rec.start = Duration::from_micros(5);
+        let records = [rec, timed("", 1)];
+        let mut out: Vec<u8> = Vec::new();
+        write_chrome_trace(&mut out, &records).unwrap();
+        let ref_out = "[\n\
+            {\"name\":\"say \\\"hi\\\"\",\"cat\":\"RangePolicy\",\"ph\":\"X\",\"ts\":5.000,\"dur\":2.000,\"pid\":0,\"tid\":0,\"args\":{\"extents\":[8]}},\n\
+            {\"name\":\"RangePolicy\",\"cat\":\"RangePolicy\",\"ph\":\"X\",\"ts\":0.000,\"dur\":1.000,\"pid\":0,\"tid\":0,\"args\":{\"extents\":[8]}}\n\
+            ]\n";
+        assert_eq!(String::from_utf8(out).unwrap(), ref_out);
+    }
+
+    #[test]
+    fn csv_output() {
+        let records = [
+            StatementRecord {
+                label: "axpy".to_string(),
+                policy: "RangePolicy",
+                extents: vec![1000],
+                start: Duration::ZERO,
+                duration: Duration::from_micros(2),
+                bytes: Some(4000),
+            },
+            StatementRecord {
+                label: "init, \"2D\"".to_string(),
+                policy: "MDRangePolicy",
+                extents: vec![10, 20],
+                start: Duration::from_micros(3),
+                duration: Duration::from_nanos(150),
+                bytes: None,
+            },
+        ];
+        let mut out: Vec<u8> = Vec::new();
+        write_csv(&mut out, &records).unwrap();
+
+        let ref_out = format!(
+            "{CSV_HEADER}\n\
+             axpy,RangePolicy,1000,2000,4000,2\n\
+             \"init, \"\"2D\"\"\",MDRangePolicy,10x20,150,,\n"
+        );
+        assert_eq!(String::from_utf8(out).unwrap(), ref_out);
+    }
+
+    #[test]
+    fn policy_extents() {
+        let range: RangePolicy<2> = RangePolicy::MDRangePolicy([0..4, 2..5]);
+        assert_eq!(extents(&range), vec![4, 3]);
+        let range: RangePolicy<1> = RangePolicy::TeamPolicy {
+            league_size: 8,
+            team_size: 4,
+            vector_size: 1,
+            scratch_size: [0; 2],
+        };
+        assert_eq!(extents(&range), vec![8, 4, 1]);
+    }
+}
@@ -0,0 +1,374 @@
+//! kernel capture analysis code
+//!
+//! This module contains a checker of the views captured by a kernel. Several views can
+//! give access to the same memory, e.g. a view & its mirrors, or overlapping subviews:
+//! a kernel writing through one of them while accessing the other through another
+//! captured view races with itself, which the borrow checker cannot detect since views
+//! are written through shared references when using parallelization features.
+//!
+//! Kernels declare the views they read & write in a [Captures] set, passed to the
+//! statement using [PolicyBuilder::captures]. Before dispatching the statement, the
+//! memory accessible through declared views is compared: distinct views sharing elements
+//! conflict if at least one of them is written. Declaring the same view for both reads
+//! & writes is not a conflict. Strided views, e.g. the columns of a
+//! [Layout::Right][crate::view::parameters::Layout::Right] matrix, are compared element
+//! by element, so that disjoint views interleaved in memory do not conflict.
+//!
+//! The check is only done in debug builds. Conflicts are reported according to the
+//! global [AliasingMode]: as a warning on the standard error output by default, or as
+//! a [StatementError::Aliasing] error.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     routines::{
+//!         aliasing::{self, AliasingMode, Captures},
+//!         parameters::PolicyBuilder,
+//!     },
+//!     view::{parameters::Layout, ViewOwned},
+//! };
+//!
+//! let mut view: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [8]);
+//! #[allow(unused_mut)]
+//! let mut mirror = view.create_mutable_mirror().unwrap();
+//! let other: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [8]);
+//!
+//! let previous = aliasing::set_mode(AliasingMode::Deny);
+//! // mirror & other do not overlap
+//! let captures = Captures::new().read(&other).write(&mirror);
+//! assert!(captures.check().is_ok());
+//! PolicyBuilder::range(0..8)
+//!     .captures(captures)
+//!     .parallel_for(|i| mirror.set([i], other.get([i])))
+//!     .unwrap();
+//! aliasing::set_mode(previous);
+//! ```
+//!
+//! [PolicyBuilder::captures]: super::parameters::PolicyBuilder::captures
+
+use std::{
+    fmt::Display,
+    ops::Range,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use super::{iter::MDIndexIter, StatementError};
+use crate::view::{parameters::DataTraits, ViewBase};
+
+// Enums
+
+/// Kind of access of a kernel to a captured view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// The kernel only reads the view.
+    Read,
+    /// The kernel writes the view, and may read it.
+    Write,
+}
+
+/// Reporting of conflicts detected before dispatching a statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AliasingMode {
+    /// Declared captures are not checked.
+    Ignore,
+    /// Conflicts are printed on the standard error output; the statement is executed.
+    #[default]
+    Warn,
+    /// Conflicts are returned as [StatementError::Aliasing] errors; the statement is not
+    /// executed.
+    Deny,
+}
+
+/// Conflict between two captured views, identified by their label, or by their
+/// position in the [Captures] set if they are not labelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasingError {
+    /// Both views are written.
+    WriteWrite(String, String),
+    /// The first view is written, the second one is read.
+    WriteRead(String, String),
+}
+
+impl Display for AliasingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AliasingError::WriteWrite(first, second) => {
+                write!(f, "written views {first} & {second} overlap")
+            }
+            AliasingError::WriteRead(written, read) => {
+                write!(f, "written view {written} overlaps read view {read}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AliasingError {}
+
+// Global mode
+
+static MODE: AtomicU8 = AtomicU8::new(AliasingMode::Warn as u8);
+
+fn decode(raw: u8) -> AliasingMode {
+    match raw {
+        0 => AliasingMode::Ignore,
+        1 => AliasingMode::Warn,
+        _ => AliasingMode::Deny,
+    }
+}
+
+/// Returns the current reporting mode of conflicts.
+pub fn mode() -> AliasingMode {
+    decode(MODE.load(Ordering::Relaxed))
+}
+
+/// Set the reporting mode of conflicts. Returns the previous mode.
+pub fn set_mode(mode: AliasingMode) -> AliasingMode {
+    decode(MODE.swap(mode as u8, Ordering::Relaxed))
+}
+
+// Captures
+
+/// View declared in a [Captures] set.
+#[derive(Debug, Clone)]
+struct CapturedView {
+    name: String,
+    access: Access,
+    /// Address of the view itself, identifying it.
+    view: usize,
+    /// Address ranges of the memory accessible through the view, sorted & disjoint.
+    regions: Vec<Range<usize>>,
+}
+
+impl CapturedView {
+    /// Returns `true` if the views share at least one address.
+    fn overlaps(&self, other: &CapturedView) -> bool {
+        let (mut lhs, mut rhs) = (
+            self.regions.iter().peekable(),
+            other.regions.iter().peekable(),
+        );
+        while let (Some(l), Some(r)) = (lhs.peek(), rhs.peek()) {
+            if l.start < r.end && r.start < l.end {
+                return true;
+            }
+            // skip the range ending first, it cannot overlap the following ones
+            if l.end <= r.end {
+                lhs.next();
+            } else {
+                rhs.next();
+            }
+        }
+        false
+    }
+}
+
+/// Returns the address ranges of the memory accessible through `view`, sorted &
+/// disjoint. Contiguous views span a single range; elements of other views are listed
+/// one by one, adjacent elements being merged.
+fn regions<const N: usize, T: DataTraits>(view: &ViewBase<'_, N, T>) -> Vec<Range<usize>> {
+    let data = view.data_slice();
+    let span = data.as_ptr_range();
+    if data.len() == view.dims().iter().product() {
+        return std::iter::once(span.start as usize..span.end as usize).collect();
+    }
+    let size = std::mem::size_of_val(data) / data.len();
+    let mut starts: Vec<usize> = MDIndexIter::new(view.dims().map(|d| 0..d))
+        .map(|idx| span.start as usize + view.flat_idx(idx) * size)
+        .collect();
+    starts.sort_unstable();
+    let mut regions: Vec<Range<usize>> = Vec::new();
+    for start in starts {
+        match regions.last_mut() {
+            Some(last) if last.end == start => last.end += size,
+            _ => regions.push(start..start + size),
+        }
+    }
+    regions
+}
+
+/// Set of the views captured by a kernel, along with their access.
+#[derive(Debug, Clone, Default)]
+pub struct Captures {
+    views: Vec<CapturedView>,
+}
+
+impl Captures {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare `view` as read by the kernel.
+    pub fn read<const N: usize, T: DataTraits>(self, view: &ViewBase<'_, N, T>) -> Self {
+        self.capture(view, Access::Read)
+    }
+
+    /// Declare `view` as written by the kernel.
+    pub fn write<const N: usize, T: DataTraits>(self, view: &ViewBase<'_, N, T>) -> Self {
+        self.capture(view, Access::Write)
+    }
+
+    fn capture<const N: usize, T: DataTraits>(
+        mut self,
+        view: &ViewBase<'_, N, T>,
+        access: Access,
+    ) -> Self {
+        let name = match view.label() {
+            "" => format!("#{}", self.views.len()),
+            label => format!("`{label}`"),
+        };
+        self.views.push(CapturedView {
+            name,
+            access,
+            view: view as *const _ as usize,
+            regions: regions(view),
+        });
+        self
+    }
+
+    /// Returns the first conflict between declared views, in declaration order.
+    pub fn check(&self) -> Result<(), AliasingError> {
+        for (k, first) in self.views.iter().enumerate() {
+            for second in &self.views[k + 1..] {
+                if first.view == second.view || !first.overlaps(second) {
+                    continue;
+                }
+                match (first.access, second.access) {
+                    (Access::Write, Access::Write) => {
+                        return Err(AliasingError::WriteWrite(
+                            first.name.clone(),
+                            second.name.clone(),
+                        ))
+                    }
+                    (Access::Write, Access::Read) => {
+                        return Err(AliasingError::WriteRead(
+                            first.name.clone(),
+                            second.name.clone(),
+                        ))
+                    }
+                    (Access::Read, Access::Write) => {
+                        return Err(AliasingError::WriteRead(
+                            second.name.clone(),
+                            first.name.clone(),
+                        ))
+                    }
+                    (Access::Read, Access::Read) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the set according to the current [AliasingMode]. Does nothing in release
+    /// builds.
+    pub(crate) fn enforce(&self) -> Result<(), StatementError> {
+        if !cfg!(debug_assertions) {
+            return Ok(());
+        }
+        match (mode(), self.check()) {
+            (AliasingMode::Ignore, _) | (_, Ok(())) => Ok(()),
+            (AliasingMode::Warn, Err(e)) => {
+                eprintln!("warning: kernel captures conflict: {e}");
+                Ok(())
+            }
+            (AliasingMode::Deny, Err(e)) => Err(StatementError::Aliasing(e)),
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        routines::parameters::PolicyBuilder,
+        view::{parameters::Layout, subview::SliceArg, ViewOwned},
+    };
+
+    #[test]
+    fn overlapping_captures() {
+        let mat: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [4, 4]).with_label("mat");
+        let other: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [4]);
+        let (row0, row1) = (
+            mat.subview::<1>([SliceArg::Index(0), SliceArg::All])
+                .unwrap(),
+            mat.subview::<1>([SliceArg::Index(1), SliceArg::All])
+                .unwrap(),
+        );
+        let mirror = mat.create_mirror().unwrap();
+
+        // disjoint regions, shared reads & in-place updates are fine
+        let ok = Captures::new()
+            .write(&row0)
+            .write(&row1)
+            .read(&other)
+            .write(&other);
+        assert_eq!(ok.check(), Ok(()));
+        let ok = Captures::new().read(&mat).read(&mirror).read(&row0);
+        assert_eq!(ok.check(), Ok(()));
+
+        let err = Captures::new().read(&row1).write(&mat).check();
+        assert_eq!(
+            err,
+            Err(AliasingError::WriteRead(
+                "`mat`".to_owned(),
+                "#0".to_owned()
+            ))
+        );
+        let err = Captures::new().write(&mirror).write(&row0).check();
+        assert_eq!(
+            err,
+            Err(AliasingError::WriteWrite("#0".to_owned(), "#1".to_owned()))
+        );
+
+        // conflicting statements are not executed when denied
+        let previous = set_mode(AliasingMode::Deny);
+        let res = PolicyBuilder::range(0..4)
+            .captures(Captures::new().read(&row1).write(&mat))
+            .parallel_for(|_| panic!("statement executed"));
+        assert!(matches!(res, Err(StatementError::Aliasing(_))));
+        set_mode(AliasingMode::Ignore);
+        let res = PolicyBuilder::range(0..4)
+            .captures(Captures::new().read(&row1).write(&mat))
+            .parallel_for(|_| {});
+        assert!(res.is_ok());
+        set_mode(previous);
+    }
+
+    #[test]
+    fn strided_captures() {
+        let mat: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [4, 4]).with_label("mat");
+        let column = |j: usize| {
+            mat.subview::<1>([SliceArg::All, SliceArg::Index(j)])
+                .unwrap()
+        };
+        let (col0, col1) = (column(0), column(1));
+        let row1 = mat
+            .subview::<1>([SliceArg::Index(1), SliceArg::All])
+            .unwrap();
+        let corner = mat
+            .subview::<2>([SliceArg::Range(2..4), SliceArg::Range(2..4)])
+            .unwrap();
+
+        // columns are interleaved in memory, but do not share any element
+        assert_eq!(Captures::new().write(&col0).write(&col1).check(), Ok(()));
+        assert_eq!(Captures::new().write(&col1).read(&corner).check(), Ok(()));
+        assert_eq!(
+            Captures::new().write(&col0).read(&row1).check(),
+            Err(AliasingError::WriteRead("#0".to_owned(), "#1".to_owned()))
+        );
+        assert_eq!(
+            Captures::new().read(&mat).write(&column(3)).check(),
+            Err(AliasingError::WriteRead(
+                "#1".to_owned(),
+                "`mat`".to_owned()
+            ))
+        );
+        assert_eq!(
+            Captures::new().write(&corner).write(&column(2)).check(),
+            Err(AliasingError::WriteWrite("#0".to_owned(), "#1".to_owned()))
+        );
+    }
+}
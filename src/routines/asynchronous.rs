@@ -0,0 +1,307 @@
+//! asynchronous statement related code
+//!
+//! This module contains asynchronous variants of the [typed][super::typed] statements.
+//! Instead of blocking until completion, they return a [StatementFuture] right after
+//! the launch, which makes it possible to overlap independent statements, e.g. using
+//! different [instances][super::instance]:
+//!
+//! - [StatementFuture::wait] blocks until the statement is complete & returns its
+//!   result,
+//! - [StatementFuture] implements [Future], so it can be awaited from an executor,
+//! - [fence] blocks until all asynchronous statements are complete, like
+//!   `Kokkos::fence`; [CpuInstance::fence] only waits for the statements of an instance.
+//!
+//! Each statement is launched from a dedicated thread, kernels being executed by the
+//! backend used by blocking statements. Kernels must therefore be `'static`: data is
+//! usually shared with them using an [Arc].
+//!
+//! ### Example
+//!
+//! ```rust
+//! use std::sync::{
+//!     atomic::{AtomicUsize, Ordering},
+//!     Arc,
+//! };
+//!
+//! use poc_kokkos_rs::routines::{
+//!     asynchronous::{fence, parallel_for, parallel_reduce},
+//!     parameters::{Range1D, ReduceOp, TypedExecutionPolicy},
+//! };
+//!
+//! let count = Arc::new(AtomicUsize::new(0));
+//! let c = count.clone();
+//! let fut_for = parallel_for(TypedExecutionPolicy::new(Range1D(0..100)), move |_| {
+//!     c.fetch_add(1, Ordering::Relaxed);
+//! });
+//! let fut_sum = parallel_reduce(TypedExecutionPolicy::new(Range1D(0..100)), ReduceOp::Sum, |i| i);
+//!
+//! // ... overlapped host work ...
+//!
+//! assert_eq!(fut_sum.wait().unwrap(), 4950);
+//! fence();
+//! assert!(fut_for.is_complete());
+//! assert_eq!(count.load(Ordering::Relaxed), 100);
+//! ```
+
+use std::{
+    future::Future,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+#[cfg(doc)]
+use super::instance::CpuInstance;
+use super::{
+    instance,
+    parameters::{ExecutionSpace, Policy, Reducer, TypedExecutionPolicy},
+    typed, StatementError,
+};
+use crate::profiling;
+
+type Outcome<T> = thread::Result<Result<T, StatementError>>;
+
+/// Completion state shared by a future & the thread executing its statement.
+struct Shared<T> {
+    state: Mutex<(Option<Outcome<T>>, Option<Waker>)>,
+    done: Condvar,
+}
+
+/// Handle to an asynchronous statement, completing with the result of the statement.
+///
+/// Dropping the handle does not cancel the statement. Panics of the kernel are
+/// propagated when the result is retrieved.
+pub struct StatementFuture<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> StatementFuture<T> {
+    /// Returns `true` if the statement is complete, i.e. if [StatementFuture::wait]
+    /// would not block.
+    pub fn is_complete(&self) -> bool {
+        self.lock().0.is_some()
+    }
+
+    /// Block until the statement is complete and returns its result.
+    pub fn wait(self) -> Result<T, StatementError> {
+        let state = self.lock();
+        let mut state = self
+            .shared
+            .done
+            .wait_while(state, |(outcome, _)| outcome.is_none())
+            .unwrap_or_else(|e| e.into_inner());
+        unwrap_outcome(state.0.take().expect("outcome is set"))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, (Option<Outcome<T>>, Option<Waker>)> {
+        self.shared.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T> Future for StatementFuture<T> {
+    type Output = Result<T, StatementError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.lock();
+        match state.0.take() {
+            Some(outcome) => Poll::Ready(unwrap_outcome(outcome)),
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn unwrap_outcome<T>(outcome: Outcome<T>) -> Result<T, StatementError> {
+    outcome.unwrap_or_else(|payload| resume_unwind(payload))
+}
+
+// Launch
+
+static IN_FLIGHT: Mutex<usize> = Mutex::new(0);
+static IDLE: Condvar = Condvar::new();
+
+/// Marks an asynchronous statement as complete when dropped.
+struct Pending;
+
+impl Pending {
+    fn new() -> Self {
+        *IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        Pending
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+        *in_flight -= 1;
+        if *in_flight == 0 {
+            IDLE.notify_all();
+        }
+    }
+}
+
+/// Execute `statement` on a new thread. The statement is registered on its instance
+/// before returning, so that fences issued right after the launch wait for it.
+fn launch<T: Send + 'static>(
+    space: ExecutionSpace,
+    statement: impl FnOnce() -> Result<T, StatementError> + Send + 'static,
+) -> StatementFuture<T> {
+    let shared = Arc::new(Shared {
+        state: Mutex::new((None, None)),
+        done: Condvar::new(),
+    });
+    let pending = Pending::new();
+    let in_flight = match space {
+        ExecutionSpace::DeviceCPUInstance(inst) => instance::track(inst),
+        _ => None,
+    };
+    let thread_shared = shared.clone();
+    // statements keep the label of the launching thread
+    let label = profiling::current_label();
+    thread::spawn(move || {
+        let outcome = catch_unwind(AssertUnwindSafe(|| profiling::labeled(&label, statement)));
+        let waker = {
+            let mut state = thread_shared
+                .state
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            state.0 = Some(outcome);
+            state.1.take()
+        };
+        thread_shared.done.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        drop(in_flight);
+        drop(pending);
+    });
+    StatementFuture { shared }
+}
+
+/// Block until all asynchronous statements, as well as all statements dispatched on
+/// instances, are complete.
+pub fn fence() {
+    let in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+    let _idle = IDLE
+        .wait_while(in_flight, |n| *n > 0)
+        .unwrap_or_else(|e| e.into_inner());
+    instance::fence_all();
+}
+
+// Statements
+
+/// Asynchronous Parallel For statement. See [typed::parallel_for].
+pub fn parallel_for<const N: usize, P>(
+    execp: TypedExecutionPolicy<P>,
+    func: impl Fn(P::Arg) + Send + Sync + 'static,
+) -> StatementFuture<()>
+where
+    P: Policy<N> + Send + 'static,
+{
+    launch(execp.space, move || typed::parallel_for(execp, func))
+}
+
+/// Asynchronous Parallel Reduce statement. See [typed::parallel_reduce].
+pub fn parallel_reduce<const N: usize, P, R>(
+    execp: TypedExecutionPolicy<P>,
+    op: R,
+    func: impl Fn(P::Arg) -> R::Value + Send + Sync + 'static,
+) -> StatementFuture<R::Value>
+where
+    P: Policy<N> + Send + 'static,
+    R: Reducer + Send + Sync + 'static,
+    R::Value: Send + 'static,
+{
+    launch(execp.space, move || typed::parallel_reduce(execp, op, func))
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::{
+        instance::CpuInstance,
+        parameters::{MDRange, Range1D, ReduceOp, Schedule},
+    };
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        task::Wake,
+        time::Duration,
+    };
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Minimal executor driving a single future on the calling thread.
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = std::pin::pin!(fut);
+        loop {
+            match fut.as_mut().poll(&mut cx) {
+                Poll::Ready(out) => return out,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn overlapped_statements() {
+        let instances = CpuInstance::partition(&[1, 1]).unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let futures: Vec<_> = instances
+            .iter()
+            .map(|inst| {
+                let execp = TypedExecutionPolicy {
+                    space: ExecutionSpace::DeviceCPUInstance(*inst),
+                    policy: MDRange([0..10, 0..10]),
+                    schedule: Schedule::Static,
+                    chunk_size: None,
+                    chunk_predicate: None,
+                };
+                let count = count.clone();
+                parallel_for(execp, move |_| {
+                    thread::sleep(Duration::from_micros(100));
+                    count.fetch_add(1, Ordering::Relaxed);
+                })
+            })
+            .collect();
+        instances.iter().for_each(CpuInstance::fence);
+        assert_eq!(count.load(Ordering::Relaxed), 200);
+        assert!(futures.iter().all(StatementFuture::is_complete));
+
+        let fut = parallel_reduce(
+            TypedExecutionPolicy::new(Range1D(0..1000)),
+            ReduceOp::Max,
+            |i| i,
+        );
+        assert_eq!(block_on(fut).unwrap(), 999);
+
+        let fut = parallel_for(TypedExecutionPolicy::new(Range1D(0..10)), |_| {});
+        fence();
+        assert!(fut.is_complete());
+        assert!(fut.wait().is_ok());
+    }
+
+    #[test]
+    fn kernel_panic() {
+        let fut = parallel_for(TypedExecutionPolicy::new(Range1D(0..10)), |i| {
+            assert!(i < 5, "out of range")
+        });
+        let res = catch_unwind(AssertUnwindSafe(|| fut.wait()));
+        assert!(res.is_err());
+        // failed statements do not block fences
+        fence();
+    }
+}
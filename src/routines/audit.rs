@@ -0,0 +1,260 @@
+//! determinism audit code
+//!
+//! This module contains code used to check whether the result of a statement depends
+//! on its execution order. The statement is executed twice, using different thread
+//! counts and schedules, and the resulting views are compared elementwise.
+//!
+//! Differing results usually point to a data race, or to a floating-point reduction
+//! whose combine order depends on the dispatch.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     functor::KernelArgs,
+//!     routines::{
+//!         audit::{audit_determinism, AuditRun, Tolerance},
+//!         parallel_for,
+//!         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy},
+//!     },
+//!     view::{parameters::Layout, ViewOwned},
+//! };
+//!
+//! let runs = [AuditRun::new(1), AuditRun::new(4)];
+//! let report = audit_determinism(runs, Tolerance::Bitwise, |run| {
+//!     #[allow(unused_mut)]
+//!     let mut view: ViewOwned<'static, 1, f64> = ViewOwned::new(Layout::Right, [100]);
+//!     let execp = ExecutionPolicy {
+//!         space: ExecutionSpace::DeviceCPU,
+//!         range: RangePolicy::RangePolicy(0..100),
+//!         schedule: run.schedule.clone(),
+//!         chunk_size: None,
+//!         chunk_predicate: None,
+//!     };
+//!     let kernel = |arg: KernelArgs<1>| match arg {
+//!         KernelArgs::Index1D(i) => view.set([i], i as f64 * 0.5),
+//!         KernelArgs::IndexND(_) => unimplemented!(),
+//!         KernelArgs::Handle(_) => unimplemented!(),
+//!     };
+//!     parallel_for(execp, kernel)?;
+//!     Ok(view)
+//! })
+//! .unwrap();
+//!
+//! assert!(report.is_deterministic());
+//! ```
+
+use std::fmt::Display;
+
+use super::{parameters::Schedule, StatementError};
+use crate::{
+    runtime::{self, RuntimeConfig, RuntimeError},
+    view::ViewOwned,
+};
+
+// Enums
+
+/// Enum used to classify possible errors occuring during an audit.
+#[derive(Debug)]
+pub enum AuditError {
+    /// The runtime could not be configured with the requested thread count.
+    Runtime(RuntimeError),
+    /// Error occured during one of the audited statements.
+    Statement(StatementError),
+}
+
+impl From<RuntimeError> for AuditError {
+    fn from(e: RuntimeError) -> Self {
+        AuditError::Runtime(e)
+    }
+}
+
+impl From<StatementError> for AuditError {
+    fn from(e: StatementError) -> Self {
+        AuditError::Statement(e)
+    }
+}
+
+impl Display for AuditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditError::Runtime(e) => write!(f, "error during runtime setup: {e}"),
+            AuditError::Statement(e) => write!(f, "error during audited statement: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AuditError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AuditError::Runtime(e) => Some(e),
+            AuditError::Statement(e) => Some(e),
+        }
+    }
+}
+
+/// Criterion used to compare results of the two runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// Values must be bitwise identical.
+    Bitwise,
+    /// Values must not differ by more than the given amount.
+    Absolute(f64),
+    /// Values must not differ by more than the given fraction of the first run's value.
+    Relative(f64),
+}
+
+impl Tolerance {
+    /// Returns `true` if the two values are considered equal.
+    pub(crate) fn accepts(&self, a: f64, b: f64) -> bool {
+        match self {
+            Tolerance::Bitwise => a.to_bits() == b.to_bits(),
+            Tolerance::Absolute(tol) => (a - b).abs() <= *tol,
+            Tolerance::Relative(tol) => (a - b).abs() <= *tol * a.abs(),
+        }
+    }
+}
+
+// Parameters & results
+
+/// Execution parameters of one of the audited runs.
+#[derive(Debug, Clone)]
+pub struct AuditRun {
+    /// Number of threads used by CPU dispatch during the run.
+    pub threads: usize,
+    /// Schedule that should be used by the statement's policies.
+    pub schedule: Schedule,
+}
+
+impl AuditRun {
+    /// Create run parameters using the given thread count & the default schedule.
+    pub fn new(threads: usize) -> Self {
+        Self {
+            threads,
+            schedule: Schedule::default(),
+        }
+    }
+}
+
+/// Result of an audit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditReport {
+    /// Number of compared elements.
+    pub compared: usize,
+    /// Number of elements differing according to the tolerance.
+    pub mismatches: usize,
+    /// Index (in layout order) of the first differing element, if any.
+    pub first_mismatch: Option<usize>,
+    /// Largest absolute difference between the two runs.
+    pub max_abs_diff: f64,
+}
+
+impl AuditReport {
+    /// Returns `true` if both runs produced the same results.
+    pub fn is_deterministic(&self) -> bool {
+        self.mismatches == 0
+    }
+}
+
+// Routines
+
+/// Execute `statement` once per run parameters & compare the produced views.
+///
+/// For each run, the runtime is initialized using its thread count, hence it should not
+/// be initialized when calling this function. Thread counts have no effect when no
+/// parallelization feature is enabled.
+///
+/// Views produced by the runs must have the same dimensions & layout, otherwise a
+/// [StatementError::DimensionMismatch] error is returned.
+pub fn audit_determinism<const N: usize>(
+    runs: [AuditRun; 2],
+    tolerance: Tolerance,
+    mut statement: impl FnMut(&AuditRun) -> Result<ViewOwned<'static, N, f64>, StatementError>,
+) -> Result<AuditReport, AuditError> {
+    let mut results = Vec::with_capacity(2);
+    for run in &runs {
+        let config = RuntimeConfig {
+            num_threads: Some(run.threads),
+            ..Default::default()
+        };
+        results.push(runtime::scoped(config, || statement(run))??);
+    }
+    let (first, second) = (&results[0], &results[1]);
+    if first.dims() != second.dims() || first.layout != second.layout {
+        return Err(StatementError::DimensionMismatch.into());
+    }
+
+    let mut report = AuditReport {
+        compared: 0,
+        mismatches: 0,
+        first_mismatch: None,
+        max_abs_diff: 0.0,
+    };
+    first
+        .values()
+        .zip(second.values())
+        .enumerate()
+        .for_each(|(idx, (a, b))| {
+            report.compared += 1;
+            report.max_abs_diff = report.max_abs_diff.max((a - b).abs());
+            if !tolerance.accepts(a, b) {
+                report.mismatches += 1;
+                report.first_mismatch.get_or_insert(idx);
+            }
+        });
+    Ok(report)
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::parameters::Layout;
+
+    #[test]
+    fn order_dependent() {
+        let runs = [AuditRun::new(1), AuditRun::new(2)];
+        // simulate an order-dependent reduction
+        let statement = |run: &AuditRun| {
+            let sum = if run.threads == 1 {
+                (0.1 + 0.2) + 0.3
+            } else {
+                0.1 + (0.2 + 0.3)
+            };
+            Ok(ViewOwned::new_from_data(vec![1.0, sum], Layout::Right, [2]))
+        };
+
+        let report = audit_determinism(runs.clone(), Tolerance::Bitwise, statement).unwrap();
+        assert!(!report.is_deterministic());
+        assert_eq!(report.first_mismatch, Some(1));
+        assert_eq!(report.compared, 2);
+
+        let report = audit_determinism(runs, Tolerance::Relative(1e-12), statement).unwrap();
+        assert!(report.is_deterministic());
+    }
+
+    #[test]
+    fn padded_views() {
+        let runs = [AuditRun::new(1), AuditRun::new(2)];
+        let report = audit_determinism(runs, Tolerance::Bitwise, |_| {
+            Ok(ViewOwned::new_padded(Layout::Right, [3, 5], 64))
+        })
+        .unwrap();
+        // padding elements are not compared
+        assert_eq!(report.compared, 15);
+    }
+
+    #[test]
+    fn shape_mismatch() {
+        let runs = [AuditRun::new(1), AuditRun::new(2)];
+        let res = audit_determinism(runs, Tolerance::Bitwise, |run| {
+            Ok(ViewOwned::new(Layout::Right, [run.threads]))
+        });
+        assert!(matches!(
+            res,
+            Err(AuditError::Statement(StatementError::DimensionMismatch))
+        ));
+    }
+}
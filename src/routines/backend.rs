@@ -0,0 +1,181 @@
+//! CPU backend selection related code
+//!
+//! This module contains the selection of the backend executing statements that target
+//! the CPU, i.e. [ExecutionSpace::DeviceCPU] & its instances. Backends are compiled in
+//! using features; when several of them are, the backend is selected at dispatch time
+//! instead of at compile time, so that backends can be compared in a single binary:
+//!
+//! - statements executed by a thread inside [`scoped`] use the given backend,
+//! - otherwise, the default backend is read once from the [BACKEND_VAR] environment
+//!   variable,
+//! - otherwise, `threads` is used if enabled, then `rayon`, then `serial`.
+//!
+//! The `serial` backend is always available. It executes statements like builds
+//! without parallelization features, e.g. team collectives are not supported.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::routines::{backend, typed::parallel_for_n};
+//!
+//! for b in backend::available() {
+//!     backend::scoped(b, || {
+//!         assert_eq!(backend::current(), b);
+//!         parallel_for_n(1024, |i| {
+//!             std::hint::black_box(i);
+//!         })
+//!         .unwrap();
+//!     });
+//! }
+//! ```
+//!
+//! [ExecutionSpace::DeviceCPU]: super::parameters::ExecutionSpace::DeviceCPU
+
+use std::{cell::Cell, fmt::Display, sync::OnceLock};
+
+/// Name of the environment variable selecting the default backend, e.g.
+/// `KOKKOS_RS_BACKEND=rayon`. See [CpuBackend::from_name] for accepted values.
+pub const BACKEND_VAR: &str = "KOKKOS_RS_BACKEND";
+
+/// CPU backends. Variants only exist if their feature is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CpuBackend {
+    /// Execute statements sequentially on the calling thread.
+    Serial,
+    /// Execute statements using a persistent worker pool, see the `threads` feature.
+    #[cfg(feature = "threads")]
+    Threads,
+    /// Execute statements using a `rayon` thread pool, see the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    Rayon,
+}
+
+impl CpuBackend {
+    /// Returns the name of the backend, which is also the name of its feature.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CpuBackend::Serial => "serial",
+            #[cfg(feature = "threads")]
+            CpuBackend::Threads => "threads",
+            #[cfg(feature = "rayon")]
+            CpuBackend::Rayon => "rayon",
+        }
+    }
+
+    /// Returns the backend named `name`, case-insensitively. Returns `None` for unknown
+    /// names & backends whose feature is not enabled.
+    pub fn from_name(name: &str) -> Option<Self> {
+        available()
+            .into_iter()
+            .find(|backend| backend.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+impl Display for CpuBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Returns the backends enabled in this build: `serial`, then `threads` & `rayon` if
+/// enabled.
+pub fn available() -> Vec<CpuBackend> {
+    vec![
+        CpuBackend::Serial,
+        #[cfg(feature = "threads")]
+        CpuBackend::Threads,
+        #[cfg(feature = "rayon")]
+        CpuBackend::Rayon,
+    ]
+}
+
+static DEFAULT: OnceLock<CpuBackend> = OnceLock::new();
+
+thread_local! {
+    static SCOPED: Cell<Option<CpuBackend>> = const { Cell::new(None) };
+}
+
+/// Returns the default backend, used by statements executed outside of [`scoped`].
+///
+/// The [BACKEND_VAR] environment variable is read at the first call. Panics if it names
+/// an unknown backend, or one whose feature is not enabled.
+pub fn default_backend() -> CpuBackend {
+    *DEFAULT.get_or_init(|| match std::env::var(BACKEND_VAR) {
+        Ok(name) => CpuBackend::from_name(&name).unwrap_or_else(|| {
+            panic!("{BACKEND_VAR}: `{name}` is not an enabled backend, see backend::available")
+        }),
+        // first parallel backend, if any
+        Err(_) => *available().get(1).unwrap_or(&CpuBackend::Serial),
+    })
+}
+
+/// Returns the backend used by statements executed by the calling thread.
+pub fn current() -> CpuBackend {
+    SCOPED.with(|s| s.get()).unwrap_or_else(default_backend)
+}
+
+/// Execute `body`, statements it executes on the calling thread using `backend`. Scopes
+/// nest: the innermost one is used.
+pub fn scoped<R>(backend: CpuBackend, body: impl FnOnce() -> R) -> R {
+    /// Restores the previous scope when dropped, even if `body` panics.
+    struct Restore(Option<CpuBackend>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED.with(|s| s.set(self.0));
+        }
+    }
+
+    let _restore = Restore(SCOPED.with(|s| s.replace(Some(backend))));
+    body()
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_backend() {
+        let names: Vec<&str> = available().iter().map(CpuBackend::name).collect();
+        assert_eq!(names[0], "serial");
+        assert_eq!(names.contains(&"threads"), cfg!(feature = "threads"));
+        assert_eq!(names.contains(&"rayon"), cfg!(feature = "rayon"));
+        assert_eq!(CpuBackend::from_name(" Serial"), Some(CpuBackend::Serial));
+        assert_eq!(CpuBackend::from_name("openmp"), None);
+
+        let outer = current();
+        scoped(CpuBackend::Serial, || {
+            assert_eq!(current(), CpuBackend::Serial);
+            let inner = *available().last().unwrap();
+            scoped(inner, || assert_eq!(current(), inner));
+            assert_eq!(current(), CpuBackend::Serial);
+        });
+        assert_eq!(current(), outer);
+    }
+
+    #[test]
+    fn statements_per_backend() {
+        use crate::routines::{
+            parameters::{ExecutionSpace, Range1D, ReduceOp, ScanMode, TypedExecutionPolicy},
+            typed::{parallel_reduce, parallel_scan},
+        };
+
+        let execp = TypedExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            ..TypedExecutionPolicy::new(Range1D(0..1000))
+        };
+        for backend in available() {
+            let (sum, offsets) = scoped(backend, || {
+                (
+                    parallel_reduce(execp.clone(), ReduceOp::Sum, |i| i).unwrap(),
+                    parallel_scan(execp.clone(), ScanMode::Inclusive, |i| i).unwrap(),
+                )
+            });
+            assert_eq!(sum, 999 * 1000 / 2, "{backend}");
+            assert_eq!(offsets[999], sum, "{backend}");
+        }
+    }
+}
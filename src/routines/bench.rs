@@ -0,0 +1,429 @@
+//! benchmarking utilities
+//!
+//! This module contains helpers used to reduce run-to-run noise when measuring the
+//! performance of parallel statements. They are used by the crate's own benchmarks,
+//! but can be used in any application.
+//!
+//! Quick performance checks, not requiring a benchmarking framework, can be done using
+//! [time_kernel]: it measures repeated executions of a kernel & returns their
+//! statistics. Timings can be gathered in a [BenchReport], printed as a table or
+//! written as CSV.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     routines::bench::{bench_prep, BenchPrep},
+//!     view::{parameters::Layout, ViewOwned},
+//! };
+//!
+//! let mut view: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [1024]);
+//!
+//! // warm-up the backend before measuring anything
+//! bench_prep(&BenchPrep::default()).unwrap();
+//! // make sure the memory of the view is actually mapped
+//! view.touch();
+//! ```
+//!
+//! ```rust
+//! use poc_kokkos_rs::routines::{
+//!     bench::{time_kernel, BenchReport},
+//!     parameters::{Range1D, TypedExecutionPolicy},
+//! };
+//!
+//! let execp = TypedExecutionPolicy::new(Range1D(0..1024));
+//! let timing = time_kernel("noop", execp, |i| {
+//!     std::hint::black_box(i);
+//! }, 5)
+//! .unwrap();
+//! assert_eq!(timing.times.len(), 5);
+//!
+//! let report = BenchReport { timings: vec![timing] };
+//! println!("{report}");
+//! ```
+
+use std::{
+    fmt::Display,
+    hint::black_box,
+    io::Write,
+    thread::available_parallelism,
+    time::{Duration, Instant},
+};
+
+use crate::{functor::KernelArgs, profiling};
+
+use super::{
+    parallel_for,
+    parameters::{
+        ExecutionPolicy, ExecutionSpace, Policy, PolicyKind, RangePolicy, Schedule,
+        TypedExecutionPolicy,
+    },
+    typed, StatementError,
+};
+
+// Enums
+
+/// Enum used to classify possible errors occuring during benchmark preparation.
+#[derive(Debug)]
+pub enum BenchError {
+    /// Error occured during one of the warm-up statements.
+    Statement(StatementError),
+    /// The calling thread could not be pinned to the requested core.
+    Pinning(&'static str),
+}
+
+impl From<StatementError> for BenchError {
+    fn from(e: StatementError) -> Self {
+        BenchError::Statement(e)
+    }
+}
+
+impl Display for BenchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BenchError::Statement(e) => write!(f, "error during warm-up: {e}"),
+            BenchError::Pinning(desc) => write!(f, "error during pinning: {desc}"),
+        }
+    }
+}
+
+impl std::error::Error for BenchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BenchError::Statement(e) => Some(e),
+            BenchError::Pinning(_) => None,
+        }
+    }
+}
+
+// Parameters
+
+/// Benchmark preparation parameters.
+#[derive(Debug, Clone)]
+pub struct BenchPrep {
+    /// Number of (empty) warm-up statements dispatched on the CPU. The first one
+    /// spins up the backend, e.g. the global thread pool when using `rayon`.
+    pub warmup_rounds: usize,
+    /// Core the calling thread should be pinned to. No pinning is done if `None`.
+    /// Pinning requires the `threads` or `rayon` feature.
+    ///
+    /// Workers of the default pool of the `threads` backend & of the global `rayon` pool
+    /// are spawned by the warm-up statements, hence they are not pinned. Note that on
+    /// Linux, threads spawned afterwards by the calling thread inherit its affinity, e.g.
+    /// the workers of a [CpuInstance][super::instance::CpuInstance] or of a runtime
+    /// initialized after the preparation.
+    pub pin_to_core: Option<usize>,
+}
+
+impl Default for BenchPrep {
+    fn default() -> Self {
+        Self {
+            warmup_rounds: 10,
+            pin_to_core: None,
+        }
+    }
+}
+
+// Routines
+
+/// Prepare the execution environment for benchmarking.
+///
+/// The function runs the warm-up statements on [ExecutionSpace::DeviceCPU] and then pins the
+/// calling thread if requested. Warm-up is done first so that backend-owned threads
+/// are created before pinning, and therefore do not inherit it.
+///
+/// Views used in the benchmark can be pre-faulted using
+/// [`touch`][crate::view::ViewBase::touch].
+pub fn bench_prep(prep: &BenchPrep) -> Result<(), BenchError> {
+    let n_threads = available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+    for _ in 0..prep.warmup_rounds {
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::RangePolicy(0..n_threads * 64),
+            schedule: Schedule::Static,
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let kernel = |arg: KernelArgs<1>| {
+            black_box(arg);
+        };
+        parallel_for(execp, kernel)?;
+    }
+
+    match prep.pin_to_core {
+        Some(core) => pin_current(core),
+        None => Ok(()),
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// Pin the calling thread to `core`.
+        ///
+        /// **Current version**: `core_affinity`
+        fn pin_current(core: usize) -> Result<(), BenchError> {
+            let core_ids = core_affinity::get_core_ids()
+                .ok_or(BenchError::Pinning("could not fetch available core IDs"))?;
+            let core_id = core_ids
+                .into_iter()
+                .find(|c| c.id == core)
+                .ok_or(BenchError::Pinning("requested core is not available"))?;
+            if !core_affinity::set_for_current(core_id) {
+                return Err(BenchError::Pinning("could not set affinity of the thread"));
+            }
+            Ok(())
+        }
+    } else {
+        /// Pin the calling thread to `core`.
+        ///
+        /// **Current version**: unavailable
+        fn pin_current(_core: usize) -> Result<(), BenchError> {
+            Err(BenchError::Pinning("pinning requires the `threads` or `rayon` feature"))
+        }
+    }
+}
+
+// Kernel timing
+
+/// Measures of the repeated executions of a kernel, see [time_kernel].
+#[derive(Debug, Clone, PartialEq)]
+pub struct KernelTiming {
+    /// Label of the kernel.
+    pub label: String,
+    /// Kind of the policy used to execute the kernel.
+    pub policy: PolicyKind,
+    /// Execution time of each measured run.
+    pub times: Vec<Duration>,
+}
+
+impl KernelTiming {
+    /// Returns the minimal execution time.
+    pub fn min(&self) -> Duration {
+        self.times.iter().min().copied().unwrap_or_default()
+    }
+
+    /// Returns the maximal execution time.
+    pub fn max(&self) -> Duration {
+        self.times.iter().max().copied().unwrap_or_default()
+    }
+
+    /// Returns the mean execution time.
+    pub fn mean(&self) -> Duration {
+        match self.times.len() {
+            0 => Duration::ZERO,
+            n => self.times.iter().sum::<Duration>() / n as u32,
+        }
+    }
+
+    /// Returns the median execution time.
+    pub fn median(&self) -> Duration {
+        let mut times = self.times.clone();
+        times.sort();
+        times.get(times.len() / 2).copied().unwrap_or_default()
+    }
+
+    /// Returns the standard deviation of execution times.
+    pub fn std_dev(&self) -> Duration {
+        if self.times.is_empty() {
+            return Duration::ZERO;
+        }
+        let mean = self.mean().as_secs_f64();
+        let var = self
+            .times
+            .iter()
+            .map(|t| (t.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / self.times.len() as f64;
+        Duration::from_secs_f64(var.sqrt())
+    }
+}
+
+impl Display for KernelTiming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<24} {:<18} {:>6} {:>12.3?} {:>12.3?} {:>12.3?} {:>12.3?}",
+            self.label,
+            self.policy,
+            self.times.len(),
+            self.median(),
+            self.mean(),
+            self.min(),
+            self.max(),
+        )
+    }
+}
+
+/// Timings of several kernels, printed as a table using its [Display] implementation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BenchReport {
+    /// Timings, in the order they are printed.
+    pub timings: Vec<KernelTiming>,
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:<18} {:>6} {:>12} {:>12} {:>12} {:>12}",
+            "label", "policy", "runs", "median", "mean", "min", "max"
+        )?;
+        self.timings
+            .iter()
+            .try_for_each(|timing| writeln!(f, "{timing}"))
+    }
+}
+
+/// Header line of the CSV output.
+pub const CSV_HEADER: &str = "label,policy,runs,median_ns,mean_ns,min_ns,max_ns,std_dev_ns";
+
+impl BenchReport {
+    /// Write the report as CSV into `out`, header included. Labels are quoted.
+    pub fn write_csv<W: Write>(&self, mut out: W) -> std::io::Result<()> {
+        writeln!(out, "{CSV_HEADER}")?;
+        for timing in &self.timings {
+            writeln!(
+                out,
+                "\"{}\",{},{},{},{},{},{},{}",
+                timing.label.replace('"', "\"\""),
+                timing.policy,
+                timing.times.len(),
+                timing.median().as_nanos(),
+                timing.mean().as_nanos(),
+                timing.min().as_nanos(),
+                timing.max().as_nanos(),
+                timing.std_dev().as_nanos(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// Measure `n_repeats` executions of a Parallel For statement, after an unmeasured
+        /// warm-up execution. See [typed::parallel_for].
+        ///
+        /// Statements are executed under `label`, see [profiling::labeled]. Measuring stops
+        /// at the first error returned by the statement.
+        ///
+        /// **Current version**: thread-safe
+        pub fn time_kernel<const N: usize, P>(
+            label: &str,
+            execp: TypedExecutionPolicy<P>,
+            func: impl Fn(P::Arg) + Send + Sync,
+            n_repeats: usize,
+        ) -> Result<KernelTiming, StatementError>
+        where
+            P: Policy<N> + Clone,
+        {
+            let policy = execp.clone().untyped().range.kind();
+            let times = profiling::labeled(label, || {
+                typed::parallel_for(execp.clone(), &func)?;
+                (0..n_repeats)
+                    .map(|_| {
+                        let start = Instant::now();
+                        typed::parallel_for(execp.clone(), &func).map(|_| start.elapsed())
+                    })
+                    .collect::<Result<Vec<Duration>, StatementError>>()
+            })?;
+            Ok(KernelTiming { label: label.to_owned(), policy, times })
+        }
+    } else {
+        /// Measure `n_repeats` executions of a Parallel For statement, after an unmeasured
+        /// warm-up execution. See [typed::parallel_for].
+        ///
+        /// Statements are executed under `label`, see [profiling::labeled]. Measuring stops
+        /// at the first error returned by the statement.
+        ///
+        /// **Current version**: no feature
+        pub fn time_kernel<const N: usize, P>(
+            label: &str,
+            execp: TypedExecutionPolicy<P>,
+            mut func: impl FnMut(P::Arg),
+            n_repeats: usize,
+        ) -> Result<KernelTiming, StatementError>
+        where
+            P: Policy<N> + Clone,
+        {
+            let policy = execp.clone().untyped().range.kind();
+            let times = profiling::labeled(label, || {
+                typed::parallel_for(execp.clone(), &mut func)?;
+                (0..n_repeats)
+                    .map(|_| {
+                        let start = Instant::now();
+                        typed::parallel_for(execp.clone(), &mut func).map(|_| start.elapsed())
+                    })
+                    .collect::<Result<Vec<Duration>, StatementError>>()
+            })?;
+            Ok(KernelTiming { label: label.to_owned(), policy, times })
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warmup_only() {
+        bench_prep(&BenchPrep::default()).unwrap();
+    }
+
+    #[test]
+    fn kernel_timing() {
+        use crate::routines::parameters::MDRange;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let count = AtomicUsize::new(0);
+        let execp = TypedExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            policy: MDRange([0..4, 0..8]),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let timing = time_kernel(
+            "bench::kernel_timing",
+            execp,
+            |_| {
+                count.fetch_add(1, Ordering::Relaxed);
+            },
+            3,
+        )
+        .unwrap();
+        // warm-up included
+        assert_eq!(count.into_inner(), 4 * 32);
+        assert_eq!(timing.policy, PolicyKind::MDRangePolicy);
+        assert_eq!(timing.times.len(), 3);
+        assert!(timing.min() <= timing.median() && timing.median() <= timing.max());
+
+        let timing = KernelTiming {
+            label: "axpy".to_string(),
+            policy: PolicyKind::RangePolicy,
+            times: [30, 10, 20].map(Duration::from_nanos).to_vec(),
+        };
+        assert_eq!(timing.mean(), Duration::from_nanos(20));
+        let report = BenchReport {
+            timings: vec![timing],
+        };
+        assert_eq!(report.to_string().lines().count(), 2);
+        let mut out: Vec<u8> = Vec::new();
+        report.write_csv(&mut out).unwrap();
+        let ref_out = format!("{CSV_HEADER}\n\"axpy\",RangePolicy,3,20,20,10,30,8\n");
+        assert_eq!(String::from_utf8(out).unwrap(), ref_out);
+    }
+
+    #[test]
+    fn missing_core() {
+        let prep = BenchPrep {
+            warmup_rounds: 0,
+            pin_to_core: Some(usize::MAX),
+        };
+        assert!(matches!(bench_prep(&prep), Err(BenchError::Pinning(_))));
+    }
+}
@@ -0,0 +1,214 @@
+//! kernel diagnostics code
+//!
+//! This module contains code used by kernels to report exceptional situations without
+//! aborting the statement or racing on standard outputs. Kernels push structured
+//! records into a bounded [`DiagnosticsSink`]; records are drained once the statement
+//! is over.
+//!
+//! Pushing a record is lock-free: each push reserves a slot using an atomic counter.
+//! Once the sink is full, additional records are dropped and counted.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     functor::KernelArgs,
+//!     routines::{
+//!         diagnostics::collect_diagnostics,
+//!         parallel_for,
+//!         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+//!     },
+//! };
+//!
+//! let jacobians: Vec<f64> = vec![1.0, -0.5, 2.0, -1.0];
+//!
+//! let (res, diags) = collect_diagnostics::<1, _>(16, |sink| {
+//!     let execp = ExecutionPolicy {
+//!         space: ExecutionSpace::DeviceCPU,
+//!         range: RangePolicy::RangePolicy(0..jacobians.len()),
+//!         schedule: Schedule::Static,
+//!         chunk_size: None,
+//!         chunk_predicate: None,
+//!     };
+//!     let kernel = |arg: KernelArgs<1>| match arg {
+//!         KernelArgs::Index1D(i) => {
+//!             if jacobians[i] < 0.0 {
+//!                 sink.warn([i], 1, jacobians[i]);
+//!             }
+//!         }
+//!         KernelArgs::IndexND(_) => unimplemented!(),
+//!         KernelArgs::Handle(_) => unimplemented!(),
+//!     };
+//!     parallel_for(execp, kernel)
+//! });
+//!
+//! res.unwrap();
+//! assert_eq!(diags.records.len(), 2);
+//! ```
+
+use std::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Severity of a diagnostic record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The kernel encountered an unexpected value but could carry on.
+    Warning,
+    /// The kernel could not compute a correct result.
+    Error,
+}
+
+/// Structured diagnostic record.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Diagnostic<const N: usize> {
+    /// Severity of the record.
+    pub severity: Severity,
+    /// Index of the iteration that pushed the record.
+    pub index: [usize; N],
+    /// User-defined code identifying the situation.
+    pub code: u32,
+    /// Value associated to the record, e.g. the offending value.
+    pub value: f64,
+}
+
+/// Bounded, lock-free sink of diagnostic records.
+pub struct DiagnosticsSink<const N: usize> {
+    slots: Box<[UnsafeCell<MaybeUninit<Diagnostic<N>>>]>,
+    /// Number of push attempts; may exceed the number of slots.
+    count: AtomicUsize,
+}
+
+// Each slot is written at most once between two drains, by the thread that reserved
+// its index; slots are only read when draining, which requires exclusive access.
+unsafe impl<const N: usize> Sync for DiagnosticsSink<N> {}
+
+/// Drained content of a [`DiagnosticsSink`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostics<const N: usize> {
+    /// Stored records. The order depends on the execution order of kernels.
+    pub records: Vec<Diagnostic<N>>,
+    /// Number of records dropped because the sink was full.
+    pub dropped: usize,
+}
+
+impl<const N: usize> DiagnosticsSink<N> {
+    /// Create a sink able to store up to `capacity` records.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity)
+                .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+                .collect(),
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Maximum number of records the sink can store.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Push a record into the sink. Returns `false` if the sink is full, in which case
+    /// the record is dropped.
+    pub fn push(&self, record: Diagnostic<N>) -> bool {
+        let idx = self.count.fetch_add(1, Ordering::Relaxed);
+        if let Some(slot) = self.slots.get(idx) {
+            // SAFETY: the index was reserved by this call only
+            unsafe { (*slot.get()).write(record) };
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Push a [Severity::Warning] record into the sink.
+    pub fn warn(&self, index: [usize; N], code: u32, value: f64) -> bool {
+        self.push(Diagnostic {
+            severity: Severity::Warning,
+            index,
+            code,
+            value,
+        })
+    }
+
+    /// Push a [Severity::Error] record into the sink.
+    pub fn error(&self, index: [usize; N], code: u32, value: f64) -> bool {
+        self.push(Diagnostic {
+            severity: Severity::Error,
+            index,
+            code,
+            value,
+        })
+    }
+
+    /// Remove all records from the sink & return them.
+    pub fn drain(&mut self) -> Diagnostics<N> {
+        let count = std::mem::take(self.count.get_mut());
+        let stored = count.min(self.capacity());
+        let records = self.slots[..stored]
+            .iter_mut()
+            // SAFETY: slots below the push count were all written by a push
+            .map(|slot| unsafe { slot.get_mut().assume_init() })
+            .collect();
+        Diagnostics {
+            records,
+            dropped: count - stored,
+        }
+    }
+}
+
+/// Run `body` with a sink of given capacity, then drain it.
+///
+/// Returns the result of `body` and the diagnostics pushed during its execution.
+pub fn collect_diagnostics<const N: usize, R>(
+    capacity: usize,
+    body: impl FnOnce(&DiagnosticsSink<N>) -> R,
+) -> (R, Diagnostics<N>) {
+    let mut sink = DiagnosticsSink::new(capacity);
+    let res = body(&sink);
+    (res, sink.drain())
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_push() {
+        let mut sink = DiagnosticsSink::<1>::new(1000);
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                let sink = &sink;
+                s.spawn(move || {
+                    for i in 0..100 {
+                        sink.error([t * 100 + i], 2, i as f64);
+                    }
+                });
+            }
+        });
+        let diags = sink.drain();
+        assert_eq!(diags.dropped, 0);
+        let mut indices: Vec<usize> = diags.records.iter().map(|r| r.index[0]).collect();
+        indices.sort();
+        assert_eq!(indices, (0..400).collect::<Vec<usize>>());
+
+        // drained sink is empty
+        assert!(sink.drain().records.is_empty());
+    }
+
+    #[test]
+    fn full_sink() {
+        let (_, diags) = collect_diagnostics::<2, _>(3, |sink| {
+            for i in 0..5 {
+                sink.warn([i, 0], 0, 0.0);
+            }
+        });
+        assert_eq!(diags.records.len(), 3);
+        assert_eq!(diags.dropped, 2);
+    }
+}
@@ -6,6 +6,29 @@
 //!
 //! The methods desccribed in this module are not meant to be used directly, they are only
 //! building blocks for the parallel statements.
+//!
+//! ### Memory model
+//!
+//! When using parallelization features, view elements are atomics accessed using
+//! relaxed ordering. Relaxed accesses alone do not guarantee that a write is visible
+//! to other threads, hence dispatch routines provide the following guarantees:
+//!
+//! - all writes made before a statement are visible to its kernels,
+//! - all writes made by the kernels of a statement are visible once it returns, i.e.
+//!   to the caller and to the kernels of subsequent statements.
+//!
+//! There is no ordering guarantee between kernels of a single statement. The `threads`
+//! backend enforces these guarantees with explicit acquire/release fences at the
+//! boundaries of each worker's chunk, so that they do not rely on the synchronization
+//! implied by the completion of pool tasks. The `rayon` backend relies on the
+//! synchronization of its job completion, which provides the same guarantees.
+//!
+//! ### Nested statements
+//!
+//! Like in Kokkos, statements launched from the kernel of a parallel statement are not
+//! parallelized: CPU dispatch routines detect that they are called from a worker (see
+//! [launch_depth]) and execute nested statements serially on the calling thread. This
+//! prevents oversubscribing threads & deadlocking thread pools.
 
 #[cfg(any(doc, feature = "rayon", feature = "gpu"))]
 use crate::functor::ForKernelType;
@@ -13,10 +36,38 @@ use crate::functor::ForKernelType;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
-use std::{fmt::Display, ops::Range};
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use std::sync::Arc;
+use std::{cell::Cell, fmt::Display};
 
-use super::parameters::{ExecutionPolicy, RangePolicy};
-use crate::functor::{KernelArgs, SerialForKernelType};
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use super::backend::{self, CpuBackend};
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use super::parameters::ChunkPredicate;
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use super::parameters::Iterate;
+#[cfg(feature = "threads")]
+use super::parameters::Schedule;
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use super::team::TeamSync;
+use super::{
+    iter::{MDIndexIter, Tiling},
+    parameters::{ExecutionPolicy, PolicyKind, RangePolicy, Reducer, ScanMode},
+    scratch::ScratchArena,
+};
+use crate::functor::{KernelArgs, SerialForKernelType, TeamHandle};
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use crate::runtime::cooperative_point;
+use crate::runtime::COOPERATIVE_CHUNK;
+use crate::view::parameters::DataTraits;
+use std::ops::{Add, Range};
+#[cfg(feature = "threads")]
+use std::sync::atomic::fence;
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
 
 // enums
 
@@ -50,42 +101,105 @@ impl std::error::Error for DispatchError {
     }
 }
 
-// dispatch routines
+/// Enum used to describe the implementation status of a policy kind for a given
+/// dispatch routine.
+///
+/// Each dispatch routine has a matching support table (e.g. [serial_support]) which
+/// is checked before execution: dispatching an [SupportLevel::Unimplemented] policy
+/// results in an error instead of a panic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupportLevel {
+    /// The policy is fully supported by the routine.
+    Full,
+    /// The policy is supported, but the routine falls back to serial execution.
+    SerialFallback,
+    /// The policy is not implemented by the routine.
+    Unimplemented,
+}
 
-// internal routines
-
-/// Builds a N-depth nested loop executing a kernel using the N resulting indices.
-/// Technically, this should be replaced by a tiling function, for both serial and parallel
-/// implementations.
-fn recursive_loop<const N: usize>(ranges: &[Range<usize>; N], mut kernel: SerialForKernelType<N>) {
-    // handles recursions
-    fn inner<const N: usize>(
-        current_depth: usize,
-        ranges: &[Range<usize>; N],
-        kernel: &mut SerialForKernelType<N>,
-        indices: &mut [usize; N],
-    ) {
-        if current_depth == N {
-            // all loops unraveled
-            // call the kernel
-            kernel(KernelArgs::IndexND(*indices))
-        } else {
-            // loop on next dimension; update indices
-            // can we avoid a clone by passing a slice starting one element
-            // after the unraveled range ?
-            ranges[current_depth].clone().for_each(|i_current| {
-                indices[current_depth] = i_current;
-                inner(current_depth + 1, ranges, kernel, indices);
-            });
+impl SupportLevel {
+    /// Returns the support level of a routine falling back to another one with
+    /// support level `self`.
+    fn as_fallback(self) -> Self {
+        match self {
+            SupportLevel::Full | SupportLevel::SerialFallback => SupportLevel::SerialFallback,
+            SupportLevel::Unimplemented => SupportLevel::Unimplemented,
         }
     }
+}
+
+const UNSUPPORTED_POLICY: &str = "policy is not implemented by this backend";
+#[cfg(any(feature = "threads", feature = "rayon"))]
+const TEAM_TOO_LARGE: &str = "team size exceeds the number of threads";
+
+// nested launches
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Returns the number of parallel statements whose kernels are being executed by the
+/// calling thread, i.e. `0` outside of kernels. Statements launched with a non-zero
+/// depth are executed serially.
+pub fn launch_depth() -> usize {
+    DEPTH.with(|d| d.get())
+}
+
+/// Marks the calling thread as executing kernels of a parallel statement until dropped.
+///
+/// Regions nest: the launch depth is incremented on entry & decremented on drop, so
+/// guards may be released in any order.
+#[cfg(any(feature = "threads", feature = "rayon"))]
+struct ParallelRegion;
+
+#[cfg(any(feature = "threads", feature = "rayon"))]
+impl ParallelRegion {
+    fn enter() -> Self {
+        DEPTH.with(|d| d.set(d.get() + 1));
+        Self
+    }
+}
 
-    let mut indices = [0; N];
-    inner(0, ranges, &mut kernel, &mut indices);
+#[cfg(any(feature = "threads", feature = "rayon"))]
+impl Drop for ParallelRegion {
+    fn drop(&mut self) {
+        DEPTH.with(|d| d.set(d.get() - 1));
+    }
 }
 
+// dispatch routines
+
 // serial dispatch
 
+/// Support table of the [serial] dispatch routine.
+pub fn serial_support(kind: PolicyKind) -> SupportLevel {
+    match kind {
+        PolicyKind::RangePolicy
+        | PolicyKind::MDRangePolicy
+        | PolicyKind::TiledMDRangePolicy
+        | PolicyKind::TeamPolicy
+        | PolicyKind::PerTeam
+        | PolicyKind::PerThread
+        | PolicyKind::TeamThreadRange
+        | PolicyKind::TeamVectorRange
+        | PolicyKind::ThreadVectorRange => SupportLevel::Full,
+        _ => SupportLevel::Unimplemented,
+    }
+}
+
+/// Returns the number of chunks tested by the chunk predicate of a policy over `range`
+/// & their length. See [ExecutionPolicy::chunk_predicate].
+fn predicate_chunks(range: &Range<usize>, chunk_size: Option<usize>) -> (usize, usize) {
+    let len = chunk_size.unwrap_or(COOPERATIVE_CHUNK).max(1);
+    (range.len().div_ceil(len), len)
+}
+
+/// Returns the bounds of the `c`-th chunk of length `len` of `range`.
+fn chunk_bounds(range: &Range<usize>, len: usize, c: usize) -> Range<usize> {
+    let start = range.start + c * len;
+    start..(start + len).min(range.end)
+}
+
 /// CPU dispatch routine of `for` statements. Does not depend on enabled feature(s).
 ///
 /// The dispatch function execute the kernel accordingly to the directives contained in the
@@ -93,8 +207,14 @@ fn recursive_loop<const N: usize>(ranges: &[Range<usize>; N], mut kernel: Serial
 /// is the invariant fallback dispatch routine.
 pub fn serial<const N: usize>(
     execp: ExecutionPolicy<N>,
-    kernel: SerialForKernelType<N>,
+    mut kernel: SerialForKernelType<N>,
 ) -> Result<(), DispatchError> {
+    let kind = execp.range.kind();
+    if serial_support(kind) == SupportLevel::Unimplemented
+        || (execp.chunk_predicate.is_some() && kind != PolicyKind::RangePolicy)
+    {
+        return Err(DispatchError::Serial(UNSUPPORTED_POLICY));
+    }
     match execp.range {
         RangePolicy::RangePolicy(range) => {
             // serial, 1D range
@@ -103,78 +223,410 @@ pub fn serial<const N: usize>(
                     "Dispatch uses N>1 for a 1D RangePolicy",
                 ));
             }
-            range.into_iter().map(KernelArgs::Index1D).for_each(kernel)
+            match execp.chunk_predicate {
+                Some(predicate) => {
+                    // inactive chunks are skipped
+                    let (n_chunks, len) = predicate_chunks(&range, execp.chunk_size);
+                    (0..n_chunks)
+                        .map(|c| chunk_bounds(&range, len, c))
+                        .filter(|bounds| predicate.test(bounds.clone()))
+                        .flatten()
+                        .map(KernelArgs::Index1D)
+                        .for_each(kernel)
+                }
+                None => range.into_iter().map(KernelArgs::Index1D).for_each(kernel),
+            }
         }
         RangePolicy::MDRangePolicy(ranges) => {
             // Kokkos does tiling to handle a MDRanges, in the case of serial
             // execution, we simply do the nested loop
-            recursive_loop(&ranges, kernel) // macros would pbly be more efficient
+            MDIndexIter::new(ranges)
+                .map(KernelArgs::IndexND)
+                .for_each(kernel)
+        }
+        RangePolicy::TiledMDRangePolicy {
+            ranges,
+            tile,
+            outer,
+            inner,
+        } => {
+            // tiles are executed one after the other
+            let tiling = Tiling::new(ranges, tile, outer).with_inner(inner);
+            (0..tiling.len())
+                .flat_map(|k| tiling.tile_indices(k))
+                .map(KernelArgs::IndexND)
+                .for_each(kernel)
         }
         RangePolicy::TeamPolicy {
-            league_size: _, // number of teams; akin to # of work items/batches
-            team_size: _,   // number of threads per team; ignored in serial dispatch
-            vector_size: _, // possible third dim parallelism; ignored in serial dispatch?
+            league_size,
+            team_size,
+            vector_size,
+            scratch_size,
         } => {
-            // interpret # of teams as # of work items (chunks);
-            // necessary because serial dispatch is the fallback implementation
-            // we ignore team size & vector size? since there's no parallelism here
-
-            // is it even possible to use chunks? It would require either:
-            //  - awareness of used external data
-            //  - owning the used data; maybe in the TeamPolicy struct
-            // 2nd option is the more plausible but it creates issues when accessing
-            // multiple views for example; It also seems incompatible with the paradigm
-
-            // -> build a team handle & let the user write its kernel using it
-            todo!()
+            // the kernel is executed once per team member, using a handle to identify it;
+            // members are executed in order, team after team
+            let scratch = ScratchArena::new(league_size, scratch_size);
+            (0..league_size * team_size)
+                .map(|idx| {
+                    KernelArgs::Handle(
+                        TeamHandle::from_flat(idx, league_size, team_size, vector_size)
+                            .with_scratch(&scratch),
+                    )
+                })
+                .for_each(kernel)
         }
-        RangePolicy::PerTeam => {
+        RangePolicy::PerTeam(handle) => {
             // used inside a team dispatch
             // executes the kernel once per team
-            todo!()
+            if handle.team_rank() == 0 {
+                kernel(KernelArgs::Handle(handle))
+            }
         }
-        RangePolicy::PerThread => {
+        RangePolicy::PerThread(handle) => {
             // used inside a team dispatch
             // executes the kernel once per threads of the team
-            todo!()
+            kernel(KernelArgs::Handle(handle))
         }
-        RangePolicy::TeamThreadRange => {
-            // same as RangePolicy but inside a team
-            todo!()
+        RangePolicy::TeamThreadRange(handle, range) => {
+            // same as RangePolicy but inside a team; the calling member only
+            // executes its share of the range
+            if N != 1 {
+                return Err(DispatchError::Serial(
+                    "Dispatch uses N>1 for a 1D TeamThreadRange",
+                ));
+            }
+            handle
+                .thread_range(&range)
+                .map(KernelArgs::Index1D)
+                .for_each(kernel)
         }
-        RangePolicy::TeamThreadMDRange => {
-            // same as MDRangePolicy but inside a team
-            todo!()
+        RangePolicy::TeamVectorRange(handle, range)
+        | RangePolicy::ThreadVectorRange(handle, range) => {
+            // executed by the calling member, vector chunk after vector chunk; the range
+            // of a TeamVectorRange is first split between members
+            if N != 1 {
+                return Err(DispatchError::Serial(
+                    "Dispatch uses N>1 for a 1D vector range",
+                ));
+            }
+            let range = match kind {
+                PolicyKind::TeamVectorRange => handle.thread_range(&range),
+                _ => range,
+            };
+            handle
+                .vector_chunks(range)
+                .for_each(|lanes| lanes.map(KernelArgs::Index1D).for_each(&mut kernel))
         }
-        RangePolicy::TeamVectorRange => todo!(),
-        RangePolicy::TeamVectorMDRange => todo!(),
-        RangePolicy::ThreadVectorRange => todo!(),
-        RangePolicy::ThreadVectorMDRange => todo!(),
+        _ => return Err(DispatchError::Serial(UNSUPPORTED_POLICY)),
     };
     Ok(())
 }
 
+/// Default tiling of MDRange policies on parallel CPU backends: tiles are segments of
+/// the last dimension, of length up to [COOPERATIVE_CHUNK].
+#[cfg(any(feature = "threads", feature = "rayon"))]
+fn default_tiling<const N: usize>(ranges: [Range<usize>; N]) -> Tiling<N> {
+    let mut tile = [1; N];
+    if let (Some(t), Some(r)) = (tile.last_mut(), ranges.last()) {
+        *t = r.len().min(COOPERATIVE_CHUNK);
+    }
+    Tiling::new(ranges, tile, Iterate::Right)
+}
+
+/// Teams of a league, distributed over groups of `team_size` workers so that the members
+/// of a team are executed concurrently & can use team collectives. See the
+/// [team][super::team] module.
+///
+/// The league is split in chunks of teams: the members of a group execute the same
+/// chunks, one member each, and synchronize using the [TeamSync] of their group.
+#[cfg(any(feature = "threads", feature = "rayon"))]
+struct League<'a> {
+    /// League, team & vector sizes of the policy.
+    sizes: [usize; 3],
+    scratch: &'a Option<Arc<ScratchArena>>,
+    /// Number of teams per chunk.
+    chunk: usize,
+    /// Next chunk to claim, if chunks are claimed dynamically.
+    next: Option<AtomicUsize>,
+    /// Chunks claimed by each group, in claim order.
+    claims: Vec<Mutex<Vec<usize>>>,
+    syncs: Vec<Option<Arc<TeamSync>>>,
+}
+
+#[cfg(any(feature = "threads", feature = "rayon"))]
+impl<'a> League<'a> {
+    /// Distribute the teams over the workers of a pool of `n_workers` workers. Chunks of
+    /// `chunk_size` teams are claimed by groups if `dynamic` is set, and assigned in a
+    /// round-robin fashion otherwise; by default, there is one chunk per group using a
+    /// static schedule, and one team per chunk using a dynamic schedule.
+    ///
+    /// An error is returned if the pool is too small to execute the members of a team
+    /// concurrently.
+    fn new(
+        sizes: [usize; 3],
+        scratch: &'a Option<Arc<ScratchArena>>,
+        n_workers: usize,
+        dynamic: bool,
+        chunk_size: Option<usize>,
+    ) -> Result<Self, DispatchError> {
+        let [league_size, team_size, _] = sizes;
+        if team_size > n_workers {
+            return Err(DispatchError::CPU(TEAM_TOO_LARGE));
+        }
+        let n_groups = (n_workers / team_size.max(1)).min(league_size);
+        let chunk = match (chunk_size, dynamic) {
+            (Some(chunk), _) => chunk,
+            (None, true) => 1,
+            (None, false) => league_size.div_ceil(n_groups.max(1)),
+        };
+        Ok(Self {
+            sizes,
+            scratch,
+            chunk: chunk.max(1),
+            next: dynamic.then(|| AtomicUsize::new(0)),
+            claims: (0..n_groups).map(|_| Mutex::new(Vec::new())).collect(),
+            syncs: (0..n_groups).map(|_| TeamSync::new(team_size)).collect(),
+        })
+    }
+
+    /// Returns the number of workers executing the league, i.e. the number of members of
+    /// all groups.
+    fn n_members(&self) -> usize {
+        self.syncs.len() * self.sizes[1]
+    }
+
+    /// Returns the teams executed by `group` at its `step`-th iteration, if any.
+    fn teams(&self, group: usize, step: usize) -> Option<Range<usize>> {
+        let c = match &self.next {
+            Some(next) => {
+                // the first member reaching a step claims the chunk of its group
+                let mut claims = self.claims[group].lock().unwrap_or_else(|e| e.into_inner());
+                while claims.len() <= step {
+                    claims.push(next.fetch_add(1, Ordering::Relaxed));
+                }
+                claims[step]
+            }
+            None => group + step * self.syncs.len(),
+        };
+        let start = c.saturating_mul(self.chunk);
+        (start < self.sizes[0]).then(|| start..(start + self.chunk).min(self.sizes[0]))
+    }
+
+    /// Execute member `worker % team_size` of the teams of group `worker / team_size`.
+    fn execute_member<const N: usize>(
+        &self,
+        worker: usize,
+        kernel: &(impl Fn(KernelArgs<N>) + Sync),
+    ) {
+        let [league_size, team_size, vector_size] = self.sizes;
+        let (group, team_rank) = (worker / team_size, worker % team_size);
+        let sync = &self.syncs[group];
+        let _guard = sync.as_deref().map(TeamSync::guard);
+        (0..)
+            .map_while(|step| self.teams(group, step))
+            .for_each(|teams| {
+                teams.for_each(|league_rank| {
+                    let idx = league_rank * team_size + team_rank;
+                    let handle = TeamHandle::from_flat(idx, league_size, team_size, vector_size)
+                        .with_scratch(self.scratch)
+                        .with_sync(sync);
+                    kernel(KernelArgs::Handle(handle));
+                });
+                cooperative_point();
+            });
+    }
+}
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "threads")] {
-        /// CPU dispatch routine of `for` statements. Implementation depends on enabled feature(s).
-        ///
-        /// The dispatch function execute the kernel accordingly to the directives contained in the
-        /// execution policy. The kernel signature varies according to enabled features.
-        ///
-        /// ### Possible Kernel Signatures
-        ///
-        /// - `rayon` feature enabled: [`ForKernelType`]
-        /// - `threads` feature enabled: `Box<impl Fn(KernelArgs<N>) + Send + Sync + 'a + Clone>`
-        /// - no feature enabled: fall back to [`SerialForKernelType`]
-        ///
-        /// The `threads` implementation cannot currently use the generic [`ForKernelType`] because
-        /// of the Clone requirement.
+        /// Support table of the `threads` backend of the [cpu] dispatch routine.
+        fn threads_support(kind: PolicyKind) -> SupportLevel {
+            match kind {
+                PolicyKind::RangePolicy
+                | PolicyKind::MDRangePolicy
+                | PolicyKind::TiledMDRangePolicy
+                | PolicyKind::TeamPolicy => SupportLevel::Full,
+                // nested policies are executed by the calling team member
+                PolicyKind::PerTeam
+                | PolicyKind::PerThread
+                | PolicyKind::TeamThreadRange
+                | PolicyKind::TeamVectorRange
+                | PolicyKind::ThreadVectorRange => SupportLevel::SerialFallback,
+                _ => SupportLevel::Unimplemented,
+            }
+        }
+
+        /// Execute `work` over `0..n_items` using one worker per thread. Workers repeatedly
+        /// claim the next `grain` items from a shared counter until all items are processed,
+        /// which balances irregular workloads. Results of `work` are returned in chunk order.
+        fn threads_dynamic<O: Send>(
+            n_items: usize,
+            grain: usize,
+            work: impl Fn(Range<usize>) -> O + Sync,
+        ) -> Vec<O> {
+            let next = AtomicUsize::new(0);
+            let workers = crate::runtime::workers();
+            fence(Ordering::Release);
+            let mut results: Vec<(usize, O)> = workers.broadcast(workers.num_workers(), |_| {
+                let _region = ParallelRegion::enter();
+                fence(Ordering::Acquire);
+                let mut results = Vec::new();
+                loop {
+                    let start = next.fetch_add(grain, Ordering::Relaxed);
+                    if start >= n_items {
+                        break;
+                    }
+                    results.push((start, work(start..(start + grain).min(n_items))));
+                }
+                fence(Ordering::Release);
+                results
+            }).into_iter().flatten().collect();
+            fence(Ordering::Acquire);
+            results.sort_unstable_by_key(|(start, _)| *start);
+            results.into_iter().map(|(_, res)| res).collect()
+        }
+
+        /// Execute `work` over `0..n_items` using chunks of `chunk` items. Chunks are
+        /// assigned to workers in a round-robin fashion, there being at most one worker per
+        /// thread. Results of `work` are returned in chunk order.
+        fn threads_static<O: Send>(
+            n_items: usize,
+            chunk: usize,
+            work: impl Fn(Range<usize>) -> O + Sync,
+        ) -> Vec<O> {
+            let n_chunks = n_items.div_ceil(chunk);
+            let workers = crate::runtime::workers();
+            let n_workers = workers.num_workers().min(n_chunks);
+            // make writes of previous statements visible to workers
+            fence(Ordering::Release);
+            let mut results: Vec<(usize, O)> = workers.broadcast(n_workers, |c| {
+                let _region = ParallelRegion::enter();
+                fence(Ordering::Acquire);
+                let results: Vec<_> = (c..n_chunks).step_by(n_workers).map(|k| {
+                    (k, work(k * chunk..((k + 1) * chunk).min(n_items)))
+                }).collect();
+                // publish the writes of the chunks
+                fence(Ordering::Release);
+                results
+            }).into_iter().flatten().collect();
+            // make writes of workers visible to the caller
+            fence(Ordering::Acquire);
+            results.sort_unstable_by_key(|(k, _)| *k);
+            results.into_iter().map(|(_, res)| res).collect()
+        }
+
+        /// Returns the length of the chunks used by [threads_schedule]. `grain` is the default
+        /// length of dynamically scheduled chunks.
+        fn chunk_len(n_items: usize, schedule: &Schedule, chunk_size: Option<usize>, grain: usize) -> usize {
+            match schedule {
+                Schedule::Dynamic => chunk_size.unwrap_or(grain),
+                // by default, compute chunk_size so that there is 1 chunk per thread
+                Schedule::Static => chunk_size.unwrap_or(n_items / crate::runtime::num_threads() + 1),
+            }
+            .max(1)
+        }
+
+        /// Execute `work` over `0..n_items` according to `schedule`.
         ///
-        /// **Current version**: `threads`
-        pub fn cpu<'a, const N: usize>(
+        /// Using a static schedule, items are split in one chunk per thread, or in chunks of
+        /// `chunk_size` items distributed in a round-robin fashion. Using a dynamic schedule,
+        /// threads claim chunks of `chunk_size` (defaulting to `grain`) items until all of them
+        /// are processed. Chunks start at multiples of their length, see [chunk_len]; results
+        /// of `work` are returned in chunk order.
+        fn threads_schedule<O: Send>(
+            n_items: usize,
+            schedule: &Schedule,
+            chunk_size: Option<usize>,
+            grain: usize,
+            work: impl Fn(Range<usize>) -> O + Sync,
+        ) -> Vec<O> {
+            let chunk = chunk_len(n_items, schedule, chunk_size, grain);
+            match schedule {
+                Schedule::Dynamic => threads_dynamic(n_items, chunk, work),
+                Schedule::Static => threads_static(n_items, chunk, work),
+            }
+        }
+
+        /// Default length of dynamically scheduled chunks of a 1D range: a few chunks per
+        /// thread, while keeping chunks cache-friendly.
+        fn range_grain(n_items: usize) -> usize {
+            (n_items / (8 * crate::runtime::num_threads())).clamp(1, COOPERATIVE_CHUNK)
+        }
+
+        /// Execute the kernel over `range` according to `schedule`, see [threads_schedule].
+        /// `args` builds the kernel arguments associated to an index.
+        fn threads_chunks<'a, const N: usize>(
+            range: Range<usize>,
+            schedule: &Schedule,
+            chunk_size: Option<usize>,
+            kernel: Box<impl Fn(KernelArgs<N>) + Send + Sync + 'a + Clone>,
+            args: impl Fn(usize) -> KernelArgs<N> + Sync,
+        ) {
+            let start = range.start;
+            threads_schedule(range.len(), schedule, chunk_size, range_grain(range.len()), |sub| {
+                (sub.start..sub.end).step_by(COOPERATIVE_CHUNK).for_each(|sub_start| {
+                    (sub_start..(sub_start + COOPERATIVE_CHUNK).min(sub.end))
+                        .map(|idx| args(start + idx))
+                        .for_each(kernel.as_ref());
+                    cooperative_point();
+                })
+            });
+        }
+
+        /// Execute the kernel over the chunks of `range` accepted by `predicate`, according
+        /// to `schedule`; chunks are the scheduling unit. See [ExecutionPolicy::chunk_predicate].
+        fn threads_active_chunks<'a, const N: usize>(
+            range: Range<usize>,
+            schedule: &Schedule,
+            chunk_size: Option<usize>,
+            predicate: &ChunkPredicate,
+            kernel: Box<impl Fn(KernelArgs<N>) + Send + Sync + 'a + Clone>,
+        ) {
+            let (n_chunks, len) = predicate_chunks(&range, chunk_size);
+            let range = &range;
+            threads_schedule(n_chunks, schedule, None, 1, |chunks| {
+                chunks.for_each(|c| {
+                    let bounds = chunk_bounds(range, len, c);
+                    if predicate.test(bounds.clone()) {
+                        bounds.map(KernelArgs::Index1D).for_each(kernel.as_ref());
+                    }
+                    cooperative_point();
+                })
+            });
+        }
+
+        /// Execute the kernel over the tiles of `tiling` according to `schedule`, see
+        /// [threads_schedule]. Dynamically scheduled threads claim tiles one (or
+        /// `chunk_size`) at a time.
+        fn threads_tiles<'a, const N: usize>(
+            tiling: Tiling<N>,
+            schedule: &Schedule,
+            chunk_size: Option<usize>,
+            kernel: Box<impl Fn(KernelArgs<N>) + Send + Sync + 'a + Clone>,
+        ) {
+            let tiling = &tiling;
+            threads_schedule(tiling.len(), schedule, chunk_size, 1, |tiles| {
+                tiles.for_each(|k| {
+                    tiling.tile_indices(k).map(KernelArgs::IndexND).for_each(kernel.as_ref());
+                    cooperative_point();
+                })
+            });
+        }
+
+        /// `threads` backend of the [cpu] dispatch routine.
+        fn threads_cpu<'a, const N: usize>(
             execp: ExecutionPolicy<N>,
             kernel: Box<impl Fn(KernelArgs<N>) + Send + Sync + 'a + Clone>, // cannot be replaced by functor type bc of Clone
         ) -> Result<(), DispatchError> {
+            if threads_support(execp.range.kind()) == SupportLevel::Unimplemented
+                || (execp.chunk_predicate.is_some() && execp.range.kind() != PolicyKind::RangePolicy)
+            {
+                return Err(DispatchError::CPU(UNSUPPORTED_POLICY));
+            }
+            if launch_depth() > 0 {
+                // nested statement
+                return serial(execp, kernel);
+            }
             match execp.range {
                 RangePolicy::RangePolicy(range) => {
                     // serial, 1D range
@@ -183,76 +635,175 @@ cfg_if::cfg_if! {
                             "Dispatch uses N>1 for a 1D RangePolicy",
                         ));
                     }
-                    // compute chunk_size so that there is 1 chunk per thread
-                    let chunk_size = range.len() / num_cpus::get() + 1;
-                    let indices = range.collect::<Vec<usize>>();
-                    // use scope to avoid 'static lifetime reqs
-                    std::thread::scope(|s| {
-                        let handles: Vec<_> = indices.chunks(chunk_size).map(|chunk| {
-                            s.spawn(|| chunk.iter().map(|idx_ref| KernelArgs::Index1D(*idx_ref)).for_each(kernel.clone()))
-                        }).collect();
-
-                        for handle in handles {
-                            handle.join().unwrap();
-                        }
-                    });
+                    match &execp.chunk_predicate {
+                        Some(predicate) => threads_active_chunks(range, &execp.schedule, execp.chunk_size, predicate, kernel),
+                        None => threads_chunks(range, &execp.schedule, execp.chunk_size, kernel, KernelArgs::Index1D),
+                    }
                 }
-                RangePolicy::MDRangePolicy(_) => {
-                    // Kokkos does tiling to handle a MDRanges
-                    unimplemented!()
+                RangePolicy::MDRangePolicy(ranges) => {
+                    threads_tiles(default_tiling(ranges), &execp.schedule, execp.chunk_size, kernel)
+                }
+                RangePolicy::TiledMDRangePolicy { ranges, tile, outer, inner } => {
+                    let tiling = Tiling::new(ranges, tile, outer).with_inner(inner);
+                    threads_tiles(tiling, &execp.schedule, execp.chunk_size, kernel)
                 }
                 RangePolicy::TeamPolicy {
-                    league_size: _, // number of teams; akin to # of work items/batches
-                    team_size: _,   // number of threads per team; ignored in serial dispatch
-                    vector_size: _, // possible third dim parallelism; ignored in serial dispatch?
+                    league_size,
+                    team_size,
+                    vector_size,
+                    scratch_size,
                 } => {
-                    // interpret # of teams as # of work items (chunks);
-                    // necessary because serial dispatch is the fallback implementation
-                    // we ignore team size & vector size? since there's no parallelism here
-
-                    // is it even possible to use chunks? It would require either:
-                    //  - awareness of used external data
-                    //  - owning the used data; maybe in the TeamPolicy struct
-                    // 2nd option is the more plausible but it creates issues when accessing
-                    // multiple views for example; It also seems incompatible with the paradigm
-
-                    // -> build a team handle & let the user write its kernel using it
-                    todo!()
-                }
-                RangePolicy::PerTeam => {
-                    // used inside a team dispatch
-                    // executes the kernel once per team
-                    todo!()
+                    // each member of a team is executed by a distinct worker
+                    if league_size == 0 || team_size == 0 {
+                        return Ok(());
+                    }
+                    let scratch = ScratchArena::new(league_size, scratch_size);
+                    let workers = crate::runtime::workers();
+                    let league = League::new(
+                        [league_size, team_size, vector_size],
+                        &scratch,
+                        workers.num_workers(),
+                        matches!(execp.schedule, Schedule::Dynamic),
+                        execp.chunk_size,
+                    )?;
+                    fence(Ordering::Release);
+                    workers.broadcast(league.n_members(), |worker| {
+                        let _region = ParallelRegion::enter();
+                        fence(Ordering::Acquire);
+                        league.execute_member(worker, kernel.as_ref());
+                        fence(Ordering::Release);
+                    });
+                    fence(Ordering::Acquire);
                 }
-                RangePolicy::PerThread => {
-                    // used inside a team dispatch
-                    // executes the kernel once per threads of the team
-                    todo!()
+                RangePolicy::PerTeam(_)
+                | RangePolicy::PerThread(_)
+                | RangePolicy::TeamThreadRange(..)
+                | RangePolicy::TeamVectorRange(..)
+                | RangePolicy::ThreadVectorRange(..) => {
+                    // nested policies are executed by the calling team member
+                    return serial(execp, kernel);
                 }
-                _ => todo!(),
+                _ => return Err(DispatchError::CPU(UNSUPPORTED_POLICY)),
             };
             Ok(())
         }
-    } else if #[cfg(feature = "rayon")] {
-        /// CPU dispatch routine of `for` statements. Implementation depends on enabled feature(s).
-        ///
-        /// The dispatch function execute the kernel accordingly to the directives contained in the
-        /// execution policy. The kernel signature varies according to enabled features.
-        ///
-        /// ### Possible Kernel Signatures
-        ///
-        /// - `rayon` feature enabled: [`ForKernelType`]
-        /// - `threads` feature enabled: `Box<impl Fn(KernelArgs<N>) + Send + Sync + 'a + Clone>`
-        /// - no feature enabled: fall back to [`SerialForKernelType`]
-        ///
-        /// The `threads` implementation cannot currently use the generic [`ForKernelType`] because
-        /// of the Clone requirement.
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "rayon")] {
+        /// Returns the length of the blocks of indices processed between two cooperative
+        /// points & the minimum number of blocks of a task, given the `chunk_size` of a
+        /// policy.
+        fn rayon_blocks(chunk_size: Option<usize>) -> (usize, usize) {
+            match chunk_size {
+                Some(chunk) => {
+                    let block = chunk.clamp(1, COOPERATIVE_CHUNK);
+                    (block, chunk.div_ceil(block))
+                }
+                None => (COOPERATIVE_CHUNK, 1),
+            }
+        }
+
+        /// Execute the kernel over `range`, using chunks of [COOPERATIVE_CHUNK] indices.
+        /// `args` builds the kernel arguments associated to an index.
         ///
-        /// **Current version**: `rayon`
-        pub fn cpu<'a, const N: usize>(
+        /// Tasks are split using work stealing, whatever the schedule; `chunk_size` sets the
+        /// minimum number of indices of a task.
+        fn rayon_chunks<const N: usize>(
+            range: Range<usize>,
+            chunk_size: Option<usize>,
+            kernel: &ForKernelType<N>,
+            args: impl Fn(usize) -> KernelArgs<N> + Sync + Send,
+        ) {
+            // making indices N-sized arrays is necessary, even with the assertion...
+            // iterate over chunks to allow cooperative points
+            let (block, min_blocks) = rayon_blocks(chunk_size);
+            let (start, end) = (range.start, range.end);
+            crate::runtime::install(|| {
+                (0..range.len().div_ceil(block))
+                    .into_par_iter()
+                    .with_min_len(min_blocks)
+                    .for_each(|b| {
+                        let _region = ParallelRegion::enter();
+                        let first = start + b * block;
+                        (first..(first + block).min(end)).map(&args).for_each(kernel);
+                        cooperative_point();
+                    })
+            })
+        }
+
+        /// Execute the kernel over the chunks of `range` accepted by `predicate`, each chunk
+        /// being a task. See [ExecutionPolicy::chunk_predicate].
+        fn rayon_active_chunks<const N: usize>(
+            range: Range<usize>,
+            chunk_size: Option<usize>,
+            predicate: &ChunkPredicate,
+            kernel: &ForKernelType<N>,
+        ) {
+            let (n_chunks, len) = predicate_chunks(&range, chunk_size);
+            crate::runtime::install(|| {
+                (0..n_chunks).into_par_iter().for_each(|c| {
+                    let _region = ParallelRegion::enter();
+                    let bounds = chunk_bounds(&range, len, c);
+                    if predicate.test(bounds.clone()) {
+                        bounds.map(KernelArgs::Index1D).for_each(kernel);
+                    }
+                    cooperative_point();
+                })
+            })
+        }
+
+        /// Execute the kernel over the tiles of `tiling`, each tile being a task. Using a
+        /// `chunk_size`, tasks are made of at least `chunk_size` tiles.
+        fn rayon_tiles<const N: usize>(
+            tiling: Tiling<N>,
+            chunk_size: Option<usize>,
+            kernel: &ForKernelType<N>,
+        ) {
+            crate::runtime::install(|| {
+                (0..tiling.len())
+                    .into_par_iter()
+                    .with_min_len(chunk_size.unwrap_or(1).max(1))
+                    .for_each(|k| {
+                        let _region = ParallelRegion::enter();
+                        tiling.tile_indices(k).map(KernelArgs::IndexND).for_each(kernel);
+                        cooperative_point();
+                    })
+            })
+        }
+
+        /// Support table of the `rayon` backend of the [cpu] dispatch routine.
+        fn rayon_support(kind: PolicyKind) -> SupportLevel {
+            match kind {
+                PolicyKind::RangePolicy
+                | PolicyKind::MDRangePolicy
+                | PolicyKind::TiledMDRangePolicy
+                | PolicyKind::TeamPolicy => SupportLevel::Full,
+                // nested policies are executed by the calling team member
+                PolicyKind::PerTeam
+                | PolicyKind::PerThread
+                | PolicyKind::TeamThreadRange
+                | PolicyKind::TeamVectorRange
+                | PolicyKind::ThreadVectorRange => SupportLevel::SerialFallback,
+                _ => SupportLevel::Unimplemented,
+            }
+        }
+
+        /// `rayon` backend of the [cpu] dispatch routine.
+        fn rayon_cpu<const N: usize>(
             execp: ExecutionPolicy<N>,
             kernel: ForKernelType<N>,
         ) -> Result<(), DispatchError> {
+            if rayon_support(execp.range.kind()) == SupportLevel::Unimplemented
+                || (execp.chunk_predicate.is_some() && execp.range.kind() != PolicyKind::RangePolicy)
+            {
+                return Err(DispatchError::CPU(UNSUPPORTED_POLICY));
+            }
+            if launch_depth() > 0 {
+                // nested statement
+                return serial(execp, kernel);
+            }
             match execp.range {
                 RangePolicy::RangePolicy(range) => {
                     // serial, 1D range
@@ -261,49 +812,156 @@ cfg_if::cfg_if! {
                             "Dispatch uses N>1 for a 1D RangePolicy",
                         ));
                     }
-                    // making indices N-sized arrays is necessary, even with the assertion...
-                    range
-                        .into_par_iter()
-                        .map(KernelArgs::Index1D)
-                        .for_each(kernel)
+                    match &execp.chunk_predicate {
+                        Some(predicate) => rayon_active_chunks(range, execp.chunk_size, predicate, &kernel),
+                        None => rayon_chunks(range, execp.chunk_size, &kernel, KernelArgs::Index1D),
+                    }
+                }
+                RangePolicy::MDRangePolicy(ranges) => {
+                    rayon_tiles(default_tiling(ranges), execp.chunk_size, &kernel)
                 }
-                RangePolicy::MDRangePolicy(_) => {
-                    // Kokkos does tiling to handle a MDRanges
-                    unimplemented!()
+                RangePolicy::TiledMDRangePolicy { ranges, tile, outer, inner } => {
+                    rayon_tiles(Tiling::new(ranges, tile, outer).with_inner(inner), execp.chunk_size, &kernel)
                 }
                 RangePolicy::TeamPolicy {
-                    league_size: _, // number of teams; akin to # of work items/batches
-                    team_size: _,   // number of threads per team; ignored in serial dispatch
-                    vector_size: _, // possible third dim parallelism; ignored in serial dispatch?
+                    league_size,
+                    team_size,
+                    vector_size,
+                    scratch_size,
                 } => {
-                    // interpret # of teams as # of work items (chunks);
-                    // necessary because serial dispatch is the fallback implementation
-                    // we ignore team size & vector size? since there's no parallelism here
-
-                    // is it even possible to use chunks? It would require either:
-                    //  - awareness of used external data
-                    //  - owning the used data; maybe in the TeamPolicy struct
-                    // 2nd option is the more plausible but it creates issues when accessing
-                    // multiple views for example; It also seems incompatible with the paradigm
-
-                    // -> build a team handle & let the user write its kernel using it
-                    todo!()
-                }
-                RangePolicy::PerTeam => {
-                    // used inside a team dispatch
-                    // executes the kernel once per team
-                    todo!()
+                    // each member of a team is executed by a distinct thread of the pool,
+                    // teams being claimed dynamically
+                    if league_size == 0 || team_size == 0 {
+                        return Ok(());
+                    }
+                    let scratch = ScratchArena::new(league_size, scratch_size);
+                    crate::runtime::install(|| {
+                        let league = League::new(
+                            [league_size, team_size, vector_size],
+                            &scratch,
+                            rayon::current_num_threads(),
+                            true,
+                            execp.chunk_size,
+                        )?;
+                        rayon::broadcast(|ctx| {
+                            if ctx.index() < league.n_members() {
+                                let _region = ParallelRegion::enter();
+                                league.execute_member(ctx.index(), &kernel);
+                            }
+                        });
+                        Ok::<_, DispatchError>(())
+                    })?;
                 }
-                RangePolicy::PerThread => {
-                    // used inside a team dispatch
-                    // executes the kernel once per threads of the team
-                    todo!()
+                RangePolicy::PerTeam(_)
+                | RangePolicy::PerThread(_)
+                | RangePolicy::TeamThreadRange(..)
+                | RangePolicy::TeamVectorRange(..)
+                | RangePolicy::ThreadVectorRange(..) => {
+                    // nested policies are executed by the calling team member
+                    return serial(execp, kernel);
                 }
-                _ => todo!(),
+                _ => return Err(DispatchError::CPU(UNSUPPORTED_POLICY)),
             };
             Ok(())
         }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "threads")] {
+        /// Support table of the [cpu] dispatch routine. Depends on enabled feature(s) & on the
+        /// [current][backend::current] backend.
+        ///
+        /// **Current version**: `threads`, `rayon` if enabled
+        pub fn cpu_support(kind: PolicyKind) -> SupportLevel {
+            match backend::current() {
+                CpuBackend::Serial => serial_support(kind).as_fallback(),
+                #[cfg(feature = "threads")]
+                CpuBackend::Threads => threads_support(kind),
+                #[cfg(feature = "rayon")]
+                CpuBackend::Rayon => rayon_support(kind),
+            }
+        }
+
+        /// CPU dispatch routine of `for` statements. Implementation depends on enabled feature(s).
+        ///
+        /// The dispatch function execute the kernel accordingly to the directives contained in the
+        /// execution policy, using the [current][backend::current] backend. The kernel signature
+        /// varies according to enabled features.
+        ///
+        /// ### Possible Kernel Signatures
+        ///
+        /// - `threads` feature enabled: `Box<impl Fn(KernelArgs<N>) + Send + Sync + 'a + Clone>`
+        /// - `rayon` feature enabled: [`ForKernelType`]
+        /// - no feature enabled: fall back to [`SerialForKernelType`]
+        ///
+        /// The `threads` implementation cannot currently use the generic [`ForKernelType`] because
+        /// of the Clone requirement.
+        ///
+        /// **Current version**: `threads`, `rayon` if enabled
+        pub fn cpu<'a, const N: usize>(
+            execp: ExecutionPolicy<N>,
+            kernel: Box<impl Fn(KernelArgs<N>) + Send + Sync + 'a + Clone>, // cannot be replaced by functor type bc of Clone
+        ) -> Result<(), DispatchError> {
+            match backend::current() {
+                CpuBackend::Serial => serial(execp, kernel),
+                #[cfg(feature = "threads")]
+                CpuBackend::Threads => threads_cpu(execp, kernel),
+                #[cfg(feature = "rayon")]
+                CpuBackend::Rayon => rayon_cpu(execp, kernel),
+            }
+        }
+    } else if #[cfg(feature = "rayon")] {
+        /// Support table of the [cpu] dispatch routine. Depends on enabled feature(s) & on the
+        /// [current][backend::current] backend.
+        ///
+        /// **Current version**: `rayon`
+        pub fn cpu_support(kind: PolicyKind) -> SupportLevel {
+            match backend::current() {
+                CpuBackend::Serial => serial_support(kind).as_fallback(),
+                #[cfg(feature = "threads")]
+                CpuBackend::Threads => threads_support(kind),
+                #[cfg(feature = "rayon")]
+                CpuBackend::Rayon => rayon_support(kind),
+            }
+        }
+
+        /// CPU dispatch routine of `for` statements. Implementation depends on enabled feature(s).
+        ///
+        /// The dispatch function execute the kernel accordingly to the directives contained in the
+        /// execution policy, using the [current][backend::current] backend. The kernel signature
+        /// varies according to enabled features.
+        ///
+        /// ### Possible Kernel Signatures
+        ///
+        /// - `threads` feature enabled: `Box<impl Fn(KernelArgs<N>) + Send + Sync + 'a + Clone>`
+        /// - `rayon` feature enabled: [`ForKernelType`]
+        /// - no feature enabled: fall back to [`SerialForKernelType`]
+        ///
+        /// The `threads` implementation cannot currently use the generic [`ForKernelType`] because
+        /// of the Clone requirement.
+        ///
+        /// **Current version**: `rayon`
+        pub fn cpu<const N: usize>(
+            execp: ExecutionPolicy<N>,
+            kernel: ForKernelType<N>,
+        ) -> Result<(), DispatchError> {
+            match backend::current() {
+                CpuBackend::Serial => serial(execp, kernel),
+                #[cfg(feature = "threads")]
+                CpuBackend::Threads => threads_cpu(execp, kernel),
+                #[cfg(feature = "rayon")]
+                CpuBackend::Rayon => rayon_cpu(execp, kernel),
+            }
+        }
     } else {
+        /// Support table of the [cpu] dispatch routine. Depends on enabled feature(s).
+        ///
+        /// **Current version**: no feature
+        pub fn cpu_support(kind: PolicyKind) -> SupportLevel {
+            serial_support(kind).as_fallback()
+        }
+
         /// CPU dispatch routine of `for` statements. Implementation depends on enabled feature(s).
         ///
         /// The dispatch function execute the kernel accordingly to the directives contained in the
@@ -330,14 +988,31 @@ cfg_if::cfg_if! {
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "gpu")] {
+        /// Support table of the [gpu] dispatch routine. Depends on enabled feature(s).
+        ///
+        /// **Current version**: `gpu`
+        pub fn gpu_support(_kind: PolicyKind) -> SupportLevel {
+            SupportLevel::Unimplemented
+        }
+
         /// GPU Dispatch routine of `for` statements. UNIMPLEMENTED
-        pub fn gpu<'a, const N: usize>(
+        ///
+        /// Rust closures cannot be compiled to shaders; built-in kernels of the
+        /// [gpu][crate::gpu] module can be used to execute common operations on the device.
+        pub fn gpu<const N: usize>(
             _execp: ExecutionPolicy<N>,
             _kernel: ForKernelType<N>,
         ) -> Result<(), DispatchError> {
-            unimplemented!()
+            Err(DispatchError::GPU(UNSUPPORTED_POLICY))
         }
     } else {
+        /// Support table of the [gpu] dispatch routine. Depends on enabled feature(s).
+        ///
+        /// **Current version**: no feature
+        pub fn gpu_support(kind: PolicyKind) -> SupportLevel {
+            serial_support(kind).as_fallback()
+        }
+
         /// GPU Dispatch routine of `for` statements. UNIMPLEMENTED
         pub fn gpu<const N: usize>(
             execp: ExecutionPolicy<N>,
@@ -348,6 +1023,548 @@ cfg_if::cfg_if! {
     }
 }
 
+// reduce dispatch
+
+// Reduce routines share the support tables of the `for` routines, restricted to range
+// policies, see [supports][super::supports]. Partial results are
+// `None` until a value is reduced, so that operators do not require an identity.
+
+/// Serial dispatch routine of `reduce` statements. Does not depend on enabled feature(s).
+pub fn serial_reduce<const N: usize, R>(
+    execp: ExecutionPolicy<N>,
+    op: &R,
+    mut kernel: impl FnMut(KernelArgs<N>) -> R::Value,
+) -> Result<Option<R::Value>, DispatchError>
+where
+    R: Reducer,
+{
+    if execp.chunk_predicate.is_some() {
+        // chunk predicates are only honoured by `for` statements
+        return Err(DispatchError::Serial(UNSUPPORTED_POLICY));
+    }
+    match execp.range {
+        RangePolicy::RangePolicy(range) => {
+            if N != 1 {
+                return Err(DispatchError::Serial(
+                    "Dispatch uses N>1 for a 1D RangePolicy",
+                ));
+            }
+            Ok(op.reduce(range.map(|i| kernel(KernelArgs::Index1D(i)))))
+        }
+        RangePolicy::MDRangePolicy(ranges) => {
+            Ok(op.reduce(MDIndexIter::new(ranges).map(|idx| kernel(KernelArgs::IndexND(idx)))))
+        }
+        RangePolicy::TiledMDRangePolicy {
+            ranges,
+            tile,
+            outer,
+            inner,
+        } => {
+            let tiling = Tiling::new(ranges, tile, outer).with_inner(inner);
+            Ok(op.reduce(
+                (0..tiling.len())
+                    .flat_map(|k| tiling.tile_indices(k))
+                    .map(|idx| kernel(KernelArgs::IndexND(idx))),
+            ))
+        }
+        _ => Err(DispatchError::Serial(UNSUPPORTED_POLICY)),
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "threads")] {
+        /// Reduce the kernel values over `range` according to `schedule`, see
+        /// [threads_schedule]. Partial results of chunks are combined in chunk order.
+        fn threads_reduce_range<const N: usize, R>(
+            range: Range<usize>,
+            schedule: &Schedule,
+            chunk_size: Option<usize>,
+            op: &R,
+            kernel: &(impl Fn(KernelArgs<N>) -> R::Value + Sync),
+        ) -> Option<R::Value>
+        where
+            R: Reducer + Sync,
+            R::Value: Send,
+        {
+            let start = range.start;
+            let partials = threads_schedule(range.len(), schedule, chunk_size, range_grain(range.len()), |sub| {
+                op.reduce((sub.start..sub.end).step_by(COOPERATIVE_CHUNK).filter_map(|sub_start| {
+                    let partial = op.reduce(
+                        (sub_start..(sub_start + COOPERATIVE_CHUNK).min(sub.end))
+                            .map(|idx| kernel(KernelArgs::Index1D(start + idx))),
+                    );
+                    cooperative_point();
+                    partial
+                }))
+            });
+            op.reduce(partials.into_iter().flatten())
+        }
+
+        /// Reduce the kernel values over the tiles of `tiling` according to `schedule`, see
+        /// [threads_schedule]. Partial results of chunks are combined in chunk order.
+        fn threads_reduce_tiles<const N: usize, R>(
+            tiling: Tiling<N>,
+            schedule: &Schedule,
+            chunk_size: Option<usize>,
+            op: &R,
+            kernel: &(impl Fn(KernelArgs<N>) -> R::Value + Sync),
+        ) -> Option<R::Value>
+        where
+            R: Reducer + Sync,
+            R::Value: Send,
+        {
+            let tiling = &tiling;
+            let partials = threads_schedule(tiling.len(), schedule, chunk_size, 1, |tiles| {
+                op.reduce(tiles.filter_map(|k| {
+                    let partial = op.reduce(tiling.tile_indices(k).map(|idx| kernel(KernelArgs::IndexND(idx))));
+                    cooperative_point();
+                    partial
+                }))
+            });
+            op.reduce(partials.into_iter().flatten())
+        }
+
+        /// `threads` backend of the [cpu_reduce] dispatch routine.
+        fn threads_cpu_reduce<const N: usize, R>(
+            execp: ExecutionPolicy<N>,
+            op: &R,
+            kernel: impl Fn(KernelArgs<N>) -> R::Value + Sync,
+        ) -> Result<Option<R::Value>, DispatchError>
+        where
+            R: Reducer + Sync,
+            R::Value: Send,
+        {
+            if threads_support(execp.range.kind()) == SupportLevel::Unimplemented
+                || execp.chunk_predicate.is_some()
+            {
+                return Err(DispatchError::CPU(UNSUPPORTED_POLICY));
+            }
+            if launch_depth() > 0 {
+                // nested statement
+                return serial_reduce(execp, op, kernel);
+            }
+            let (schedule, chunk_size) = (&execp.schedule, execp.chunk_size);
+            match execp.range {
+                RangePolicy::RangePolicy(range) => {
+                    if N != 1 {
+                        return Err(DispatchError::CPU(
+                            "Dispatch uses N>1 for a 1D RangePolicy",
+                        ));
+                    }
+                    Ok(threads_reduce_range(range, schedule, chunk_size, op, &kernel))
+                }
+                RangePolicy::MDRangePolicy(ranges) => {
+                    Ok(threads_reduce_tiles(default_tiling(ranges), schedule, chunk_size, op, &kernel))
+                }
+                RangePolicy::TiledMDRangePolicy { ranges, tile, outer, inner } => {
+                    let tiling = Tiling::new(ranges, tile, outer).with_inner(inner);
+                    Ok(threads_reduce_tiles(tiling, schedule, chunk_size, op, &kernel))
+                }
+                _ => Err(DispatchError::CPU(UNSUPPORTED_POLICY)),
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "rayon")] {
+        /// Reduce the kernel values over `range`, see [rayon_chunks] for the splitting of
+        /// the range.
+        fn rayon_reduce_range<const N: usize, R>(
+            range: Range<usize>,
+            chunk_size: Option<usize>,
+            op: &R,
+            kernel: &(impl Fn(KernelArgs<N>) -> R::Value + Sync),
+        ) -> Option<R::Value>
+        where
+            R: Reducer + Sync,
+            R::Value: Send,
+        {
+            let (block, min_blocks) = rayon_blocks(chunk_size);
+            let (start, end) = (range.start, range.end);
+            let partials: Vec<Option<R::Value>> = crate::runtime::install(|| {
+                (0..range.len().div_ceil(block))
+                    .into_par_iter()
+                    .with_min_len(min_blocks)
+                    .map(|b| {
+                        let _region = ParallelRegion::enter();
+                        let first = start + b * block;
+                        let partial =
+                            op.reduce((first..(first + block).min(end)).map(|i| kernel(KernelArgs::Index1D(i))));
+                        cooperative_point();
+                        partial
+                    })
+                    .collect()
+            });
+            // combine in block order
+            op.reduce(partials.into_iter().flatten())
+        }
+
+        /// Reduce the kernel values over the tiles of `tiling`, each tile being a task. Using
+        /// a `chunk_size`, tasks are made of at least `chunk_size` tiles.
+        fn rayon_reduce_tiles<const N: usize, R>(
+            tiling: Tiling<N>,
+            chunk_size: Option<usize>,
+            op: &R,
+            kernel: &(impl Fn(KernelArgs<N>) -> R::Value + Sync),
+        ) -> Option<R::Value>
+        where
+            R: Reducer + Sync,
+            R::Value: Send,
+        {
+            let partials: Vec<Option<R::Value>> = crate::runtime::install(|| {
+                (0..tiling.len())
+                    .into_par_iter()
+                    .with_min_len(chunk_size.unwrap_or(1).max(1))
+                    .map(|k| {
+                        let _region = ParallelRegion::enter();
+                        let partial = op.reduce(tiling.tile_indices(k).map(|idx| kernel(KernelArgs::IndexND(idx))));
+                        cooperative_point();
+                        partial
+                    })
+                    .collect()
+            });
+            // combine in tile order
+            op.reduce(partials.into_iter().flatten())
+        }
+
+        /// `rayon` backend of the [cpu_reduce] dispatch routine.
+        fn rayon_cpu_reduce<const N: usize, R>(
+            execp: ExecutionPolicy<N>,
+            op: &R,
+            kernel: impl Fn(KernelArgs<N>) -> R::Value + Sync,
+        ) -> Result<Option<R::Value>, DispatchError>
+        where
+            R: Reducer + Sync,
+            R::Value: Send,
+        {
+            if rayon_support(execp.range.kind()) == SupportLevel::Unimplemented
+                || execp.chunk_predicate.is_some()
+            {
+                return Err(DispatchError::CPU(UNSUPPORTED_POLICY));
+            }
+            if launch_depth() > 0 {
+                // nested statement
+                return serial_reduce(execp, op, kernel);
+            }
+            match execp.range {
+                RangePolicy::RangePolicy(range) => {
+                    if N != 1 {
+                        return Err(DispatchError::CPU(
+                            "Dispatch uses N>1 for a 1D RangePolicy",
+                        ));
+                    }
+                    Ok(rayon_reduce_range(range, execp.chunk_size, op, &kernel))
+                }
+                RangePolicy::MDRangePolicy(ranges) => {
+                    Ok(rayon_reduce_tiles(default_tiling(ranges), execp.chunk_size, op, &kernel))
+                }
+                RangePolicy::TiledMDRangePolicy { ranges, tile, outer, inner } => {
+                    let tiling = Tiling::new(ranges, tile, outer).with_inner(inner);
+                    Ok(rayon_reduce_tiles(tiling, execp.chunk_size, op, &kernel))
+                }
+                _ => Err(DispatchError::CPU(UNSUPPORTED_POLICY)),
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// CPU dispatch routine of `reduce` statements, using the [current][backend::current]
+        /// backend. Implementation depends on enabled feature(s).
+        ///
+        /// **Current version**: thread-safe
+        pub fn cpu_reduce<const N: usize, R>(
+            execp: ExecutionPolicy<N>,
+            op: &R,
+            kernel: impl Fn(KernelArgs<N>) -> R::Value + Sync,
+        ) -> Result<Option<R::Value>, DispatchError>
+        where
+            R: Reducer + Sync,
+            R::Value: Send,
+        {
+            match backend::current() {
+                CpuBackend::Serial => serial_reduce(execp, op, kernel),
+                #[cfg(feature = "threads")]
+                CpuBackend::Threads => threads_cpu_reduce(execp, op, kernel),
+                #[cfg(feature = "rayon")]
+                CpuBackend::Rayon => rayon_cpu_reduce(execp, op, kernel),
+            }
+        }
+    } else {
+        /// CPU dispatch routine of `reduce` statements. Implementation depends on enabled
+        /// feature(s).
+        ///
+        /// **Current version**: no feature
+        pub fn cpu_reduce<const N: usize, R>(
+            execp: ExecutionPolicy<N>,
+            op: &R,
+            kernel: impl FnMut(KernelArgs<N>) -> R::Value,
+        ) -> Result<Option<R::Value>, DispatchError>
+        where
+            R: Reducer,
+        {
+            serial_reduce(execp, op, kernel)
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "gpu")] {
+        /// GPU dispatch routine of `reduce` statements. UNIMPLEMENTED
+        pub fn gpu_reduce<const N: usize, R>(
+            _execp: ExecutionPolicy<N>,
+            _op: &R,
+            _kernel: impl FnMut(KernelArgs<N>) -> R::Value,
+        ) -> Result<Option<R::Value>, DispatchError>
+        where
+            R: Reducer,
+        {
+            Err(DispatchError::GPU(UNSUPPORTED_POLICY))
+        }
+    } else {
+        /// GPU dispatch routine of `reduce` statements. UNIMPLEMENTED
+        pub fn gpu_reduce<const N: usize, R>(
+            execp: ExecutionPolicy<N>,
+            op: &R,
+            kernel: impl FnMut(KernelArgs<N>) -> R::Value,
+        ) -> Result<Option<R::Value>, DispatchError>
+        where
+            R: Reducer,
+        {
+            serial_reduce(execp, op, kernel)
+        }
+    }
+}
+
+// scan dispatch
+
+// Scans are done in three passes: each chunk is scanned locally, chunk totals are
+// scanned sequentially, and the resulting offsets are added to each chunk. `T::default()`
+// is used as the identity of the addition.
+
+/// Scan the values of a chunk of indices starting at `start` into `out`, returning the
+/// total of the chunk.
+fn scan_chunk<T>(
+    out: &mut [T],
+    start: usize,
+    mode: ScanMode,
+    mut kernel: impl FnMut(KernelArgs<1>) -> T,
+) -> T
+where
+    T: DataTraits + Add<Output = T>,
+{
+    let mut acc = T::default();
+    out.iter_mut().enumerate().for_each(|(i, res)| {
+        let val = kernel(KernelArgs::Index1D(start + i));
+        match mode {
+            ScanMode::Inclusive => {
+                acc = acc + val;
+                *res = acc;
+            }
+            ScanMode::Exclusive => {
+                *res = acc;
+                acc = acc + val;
+            }
+        }
+    });
+    acc
+}
+
+/// Returns the offset of each chunk given their totals.
+#[cfg(any(feature = "threads", feature = "rayon"))]
+fn chunk_offsets<T>(totals: &[T]) -> Vec<T>
+where
+    T: DataTraits + Add<Output = T>,
+{
+    let mut acc = T::default();
+    totals
+        .iter()
+        .map(|total| {
+            let offset = acc;
+            acc = acc + *total;
+            offset
+        })
+        .collect()
+}
+
+/// Serial dispatch routine of `scan` statements. Does not depend on enabled feature(s).
+pub fn serial_scan<T>(
+    execp: ExecutionPolicy<1>,
+    mode: ScanMode,
+    kernel: impl FnMut(KernelArgs<1>) -> T,
+) -> Result<Vec<T>, DispatchError>
+where
+    T: DataTraits + Add<Output = T>,
+{
+    if execp.chunk_predicate.is_some() {
+        // chunk predicates are only honoured by `for` statements
+        return Err(DispatchError::Serial(UNSUPPORTED_POLICY));
+    }
+    match execp.range {
+        RangePolicy::RangePolicy(range) => {
+            let mut out = vec![T::default(); range.len()];
+            scan_chunk(&mut out, range.start, mode, kernel);
+            Ok(out)
+        }
+        _ => Err(DispatchError::Serial(UNSUPPORTED_POLICY)),
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "threads")] {
+        /// `threads` backend of the [cpu_scan] dispatch routine.
+        fn threads_cpu_scan<T>(
+            execp: ExecutionPolicy<1>,
+            mode: ScanMode,
+            kernel: impl Fn(KernelArgs<1>) -> T + Sync,
+        ) -> Result<Vec<T>, DispatchError>
+        where
+            T: DataTraits + Add<Output = T> + Send + Sync,
+        {
+            if launch_depth() > 0 {
+                // nested statement
+                return serial_scan(execp, mode, kernel);
+            }
+            let (RangePolicy::RangePolicy(range), None) = (execp.range, &execp.chunk_predicate) else {
+                return Err(DispatchError::CPU(UNSUPPORTED_POLICY));
+            };
+            let (schedule, chunk_size) = (&execp.schedule, execp.chunk_size);
+            let (n_items, grain) = (range.len(), range_grain(range.len()));
+            let len = chunk_len(n_items, schedule, chunk_size, grain);
+            let mut out = vec![T::default(); n_items];
+            {
+                // chunks claimed by workers are disjoint, locks are never contended
+                let chunks: Vec<Mutex<&mut [T]>> = out.chunks_mut(len).map(Mutex::new).collect();
+                let (chunks, kernel) = (&chunks, &kernel);
+                let totals = threads_schedule(n_items, schedule, chunk_size, grain, |sub| {
+                    let mut chunk = chunks[sub.start / len].lock().unwrap();
+                    scan_chunk(&mut chunk, range.start + sub.start, mode, kernel)
+                });
+                let offsets = chunk_offsets(&totals);
+                threads_schedule(n_items, schedule, chunk_size, grain, |sub| {
+                    let k = sub.start / len;
+                    if k > 0 {
+                        let offset = offsets[k];
+                        chunks[k].lock().unwrap().iter_mut().for_each(|res| *res = offset + *res)
+                    }
+                });
+            }
+            Ok(out)
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "rayon")] {
+        /// `rayon` backend of the [cpu_scan] dispatch routine.
+        fn rayon_cpu_scan<T>(
+            execp: ExecutionPolicy<1>,
+            mode: ScanMode,
+            kernel: impl Fn(KernelArgs<1>) -> T + Sync,
+        ) -> Result<Vec<T>, DispatchError>
+        where
+            T: DataTraits + Add<Output = T> + Send + Sync,
+        {
+            if launch_depth() > 0 {
+                // nested statement
+                return serial_scan(execp, mode, kernel);
+            }
+            let (RangePolicy::RangePolicy(range), None) = (execp.range, &execp.chunk_predicate) else {
+                return Err(DispatchError::CPU(UNSUPPORTED_POLICY));
+            };
+            // tasks are split using work stealing, whatever the schedule
+            let chunk_size = execp
+                .chunk_size
+                .unwrap_or(range.len() / crate::runtime::num_threads() + 1)
+                .max(1);
+            let mut out = vec![T::default(); range.len()];
+            crate::runtime::install(|| {
+                let totals: Vec<T> = out
+                    .par_chunks_mut(chunk_size)
+                    .enumerate()
+                    .map(|(c, chunk)| {
+                        let _region = ParallelRegion::enter();
+                        scan_chunk(chunk, range.start + c * chunk_size, mode, &kernel)
+                    })
+                    .collect();
+                let offsets = chunk_offsets(&totals);
+                out.par_chunks_mut(chunk_size)
+                    .zip(offsets)
+                    .skip(1)
+                    .for_each(|(chunk, offset)| chunk.iter_mut().for_each(|res| *res = offset + *res));
+            });
+            Ok(out)
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// CPU dispatch routine of `scan` statements, using the [current][backend::current]
+        /// backend. Implementation depends on enabled feature(s).
+        ///
+        /// **Current version**: thread-safe
+        pub fn cpu_scan<T>(
+            execp: ExecutionPolicy<1>,
+            mode: ScanMode,
+            kernel: impl Fn(KernelArgs<1>) -> T + Sync,
+        ) -> Result<Vec<T>, DispatchError>
+        where
+            T: DataTraits + Add<Output = T> + Send + Sync,
+        {
+            match backend::current() {
+                CpuBackend::Serial => serial_scan(execp, mode, kernel),
+                #[cfg(feature = "threads")]
+                CpuBackend::Threads => threads_cpu_scan(execp, mode, kernel),
+                #[cfg(feature = "rayon")]
+                CpuBackend::Rayon => rayon_cpu_scan(execp, mode, kernel),
+            }
+        }
+    } else {
+        /// CPU dispatch routine of `scan` statements. Implementation depends on enabled
+        /// feature(s).
+        ///
+        /// **Current version**: no feature
+        pub fn cpu_scan<T>(
+            execp: ExecutionPolicy<1>,
+            mode: ScanMode,
+            kernel: impl FnMut(KernelArgs<1>) -> T,
+        ) -> Result<Vec<T>, DispatchError>
+        where
+            T: DataTraits + Add<Output = T>,
+        {
+            serial_scan(execp, mode, kernel)
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "gpu")] {
+        /// GPU dispatch routine of `scan` statements. UNIMPLEMENTED
+        pub fn gpu_scan<T>(
+            _execp: ExecutionPolicy<1>,
+            _mode: ScanMode,
+            _kernel: impl FnMut(KernelArgs<1>) -> T,
+        ) -> Result<Vec<T>, DispatchError> {
+            Err(DispatchError::GPU(UNSUPPORTED_POLICY))
+        }
+    } else {
+        /// GPU dispatch routine of `scan` statements. UNIMPLEMENTED
+        pub fn gpu_scan<T>(
+            execp: ExecutionPolicy<1>,
+            mode: ScanMode,
+            kernel: impl FnMut(KernelArgs<1>) -> T,
+        ) -> Result<Vec<T>, DispatchError>
+        where
+            T: DataTraits + Add<Output = T>,
+        {
+            serial_scan(execp, mode, kernel)
+        }
+    }
+}
+
 // ~~~~~~
 // Tests
 
@@ -373,13 +1590,15 @@ mod tests {
             space: ExecutionSpace::DeviceCPU,
             range: rangep,
             schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
         };
 
         // very messy way to write a kernel but it should work for now
         let kernel = Box::new(|arg: KernelArgs<1>| match arg {
             KernelArgs::Index1D(i) => mat.set([i], 1.0),
             KernelArgs::IndexND(_) => unimplemented!(),
-            KernelArgs::Handle => unimplemented!(),
+            KernelArgs::Handle(_) => unimplemented!(),
         });
 
         serial(execp, kernel).unwrap();
@@ -408,13 +1627,15 @@ mod tests {
             space: ExecutionSpace::DeviceCPU,
             range: rangep,
             schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
         };
 
         // very messy way to write a kernel but it should work for now
         let kernel = Box::new(|arg: KernelArgs<2>| match arg {
             KernelArgs::Index1D(_) => unimplemented!(),
             KernelArgs::IndexND([i, j]) => mat.set([i, j], 1.0),
-            KernelArgs::Handle => unimplemented!(),
+            KernelArgs::Handle(_) => unimplemented!(),
         });
 
         serial(execp, kernel).unwrap();
@@ -445,16 +1666,176 @@ mod tests {
             space: ExecutionSpace::DeviceCPU,
             range: rangep,
             schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
         };
 
         // very messy way to write a kernel but it should work for now
         let kernel = Box::new(|arg: KernelArgs<1>| match arg {
             KernelArgs::Index1D(_) => unimplemented!(),
             KernelArgs::IndexND(idx) => mat.set(idx, 1.0),
-            KernelArgs::Handle => unimplemented!(),
+            KernelArgs::Handle(_) => unimplemented!(),
         });
 
         serial(execp, kernel).unwrap();
         assert_eq!(mat.raw_val().unwrap(), ref_mat.raw_val().unwrap());
     }
+
+    #[test]
+    fn chunk_sizes() {
+        use crate::routines::{
+            parameters::{ExecutionSpace, MDRange, Range1D, Schedule, TypedExecutionPolicy},
+            typed::parallel_for,
+        };
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let hits: Vec<AtomicUsize> = (0..1000).map(|_| AtomicUsize::new(0)).collect();
+        for schedule in [Schedule::Static, Schedule::Dynamic] {
+            for chunk_size in [None, Some(0), Some(1), Some(7), Some(5000)] {
+                let execp = TypedExecutionPolicy {
+                    space: ExecutionSpace::DeviceCPU,
+                    policy: Range1D(3..1000),
+                    schedule: schedule.clone(),
+                    chunk_size,
+                    chunk_predicate: None,
+                };
+                parallel_for(execp, |i| {
+                    hits[i].fetch_add(1, Ordering::Relaxed);
+                })
+                .unwrap();
+                let execp = TypedExecutionPolicy {
+                    space: ExecutionSpace::DeviceCPU,
+                    policy: MDRange([0..10, 0..100]),
+                    schedule: schedule.clone(),
+                    chunk_size,
+                    chunk_predicate: None,
+                };
+                parallel_for(execp, |[i, j]| {
+                    hits[100 * i + j].fetch_add(1, Ordering::Relaxed);
+                })
+                .unwrap();
+            }
+        }
+        // each index is executed exactly once per statement
+        assert!(hits[..3].iter().all(|h| h.load(Ordering::Relaxed) == 10));
+        assert!(hits[3..].iter().all(|h| h.load(Ordering::Relaxed) == 20));
+    }
+
+    #[test]
+    fn reduce_scan_chunk_sizes() {
+        use crate::routines::{
+            parameters::{
+                ExecutionSpace, MDRange, Range1D, ReduceOp, ScanMode, Schedule,
+                TypedExecutionPolicy,
+            },
+            typed::{parallel_reduce, parallel_scan},
+        };
+
+        let ref_scan: Vec<usize> = (3..1000)
+            .scan(0, |acc, i| {
+                *acc += i;
+                Some(*acc)
+            })
+            .collect();
+        for schedule in [Schedule::Static, Schedule::Dynamic] {
+            for chunk_size in [None, Some(0), Some(1), Some(7), Some(5000)] {
+                let execp = TypedExecutionPolicy {
+                    space: ExecutionSpace::DeviceCPU,
+                    policy: Range1D(3..1000),
+                    schedule: schedule.clone(),
+                    chunk_size,
+                    chunk_predicate: None,
+                };
+                let sum = parallel_reduce(execp.clone(), ReduceOp::Sum, |i| i).unwrap();
+                assert_eq!(sum, (3..1000).sum::<usize>());
+                let max =
+                    parallel_reduce(execp.clone(), ReduceOp::Max, |i| (i * 37) % 1000).unwrap();
+                assert_eq!(max, 999);
+                let scan = parallel_scan(execp, ScanMode::Inclusive, |i| i).unwrap();
+                assert_eq!(scan, ref_scan);
+                let execp = TypedExecutionPolicy {
+                    space: ExecutionSpace::DeviceCPU,
+                    policy: MDRange([0..10, 0..100]),
+                    schedule: schedule.clone(),
+                    chunk_size,
+                    chunk_predicate: None,
+                };
+                let sum = parallel_reduce(execp, ReduceOp::Sum, |[i, j]| 100 * i + j).unwrap();
+                assert_eq!(sum, (0..1000).sum::<usize>());
+            }
+        }
+    }
+
+    #[test]
+    fn nested_statements() {
+        use super::launch_depth;
+        use crate::routines::{
+            parameters::{
+                ExecutionSpace, Range1D, ReduceOp, ScanMode, Schedule, TypedExecutionPolicy,
+            },
+            typed::{parallel_for, parallel_reduce, parallel_scan},
+        };
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let policy = |n: usize| TypedExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            policy: Range1D(0..n),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let hits: Vec<AtomicUsize> = (0..400).map(|_| AtomicUsize::new(0)).collect();
+        let sums: Vec<AtomicUsize> = (0..20).map(|_| AtomicUsize::new(0)).collect();
+        parallel_for(policy(20), |i| {
+            let outer = launch_depth();
+            parallel_for(policy(20), |j| {
+                // inner kernels run on the thread of the outer kernel
+                assert_eq!(launch_depth(), outer);
+                hits[20 * i + j].fetch_add(1, Ordering::Relaxed);
+            })
+            .unwrap();
+            let sum = parallel_reduce(policy(20), ReduceOp::Sum, |j| {
+                assert_eq!(launch_depth(), outer);
+                i * j
+            })
+            .unwrap();
+            let scan = parallel_scan(policy(20), ScanMode::Inclusive, |j| {
+                assert_eq!(launch_depth(), outer);
+                i * j
+            })
+            .unwrap();
+            assert_eq!(scan[19], sum);
+            sums[i].store(sum, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert_eq!(launch_depth(), 0);
+        assert!(hits.iter().all(|h| h.load(Ordering::Relaxed) == 1));
+        assert!((0..20).all(|i| sums[i].load(Ordering::Relaxed) == i * 190));
+    }
+
+    #[test]
+    fn unsupported_policy() {
+        use super::*;
+        use crate::routines::parameters::{ExecutionSpace, Schedule};
+
+        assert_eq!(
+            serial_support(PolicyKind::TeamVectorMDRange),
+            SupportLevel::Unimplemented
+        );
+        let execp = ExecutionPolicy::<1> {
+            space: ExecutionSpace::Serial,
+            range: RangePolicy::TeamVectorMDRange,
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let kernel = Box::new(|_: KernelArgs<1>| {});
+        assert!(serial(execp.clone(), kernel).is_err());
+        // parallel backends return an error as well
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            ..execp
+        };
+        assert!(crate::routines::parallel_for(execp, |_| {}).is_err());
+    }
 }
@@ -0,0 +1,317 @@
+//! kernel graph related code
+//!
+//! This module contains an equivalent of `Kokkos::Graph`: statements are recorded once
+//! in a [GraphBuilder], along with their dependencies, then the instantiated [Graph] is
+//! launched as many times as needed, e.g. once per timestep. Kernels & policies are
+//! stored when recording, so launching the graph does not box kernels again.
+//!
+//! Dependencies of a node must be recorded before it, which makes graphs acyclic by
+//! construction. When instantiating, nodes are sorted into levels: the nodes of a level
+//! only depend on nodes of previous levels. Levels are launched in order, and nodes of
+//! a level are currently launched one after the other; the level structure is what a
+//! concurrent or GPU launch of the graph would use.
+//!
+//! The result of a reduce node is stored in a [ReduceResult], updated at each launch.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! use poc_kokkos_rs::routines::{
+//!     graph::GraphBuilder,
+//!     parameters::{Range1D, ReduceOp, TypedExecutionPolicy},
+//! };
+//!
+//! let cells: Vec<AtomicUsize> = (0..64).map(|_| AtomicUsize::new(0)).collect();
+//! let execp = TypedExecutionPolicy::new(Range1D(0..64));
+//!
+//! let mut builder = GraphBuilder::new();
+//! let step = builder.parallel_for(&[], execp.clone(), |i| {
+//!     cells[i].fetch_add(1, Ordering::Relaxed);
+//! });
+//! let (_, total) = builder.parallel_reduce(&[step], execp, ReduceOp::Sum, |i| {
+//!     cells[i].load(Ordering::Relaxed)
+//! });
+//! let mut graph = builder.instantiate();
+//!
+//! for t in 1..=10 {
+//!     graph.launch().unwrap();
+//!     assert_eq!(total.get(), Some(64 * t));
+//! }
+//! ```
+
+use std::{cell::Cell, rc::Rc};
+
+use super::{
+    parameters::{Policy, Reducer, TypedExecutionPolicy},
+    typed, StatementError,
+};
+
+/// Identifier of a node, returned when the node is recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+/// Result of a reduce node. It holds the value computed by the last launch of the
+/// graph, `None` before the first one.
+#[derive(Debug, Clone)]
+pub struct ReduceResult<T: Copy>(Rc<Cell<Option<T>>>);
+
+impl<T: Copy> ReduceResult<T> {
+    /// Returns the value computed by the last launch of the graph.
+    pub fn get(&self) -> Option<T> {
+        self.0.get()
+    }
+}
+
+/// Recorded statement.
+struct Node<'a> {
+    deps: Vec<NodeId>,
+    run: Box<dyn FnMut() -> Result<(), StatementError> + 'a>,
+}
+
+/// Builder recording the nodes of a [Graph].
+#[derive(Default)]
+pub struct GraphBuilder<'a> {
+    nodes: Vec<Node<'a>>,
+}
+
+impl<'a> GraphBuilder<'a> {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a node executing `run` after `deps`.
+    ///
+    /// Panics if a dependency was not recorded by this builder.
+    fn record(
+        &mut self,
+        deps: &[NodeId],
+        run: Box<dyn FnMut() -> Result<(), StatementError> + 'a>,
+    ) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        assert!(
+            deps.iter().all(|dep| *dep < id),
+            "dependencies must be recorded before their dependents"
+        );
+        self.nodes.push(Node {
+            deps: deps.to_vec(),
+            run,
+        });
+        id
+    }
+
+    /// Sort recorded nodes into levels & returns the launchable graph.
+    pub fn instantiate(self) -> Graph<'a> {
+        let mut level_of: Vec<usize> = Vec::with_capacity(self.nodes.len());
+        let mut levels: Vec<Vec<NodeId>> = Vec::new();
+        for (k, node) in self.nodes.iter().enumerate() {
+            // dependencies precede the node, their level is known
+            let level = node
+                .deps
+                .iter()
+                .map(|dep| level_of[dep.0] + 1)
+                .max()
+                .unwrap_or(0);
+            level_of.push(level);
+            if level == levels.len() {
+                levels.push(Vec::new());
+            }
+            levels[level].push(NodeId(k));
+        }
+        Graph {
+            nodes: self.nodes,
+            levels,
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        impl<'a> GraphBuilder<'a> {
+            /// Record a Parallel For statement executed after `deps`. See
+            /// [typed::parallel_for].
+            ///
+            /// **Current version**: thread-safe
+            pub fn parallel_for<const N: usize, P>(
+                &mut self,
+                deps: &[NodeId],
+                execp: TypedExecutionPolicy<P>,
+                func: impl Fn(P::Arg) + Send + Sync + 'a,
+            ) -> NodeId
+            where
+                P: Policy<N> + Clone + 'a,
+            {
+                self.record(deps, Box::new(move || typed::parallel_for(execp.clone(), &func)))
+            }
+
+            /// Record a Parallel Reduce statement executed after `deps`. See
+            /// [typed::parallel_reduce].
+            ///
+            /// **Current version**: thread-safe
+            pub fn parallel_reduce<const N: usize, P, R>(
+                &mut self,
+                deps: &[NodeId],
+                execp: TypedExecutionPolicy<P>,
+                op: R,
+                func: impl Fn(P::Arg) -> R::Value + Send + Sync + 'a,
+            ) -> (NodeId, ReduceResult<R::Value>)
+            where
+                P: Policy<N> + Clone + 'a,
+                R: Reducer + Sync + Clone + 'a,
+                R::Value: Send,
+            {
+                let result = ReduceResult(Rc::new(Cell::new(None)));
+                let slot = result.0.clone();
+                let id = self.record(deps, Box::new(move || {
+                    slot.set(Some(typed::parallel_reduce(execp.clone(), op.clone(), &func)?));
+                    Ok(())
+                }));
+                (id, result)
+            }
+        }
+    } else {
+        impl<'a> GraphBuilder<'a> {
+            /// Record a Parallel For statement executed after `deps`. See
+            /// [typed::parallel_for].
+            ///
+            /// **Current version**: no feature
+            pub fn parallel_for<const N: usize, P>(
+                &mut self,
+                deps: &[NodeId],
+                execp: TypedExecutionPolicy<P>,
+                mut func: impl FnMut(P::Arg) + 'a,
+            ) -> NodeId
+            where
+                P: Policy<N> + Clone + 'a,
+            {
+                self.record(deps, Box::new(move || typed::parallel_for(execp.clone(), &mut func)))
+            }
+
+            /// Record a Parallel Reduce statement executed after `deps`. See
+            /// [typed::parallel_reduce].
+            ///
+            /// **Current version**: no feature
+            pub fn parallel_reduce<const N: usize, P, R>(
+                &mut self,
+                deps: &[NodeId],
+                execp: TypedExecutionPolicy<P>,
+                op: R,
+                mut func: impl FnMut(P::Arg) -> R::Value + 'a,
+            ) -> (NodeId, ReduceResult<R::Value>)
+            where
+                P: Policy<N> + Clone + 'a,
+                R: Reducer + Clone + 'a,
+            {
+                let result = ReduceResult(Rc::new(Cell::new(None)));
+                let slot = result.0.clone();
+                let id = self.record(deps, Box::new(move || {
+                    slot.set(Some(typed::parallel_reduce(execp.clone(), op.clone(), &mut func)?));
+                    Ok(())
+                }));
+                (id, result)
+            }
+        }
+    }
+}
+
+/// Instantiated graph, launched using [Graph::launch].
+pub struct Graph<'a> {
+    nodes: Vec<Node<'a>>,
+    levels: Vec<Vec<NodeId>>,
+}
+
+impl Graph<'_> {
+    /// Returns the number of nodes of the graph.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the levels of the graph, in launch order. Nodes of a level only depend
+    /// on nodes of previous levels.
+    pub fn levels(&self) -> &[Vec<NodeId>] {
+        &self.levels
+    }
+
+    /// Launch all nodes of the graph, level after level.
+    ///
+    /// The launch stops at the first statement returning an error, which is returned.
+    pub fn launch(&mut self) -> Result<(), StatementError> {
+        let nodes = &mut self.nodes;
+        self.levels
+            .iter()
+            .flatten()
+            .try_for_each(|id| (nodes[id.0].run)())
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::parameters::{
+        ExecutionSpace, MDRange, Range1D, ReduceOp, Schedule, TypedExecutionPolicy,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn diamond_graph() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            fn execp<P>(space: ExecutionSpace, policy: P) -> TypedExecutionPolicy<P> {
+                TypedExecutionPolicy {
+                    space,
+                    policy,
+                    schedule: Schedule::default(),
+                    chunk_size: None,
+                    chunk_predicate: None,
+                }
+            }
+            let (a, b, c) = (
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+            );
+            let mut builder = GraphBuilder::new();
+            let init = builder.parallel_for(&[], execp(space, Range1D(0..10)), |_| {
+                a.fetch_add(1, Ordering::Relaxed);
+            });
+            let left = builder.parallel_for(&[init], execp(space, Range1D(0..5)), |_| {
+                b.fetch_add(a.load(Ordering::Relaxed), Ordering::Relaxed);
+            });
+            let right = builder.parallel_for(&[init], execp(space, MDRange([0..2, 0..3])), |_| {
+                c.fetch_add(1, Ordering::Relaxed);
+            });
+            let (last, sum) = builder.parallel_reduce(
+                &[left, right],
+                execp(space, Range1D(0..1)),
+                ReduceOp::Sum,
+                |_| b.load(Ordering::Relaxed) + c.load(Ordering::Relaxed),
+            );
+            let mut graph = builder.instantiate();
+            assert_eq!(graph.num_nodes(), 4);
+            assert_eq!(graph.levels(), [vec![init], vec![left, right], vec![last]]);
+            assert_eq!(sum.get(), None);
+
+            graph.launch().unwrap();
+            assert_eq!(sum.get(), Some(5 * 10 + 6));
+            graph.launch().unwrap();
+            assert_eq!(sum.get(), Some(5 * 10 + 5 * 20 + 12));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "dependencies must be recorded before their dependents")]
+    fn foreign_dependency() {
+        let mut other = GraphBuilder::new();
+        other.parallel_for(&[], TypedExecutionPolicy::new(Range1D(0..1)), |_| {});
+        let foreign = other.parallel_for(&[], TypedExecutionPolicy::new(Range1D(0..1)), |_| {});
+        GraphBuilder::new().parallel_for(
+            &[foreign],
+            TypedExecutionPolicy::new(Range1D(0..1)),
+            |_| {},
+        );
+    }
+}
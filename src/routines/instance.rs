@@ -0,0 +1,408 @@
+//! execution space instance related code
+//!
+//! This module contains [CpuInstance], the equivalent of Kokkos' execution space
+//! instances. An instance owns a slice of the CPU threads: statements dispatched using
+//! [ExecutionSpace::DeviceCPUInstance] only use the threads of their instance, which
+//! makes it possible to run independent statements concurrently on separate resources
+//! by launching them from different threads.
+//!
+//! Using the `rayon` feature, each instance has its own thread pool; using the
+//! `threads` feature, each instance has its own persistent workers. Without parallelization feature, statements are executed serially.
+//!
+//! [CpuInstance::fence] blocks until all statements dispatched on an instance are
+//! complete. Instances are registered globally until they are released using
+//! [CpuInstance::release], which frees their resources (e.g. their thread pool).
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::routines::{
+//!     instance::CpuInstance,
+//!     parameters::{ExecutionSpace, Range1D, Schedule, TypedExecutionPolicy},
+//!     typed::parallel_for,
+//! };
+//!
+//! let instances = CpuInstance::partition(&[1, 1]).unwrap();
+//! std::thread::scope(|s| {
+//!     for (k, instance) in instances.iter().enumerate() {
+//!         s.spawn(move || {
+//!             let execp = TypedExecutionPolicy {
+//!                 space: ExecutionSpace::DeviceCPUInstance(*instance),
+//!                 policy: Range1D(0..4),
+//!                 schedule: Schedule::Static,
+//!                 chunk_size: None,
+//!                 chunk_predicate: None,
+//!             };
+//!             parallel_for(execp, |i| println!("Hello from instance {k}, iteration {i}"))
+//!                 .unwrap();
+//!         });
+//!     }
+//! });
+//! instances.iter().for_each(CpuInstance::fence);
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Condvar, Mutex, RwLock,
+    },
+};
+
+use super::dispatch::DispatchError;
+#[cfg(doc)]
+use super::parameters::ExecutionSpace;
+#[cfg(feature = "threads")]
+use crate::runtime::WorkerPool;
+use crate::runtime::{self, RuntimeError};
+
+/// Handle to an execution space instance using a slice of the CPU threads.
+///
+/// Instances are cheap to copy; copies refer to the same resources, which are kept until
+/// one of the copies is [released][CpuInstance::release].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuInstance {
+    id: usize,
+    first_thread: usize,
+    num_threads: usize,
+}
+
+/// Resources of an instance.
+struct InstanceState {
+    in_flight: Mutex<usize>,
+    idle: Condvar,
+    #[cfg(feature = "rayon")]
+    pool: Arc<rayon::ThreadPool>,
+    #[cfg(feature = "threads")]
+    workers: Arc<WorkerPool>,
+}
+
+impl InstanceState {
+    /// Block until no statement is in flight.
+    fn wait_idle(&self) {
+        let in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        let _idle = self
+            .idle
+            .wait_while(in_flight, |n| *n > 0)
+            .unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+static INSTANCES: RwLock<BTreeMap<usize, Arc<InstanceState>>> = RwLock::new(BTreeMap::new());
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Instance used by the statement executed by a thread, along with its resources.
+type Current = (CpuInstance, Arc<InstanceState>);
+
+thread_local! {
+    static CURRENT: RefCell<Option<Current>> = const { RefCell::new(None) };
+}
+
+impl CpuInstance {
+    /// Create an instance using `num_threads` threads.
+    pub fn new(num_threads: usize) -> Result<Self, RuntimeError> {
+        Self::with_offset(0, num_threads)
+    }
+
+    /// Split the threads of the runtime into instances, proportionally to `weights`.
+    /// Each instance uses at least one thread; zero weights are treated as 1.
+    ///
+    /// When pinning is enabled, instances are pinned to disjoint sets of cores as long
+    /// as there are enough of them.
+    pub fn partition(weights: &[usize]) -> Result<Vec<Self>, RuntimeError> {
+        let weights: Vec<usize> = weights.iter().map(|w| (*w).max(1)).collect();
+        let total: usize = weights.iter().sum();
+        let available = runtime::num_threads().max(weights.len());
+        let mut first_thread = 0;
+        weights
+            .iter()
+            .map(|w| {
+                let num_threads = (available * w / total).max(1);
+                let instance = Self::with_offset(first_thread, num_threads);
+                first_thread += num_threads;
+                instance
+            })
+            .collect()
+    }
+
+    fn with_offset(first_thread: usize, num_threads: usize) -> Result<Self, RuntimeError> {
+        if num_threads == 0 {
+            return Err(RuntimeError::Backend(
+                "an instance needs at least one thread",
+            ));
+        }
+        #[cfg(feature = "rayon")]
+        let pool = {
+            let (pinning, n_workers) = (
+                runtime::config().map(|c| c.pinning).unwrap_or_default(),
+                runtime::num_threads(),
+            );
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .start_handler(move |idx| {
+                    runtime::pin_to_core(&pinning, first_thread + idx, n_workers)
+                })
+                .build()
+                .map_err(|_| RuntimeError::Backend("could not build the thread pool"))?
+        };
+        #[cfg(feature = "threads")]
+        let workers = {
+            let pinning = runtime::config().map(|c| c.pinning).unwrap_or_default();
+            let n_pinned = runtime::num_threads();
+            WorkerPool::new(num_threads, &pinning, first_thread, n_pinned)?
+        };
+        let state = InstanceState {
+            in_flight: Mutex::new(0),
+            idle: Condvar::new(),
+            #[cfg(feature = "rayon")]
+            pool: Arc::new(pool),
+            #[cfg(feature = "threads")]
+            workers: Arc::new(workers),
+        };
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        INSTANCES
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, Arc::new(state));
+        Ok(Self {
+            id,
+            first_thread,
+            num_threads,
+        })
+    }
+
+    /// Returns the number of threads used by the instance.
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    /// Returns the index of the first thread of the instance among the threads of the
+    /// runtime, used for pinning.
+    pub fn first_thread(&self) -> usize {
+        self.first_thread
+    }
+
+    /// Block until all statements dispatched on the instance are complete, including
+    /// asynchronous ones.
+    /// Returns immediately if the instance has been released.
+    pub fn fence(&self) {
+        if let Some(state) = self.state() {
+            state.wait_idle();
+        }
+    }
+
+    /// Release the resources of the instance, once all statements dispatched on it are
+    /// complete. Statements dispatched afterward on the instance, or on any of its copies,
+    /// return an error.
+    pub fn release(self) {
+        let state = INSTANCES
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+        // resources are dropped along with the last statement holding them
+        if let Some(state) = state {
+            state.wait_idle();
+        }
+    }
+
+    fn state(&self) -> Option<Arc<InstanceState>> {
+        INSTANCES
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&self.id)
+            .cloned()
+    }
+}
+
+/// Marks a statement as complete when dropped, even if its kernel panics.
+pub(crate) struct InFlight {
+    state: Arc<InstanceState>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut in_flight = self
+            .state
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *in_flight -= 1;
+        if *in_flight == 0 {
+            self.state.idle.notify_all();
+        }
+    }
+}
+
+/// Restores the instance of the calling thread when dropped.
+struct Restore(Option<Current>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        CURRENT.with(|c| *c.borrow_mut() = self.0.take());
+    }
+}
+
+/// Register a statement on `instance`; [CpuInstance::fence] waits until the returned
+/// guard is dropped. Returns `None` if the instance has been released.
+pub(crate) fn track(instance: CpuInstance) -> Option<InFlight> {
+    let state = instance.state()?;
+    *state.in_flight.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+    Some(InFlight { state })
+}
+
+/// Execute `op` on the calling thread, dispatch routines using the resources of
+/// `instance` instead of the ones of the runtime.
+pub(crate) fn execute<R>(
+    instance: CpuInstance,
+    op: impl FnOnce() -> Result<R, DispatchError>,
+) -> Result<R, DispatchError> {
+    let Some(in_flight) = track(instance) else {
+        return Err(DispatchError::CPU("instance has been released"));
+    };
+    let current = (instance, in_flight.state.clone());
+    let _restore = Restore(CURRENT.with(|c| c.replace(Some(current))));
+    op()
+}
+
+/// Block until all statements dispatched on any instance are complete.
+pub(crate) fn fence_all() {
+    let states: Vec<_> = INSTANCES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .values()
+        .cloned()
+        .collect();
+    states.iter().for_each(|state| state.wait_idle());
+}
+
+/// Returns the instance used by the statement executed by the calling thread, if any.
+pub(crate) fn current() -> Option<CpuInstance> {
+    CURRENT.with(|c| c.borrow().as_ref().map(|(instance, _)| *instance))
+}
+
+/// Returns the thread pool of the instance used by the statement executed by the calling
+/// thread, if any.
+#[cfg(feature = "rayon")]
+pub(crate) fn current_pool() -> Option<Arc<rayon::ThreadPool>> {
+    CURRENT.with(|c| c.borrow().as_ref().map(|(_, state)| state.pool.clone()))
+}
+
+/// Returns the worker pool of the instance used by the statement executed by the calling
+/// thread, if any.
+#[cfg(feature = "threads")]
+pub(crate) fn current_workers() -> Option<Arc<WorkerPool>> {
+    CURRENT.with(|c| c.borrow().as_ref().map(|(_, state)| state.workers.clone()))
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::{
+        parameters::{ExecutionSpace, Range1D, ReduceOp, Schedule, TypedExecutionPolicy},
+        typed::{parallel_for, parallel_reduce},
+    };
+    use std::{
+        collections::HashSet,
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[test]
+    fn partition_threads() {
+        let instances = CpuInstance::partition(&[1, 3]).unwrap();
+        assert_eq!(instances.len(), 2);
+        assert!(instances[0].num_threads() <= instances[1].num_threads());
+        assert_eq!(instances[1].first_thread(), instances[0].num_threads());
+        assert_ne!(instances[0], instances[1]);
+        assert!(CpuInstance::new(0).is_err());
+        assert_eq!(CpuInstance::new(2).unwrap().num_threads(), 2);
+    }
+
+    #[test]
+    fn concurrent_instances() {
+        let instances = CpuInstance::partition(&[1, 1]).unwrap();
+        let count = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for instance in &instances {
+                let count = &count;
+                s.spawn(move || {
+                    let workers = Mutex::new(HashSet::new());
+                    let execp = TypedExecutionPolicy {
+                        space: ExecutionSpace::DeviceCPUInstance(*instance),
+                        policy: Range1D(0..1000),
+                        schedule: Schedule::Dynamic,
+                        chunk_size: None,
+                        chunk_predicate: None,
+                    };
+                    parallel_for(execp.clone(), |_| {
+                        workers.lock().unwrap().insert(std::thread::current().id());
+                        count.fetch_add(1, Ordering::Relaxed);
+                    })
+                    .unwrap();
+                    assert!(workers.into_inner().unwrap().len() <= instance.num_threads());
+                    let sum = parallel_reduce(execp, ReduceOp::Sum, |i| i).unwrap();
+                    assert_eq!(sum, 499500);
+                });
+            }
+        });
+        instances.iter().for_each(CpuInstance::fence);
+        assert_eq!(count.load(Ordering::Relaxed), 2000);
+        // the override does not outlive the statement
+        assert!(current().is_none());
+    }
+
+    #[test]
+    fn release() {
+        let instance = CpuInstance::new(2).unwrap();
+        let execp = TypedExecutionPolicy {
+            space: ExecutionSpace::DeviceCPUInstance(instance),
+            policy: Range1D(0..100),
+            schedule: Schedule::Static,
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let sum = parallel_reduce(execp.clone(), ReduceOp::Sum, |i| i).unwrap();
+        assert_eq!(sum, 4950);
+
+        let state = Arc::downgrade(&instance.state().unwrap());
+        let copy = instance;
+        instance.release();
+        // resources are freed, copies can no longer be used
+        assert!(state.upgrade().is_none());
+        assert!(parallel_for(execp, |_| {}).is_err());
+        copy.fence();
+        copy.release();
+    }
+
+    #[test]
+    fn fence_waits() {
+        let instance = CpuInstance::new(1).unwrap();
+        let done = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let execp = TypedExecutionPolicy {
+                    space: ExecutionSpace::DeviceCPUInstance(instance),
+                    policy: Range1D(0..4),
+                    schedule: Schedule::Static,
+                    chunk_size: None,
+                    chunk_predicate: None,
+                };
+                parallel_for(execp, |_| {
+                    std::thread::sleep(Duration::from_millis(5));
+                    done.fetch_add(1, Ordering::Relaxed);
+                })
+                .unwrap();
+            });
+            // wait for the statement to start
+            while done.load(Ordering::Relaxed) == 0 {
+                std::thread::yield_now();
+            }
+            instance.fence();
+            assert_eq!(done.load(Ordering::Relaxed), 4);
+        });
+    }
+}
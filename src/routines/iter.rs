@@ -0,0 +1,263 @@
+//! iteration space related code
+//!
+//! This module contains iterators over the index space of policies. They are used by
+//! the dispatch routines, but can also be reused to build other execution strategies
+//! (e.g. tiling, device emulation).
+
+use std::ops::Range;
+
+use super::parameters::Iterate;
+
+/// Iterator over the indices of a N-dimensional range.
+///
+/// Indices are yielded in row-major order, i.e. the last index varies the fastest, unless
+/// built using [MDIndexIter::with_iterate]. The iteration is done by advancing the current index in place, like an odometer, which
+/// avoids both recursion and cloning ranges.
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::routines::iter::MDIndexIter;
+///
+/// let indices: Vec<[usize; 2]> = MDIndexIter::new([0..2, 3..5]).collect();
+///
+/// assert_eq!(indices, vec![[0, 3], [0, 4], [1, 3], [1, 4]]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MDIndexIter<const N: usize> {
+    /// Iterated ranges.
+    ranges: [Range<usize>; N],
+    /// Next index to yield.
+    current: [usize; N],
+    /// Number of indices left to yield.
+    remaining: usize,
+    /// Iteration order.
+    iterate: Iterate,
+}
+
+impl<const N: usize> MDIndexIter<N> {
+    /// Create an iterator over the cartesian product of `ranges`.
+    pub fn new(ranges: [Range<usize>; N]) -> Self {
+        Self::with_iterate(ranges, Iterate::Right)
+    }
+
+    /// Create an iterator over the cartesian product of `ranges`, using the given
+    /// iteration order.
+    pub fn with_iterate(ranges: [Range<usize>; N], iterate: Iterate) -> Self {
+        let remaining = ranges.iter().map(|r| r.len()).product();
+        let current = ranges.clone().map(|r| r.start);
+        Self {
+            ranges,
+            current,
+            remaining,
+            iterate,
+        }
+    }
+
+    /// Advance the index of dimension `dim`, returns `false` if it wrapped around.
+    #[inline]
+    fn advance(&mut self, dim: usize) -> bool {
+        self.current[dim] += 1;
+        if self.current[dim] < self.ranges[dim].end {
+            return true;
+        }
+        self.current[dim] = self.ranges[dim].start;
+        false
+    }
+}
+
+impl<const N: usize> Iterator for MDIndexIter<N> {
+    type Item = [usize; N];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let res = self.current;
+        self.remaining -= 1;
+
+        // advance the index, starting from the fastest dimension
+        match self.iterate {
+            Iterate::Right => {
+                let _ = (0..N).rev().any(|dim| self.advance(dim));
+            }
+            Iterate::Left => {
+                let _ = (0..N).any(|dim| self.advance(dim));
+            }
+        }
+
+        Some(res)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for MDIndexIter<N> {}
+
+impl<const N: usize> std::iter::FusedIterator for MDIndexIter<N> {}
+
+/// Tiling of a N-dimensional range.
+///
+/// The range is split into tiles of fixed size, tiles at the upper bounds being
+/// truncated. Tiles are numbered using the iteration order, so that consecutive tiles
+/// are adjacent along the fastest dimension. Indices of each tile are iterated in the
+/// same order, unless set otherwise using [Tiling::with_inner].
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::routines::{iter::Tiling, parameters::Iterate};
+///
+/// let tiling = Tiling::new([0..5, 0..4], [2, 4], Iterate::Right);
+///
+/// assert_eq!(tiling.len(), 3);
+/// assert_eq!(tiling.tile(2), [4..5, 0..4]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tiling<const N: usize> {
+    /// Tiled ranges.
+    ranges: [Range<usize>; N],
+    /// Tile size of each dimension.
+    tile: [usize; N],
+    /// Number of tiles along each dimension.
+    counts: [usize; N],
+    /// Iteration order of tiles.
+    iterate: Iterate,
+    /// Iteration order of the indices of each tile.
+    inner: Iterate,
+}
+
+impl<const N: usize> Tiling<N> {
+    /// Create a tiling of `ranges`. Tile sizes of 0 are treated as 1.
+    pub fn new(ranges: [Range<usize>; N], tile: [usize; N], iterate: Iterate) -> Self {
+        let tile = tile.map(|t| t.max(1));
+        let mut counts = [0; N];
+        counts
+            .iter_mut()
+            .zip(ranges.iter().zip(tile.iter()))
+            .for_each(|(c, (r, t))| *c = r.len().div_ceil(*t));
+        Self {
+            ranges,
+            tile,
+            counts,
+            iterate,
+            inner: iterate,
+        }
+    }
+
+    /// Set the iteration order of the indices of each tile.
+    pub fn with_inner(self, inner: Iterate) -> Self {
+        Self { inner, ..self }
+    }
+
+    /// Returns the number of tiles.
+    pub fn len(&self) -> usize {
+        self.counts.iter().product()
+    }
+
+    /// Returns `true` if there is no tile, i.e. if one of the ranges is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the iteration order of the tiles.
+    pub fn iterate(&self) -> Iterate {
+        self.iterate
+    }
+
+    /// Returns the iteration order of the indices of each tile.
+    pub fn inner(&self) -> Iterate {
+        self.inner
+    }
+
+    /// Returns the ranges covered by the `k`-th tile.
+    pub fn tile(&self, mut k: usize) -> [Range<usize>; N] {
+        let mut res = self.ranges.clone();
+        let mut set_dim = |dim: usize| {
+            let start = self.ranges[dim].start + (k % self.counts[dim]) * self.tile[dim];
+            res[dim] = start..(start + self.tile[dim]).min(self.ranges[dim].end);
+            k /= self.counts[dim];
+        };
+        match self.iterate {
+            Iterate::Right => (0..N).rev().for_each(&mut set_dim),
+            Iterate::Left => (0..N).for_each(&mut set_dim),
+        }
+        res
+    }
+
+    /// Returns an iterator over the indices of the `k`-th tile, in inner iteration order.
+    pub fn tile_indices(&self, k: usize) -> MDIndexIter<N> {
+        MDIndexIter::with_iterate(self.tile(k), self.inner)
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_3d() {
+        let mut ref_indices = Vec::new();
+        for i in 1..3 {
+            for j in 0..4 {
+                for k in 2..5 {
+                    ref_indices.push([i, j, k]);
+                }
+            }
+        }
+        let iter = MDIndexIter::new([1..3, 0..4, 2..5]);
+        assert_eq!(iter.len(), ref_indices.len());
+        assert_eq!(iter.collect::<Vec<_>>(), ref_indices);
+    }
+
+    #[test]
+    fn order_left() {
+        let indices: Vec<[usize; 2]> =
+            MDIndexIter::with_iterate([0..2, 3..5], Iterate::Left).collect();
+        assert_eq!(indices, vec![[0, 3], [1, 3], [0, 4], [1, 4]]);
+    }
+
+    #[test]
+    fn tiling_cover() {
+        for iterate in [Iterate::Right, Iterate::Left] {
+            let tiling = Tiling::new([1..8, 0..5, 2..4], [3, 2, 0], iterate);
+            assert_eq!(tiling.len(), 3 * 3 * 2);
+            let mut indices: Vec<[usize; 3]> = (0..tiling.len())
+                .flat_map(|k| tiling.tile_indices(k))
+                .collect();
+            indices.sort();
+            assert_eq!(
+                indices,
+                MDIndexIter::new([1..8, 0..5, 2..4]).collect::<Vec<_>>()
+            );
+        }
+        let tiling = Tiling::new([0..4, 0..4], [2, 2], Iterate::Left);
+        assert_eq!(tiling.tile(1), [2..4, 0..2]);
+        let tiling = tiling.with_inner(Iterate::Right);
+        assert_eq!(
+            (tiling.iterate(), tiling.inner()),
+            (Iterate::Left, Iterate::Right)
+        );
+        assert_eq!(
+            tiling.tile_indices(1).take(2).collect::<Vec<_>>(),
+            vec![[2, 0], [2, 1]]
+        );
+        #[allow(clippy::reversed_empty_ranges)]
+        let tiling = Tiling::new([0..4, 3..3], [2, 2], Iterate::Left);
+        assert!(tiling.is_empty());
+    }
+
+    #[test]
+    fn empty_range() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let mut iter = MDIndexIter::new([0..4, 3..3, 0..2]);
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+    }
+}
@@ -5,20 +5,72 @@
 //!
 //! Parameters of aforementionned statements are defined in the [`parameters`] sub-module.
 //!
-//! Dispatch code is defined in the [`dispatch`] sub-module.
+//! Dispatch code is defined in the [`dispatch`] sub-module; when several parallelization
+//! features are enabled, the CPU backend it uses is selected at runtime using the
+//! [`backend`] sub-module. Iterators over the index space of policies are defined in the
+//! [`iter`] sub-module.
+//!
+//! Utilities used to prepare & time benchmarks of statements are defined in the
+//! [`bench`] sub-module, measured sweeps over candidate configurations in the [`tune`]
+//! sub-module, and checks of execution-order dependency in the [`audit`] sub-module.
+//! Kernels can report exceptional situations using the [`diagnostics`] sub-module, and
+//! declare the views they capture to detect overlapping views using the [`aliasing`]
+//! sub-module.
+//! Execution space instances, used to run statements concurrently on separate slices of
+//! the CPU threads, are defined in the [`instance`] sub-module. Asynchronous variants of
+//! statements, returning a future instead of blocking, are defined in the
+//! [`asynchronous`] sub-module, and graphs of statements recorded once & launched
+//! repeatedly in the [`graph`] sub-module. Task parallelism, i.e. spawning tasks
+//! returning futures, is supported by the scheduler of the [`task`] sub-module.
+//! Team-based statements can request per-team scratch memory, defined in the
+//! [`scratch`] sub-module, and synchronize their members using the collectives of the
+//! [`team`] sub-module.
+//! Statements checking the rank of kernels at compile time are defined in the
+//! [`typed`] sub-module; their kernels receive the argument of the policy directly and
+//! they should be preferred over the [`KernelArgs`]-based statements of this module.
 //!
 //! Currently implemented statements:
 //!
 //! - `parallel_for`
+//! - `parallel_reduce`
+//! - `parallel_scan`: inclusive & exclusive prefix sums
+//! - `parallel_for_skip_chunks`: `parallel_for` variant able to skip inactive chunks of
+//!   the iteration range
+//! - `parallel_for_colored`: `parallel_for` variant executing colors of a
+//!   [`ColoredPolicy`][parameters::ColoredPolicy] one after the other
 
+pub mod aliasing;
+pub mod asynchronous;
+pub mod audit;
+pub mod backend;
+pub mod bench;
+pub mod diagnostics;
 pub mod dispatch;
+pub mod graph;
+pub mod instance;
+pub mod iter;
 pub mod parameters;
+pub mod scratch;
+pub mod task;
+pub mod team;
+pub mod tune;
+pub mod typed;
 
-use std::fmt::Display;
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use std::sync::Mutex;
+use std::{fmt::Display, ops::Range};
 
-use crate::functor::KernelArgs;
+use crate::{functor::KernelArgs, profiling, view::parameters::DataTraits};
+use std::ops::Add;
 
-use self::{dispatch::DispatchError, parameters::ExecutionPolicy};
+use self::{
+    aliasing::AliasingError,
+    dispatch::{DispatchError, SupportLevel},
+    parameters::{
+        ColoredPolicy, ExecutionPolicy, ExecutionSpace, PolicyKind, RangePolicy, Reducer, ScanMode,
+        StatementKind,
+    },
+};
 
 // Enums
 
@@ -32,8 +84,20 @@ pub enum StatementError {
     InconsistentDepth,
     /// What did I mean by this?
     InconsistentExecSpace,
+    /// Error raised when operands of a statement have inconsistent dimensions.
+    DimensionMismatch,
+    /// Error raised when a statement does not handle the kind of the given policy.
+    UnsupportedPolicy(PolicyKind),
+    /// Error raised when views captured by the kernel conflict, see [`aliasing`].
+    Aliasing(AliasingError),
+    /// Error raised when iterations of a fallible kernel failed. Errors are stored in
+    /// the order they occured; the vector is never empty.
+    Kernel(Vec<KernelError>),
 }
 
+/// Error returned by an iteration of a kernel passed to [try_parallel_for].
+pub type KernelError = Box<dyn std::error::Error + Send + Sync>;
+
 impl From<DispatchError> for StatementError {
     fn from(e: DispatchError) -> Self {
         StatementError::Dispatch(e)
@@ -50,6 +114,20 @@ impl Display for StatementError {
             StatementError::InconsistentExecSpace => {
                 write!(f, "?")
             }
+            StatementError::DimensionMismatch => {
+                write!(f, "inconsistent dimensions of the statement's operands")
+            }
+            StatementError::UnsupportedPolicy(kind) => {
+                write!(f, "{kind} is not supported by this statement")
+            }
+            StatementError::Aliasing(e) => write!(f, "{e}"),
+            StatementError::Kernel(errs) => match errs.first() {
+                Some(e) if errs.len() > 1 => {
+                    write!(f, "{e} (and {} other kernel errors)", errs.len() - 1)
+                }
+                Some(e) => write!(f, "{e}"),
+                None => write!(f, "kernel failed"),
+            },
         }
     }
 }
@@ -60,10 +138,62 @@ impl std::error::Error for StatementError {
             StatementError::Dispatch(e) => Some(e),
             StatementError::InconsistentDepth => None,
             StatementError::InconsistentExecSpace => None,
+            StatementError::DimensionMismatch => None,
+            StatementError::UnsupportedPolicy(_) => None,
+            StatementError::Aliasing(e) => Some(e),
+            StatementError::Kernel(errs) => errs
+                .first()
+                .map(|e| e.as_ref() as &(dyn std::error::Error + 'static)),
         }
     }
 }
 
+// Queries
+
+/// Returns the implementation status of a policy kind for a given statement kind &
+/// execution space, according to enabled features.
+///
+/// This can be used to select alternative algorithms instead of running into an error
+/// at dispatch time. Reduce statements only support range policies, i.e. 1D, MD & tiled
+/// MD ranges, and scan statements only support 1D ranges.
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::routines::{
+///     dispatch::SupportLevel,
+///     parameters::{ExecutionSpace, PolicyKind, StatementKind},
+///     supports,
+/// };
+///
+/// assert_eq!(
+///     supports(StatementKind::For, PolicyKind::RangePolicy, ExecutionSpace::Serial),
+///     SupportLevel::Full,
+/// );
+/// assert_eq!(
+///     supports(StatementKind::Reduce, PolicyKind::TeamPolicy, ExecutionSpace::Serial),
+///     SupportLevel::Unimplemented,
+/// );
+/// ```
+pub fn supports(statement: StatementKind, kind: PolicyKind, space: ExecutionSpace) -> SupportLevel {
+    let for_support = match space {
+        ExecutionSpace::Serial => dispatch::serial_support(kind),
+        ExecutionSpace::DeviceCPU | ExecutionSpace::DeviceCPUInstance(_) => {
+            dispatch::cpu_support(kind)
+        }
+        ExecutionSpace::DeviceGPU => dispatch::gpu_support(kind),
+    };
+    match (statement, kind) {
+        (StatementKind::For, _)
+        | (
+            StatementKind::Reduce,
+            PolicyKind::RangePolicy | PolicyKind::MDRangePolicy | PolicyKind::TiledMDRangePolicy,
+        )
+        | (StatementKind::Scan, PolicyKind::RangePolicy) => for_support,
+        _ => SupportLevel::Unimplemented,
+    }
+}
+
 // Statements
 
 // All of this would be half as long if impl trait in type aliases was stabilized
@@ -93,13 +223,15 @@ cfg_if::cfg_if! {
         ///             println!("Hello from iteration {i}")
         ///         },
         ///         KernelArgs::IndexND(_) => unimplemented!(),
-        ///         KernelArgs::Handle => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
         ///     };
         ///
         /// let execp =  ExecutionPolicy {
         ///         space: ExecutionSpace::DeviceCPU,
         ///         range: RangePolicy::RangePolicy(0..length),
         ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
         ///     };
         ///
         /// parallel_for(execp, kern).unwrap();
@@ -112,13 +244,20 @@ cfg_if::cfg_if! {
 
             // data prep?
             let kernel = Box::new(func);
+            let measure = profiling::begin(&execp.range);
 
             // dispatch
             let res = match execp.space {
                 parameters::ExecutionSpace::Serial => dispatch::serial(execp, kernel),
                 parameters::ExecutionSpace::DeviceCPU => dispatch::cpu(execp, kernel),
+                parameters::ExecutionSpace::DeviceCPUInstance(inst) => {
+                    instance::execute(inst, || dispatch::cpu(execp, kernel))
+                }
                 parameters::ExecutionSpace::DeviceGPU => dispatch::gpu(execp, kernel),
             };
+            if let Some(m) = measure {
+                m.end()
+            }
 
             // Ok or converts error
             res.map_err(|e| e.into())
@@ -147,13 +286,15 @@ cfg_if::cfg_if! {
         ///             println!("Hello from iteration {i}")
         ///         },
         ///         KernelArgs::IndexND(_) => unimplemented!(),
-        ///         KernelArgs::Handle => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
         ///     };
         ///
         /// let execp =  ExecutionPolicy {
         ///         space: ExecutionSpace::DeviceCPU,
         ///         range: RangePolicy::RangePolicy(0..length),
         ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
         ///     };
         ///
         /// parallel_for(execp, kern).unwrap();
@@ -166,13 +307,20 @@ cfg_if::cfg_if! {
 
             // data prep?
             let kernel = Box::new(func);
+            let measure = profiling::begin(&execp.range);
 
             // dispatch
             let res = match execp.space {
                 parameters::ExecutionSpace::Serial => dispatch::serial(execp, kernel),
                 parameters::ExecutionSpace::DeviceCPU => dispatch::cpu(execp, kernel),
+                parameters::ExecutionSpace::DeviceCPUInstance(inst) => {
+                    instance::execute(inst, || dispatch::cpu(execp, kernel))
+                }
                 parameters::ExecutionSpace::DeviceGPU => dispatch::gpu(execp, kernel),
             };
+            if let Some(m) = measure {
+                m.end()
+            }
 
             // Ok or converts error
             res.map_err(|e| e.into())
@@ -201,13 +349,15 @@ cfg_if::cfg_if! {
         ///             println!("Hello from iteration {i}")
         ///         },
         ///         KernelArgs::IndexND(_) => unimplemented!(),
-        ///         KernelArgs::Handle => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
         ///     };
         ///
         /// let execp =  ExecutionPolicy {
         ///         space: ExecutionSpace::DeviceCPU,
         ///         range: RangePolicy::RangePolicy(0..length),
         ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
         ///     };
         ///
         /// parallel_for(execp, kern).unwrap();
@@ -220,16 +370,1558 @@ cfg_if::cfg_if! {
 
             // data prep?
             let kernel = Box::new(func);
+            let measure = profiling::begin(&execp.range);
 
             // dispatch
             let res = match execp.space {
                 parameters::ExecutionSpace::Serial => dispatch::serial(execp, kernel),
                 parameters::ExecutionSpace::DeviceCPU => dispatch::cpu(execp, kernel),
+                parameters::ExecutionSpace::DeviceCPUInstance(inst) => {
+                    instance::execute(inst, || dispatch::cpu(execp, kernel))
+                }
                 parameters::ExecutionSpace::DeviceGPU => dispatch::gpu(execp, kernel),
             };
+            if let Some(m) = measure {
+                m.end()
+            }
 
             // Ok or converts error
             res.map_err(|e| e.into())
         }
     }
 }
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// Fallible Parallel For statement.
+        ///
+        /// The kernel returns a `Result` instead of panicking on failure. All iterations are
+        /// executed; errors are collected and returned as a [StatementError::Kernel].
+        ///
+        /// **Current version**: thread-safe
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{
+        ///         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        ///         try_parallel_for, StatementError,
+        ///     },
+        /// };
+        ///
+        /// let execp =  ExecutionPolicy {
+        ///         space: ExecutionSpace::DeviceCPU,
+        ///         range: RangePolicy::RangePolicy(0..8),
+        ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
+        ///     };
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(5) => Err("iteration 5 failed"),
+        ///         KernelArgs::Index1D(_) => Ok(()),
+        ///         _ => unimplemented!(),
+        ///     };
+        ///
+        /// match try_parallel_for(execp, kern) {
+        ///     Err(StatementError::Kernel(errs)) => assert_eq!(errs.len(), 1),
+        ///     _ => unreachable!(),
+        /// }
+        /// ```
+        pub fn try_parallel_for<const N: usize, E>(
+            execp: ExecutionPolicy<N>,
+            func: impl Fn(KernelArgs<N>) -> Result<(), E> + Send + Sync,
+        ) -> Result<(), StatementError>
+        where
+            E: Into<KernelError>,
+        {
+            let errs: Mutex<Vec<KernelError>> = Mutex::new(Vec::new());
+            let kernel = |arg: KernelArgs<N>| {
+                if let Err(e) = func(arg) {
+                    errs.lock().unwrap().push(e.into());
+                }
+            };
+            parallel_for(execp, kernel)?;
+
+            let errs = errs.into_inner().unwrap();
+            if errs.is_empty() {
+                Ok(())
+            } else {
+                Err(StatementError::Kernel(errs))
+            }
+        }
+    } else {
+        /// Fallible Parallel For statement.
+        ///
+        /// The kernel returns a `Result` instead of panicking on failure. All iterations are
+        /// executed; errors are collected and returned as a [StatementError::Kernel].
+        ///
+        /// **Current version**: no feature
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{
+        ///         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        ///         try_parallel_for, StatementError,
+        ///     },
+        /// };
+        ///
+        /// let execp =  ExecutionPolicy {
+        ///         space: ExecutionSpace::DeviceCPU,
+        ///         range: RangePolicy::RangePolicy(0..8),
+        ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
+        ///     };
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(5) => Err("iteration 5 failed"),
+        ///         KernelArgs::Index1D(_) => Ok(()),
+        ///         _ => unimplemented!(),
+        ///     };
+        ///
+        /// match try_parallel_for(execp, kern) {
+        ///     Err(StatementError::Kernel(errs)) => assert_eq!(errs.len(), 1),
+        ///     _ => unreachable!(),
+        /// }
+        /// ```
+        pub fn try_parallel_for<const N: usize, E>(
+            execp: ExecutionPolicy<N>,
+            mut func: impl FnMut(KernelArgs<N>) -> Result<(), E>,
+        ) -> Result<(), StatementError>
+        where
+            E: Into<KernelError>,
+        {
+            let mut errs: Vec<KernelError> = Vec::new();
+            let kernel = |arg: KernelArgs<N>| {
+                if let Err(e) = func(arg) {
+                    errs.push(e.into());
+                }
+            };
+            parallel_for(execp, kernel)?;
+
+            if errs.is_empty() {
+                Ok(())
+            } else {
+                Err(StatementError::Kernel(errs))
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// Parallel Reduce statement.
+        ///
+        /// The kernel produces a value for each index; values are combined using the
+        /// reducer. The combine order depends on the dispatch, hence results of
+        /// floating-point reductions may vary slightly between backends.
+        ///
+        /// Several values can be reduced in a single pass by using an array or a pair of
+        /// [ReduceOp][parameters::ReduceOp] as the reducer, see [Reducer].
+        ///
+        /// Reducing an empty range yields the identity of the operator, e.g. the largest
+        /// value of the type for [ReduceOp::Min][parameters::ReduceOp::Min].
+        ///
+        /// **Current version**: thread-safe
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{
+        ///         parallel_reduce,
+        ///         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, ReduceOp, Schedule},
+        ///     },
+        /// };
+        ///
+        /// let execp =  ExecutionPolicy {
+        ///         space: ExecutionSpace::DeviceCPU,
+        ///         range: RangePolicy::RangePolicy(0..100),
+        ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
+        ///     };
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(i) => i as f64,
+        ///         KernelArgs::IndexND(_) => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
+        ///     };
+        ///
+        /// let sum = parallel_reduce(execp.clone(), ReduceOp::Sum, kern).unwrap();
+        /// assert_eq!(sum, 4950.0);
+        ///
+        /// // min & max in a single pass
+        /// let minmax = |arg: KernelArgs<1>| [kern(arg); 2];
+        /// let [min, max] = parallel_reduce(execp, [ReduceOp::Min, ReduceOp::Max], minmax).unwrap();
+        /// assert_eq!((min, max), (0.0, 99.0));
+        /// ```
+        pub fn parallel_reduce<const N: usize, R>(
+            execp: ExecutionPolicy<N>,
+            op: R,
+            func: impl Fn(KernelArgs<N>) -> R::Value + Send + Sync,
+        ) -> Result<R::Value, StatementError>
+        where
+            R: Reducer + Sync,
+            R::Value: Send,
+        {
+            let measure = profiling::begin(&execp.range);
+
+            // dispatch
+            let res = match execp.space {
+                parameters::ExecutionSpace::Serial => dispatch::serial_reduce(execp, &op, func),
+                parameters::ExecutionSpace::DeviceCPU => dispatch::cpu_reduce(execp, &op, func),
+                parameters::ExecutionSpace::DeviceCPUInstance(inst) => {
+                    instance::execute(inst, || dispatch::cpu_reduce(execp, &op, func))
+                }
+                parameters::ExecutionSpace::DeviceGPU => dispatch::gpu_reduce(execp, &op, func),
+            };
+            if let Some(m) = measure {
+                m.end()
+            }
+
+            // Ok or converts error
+            res.map(|val| op.finalize(val.unwrap_or_else(|| op.empty())))
+                .map_err(|e| e.into())
+        }
+    } else {
+        /// Parallel Reduce statement.
+        ///
+        /// The kernel produces a value for each index; values are combined using the
+        /// reducer. The combine order depends on the dispatch, hence results of
+        /// floating-point reductions may vary slightly between backends.
+        ///
+        /// Several values can be reduced in a single pass by using an array or a pair of
+        /// [ReduceOp][parameters::ReduceOp] as the reducer, see [Reducer].
+        ///
+        /// Reducing an empty range yields the identity of the operator, e.g. the largest
+        /// value of the type for [ReduceOp::Min][parameters::ReduceOp::Min].
+        ///
+        /// **Current version**: no feature
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{
+        ///         parallel_reduce,
+        ///         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, ReduceOp, Schedule},
+        ///     },
+        /// };
+        ///
+        /// let execp =  ExecutionPolicy {
+        ///         space: ExecutionSpace::DeviceCPU,
+        ///         range: RangePolicy::RangePolicy(0..100),
+        ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
+        ///     };
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(i) => i as f64,
+        ///         KernelArgs::IndexND(_) => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
+        ///     };
+        ///
+        /// let sum = parallel_reduce(execp.clone(), ReduceOp::Sum, kern).unwrap();
+        /// assert_eq!(sum, 4950.0);
+        ///
+        /// // min & max in a single pass
+        /// let minmax = |arg: KernelArgs<1>| [kern(arg); 2];
+        /// let [min, max] = parallel_reduce(execp, [ReduceOp::Min, ReduceOp::Max], minmax).unwrap();
+        /// assert_eq!((min, max), (0.0, 99.0));
+        /// ```
+        pub fn parallel_reduce<const N: usize, R>(
+            execp: ExecutionPolicy<N>,
+            op: R,
+            func: impl FnMut(KernelArgs<N>) -> R::Value,
+        ) -> Result<R::Value, StatementError>
+        where
+            R: Reducer,
+        {
+            let measure = profiling::begin(&execp.range);
+
+            // dispatch
+            let res = match execp.space {
+                parameters::ExecutionSpace::Serial => dispatch::serial_reduce(execp, &op, func),
+                parameters::ExecutionSpace::DeviceCPU => dispatch::cpu_reduce(execp, &op, func),
+                parameters::ExecutionSpace::DeviceCPUInstance(inst) => {
+                    instance::execute(inst, || dispatch::cpu_reduce(execp, &op, func))
+                }
+                parameters::ExecutionSpace::DeviceGPU => dispatch::gpu_reduce(execp, &op, func),
+            };
+            if let Some(m) = measure {
+                m.end()
+            }
+
+            // Ok or converts error
+            res.map(|val| op.finalize(val.unwrap_or_else(|| op.empty())))
+                .map_err(|e| e.into())
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// Parallel Scan statement.
+        ///
+        /// The kernel produces a value for each index of the range; the statement returns
+        /// the prefix sums of these values, the first element corresponding to the start
+        /// of the range. `T::default()` is used as the identity, i.e. zero for numeric
+        /// types. Only [RangePolicy::RangePolicy] is supported.
+        ///
+        /// The returned vector can be used as the storage of an output view, e.g. using
+        /// [ViewOwned::new_from_data][crate::view::ViewBase::new_from_data].
+        ///
+        /// **Current version**: thread-safe
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{
+        ///         parallel_scan,
+        ///         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, ScanMode, Schedule},
+        ///     },
+        /// };
+        ///
+        /// let execp =  ExecutionPolicy {
+        ///         space: ExecutionSpace::DeviceCPU,
+        ///         range: RangePolicy::RangePolicy(1..5),
+        ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
+        ///     };
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(i) => i,
+        ///         KernelArgs::IndexND(_) => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
+        ///     };
+        ///
+        /// let offsets = parallel_scan(execp, ScanMode::Exclusive, kern).unwrap();
+        /// assert_eq!(offsets, vec![0, 1, 3, 6]);
+        /// ```
+        pub fn parallel_scan<T>(
+            execp: ExecutionPolicy<1>,
+            mode: ScanMode,
+            func: impl Fn(KernelArgs<1>) -> T + Send + Sync,
+        ) -> Result<Vec<T>, StatementError>
+        where
+            T: DataTraits + Add<Output = T> + Send + Sync,
+        {
+            let measure = profiling::begin(&execp.range);
+
+            // dispatch
+            let res = match execp.space {
+                parameters::ExecutionSpace::Serial => dispatch::serial_scan(execp, mode, func),
+                parameters::ExecutionSpace::DeviceCPU => dispatch::cpu_scan(execp, mode, func),
+                parameters::ExecutionSpace::DeviceCPUInstance(inst) => {
+                    instance::execute(inst, || dispatch::cpu_scan(execp, mode, func))
+                }
+                parameters::ExecutionSpace::DeviceGPU => dispatch::gpu_scan(execp, mode, func),
+            };
+            if let Some(m) = measure {
+                m.end()
+            }
+
+            // Ok or converts error
+            res.map_err(|e| e.into())
+        }
+    } else {
+        /// Parallel Scan statement.
+        ///
+        /// The kernel produces a value for each index of the range; the statement returns
+        /// the prefix sums of these values, the first element corresponding to the start
+        /// of the range. `T::default()` is used as the identity, i.e. zero for numeric
+        /// types. Only [RangePolicy::RangePolicy] is supported.
+        ///
+        /// The returned vector can be used as the storage of an output view, e.g. using
+        /// [ViewOwned::new_from_data][crate::view::ViewBase::new_from_data].
+        ///
+        /// **Current version**: no feature
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{
+        ///         parallel_scan,
+        ///         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, ScanMode, Schedule},
+        ///     },
+        /// };
+        ///
+        /// let execp =  ExecutionPolicy {
+        ///         space: ExecutionSpace::DeviceCPU,
+        ///         range: RangePolicy::RangePolicy(1..5),
+        ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
+        ///     };
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(i) => i,
+        ///         KernelArgs::IndexND(_) => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
+        ///     };
+        ///
+        /// let offsets = parallel_scan(execp, ScanMode::Exclusive, kern).unwrap();
+        /// assert_eq!(offsets, vec![0, 1, 3, 6]);
+        /// ```
+        pub fn parallel_scan<T>(
+            execp: ExecutionPolicy<1>,
+            mode: ScanMode,
+            func: impl FnMut(KernelArgs<1>) -> T,
+        ) -> Result<Vec<T>, StatementError>
+        where
+            T: DataTraits + Add<Output = T>,
+        {
+            let measure = profiling::begin(&execp.range);
+
+            // dispatch
+            let res = match execp.space {
+                parameters::ExecutionSpace::Serial => dispatch::serial_scan(execp, mode, func),
+                parameters::ExecutionSpace::DeviceCPU => dispatch::cpu_scan(execp, mode, func),
+                parameters::ExecutionSpace::DeviceCPUInstance(inst) => {
+                    instance::execute(inst, || dispatch::cpu_scan(execp, mode, func))
+                }
+                parameters::ExecutionSpace::DeviceGPU => dispatch::gpu_scan(execp, mode, func),
+            };
+            if let Some(m) = measure {
+                m.end()
+            }
+
+            // Ok or converts error
+            res.map_err(|e| e.into())
+        }
+    }
+}
+
+/// Returns the range of a 1D policy split into chunks of (at most) `chunk_size` indices.
+fn split_range(
+    execp: &ExecutionPolicy<1>,
+    chunk_size: usize,
+) -> Result<Range<usize>, StatementError> {
+    match &execp.range {
+        RangePolicy::RangePolicy(range) => Ok(range.clone()),
+        other => Err(StatementError::UnsupportedPolicy(other.kind())),
+    }
+    .map(|range| range.start..range.start + range.len().div_ceil(chunk_size.max(1)))
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// Parallel For statement skipping inactive chunks.
+        ///
+        /// The range of the policy is split into chunks of `chunk_size` indices. Before
+        /// executing a chunk, `predicate` is evaluated using the bounds of the chunk; the
+        /// chunk is skipped if it returns `false`. This can be used to avoid traversing inactive
+        /// regions of spatially sparse workloads without changing the kernel.
+        ///
+        /// Chunks are dispatched according to the policy, the kernel is executed sequentially
+        /// inside each chunk. Only [RangePolicy::RangePolicy] is supported.
+        ///
+        /// Unlike [ExecutionPolicy::chunk_predicate][parameters::ExecutionPolicy::chunk_predicate],
+        /// the predicate may borrow from its environment.
+        ///
+        /// **Current version**: thread-safe
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{
+        ///         parallel_for_skip_chunks,
+        ///         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        ///     },
+        /// };
+        ///
+        /// let execp =  ExecutionPolicy {
+        ///         space: ExecutionSpace::DeviceCPU,
+        ///         range: RangePolicy::RangePolicy(0..1000),
+        ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
+        ///     };
+        ///
+        /// // only the 100 first indices are active
+        /// let predicate = |bounds: std::ops::Range<usize>| bounds.start < 100;
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(i) => assert!(i < 100),
+        ///         KernelArgs::IndexND(_) => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
+        ///     };
+        ///
+        /// parallel_for_skip_chunks(execp, 50, predicate, kern).unwrap();
+        /// ```
+        pub fn parallel_for_skip_chunks(
+            execp: ExecutionPolicy<1>,
+            chunk_size: usize,
+            predicate: impl Fn(Range<usize>) -> bool + Sync,
+            func: impl Fn(KernelArgs<1>) + Sync,
+        ) -> Result<(), StatementError> {
+            let chunks = split_range(&execp, chunk_size)?;
+            let RangePolicy::RangePolicy(range) = &execp.range else {
+                unreachable!()
+            };
+            let (start, end, chunk_size) = (range.start, range.end, chunk_size.max(1));
+
+            // capture the kernel & predicate by reference to satisfy backend requirements
+            let (predicate, func) = (&predicate, &func);
+            let chunk_kernel = move |arg: KernelArgs<1>| {
+                if let KernelArgs::Index1D(c) = arg {
+                    let c_start = start + (c - chunks.start) * chunk_size;
+                    let bounds = c_start..(c_start + chunk_size).min(end);
+                    if predicate(bounds.clone()) {
+                        bounds.map(KernelArgs::Index1D).for_each(func);
+                    }
+                }
+            };
+
+            let chunk_execp = ExecutionPolicy {
+                range: RangePolicy::RangePolicy(chunks.clone()),
+                ..execp
+            };
+            parallel_for(chunk_execp, chunk_kernel)
+        }
+    } else {
+        /// Parallel For statement skipping inactive chunks.
+        ///
+        /// The range of the policy is split into chunks of `chunk_size` indices. Before
+        /// executing a chunk, `predicate` is evaluated using the bounds of the chunk; the
+        /// chunk is skipped if it returns `false`. This can be used to avoid traversing inactive
+        /// regions of spatially sparse workloads without changing the kernel.
+        ///
+        /// Chunks are dispatched according to the policy, the kernel is executed sequentially
+        /// inside each chunk. Only [RangePolicy::RangePolicy] is supported.
+        ///
+        /// Unlike [ExecutionPolicy::chunk_predicate][parameters::ExecutionPolicy::chunk_predicate],
+        /// the predicate may borrow from its environment.
+        ///
+        /// **Current version**: no feature
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{
+        ///         parallel_for_skip_chunks,
+        ///         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        ///     },
+        /// };
+        ///
+        /// let execp =  ExecutionPolicy {
+        ///         space: ExecutionSpace::DeviceCPU,
+        ///         range: RangePolicy::RangePolicy(0..1000),
+        ///         schedule: Schedule::Static,
+        ///         chunk_size: None,
+        ///         chunk_predicate: None,
+        ///     };
+        ///
+        /// // only the 100 first indices are active
+        /// let predicate = |bounds: std::ops::Range<usize>| bounds.start < 100;
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(i) => assert!(i < 100),
+        ///         KernelArgs::IndexND(_) => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
+        ///     };
+        ///
+        /// parallel_for_skip_chunks(execp, 50, predicate, kern).unwrap();
+        /// ```
+        pub fn parallel_for_skip_chunks(
+            execp: ExecutionPolicy<1>,
+            chunk_size: usize,
+            predicate: impl Fn(Range<usize>) -> bool,
+            mut func: impl FnMut(KernelArgs<1>),
+        ) -> Result<(), StatementError> {
+            let chunks = split_range(&execp, chunk_size)?;
+            let RangePolicy::RangePolicy(range) = &execp.range else {
+                unreachable!()
+            };
+            let (start, end, chunk_size) = (range.start, range.end, chunk_size.max(1));
+
+            let chunk_kernel = |arg: KernelArgs<1>| {
+                if let KernelArgs::Index1D(c) = arg {
+                    let c_start = start + (c - chunks.start) * chunk_size;
+                    let bounds = c_start..(c_start + chunk_size).min(end);
+                    if predicate(bounds.clone()) {
+                        bounds.map(KernelArgs::Index1D).for_each(&mut func);
+                    }
+                }
+            };
+
+            let chunk_execp = ExecutionPolicy {
+                range: RangePolicy::RangePolicy(chunks.clone()),
+                ..execp
+            };
+            parallel_for(chunk_execp, chunk_kernel)
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// Parallel For statement over a colored index range.
+        ///
+        /// Colors of the policy are executed in increasing order; indices of a color are
+        /// dispatched in parallel according to the policy. The kernel receives the index
+        /// from the original range, not its rank inside the color.
+        ///
+        /// **Current version**: thread-safe
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{parallel_for_colored, parameters::ColoredPolicy},
+        ///     view::{parameters::Layout, ViewOwned},
+        /// };
+        ///
+        /// let colors: ViewOwned<'_, 1, usize> =
+        ///     ViewOwned::new_from_data(vec![0, 1, 0, 1], Layout::Right, [4]);
+        /// let colorp = ColoredPolicy::new(&colors);
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(i) => {
+        ///             // scatter to shared entities without atomics
+        ///         },
+        ///         KernelArgs::IndexND(_) => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
+        ///     };
+        ///
+        /// parallel_for_colored(&colorp, kern).unwrap();
+        /// ```
+        pub fn parallel_for_colored(
+            colorp: &ColoredPolicy,
+            func: impl Fn(KernelArgs<1>) + Sync,
+        ) -> Result<(), StatementError> {
+            let func = &func;
+            for color in 0..colorp.n_colors() {
+                let indices = colorp.indices(color);
+                let execp = ExecutionPolicy {
+                    space: colorp.space,
+                    range: RangePolicy::RangePolicy(0..indices.len()),
+                    schedule: colorp.schedule.clone(),
+                    chunk_size: colorp.chunk_size,
+                    chunk_predicate: None,
+                };
+                let color_kernel = move |arg: KernelArgs<1>| {
+                    if let KernelArgs::Index1D(i) = arg {
+                        func(KernelArgs::Index1D(indices[i]))
+                    }
+                };
+                parallel_for(execp, color_kernel)?;
+            }
+            Ok(())
+        }
+    } else {
+        /// Parallel For statement over a colored index range.
+        ///
+        /// Colors of the policy are executed in increasing order; indices of a color are
+        /// dispatched in parallel according to the policy. The kernel receives the index
+        /// from the original range, not its rank inside the color.
+        ///
+        /// **Current version**: no feature
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::{
+        ///     functor::KernelArgs,
+        ///     routines::{parallel_for_colored, parameters::ColoredPolicy},
+        ///     view::{parameters::Layout, ViewOwned},
+        /// };
+        ///
+        /// let colors: ViewOwned<'_, 1, usize> =
+        ///     ViewOwned::new_from_data(vec![0, 1, 0, 1], Layout::Right, [4]);
+        /// let colorp = ColoredPolicy::new(&colors);
+        ///
+        /// let kern = |arg: KernelArgs<1>| match arg {
+        ///         KernelArgs::Index1D(i) => {
+        ///             // scatter to shared entities without atomics
+        ///         },
+        ///         KernelArgs::IndexND(_) => unimplemented!(),
+        ///         KernelArgs::Handle(_) => unimplemented!(),
+        ///     };
+        ///
+        /// parallel_for_colored(&colorp, kern).unwrap();
+        /// ```
+        pub fn parallel_for_colored(
+            colorp: &ColoredPolicy,
+            mut func: impl FnMut(KernelArgs<1>),
+        ) -> Result<(), StatementError> {
+            for color in 0..colorp.n_colors() {
+                let indices = colorp.indices(color);
+                let execp = ExecutionPolicy {
+                    space: colorp.space,
+                    range: RangePolicy::RangePolicy(0..indices.len()),
+                    schedule: colorp.schedule.clone(),
+                    chunk_size: colorp.chunk_size,
+                    chunk_predicate: None,
+                };
+                let color_kernel = |arg: KernelArgs<1>| {
+                    if let KernelArgs::Index1D(i) = arg {
+                        func(KernelArgs::Index1D(indices[i]))
+                    }
+                };
+                parallel_for(execp, color_kernel)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::{
+        instance::CpuInstance,
+        iter::MDIndexIter,
+        parameters::{Iterate, MaxLoc, MinLoc, Prod, ReduceOp, Schedule, ValLoc},
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    };
+
+    /// Returns a space with enough threads to execute the members of the teams of these
+    /// tests concurrently, whatever the number of cores of the machine.
+    fn team_space() -> ExecutionSpace {
+        static INSTANCE: OnceLock<CpuInstance> = OnceLock::new();
+        ExecutionSpace::DeviceCPUInstance(*INSTANCE.get_or_init(|| CpuInstance::new(4).unwrap()))
+    }
+
+    #[test]
+    fn support_queries() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            let level = |statement, kind| supports(statement, kind, space);
+            assert_ne!(
+                level(StatementKind::Scan, PolicyKind::RangePolicy),
+                SupportLevel::Unimplemented
+            );
+            assert_ne!(
+                level(StatementKind::For, PolicyKind::TeamPolicy),
+                SupportLevel::Unimplemented
+            );
+            // reduce & scan statements reject team policies
+            assert_eq!(
+                level(StatementKind::Reduce, PolicyKind::TeamPolicy),
+                SupportLevel::Unimplemented
+            );
+            assert_eq!(
+                level(StatementKind::Scan, PolicyKind::TeamPolicy),
+                SupportLevel::Unimplemented
+            );
+            assert_eq!(
+                level(StatementKind::Reduce, PolicyKind::TiledMDRangePolicy),
+                level(StatementKind::For, PolicyKind::TiledMDRangePolicy)
+            );
+            assert_eq!(
+                level(StatementKind::Scan, PolicyKind::MDRangePolicy),
+                SupportLevel::Unimplemented
+            );
+        }
+    }
+
+    #[test]
+    fn reduce_ops() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            let execp = ExecutionPolicy {
+                space,
+                range: RangePolicy::RangePolicy(0..1000),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let kernel = |arg: KernelArgs<1>| match arg {
+                KernelArgs::Index1D(i) => (i as i64 - 400).abs(),
+                _ => unimplemented!(),
+            };
+            assert_eq!(
+                parallel_reduce(execp.clone(), ReduceOp::Min, kernel).unwrap(),
+                0
+            );
+            assert_eq!(
+                parallel_reduce(execp.clone(), ReduceOp::Max, kernel).unwrap(),
+                599
+            );
+            let sum = (0..1000).map(|i: i64| (i - 400).abs()).sum::<i64>();
+            assert_eq!(
+                parallel_reduce(execp.clone(), ReduceOp::Sum, kernel).unwrap(),
+                sum
+            );
+            let custom = ReduceOp::Custom(|a: i64, b: i64| a.max(b));
+            assert_eq!(parallel_reduce(execp, custom, kernel).unwrap(), 599);
+        }
+
+        // MDRange & empty ranges
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::Serial,
+            range: RangePolicy::MDRangePolicy([0..3, 0..4]),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let kernel = |arg: KernelArgs<2>| match arg {
+            KernelArgs::IndexND([i, j]) => (i * j) as f64,
+            _ => unimplemented!(),
+        };
+        assert_eq!(parallel_reduce(execp, ReduceOp::Sum, kernel).unwrap(), 18.0);
+        let execp: ExecutionPolicy<1> = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::RangePolicy(5..5),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        assert_eq!(
+            parallel_reduce(execp.clone(), ReduceOp::Min, |_| 1.0).unwrap(),
+            f64::MAX
+        );
+        assert_eq!(
+            parallel_reduce(execp.clone(), ReduceOp::Max, |_| 1).unwrap(),
+            i32::MIN
+        );
+        assert_eq!(parallel_reduce(execp, ReduceOp::Sum, |_| 1.0).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn reduction_strategies() {
+        use super::parameters::{ReductionStrategy, SumWith};
+
+        let kernel = |arg: KernelArgs<1>| match arg {
+            KernelArgs::Index1D(0) => 1.0,
+            _ => 1.0e-16,
+        };
+        let exact: f64 = 1.0 + 1.0e-11;
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            for schedule in [Schedule::Static, Schedule::Dynamic] {
+                let execp = ExecutionPolicy {
+                    space,
+                    range: RangePolicy::RangePolicy(0..100_001),
+                    schedule,
+                    chunk_size: None,
+                    chunk_predicate: None,
+                };
+                for strategy in [ReductionStrategy::Pairwise, ReductionStrategy::Kahan] {
+                    let sum =
+                        parallel_reduce(execp.clone(), SumWith::new(strategy), kernel).unwrap();
+                    assert!((sum - exact).abs() < 1.0e-14);
+                }
+            }
+        }
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::Serial,
+            range: RangePolicy::RangePolicy(0..100_001),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        // small values are absorbed one by one when accumulating sequentially
+        let sequential = SumWith::new(ReductionStrategy::Sequential);
+        assert_eq!(parallel_reduce(execp, sequential, kernel).unwrap(), 1.0);
+        let execp: ExecutionPolicy<1> = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::RangePolicy(5..5),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let kahan = SumWith::new(ReductionStrategy::Kahan);
+        assert_eq!(parallel_reduce(execp, kahan, |_| 1.0).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn multi_reduce() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            let execp = ExecutionPolicy {
+                space,
+                range: RangePolicy::RangePolicy(0..1000),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let kernel = |arg: KernelArgs<1>| match arg {
+                KernelArgs::Index1D(i) => (i as i64 - 400).abs(),
+                _ => unimplemented!(),
+            };
+            let sum = (0..1000).map(|i: i64| (i - 400).abs()).sum::<i64>();
+            let ops = [ReduceOp::Min, ReduceOp::Max, ReduceOp::Sum];
+            let res = parallel_reduce(execp.clone(), ops, |arg| [kernel(arg); 3]).unwrap();
+            assert_eq!(res, [0, 599, sum]);
+
+            // values of a pair may have different types
+            let ops = (ReduceOp::Sum, ReduceOp::Max);
+            let pair = |arg| (1usize, kernel(arg) as f64);
+            assert_eq!(parallel_reduce(execp, ops, pair).unwrap(), (1000, 599.0));
+        }
+
+        // MDRange & empty ranges
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::MDRangePolicy([0..3, 0..4]),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let kernel = |arg: KernelArgs<2>| match arg {
+            KernelArgs::IndexND([i, j]) => [(i * j) as f64, (i + j) as f64],
+            _ => unimplemented!(),
+        };
+        let ops = [ReduceOp::Sum, ReduceOp::Max];
+        assert_eq!(parallel_reduce(execp, ops, kernel).unwrap(), [18.0, 5.0]);
+        let execp: ExecutionPolicy<1> = ExecutionPolicy {
+            space: ExecutionSpace::Serial,
+            range: RangePolicy::RangePolicy(5..5),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let ops = (ReduceOp::Min, [ReduceOp::Sum; 2]);
+        let res = parallel_reduce(execp, ops, |_| (1, [1.0, 2.0])).unwrap();
+        assert_eq!(res, (i32::MAX, [0.0, 0.0]));
+    }
+
+    /// Mean reducer, dividing the sum of values by their count in its final step.
+    struct Mean;
+
+    impl Reducer for Mean {
+        type Value = (f64, f64);
+
+        fn combine(&self, lhs: (f64, f64), rhs: (f64, f64)) -> (f64, f64) {
+            (lhs.0 + rhs.0, lhs.1 + rhs.1)
+        }
+
+        fn empty(&self) -> (f64, f64) {
+            (0.0, 0.0)
+        }
+
+        fn finalize(&self, (sum, count): (f64, f64)) -> (f64, f64) {
+            (sum / count.max(1.0), count)
+        }
+    }
+
+    #[test]
+    fn standard_reducers() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            for schedule in [Schedule::Static, Schedule::Dynamic] {
+                let execp = ExecutionPolicy {
+                    space,
+                    range: RangePolicy::RangePolicy(0..1000),
+                    schedule,
+                    chunk_size: Some(16),
+                    chunk_predicate: None,
+                };
+                // extrema are reached twice, the lowest location is kept
+                let kernel = |arg: KernelArgs<1>| match arg {
+                    KernelArgs::Index1D(i) => ValLoc {
+                        val: (i % 500) as i32 - 100,
+                        loc: i,
+                    },
+                    _ => unimplemented!(),
+                };
+                let min = parallel_reduce(execp.clone(), MinLoc::new(), kernel).unwrap();
+                assert_eq!(min, ValLoc { val: -100, loc: 0 });
+                let max = parallel_reduce(execp.clone(), MaxLoc::new(), kernel).unwrap();
+                assert_eq!(max, ValLoc { val: 399, loc: 499 });
+
+                let prod = parallel_reduce(execp.clone(), Prod::new(), |arg| match arg {
+                    KernelArgs::Index1D(i) if i % 100 == 0 => 2u64,
+                    _ => 1,
+                });
+                assert_eq!(prod.unwrap(), 1024);
+
+                let mean = parallel_reduce(execp, Mean, |arg| match arg {
+                    KernelArgs::Index1D(i) => (i as f64, 1.0),
+                    _ => unimplemented!(),
+                });
+                assert_eq!(mean.unwrap(), (499.5, 1000.0));
+            }
+        }
+    }
+
+    #[test]
+    fn dynamic_schedule() {
+        // irregular workload: the cost of an iteration grows with its index
+        let hits: Vec<AtomicUsize> = (0..1000).map(|_| AtomicUsize::new(0)).collect();
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::RangePolicy(0..1000),
+            schedule: Schedule::Dynamic,
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let kernel = |arg: KernelArgs<1>| match arg {
+            KernelArgs::Index1D(i) => {
+                let work = (0..i).fold(0usize, |acc, x| acc.wrapping_add(x * x));
+                std::hint::black_box(work);
+                hits[i].fetch_add(1, Ordering::Relaxed);
+            }
+            _ => unimplemented!(),
+        };
+        parallel_for(execp, kernel).unwrap();
+        assert!(hits.iter().all(|h| h.load(Ordering::Relaxed) == 1));
+
+        let hits: Vec<AtomicUsize> = (0..12 * 70).map(|_| AtomicUsize::new(0)).collect();
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::TiledMDRangePolicy {
+                ranges: [0..12, 0..70],
+                tile: [5, 16],
+                outer: Iterate::Left,
+                inner: Iterate::Right,
+            },
+            schedule: Schedule::Dynamic,
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let kernel = |arg: KernelArgs<2>| match arg {
+            KernelArgs::IndexND([i, j]) => {
+                hits[i * 70 + j].fetch_add(1, Ordering::Relaxed);
+            }
+            _ => unimplemented!(),
+        };
+        parallel_for(execp, kernel).unwrap();
+        assert!(hits.iter().all(|h| h.load(Ordering::Relaxed) == 1));
+    }
+
+    #[test]
+    fn fallible_for() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            let execp = ExecutionPolicy {
+                space,
+                range: RangePolicy::RangePolicy(0..100),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let count = AtomicUsize::new(0);
+            let kernel = |arg: KernelArgs<1>| match arg {
+                KernelArgs::Index1D(i) => {
+                    count.fetch_add(1, Ordering::Relaxed);
+                    if i % 10 == 3 {
+                        Err(format!("iteration {i} failed"))
+                    } else {
+                        Ok(())
+                    }
+                }
+                _ => unimplemented!(),
+            };
+            match try_parallel_for(execp.clone(), kernel) {
+                Err(StatementError::Kernel(errs)) => {
+                    assert_eq!(errs.len(), 10);
+                    assert!(errs.iter().any(|e| e.to_string() == "iteration 43 failed"));
+                }
+                _ => panic!("kernel errors were not reported"),
+            }
+            // all iterations are executed
+            assert_eq!(count.load(Ordering::Relaxed), 100);
+
+            let ok = |_: KernelArgs<1>| Ok::<(), String>(());
+            assert!(try_parallel_for(execp, ok).is_ok());
+        }
+    }
+
+    #[test]
+    fn scan_modes() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            let execp = ExecutionPolicy {
+                space,
+                range: RangePolicy::RangePolicy(3..10_003),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let kernel = |arg: KernelArgs<1>| match arg {
+                KernelArgs::Index1D(i) => i % 7,
+                _ => unimplemented!(),
+            };
+            let inclusive = parallel_scan(execp.clone(), ScanMode::Inclusive, kernel).unwrap();
+            let exclusive = parallel_scan(execp, ScanMode::Exclusive, kernel).unwrap();
+
+            let mut acc = 0;
+            for (k, i) in (3..10_003).enumerate() {
+                assert_eq!(exclusive[k], acc);
+                acc += i % 7;
+                assert_eq!(inclusive[k], acc);
+            }
+        }
+    }
+
+    #[test]
+    fn team_policy() {
+        for space in [ExecutionSpace::Serial, team_space()] {
+            let execp = ExecutionPolicy {
+                space,
+                range: RangePolicy::TeamPolicy {
+                    league_size: 4,
+                    team_size: 3,
+                    vector_size: 1,
+                    scratch_size: [0, 0],
+                },
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let visits: Vec<AtomicUsize> = (0..4 * 10).map(|_| AtomicUsize::new(0)).collect();
+            let singles = AtomicUsize::new(0);
+            let kernel = |arg: KernelArgs<1>| {
+                let KernelArgs::Handle(handle) = arg else {
+                    unimplemented!()
+                };
+                assert_eq!(handle.team_size(), 3);
+                let nested = |range| ExecutionPolicy {
+                    space: ExecutionSpace::Serial,
+                    range,
+                    schedule: Schedule::default(),
+                    chunk_size: None,
+                    chunk_predicate: None,
+                };
+                let team = handle.league_rank();
+                parallel_for(
+                    nested(RangePolicy::TeamThreadRange(handle.clone(), 0..10)),
+                    |arg: KernelArgs<1>| {
+                        if let KernelArgs::Index1D(i) = arg {
+                            visits[team * 10 + i].fetch_add(1, Ordering::Relaxed);
+                        }
+                    },
+                )
+                .unwrap();
+                parallel_for(nested(RangePolicy::PerTeam(handle)), |_: KernelArgs<1>| {
+                    singles.fetch_add(1, Ordering::Relaxed);
+                })
+                .unwrap();
+            };
+            parallel_for(execp, kernel).unwrap();
+
+            assert!(visits.iter().all(|v| v.load(Ordering::Relaxed) == 1));
+            assert_eq!(singles.into_inner(), 4);
+        }
+    }
+
+    #[test]
+    fn vector_ranges() {
+        for space in [ExecutionSpace::Serial, team_space()] {
+            let execp = ExecutionPolicy {
+                space,
+                range: RangePolicy::TeamPolicy {
+                    league_size: 2,
+                    team_size: 3,
+                    vector_size: 4,
+                    scratch_size: [0, 0],
+                },
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let team_visits: Vec<AtomicUsize> = (0..2 * 25).map(|_| AtomicUsize::new(0)).collect();
+            let thread_visits: Vec<AtomicUsize> = (0..25).map(|_| AtomicUsize::new(0)).collect();
+            let kernel = |arg: KernelArgs<1>| {
+                let KernelArgs::Handle(handle) = arg else {
+                    unimplemented!()
+                };
+                let chunks: Vec<_> = handle.vector_chunks(3..13).collect();
+                assert_eq!(chunks, vec![3..7, 7..11, 11..13]);
+                let nested = |range| ExecutionPolicy {
+                    space: ExecutionSpace::Serial,
+                    range,
+                    schedule: Schedule::default(),
+                    chunk_size: None,
+                    chunk_predicate: None,
+                };
+                let team = handle.league_rank();
+                let team_range = RangePolicy::TeamVectorRange(handle.clone(), 0..25);
+                parallel_for(nested(team_range), |arg: KernelArgs<1>| {
+                    if let KernelArgs::Index1D(i) = arg {
+                        team_visits[team * 25 + i].fetch_add(1, Ordering::Relaxed);
+                    }
+                })
+                .unwrap();
+                let thread_range = RangePolicy::ThreadVectorRange(handle, 0..25);
+                parallel_for(nested(thread_range), |arg: KernelArgs<1>| {
+                    if let KernelArgs::Index1D(i) = arg {
+                        thread_visits[i].fetch_add(1, Ordering::Relaxed);
+                    }
+                })
+                .unwrap();
+            };
+            parallel_for(execp, kernel).unwrap();
+
+            // team ranges are split between members, thread ranges are not
+            assert!(team_visits.iter().all(|v| v.load(Ordering::Relaxed) == 1));
+            assert!(thread_visits.iter().all(|v| v.load(Ordering::Relaxed) == 6));
+        }
+    }
+
+    #[test]
+    fn team_collectives() {
+        let execp = |space, league_size, team_size| ExecutionPolicy {
+            space,
+            range: RangePolicy::TeamPolicy {
+                league_size,
+                team_size,
+                vector_size: 1,
+                scratch_size: [0, 0],
+            },
+            schedule: Schedule::Dynamic,
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let kernel = |arg: KernelArgs<1>| {
+            let KernelArgs::Handle(handle) = arg else {
+                unimplemented!()
+            };
+            let rank = handle.team_rank();
+            let size = handle.team_reduce(rank + 1, &ReduceOp::Max);
+            assert_eq!(size, handle.team_size());
+            assert_eq!(
+                handle.team_scan(rank, ScanMode::Inclusive),
+                (0..=rank).sum()
+            );
+            handle.team_barrier();
+            let root = size - 1;
+            let val = handle.team_broadcast(10 * handle.league_rank() + rank, root);
+            assert_eq!(val, 10 * handle.league_rank() + root);
+        };
+
+        // single-member teams can use collectives wherever they are executed
+        for space in [ExecutionSpace::Serial, team_space()] {
+            parallel_for(execp(space, 4, 1), kernel).unwrap();
+        }
+        if cfg!(any(feature = "threads", feature = "rayon")) {
+            parallel_for(execp(team_space(), 16, 4), kernel).unwrap();
+            // members of a team cannot share threads
+            let small = CpuInstance::new(2).unwrap();
+            let space = ExecutionSpace::DeviceCPUInstance(small);
+            assert!(parallel_for(execp(space, 4, 3), kernel).is_err());
+            small.release();
+        }
+        // members of serial teams are not executed concurrently
+        let sequential = std::panic::AssertUnwindSafe(|| {
+            parallel_for(execp(ExecutionSpace::Serial, 1, 2), kernel).unwrap()
+        });
+        assert!(std::panic::catch_unwind(sequential).is_err());
+    }
+
+    #[test]
+    fn team_single() {
+        use crate::routines::team::{single, single_broadcast};
+
+        for (space, team_size) in [(ExecutionSpace::Serial, 3), (ExecutionSpace::DeviceCPU, 1)] {
+            let execp = ExecutionPolicy {
+                space,
+                range: RangePolicy::TeamPolicy {
+                    league_size: 4,
+                    team_size,
+                    vector_size: 1,
+                    scratch_size: [0, 0],
+                },
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let (teams, members) = (AtomicUsize::new(0), AtomicUsize::new(0));
+            let kernel = |arg: KernelArgs<1>| {
+                let KernelArgs::Handle(handle) = arg else {
+                    unimplemented!()
+                };
+                let once = single(RangePolicy::<1>::PerTeam(handle.clone()), || {
+                    teams.fetch_add(1, Ordering::Relaxed)
+                })
+                .unwrap();
+                assert_eq!(once.is_some(), handle.team_rank() == 0);
+                let rank = single_broadcast(RangePolicy::<1>::PerThread(handle.clone()), || {
+                    members.fetch_add(1, Ordering::Relaxed);
+                    handle.team_rank()
+                })
+                .unwrap();
+                assert_eq!(rank, handle.team_rank());
+                let range = RangePolicy::<1>::TeamThreadRange(handle, 0..1);
+                assert!(single(range, || ()).is_err());
+            };
+            parallel_for(execp, kernel).unwrap();
+            assert_eq!(teams.into_inner(), 4);
+            assert_eq!(members.into_inner(), 4 * team_size);
+        }
+
+        #[cfg(any(feature = "threads", feature = "rayon"))]
+        {
+            use crate::routines::parameters::{Team, TypedExecutionPolicy};
+
+            let execp = TypedExecutionPolicy {
+                space: team_space(),
+                policy: Team {
+                    league_size: 8,
+                    team_size: 4,
+                    vector_size: 1,
+                    scratch_size: [0, 0],
+                },
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            typed::parallel_for(execp, |handle| {
+                let team = handle.league_rank();
+                let val = single_broadcast(RangePolicy::<1>::PerTeam(handle), || 10 * team);
+                assert_eq!(val.unwrap(), 10 * team);
+            })
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn team_scratch() {
+        use crate::view::parameters::Layout;
+
+        for space in [ExecutionSpace::Serial, team_space()] {
+            let execp = ExecutionPolicy {
+                space,
+                range: RangePolicy::TeamPolicy {
+                    league_size: 8,
+                    team_size: 4,
+                    vector_size: 1,
+                    scratch_size: [30, 0],
+                },
+                schedule: Schedule::Dynamic,
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let kernel = |arg: KernelArgs<1>| {
+                let KernelArgs::Handle(handle) = arg else {
+                    unimplemented!()
+                };
+                assert_eq!(handle.team_scratch(1).size(), 0);
+                let mut scratch = handle.team_scratch(0);
+                assert_eq!(scratch.size(), 32);
+                let slots = scratch.view::<usize, 1>(Layout::Right, [4]).unwrap();
+                let team = handle.league_rank() + 1;
+                slots.set([handle.team_rank()], team);
+                // other slots are written by members of the same team only
+                assert!((0..4).all(|m| [0, team].contains(&slots.get([m]))));
+            };
+            parallel_for(execp, kernel).unwrap();
+        }
+    }
+
+    #[test]
+    fn tiled_mdrange() {
+        use crate::view::{parameters::Layout, ViewOwned};
+
+        let layout = Layout::Left;
+        let view: ViewOwned<'_, 3, usize> = ViewOwned::new(layout, [7, 9, 5000]);
+        let visits: Vec<AtomicUsize> = (0..7 * 9 * 5000).map(|_| AtomicUsize::new(0)).collect();
+        let kernel = |arg: KernelArgs<3>| {
+            if let KernelArgs::IndexND(idx) = arg {
+                visits[view.flat_idx(idx)].fetch_add(1, Ordering::Relaxed);
+            }
+        };
+        let ranges = [0..7, 2..9, 0..5000];
+        let ref_sum: usize = MDIndexIter::new(ranges.clone())
+            .map(|[i, j, k]| i + j + k)
+            .sum();
+        for range in [
+            RangePolicy::MDRangePolicy(ranges.clone()),
+            RangePolicy::TiledMDRangePolicy {
+                ranges: ranges.clone(),
+                tile: [4, 4, 64],
+                outer: Iterate::from(&layout),
+                inner: Iterate::from(&layout),
+            },
+        ] {
+            for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+                let execp = ExecutionPolicy {
+                    space,
+                    range: range.clone(),
+                    schedule: Schedule::default(),
+                    chunk_size: None,
+                    chunk_predicate: None,
+                };
+                parallel_for(execp.clone(), kernel).unwrap();
+                let sum = parallel_reduce(execp, ReduceOp::Sum, |arg: KernelArgs<3>| match arg {
+                    KernelArgs::IndexND([i, j, k]) => i + j + k,
+                    _ => unimplemented!(),
+                })
+                .unwrap();
+                assert_eq!(sum, ref_sum);
+            }
+        }
+        // each index of the ranges was visited 4 times, others never
+        MDIndexIter::new([0..7, 0..9, 0..5000]).for_each(|idx| {
+            let expected = if idx[1] >= 2 { 4 } else { 0 };
+            assert_eq!(visits[view.flat_idx(idx)].load(Ordering::Relaxed), expected);
+        });
+    }
+
+    #[test]
+    fn tiled_mdrange_order() {
+        // tiles in row-major order, indices of each tile in column-major order
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::Serial,
+            range: RangePolicy::TiledMDRangePolicy {
+                ranges: [0..4, 0..4],
+                tile: [2, 2],
+                outer: Iterate::Right,
+                inner: Iterate::Left,
+            },
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let order = std::sync::Mutex::new(Vec::new());
+        let kernel = |arg: KernelArgs<2>| {
+            if let KernelArgs::IndexND(idx) = arg {
+                order.lock().unwrap().push(idx);
+            }
+        };
+        parallel_for(execp, kernel).unwrap();
+        let order = order.into_inner().unwrap();
+        assert_eq!(order.len(), 16);
+        assert_eq!(order[..6], [[0, 0], [1, 0], [0, 1], [1, 1], [0, 2], [1, 2]]);
+        assert_eq!(order[8..10], [[2, 0], [3, 0]]);
+    }
+
+    #[test]
+    fn skip_chunks() {
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::RangePolicy(10..105),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let count = AtomicUsize::new(0);
+        let sum = AtomicUsize::new(0);
+        // skip the chunk [30; 40[
+        let predicate = |bounds: Range<usize>| bounds.start != 30;
+        let kernel = |arg: KernelArgs<1>| {
+            if let KernelArgs::Index1D(i) = arg {
+                count.fetch_add(1, Ordering::Relaxed);
+                sum.fetch_add(i, Ordering::Relaxed);
+            }
+        };
+        parallel_for_skip_chunks(execp, 10, predicate, kernel).unwrap();
+
+        assert_eq!(count.into_inner(), 95 - 10);
+        assert_eq!(
+            sum.into_inner(),
+            (10..105).sum::<usize>() - (30..40).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn chunk_predicate() {
+        use crate::routines::parameters::ChunkPredicate;
+
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            for schedule in [Schedule::Static, Schedule::Dynamic] {
+                // skip the chunk [30; 40[
+                let execp = ExecutionPolicy {
+                    space,
+                    range: RangePolicy::RangePolicy(10..105),
+                    schedule,
+                    chunk_size: Some(10),
+                    chunk_predicate: Some(ChunkPredicate::new(|bounds| bounds.start != 30)),
+                };
+                let count = AtomicUsize::new(0);
+                let sum = AtomicUsize::new(0);
+                let kernel = |arg: KernelArgs<1>| {
+                    if let KernelArgs::Index1D(i) = arg {
+                        count.fetch_add(1, Ordering::Relaxed);
+                        sum.fetch_add(i, Ordering::Relaxed);
+                    }
+                };
+                parallel_for(execp.clone(), kernel).unwrap();
+                assert_eq!(count.into_inner(), 95 - 10);
+                assert_eq!(
+                    sum.into_inner(),
+                    (10..105).sum::<usize>() - (30..40).sum::<usize>()
+                );
+
+                // only honoured by for statements over 1D ranges
+                assert!(parallel_reduce(execp.clone(), ReduceOp::Sum, |_| 1).is_err());
+                let execp = ExecutionPolicy {
+                    range: RangePolicy::TeamPolicy {
+                        league_size: 2,
+                        team_size: 2,
+                        vector_size: 1,
+                        scratch_size: [0, 0],
+                    },
+                    ..execp
+                };
+                assert!(parallel_for(execp, |_| {}).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn colored() {
+        use crate::view::{parameters::Layout, ViewOwned};
+
+        // 1D mesh of 8 elements & 9 nodes; element e touches nodes e & e+1
+        let colors: ViewOwned<'_, 1, usize> =
+            ViewOwned::new_from_data((0..8).map(|e| e % 2).collect(), Layout::Right, [8]);
+        let mut colorp = ColoredPolicy::new(&colors);
+        colorp.space = ExecutionSpace::DeviceCPU;
+        for (schedule, chunk_size) in [(Schedule::Static, None), (Schedule::Dynamic, Some(1))] {
+            colorp.schedule = schedule;
+            colorp.chunk_size = chunk_size;
+            let nodes: Vec<AtomicUsize> = (0..9).map(|_| AtomicUsize::new(0)).collect();
+            let order = AtomicUsize::new(0);
+
+            let kernel = |arg: KernelArgs<1>| {
+                if let KernelArgs::Index1D(e) = arg {
+                    // even elements must all be executed before odd ones
+                    let rank = order.fetch_add(1, Ordering::Relaxed);
+                    assert_eq!(rank < 4, e % 2 == 0);
+                    for n in [e, e + 1] {
+                        let val = nodes[n].load(Ordering::Relaxed);
+                        nodes[n].store(val + 1, Ordering::Relaxed);
+                    }
+                }
+            };
+            parallel_for_colored(&colorp, kernel).unwrap();
+
+            let counts: Vec<usize> = nodes.into_iter().map(AtomicUsize::into_inner).collect();
+            assert_eq!(counts, vec![1, 2, 2, 2, 2, 2, 2, 2, 1]);
+        }
+    }
+
+    #[test]
+    fn skip_chunks_team() {
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::Serial,
+            range: RangePolicy::TeamPolicy {
+                league_size: 4,
+                team_size: 2,
+                vector_size: 1,
+                scratch_size: [0, 0],
+            },
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let res = parallel_for_skip_chunks(execp, 10, |_| true, |_| {});
+        assert!(matches!(
+            res,
+            Err(StatementError::UnsupportedPolicy(PolicyKind::TeamPolicy))
+        ));
+    }
+}
@@ -9,13 +9,27 @@
 //!   subparameter of execution policies.
 //!
 
-use std::ops::Range;
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    ops::{Add, Mul, Range},
+    sync::Arc,
+};
+
+use super::{aliasing::Captures, instance::CpuInstance, scratch::SCRATCH_LEVELS, StatementError};
+use crate::{
+    functor::{KernelArgs, TeamHandle},
+    view::{
+        parameters::{DataTraits, IntegerTraits, Layout, ReductionIdentity, Scalar},
+        ViewBase,
+    },
+};
 
 /// Execution Space enum.
 ///
 /// Used to specify the target device of execution for the dispatch.
 /// Defaults to [ExecutionSpace::Serial].
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum ExecutionSpace {
     #[default]
     /// Default value. Execute the kernel sequentially.
@@ -25,6 +39,9 @@ pub enum ExecutionSpace {
     DeviceCPU,
     /// Target the GPU. UNIMPLEMENTED.
     DeviceGPU,
+    /// Target the CPU, using the threads of an instance. Execute the kernel like
+    /// [ExecutionSpace::DeviceCPU]. See the [instance][super::instance] module.
+    DeviceCPUInstance(CpuInstance),
 }
 
 #[derive(Debug, Clone)]
@@ -35,9 +52,25 @@ pub enum RangePolicy<const N: usize> {
     // Outer range
     /// 1D iteration range.
     RangePolicy(Range<usize>),
-    /// N-dimensional iteration range.
+    /// N-dimensional iteration range. Parallel CPU backends split it into tiles of
+    /// default size, iterated using [Iterate::Right].
     MDRangePolicy([Range<usize>; N]),
-    /// Team-based iteration policy.
+    /// N-dimensional iteration range, split into tiles. Tiles are distributed over
+    /// computational ressources; indices of a tile are iterated in the given order. Both
+    /// orders are independent of the layout of accessed views, like Kokkos'
+    /// `Rank<N, OuterIterate, InnerIterate>`.
+    TiledMDRangePolicy {
+        /// Iterated ranges.
+        ranges: [Range<usize>; N],
+        /// Tile size of each dimension. Sizes of 0 are treated as 1.
+        tile: [usize; N],
+        /// Iteration order of tiles.
+        outer: Iterate,
+        /// Iteration order of the indices of each tile.
+        inner: Iterate,
+    },
+    /// Team-based iteration policy. The kernel is executed once per member of each team,
+    /// and receives a [TeamHandle] identifying the member.
     TeamPolicy {
         /// Number of team.
         league_size: usize,
@@ -45,46 +78,820 @@ pub enum RangePolicy<const N: usize> {
         team_size: usize,
         /// Number of vector
         vector_size: usize,
+        /// Scratch memory requested per team at each level, in bytes, see
+        /// [TeamHandle::team_scratch].
+        scratch_size: [usize; SCRATCH_LEVELS],
     },
 
     // Specifics
-    /// Policy used to ensure each team execute the body once and only once.
-    PerTeam,
-    /// Policy used to ensure each thread execute the body once and only once.
-    PerThread,
+    /// Policy used to ensure each team execute the body once and only once. Used
+    /// inside a team-based kernel, the body is executed by the first member of the team.
+    /// See also [single][super::team::single].
+    PerTeam(TeamHandle),
+    /// Policy used to ensure each thread execute the body once and only once. See also
+    /// [single][super::team::single].
+    PerThread(TeamHandle),
 
     // Medium range
-    /// Medium-level depth. Can host further nests using vectors.
-    TeamThreadRange,
+    /// Medium-level depth. Can host further nests using vectors. Used inside a team-based
+    /// kernel, the range is split between the members of the team, see
+    /// [TeamHandle::thread_range].
+    TeamThreadRange(TeamHandle, Range<usize>),
     /// Medium-level depth. Can host further nests using vectors.
     TeamThreadMDRange,
 
-    /// Medium-level depth. Cannot host further nests.
-    TeamVectorRange,
+    /// Medium-level depth. Cannot host further nests. Used inside a team-based kernel,
+    /// the range is split between the members of the team like a
+    /// [TeamThreadRange][RangePolicy::TeamThreadRange], and each member executes its part
+    /// by vector chunks, see [TeamHandle::vector_chunks].
+    TeamVectorRange(TeamHandle, Range<usize>),
     /// Medium-level depth. Cannot host further nests.
     TeamVectorMDRange,
 
     // Inner Range
+    /// Inner-level depth. Cannot host further nests. Used inside a team-based kernel, the
+    /// calling member executes the whole range by vector chunks, see
+    /// [TeamHandle::vector_chunks].
+    ThreadVectorRange(TeamHandle, Range<usize>),
     /// Inner-level depth. Cannot host further nests.
+    ThreadVectorMDRange,
+}
+
+impl<const N: usize> RangePolicy<N> {
+    /// Build a [RangePolicy::MDRangePolicy] covering the given extents, e.g. the
+    /// dimensions of a view.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::{
+    ///     routines::parameters::RangePolicy,
+    ///     view::{parameters::Layout, ViewOwned},
+    /// };
+    ///
+    /// let view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [8, 4]);
+    /// let rangep = RangePolicy::from_dims(view.dims());
+    ///
+    /// assert!(matches!(rangep, RangePolicy::MDRangePolicy([r0, r1]) if r0 == (0..8) && r1 == (0..4)));
+    /// ```
+    pub fn from_dims(dims: [usize; N]) -> Self {
+        RangePolicy::MDRangePolicy(dims.map(|d| 0..d))
+    }
+
+    /// Build a [RangePolicy::MDRangePolicy] covering the given extents minus a halo of
+    /// width `halo[i]` on both sides of dimension `i`.
+    ///
+    /// If the halo is larger than the extent, the resulting range is empty.
+    pub fn from_dims_with_halo(dims: [usize; N], halo: [usize; N]) -> Self {
+        let mut ranges = dims.map(|d| 0..d);
+        ranges.iter_mut().zip(halo.iter()).for_each(|(r, h)| {
+            *r = *h..r.end.saturating_sub(*h).max(*h);
+        });
+        RangePolicy::MDRangePolicy(ranges)
+    }
+
+    /// Returns the kind of the policy, i.e. the variant without its parameters.
+    pub fn kind(&self) -> PolicyKind {
+        match self {
+            RangePolicy::RangePolicy(_) => PolicyKind::RangePolicy,
+            RangePolicy::MDRangePolicy(_) => PolicyKind::MDRangePolicy,
+            RangePolicy::TiledMDRangePolicy { .. } => PolicyKind::TiledMDRangePolicy,
+            RangePolicy::TeamPolicy { .. } => PolicyKind::TeamPolicy,
+            RangePolicy::PerTeam(_) => PolicyKind::PerTeam,
+            RangePolicy::PerThread(_) => PolicyKind::PerThread,
+            RangePolicy::TeamThreadRange(..) => PolicyKind::TeamThreadRange,
+            RangePolicy::TeamThreadMDRange => PolicyKind::TeamThreadMDRange,
+            RangePolicy::TeamVectorRange(..) => PolicyKind::TeamVectorRange,
+            RangePolicy::TeamVectorMDRange => PolicyKind::TeamVectorMDRange,
+            RangePolicy::ThreadVectorRange(..) => PolicyKind::ThreadVectorRange,
+            RangePolicy::ThreadVectorMDRange => PolicyKind::ThreadVectorMDRange,
+        }
+    }
+}
+
+/// Statement kind enum, used when querying implementation status, see
+/// [supports][super::supports].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatementKind {
+    /// Parallel For statements.
+    For,
+    /// Parallel Reduce statements.
+    Reduce,
+    /// Parallel Scan statements.
+    Scan,
+}
+
+/// Policy kind enum.
+///
+/// Fieldless counterpart of [RangePolicy], used to refer to a type of policy independently
+/// of its parameters, e.g. when querying implementation status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PolicyKind {
+    /// See [RangePolicy::RangePolicy].
+    RangePolicy,
+    /// See [RangePolicy::MDRangePolicy].
+    MDRangePolicy,
+    /// See [RangePolicy::TiledMDRangePolicy].
+    TiledMDRangePolicy,
+    /// See [RangePolicy::TeamPolicy].
+    TeamPolicy,
+    /// See [RangePolicy::PerTeam].
+    PerTeam,
+    /// See [RangePolicy::PerThread].
+    PerThread,
+    /// See [RangePolicy::TeamThreadRange].
+    TeamThreadRange,
+    /// See [RangePolicy::TeamThreadMDRange].
+    TeamThreadMDRange,
+    /// See [RangePolicy::TeamVectorRange].
+    TeamVectorRange,
+    /// See [RangePolicy::TeamVectorMDRange].
+    TeamVectorMDRange,
+    /// See [RangePolicy::ThreadVectorRange].
     ThreadVectorRange,
-    /// Inner-level depth. Cannot host further nests.
+    /// See [RangePolicy::ThreadVectorMDRange].
     ThreadVectorMDRange,
 }
 
-/// Scheduling enum. CURRENTLY IGNORED.
+impl PolicyKind {
+    /// All existing policy kinds.
+    pub const ALL: [PolicyKind; 12] = [
+        PolicyKind::RangePolicy,
+        PolicyKind::MDRangePolicy,
+        PolicyKind::TiledMDRangePolicy,
+        PolicyKind::TeamPolicy,
+        PolicyKind::PerTeam,
+        PolicyKind::PerThread,
+        PolicyKind::TeamThreadRange,
+        PolicyKind::TeamThreadMDRange,
+        PolicyKind::TeamVectorRange,
+        PolicyKind::TeamVectorMDRange,
+        PolicyKind::ThreadVectorRange,
+        PolicyKind::ThreadVectorMDRange,
+    ];
+
+    /// Returns the name of the policy kind.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PolicyKind::RangePolicy => "RangePolicy",
+            PolicyKind::MDRangePolicy => "MDRangePolicy",
+            PolicyKind::TiledMDRangePolicy => "TiledMDRangePolicy",
+            PolicyKind::TeamPolicy => "TeamPolicy",
+            PolicyKind::PerTeam => "PerTeam",
+            PolicyKind::PerThread => "PerThread",
+            PolicyKind::TeamThreadRange => "TeamThreadRange",
+            PolicyKind::TeamThreadMDRange => "TeamThreadMDRange",
+            PolicyKind::TeamVectorRange => "TeamVectorRange",
+            PolicyKind::TeamVectorMDRange => "TeamVectorMDRange",
+            PolicyKind::ThreadVectorRange => "ThreadVectorRange",
+            PolicyKind::ThreadVectorMDRange => "ThreadVectorMDRange",
+        }
+    }
+}
+
+impl Display for PolicyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Iteration order enum.
+///
+/// Used to specify the order in which N-dimensional indices are iterated. Matching the
+/// layout of the accessed views preserves cache locality, see the [From] implementation
+/// for [Layout]. Defaults to [Iterate::Right].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Iterate {
+    #[default]
+    /// The last index varies the fastest.
+    Right,
+    /// The first index varies the fastest.
+    Left,
+}
+
+impl<const N: usize> From<&Layout<N>> for Iterate {
+    /// Returns the iteration order matching the memory layout, i.e. the one making the
+    /// smallest stride vary the fastest.
+    fn from(layout: &Layout<N>) -> Self {
+        match layout {
+            Layout::Right => Iterate::Right,
+            Layout::Left => Iterate::Left,
+            Layout::Stride { s } => {
+                if s.first() < s.last() {
+                    Iterate::Left
+                } else {
+                    Iterate::Right
+                }
+            }
+        }
+    }
+}
+
+/// Scheduling enum.
+///
+/// Used to set the workload scheduling policy. Defaults to [Schedule::Static]. It is
+/// currently used by `for` statements dispatched using the `threads` backend; the `rayon`
+/// backend always relies on work stealing, and other dispatches ignore it.
+///
+/// The chunk size of the execution policy refines the schedule:
 ///
-/// Used to set the workload scheduling policy. Defaults to [Schedule::Static].
+/// - `threads` backend: chunks are distributed in a round-robin fashion using a static
+///   schedule, and claimed one at a time using a dynamic one,
+/// - `rayon` backend: chunks are the minimum size of tasks, like `with_min_len`.
 #[derive(Debug, Default, Clone)]
 pub enum Schedule {
     #[default]
     /// Default value. Workload is divided once and split equally between
     /// computational ressources.
     Static,
-    /// Dynamic scheduling. Workload is divided at start, split between computational
-    /// ressources and work stealing is enabled.
+    /// Dynamic scheduling. Workload is divided in small chunks that idle computational
+    /// ressources claim until all of it is processed. This balances irregular workloads
+    /// at the cost of some synchronization.
     Dynamic,
 }
 
+/// Combine strategy of floating-point reductions.
+///
+/// Strategies are used by [ViewBase::sum_with] host reductions, and by `parallel_reduce`
+/// statements through the [SumWith] reducer.
+///
+/// Strategies trade accuracy for performance. Error bounds below are given for the sum
+/// of `n` values `x_i`, `eps` being the machine epsilon of the type; they are worst-case
+/// bounds on the absolute error, the typical error being much lower.
+///
+/// Defaults to [ReductionStrategy::Chunked] using chunks of 128 elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReductionStrategy {
+    /// Accumulate values one after the other.
+    ///
+    /// Error bound: `(n - 1) * eps * sum(|x_i|)`.
+    Sequential,
+    /// Accumulate values sequentially inside chunks of `chunk` elements, then combine
+    /// the chunk results pairwise.
+    ///
+    /// Error bound: `(chunk + log2(n / chunk)) * eps * sum(|x_i|)`.
+    Chunked {
+        /// Number of elements accumulated sequentially.
+        chunk: usize,
+    },
+    /// Combine all values pairwise; equivalent to chunks of a single element.
+    ///
+    /// Error bound: `log2(n) * eps * sum(|x_i|)`.
+    Pairwise,
+    /// Accumulate values sequentially using Kahan compensated summation. Slowest
+    /// strategy, but the error does not grow with `n`.
+    ///
+    /// Error bound: `(2 * eps + O(n * eps^2)) * sum(|x_i|)`.
+    Kahan,
+}
+
+impl Default for ReductionStrategy {
+    fn default() -> Self {
+        ReductionStrategy::Chunked { chunk: 128 }
+    }
+}
+
+impl ReductionStrategy {
+    /// Sum `values` using the strategy.
+    pub fn sum<T: Scalar>(&self, values: impl Iterator<Item = T>) -> T {
+        match *self {
+            ReductionStrategy::Sequential => values.fold(T::zero(), |acc, val| acc + val),
+            ReductionStrategy::Chunked { chunk } => pairwise_sum(values, chunk.max(1)),
+            ReductionStrategy::Pairwise => pairwise_sum(values, 1),
+            ReductionStrategy::Kahan => {
+                let (sum, _) = values.fold((T::zero(), T::zero()), |(sum, comp), val| {
+                    let y = val - comp;
+                    let t = sum + y;
+                    (t, (t - sum) - y)
+                });
+                sum
+            }
+        }
+    }
+}
+
+/// Sum values sequentially by chunks, then combine chunk results pairwise.
+///
+/// Combination is done on the fly using a stack of partial sums: two partial sums are
+/// merged as soon as they cover the same number of chunks.
+fn pairwise_sum<T: Scalar>(mut values: impl Iterator<Item = T>, chunk: usize) -> T {
+    // (number of chunks covered, partial sum)
+    let mut stack: Vec<(usize, T)> = Vec::new();
+    loop {
+        let mut count = 0;
+        let partial = values
+            .by_ref()
+            .take(chunk)
+            .inspect(|_| count += 1)
+            .fold(T::zero(), |acc, val| acc + val);
+        if count == 0 {
+            break;
+        }
+        let mut top = (1, partial);
+        while let Some(&(n, prev)) = stack.last() {
+            if n != top.0 {
+                break;
+            }
+            stack.pop();
+            top = (2 * n, prev + top.1);
+        }
+        stack.push(top);
+    }
+    // remaining sums are merged from the smallest to the largest
+    stack
+        .into_iter()
+        .rev()
+        .fold(T::zero(), |acc, (_, val)| val + acc)
+}
+
+/// Reduction operator of a `parallel_reduce` statement.
+#[derive(Debug, Clone, Copy)]
+pub enum ReduceOp<T> {
+    /// Sum of the values.
+    Sum,
+    /// Minimum of the values.
+    Min,
+    /// Maximum of the values.
+    Max,
+    /// User-defined operator. It must be associative, since the combine order depends
+    /// on the dispatch.
+    Custom(fn(T, T) -> T),
+}
+
+impl<T> ReduceOp<T>
+where
+    T: DataTraits + Add<Output = T> + PartialOrd,
+{
+    /// Combine two values using the operator.
+    pub fn combine(&self, lhs: T, rhs: T) -> T {
+        match self {
+            ReduceOp::Sum => lhs + rhs,
+            ReduceOp::Min => {
+                if rhs < lhs {
+                    rhs
+                } else {
+                    lhs
+                }
+            }
+            ReduceOp::Max => {
+                if rhs > lhs {
+                    rhs
+                } else {
+                    lhs
+                }
+            }
+            ReduceOp::Custom(f) => f(lhs, rhs),
+        }
+    }
+}
+
+/// Combine logic of a `parallel_reduce` statement.
+///
+/// A reducer defines the identity of the reduction ([Reducer::empty]), its join
+/// ([Reducer::combine]) & an optional final step ([Reducer::finalize]). The join is used
+/// to combine values of a thread as well as partial results of different threads, on all
+/// backends; it must be associative, since the combine order depends on the dispatch.
+///
+/// Besides [ReduceOp], the standard Kokkos reducers are provided by [Prod], [MinLoc] &
+/// [MaxLoc]. The trait is also implemented by arrays & pairs of reducers. These
+/// reduce several values in a single traversal of the index space: the kernel returns
+/// an array (resp. a pair) of values, each one being combined using the corresponding
+/// reducer.
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::routines::parameters::{ReduceOp, Reducer};
+///
+/// let minmax = [ReduceOp::Min, ReduceOp::Max];
+/// assert_eq!(minmax.combine([1.0, 1.0], [0.0, 0.0]), [0.0, 1.0]);
+///
+/// let count_sum = (ReduceOp::Sum, ReduceOp::Sum);
+/// assert_eq!(count_sum.combine((1, 2.5), (1, 0.5)), (2, 3.0));
+/// ```
+pub trait Reducer {
+    /// Type of the values produced by the kernel & of the result.
+    type Value: Copy;
+
+    /// Combine two values.
+    fn combine(&self, lhs: Self::Value, rhs: Self::Value) -> Self::Value;
+
+    /// Returns the result of a reduction over an empty range.
+    fn empty(&self) -> Self::Value;
+
+    /// Combine two partial results, `None` meaning that no value was reduced yet.
+    fn combine_partial(
+        &self,
+        lhs: Option<Self::Value>,
+        rhs: Option<Self::Value>,
+    ) -> Option<Self::Value> {
+        match (lhs, rhs) {
+            (Some(l), Some(r)) => Some(self.combine(l, r)),
+            (l, None) => l,
+            (None, r) => r,
+        }
+    }
+
+    /// Reduce a sequence of values, returning `None` if it is empty. Dispatch routines
+    /// use it to reduce the values of a chunk of indices, then to combine the partial
+    /// results of chunks, in order.
+    ///
+    /// The default implementation combines values one after the other.
+    fn reduce(&self, values: impl Iterator<Item = Self::Value>) -> Option<Self::Value> {
+        values.fold(None, |acc, val| self.combine_partial(acc, Some(val)))
+    }
+
+    /// Final step applied once to the result of the reduction, including the result of
+    /// empty reductions.
+    ///
+    /// The default implementation returns the value unchanged.
+    fn finalize(&self, value: Self::Value) -> Self::Value {
+        value
+    }
+}
+
+impl<T> Reducer for ReduceOp<T>
+where
+    T: DataTraits + Add<Output = T> + PartialOrd + ReductionIdentity,
+{
+    type Value = T;
+
+    fn combine(&self, lhs: T, rhs: T) -> T {
+        ReduceOp::combine(self, lhs, rhs)
+    }
+
+    /// Returns the identity of the operator, i.e. zero for sums, the largest value of
+    /// the type for minimums & the lowest one for maximums. [ReduceOp::Custom] operators
+    /// have no known identity, `T::default()` is returned.
+    fn empty(&self) -> T {
+        match self {
+            ReduceOp::Sum | ReduceOp::Custom(_) => T::default(),
+            ReduceOp::Min => T::min_identity(),
+            ReduceOp::Max => T::max_identity(),
+        }
+    }
+}
+
+/// Sum reducer using a given [ReductionStrategy], to control the accuracy of
+/// floating-point `parallel_reduce` statements.
+///
+/// The strategy is used to sum the values of each chunk of indices, then to combine the
+/// partial results of chunks. Since chunks depend on the dispatch, so does the error.
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::{
+///     functor::KernelArgs,
+///     routines::{
+///         parallel_reduce,
+///         parameters::{
+///             ExecutionPolicy, ExecutionSpace, RangePolicy, ReductionStrategy, Schedule, SumWith,
+///         },
+///     },
+/// };
+///
+/// let execp = ExecutionPolicy {
+///     space: ExecutionSpace::Serial,
+///     range: RangePolicy::RangePolicy(0..1001),
+///     schedule: Schedule::default(),
+///     chunk_size: None,
+///     chunk_predicate: None,
+/// };
+/// let kern = |arg: KernelArgs<1>| match arg {
+///     KernelArgs::Index1D(0) => 1.0,
+///     _ => 1.0e-16,
+/// };
+///
+/// // small values are absorbed one by one when accumulating sequentially
+/// let sequential = SumWith::new(ReductionStrategy::Sequential);
+/// assert_eq!(parallel_reduce(execp.clone(), sequential, kern).unwrap(), 1.0);
+/// let kahan = SumWith::new(ReductionStrategy::Kahan);
+/// assert!(parallel_reduce(execp, kahan, kern).unwrap() > 1.0);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SumWith<T> {
+    /// Combine strategy of the values.
+    pub strategy: ReductionStrategy,
+    value: PhantomData<T>,
+}
+
+impl<T> SumWith<T> {
+    /// Build a sum reducer using `strategy`.
+    pub fn new(strategy: ReductionStrategy) -> Self {
+        Self {
+            strategy,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<T: Scalar> Reducer for SumWith<T> {
+    type Value = T;
+
+    fn combine(&self, lhs: T, rhs: T) -> T {
+        lhs + rhs
+    }
+
+    fn empty(&self) -> T {
+        T::zero()
+    }
+
+    fn reduce(&self, values: impl Iterator<Item = T>) -> Option<T> {
+        let mut values = values.peekable();
+        values.peek()?;
+        Some(self.strategy.sum(values))
+    }
+}
+
+/// Integer sum reducer keeping track of overflows.
+///
+/// Values are pairs of a wrapping sum & of the number of times it wrapped around the
+/// bounds of the type, positive when wrapping upwards; kernels produce `(value, 0)`. The
+/// count is zero if and only if the exact sum fits in the type, whatever the order in
+/// which values are combined, see [OverflowSum::checked] & [OverflowSum::saturating].
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::routines::parameters::{OverflowSum, Reducer};
+///
+/// let sum = OverflowSum::new();
+/// let result = sum.reduce([(-100i8, 0), (-100, 0), (50, 0)].into_iter()).unwrap();
+/// assert_eq!(OverflowSum::checked(result), None);
+/// assert_eq!(OverflowSum::saturating(result), i8::MIN);
+///
+/// let result = sum.reduce([(100i8, 0), (100, 0), (-100, 0)].into_iter()).unwrap();
+/// assert_eq!(OverflowSum::checked(result), Some(100));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OverflowSum<T> {
+    value: PhantomData<T>,
+}
+
+impl<T: IntegerTraits> OverflowSum<T> {
+    /// Build an overflow-tracking sum reducer.
+    pub fn new() -> Self {
+        Self { value: PhantomData }
+    }
+
+    /// Returns the exact sum, or `None` if it does not fit in the type.
+    pub fn checked((sum, wraps): (T, i64)) -> Option<T> {
+        (wraps == 0).then_some(sum)
+    }
+
+    /// Returns the exact sum, clamped to the bounds of the type.
+    pub fn saturating((sum, wraps): (T, i64)) -> T {
+        match wraps.cmp(&0) {
+            std::cmp::Ordering::Equal => sum,
+            std::cmp::Ordering::Greater => T::MAX,
+            std::cmp::Ordering::Less => T::MIN,
+        }
+    }
+}
+
+impl<T: IntegerTraits> Reducer for OverflowSum<T> {
+    type Value = (T, i64);
+
+    fn combine(&self, (lhs, lhs_wraps): (T, i64), (rhs, rhs_wraps): (T, i64)) -> (T, i64) {
+        let (sum, wrapped) = lhs.overflowing_add(rhs);
+        // an overflow wraps in the direction of the sign of the right-hand side
+        let wraps = match (wrapped, rhs > T::default()) {
+            (false, _) => 0,
+            (true, true) => 1,
+            (true, false) => -1,
+        };
+        (sum, lhs_wraps + rhs_wraps + wraps)
+    }
+
+    fn empty(&self) -> (T, i64) {
+        (T::default(), 0)
+    }
+}
+
+/// Product reducer.
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::routines::parameters::{Prod, Reducer};
+///
+/// let prod = Prod::new();
+/// assert_eq!(prod.reduce([2, 3, 4].into_iter()), Some(24));
+/// assert_eq!(prod.empty(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Prod<T> {
+    value: PhantomData<T>,
+}
+
+impl<T> Prod<T> {
+    /// Build a product reducer.
+    pub fn new() -> Self {
+        Self { value: PhantomData }
+    }
+}
+
+impl<T> Reducer for Prod<T>
+where
+    T: DataTraits + Mul<Output = T> + From<u8>,
+{
+    type Value = T;
+
+    fn combine(&self, lhs: T, rhs: T) -> T {
+        lhs * rhs
+    }
+
+    fn empty(&self) -> T {
+        T::from(1)
+    }
+}
+
+/// Value & location pair, produced by the kernels of [MinLoc] & [MaxLoc] reductions.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ValLoc<T, I = usize> {
+    /// Reduced value.
+    pub val: T,
+    /// Location of the value, usually its index.
+    pub loc: I,
+}
+
+/// Minimum reducer tracking the location of the minimum.
+///
+/// When several locations hold the minimum, the lowest one is kept, so that the result
+/// does not depend on the dispatch. Empty reductions return the identity of the
+/// minimum, located at `I::default()`.
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::{
+///     functor::KernelArgs,
+///     routines::{
+///         parallel_reduce,
+///         parameters::{ExecutionPolicy, ExecutionSpace, MinLoc, RangePolicy, Schedule, ValLoc},
+///     },
+/// };
+///
+/// let values = [3.0, 1.0, 2.0, 1.0];
+/// let execp = ExecutionPolicy {
+///     space: ExecutionSpace::DeviceCPU,
+///     range: RangePolicy::RangePolicy(0..values.len()),
+///     schedule: Schedule::default(),
+///     chunk_size: None,
+///     chunk_predicate: None,
+/// };
+/// let kern = |arg: KernelArgs<1>| match arg {
+///     KernelArgs::Index1D(i) => ValLoc { val: values[i], loc: i },
+///     _ => unimplemented!(),
+/// };
+///
+/// let min = parallel_reduce(execp, MinLoc::new(), kern).unwrap();
+/// assert_eq!(min, ValLoc { val: 1.0, loc: 1 });
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinLoc<T, I = usize> {
+    value: PhantomData<(T, I)>,
+}
+
+/// Maximum reducer tracking the location of the maximum.
+///
+/// When several locations hold the maximum, the lowest one is kept, so that the result
+/// does not depend on the dispatch. Empty reductions return the identity of the
+/// maximum, located at `I::default()`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxLoc<T, I = usize> {
+    value: PhantomData<(T, I)>,
+}
+
+impl<T, I> MinLoc<T, I> {
+    /// Build a location-tracking minimum reducer.
+    pub fn new() -> Self {
+        Self { value: PhantomData }
+    }
+}
+
+impl<T, I> MaxLoc<T, I> {
+    /// Build a location-tracking maximum reducer.
+    pub fn new() -> Self {
+        Self { value: PhantomData }
+    }
+}
+
+impl<T, I> Reducer for MinLoc<T, I>
+where
+    T: ReductionIdentity + PartialOrd,
+    I: Copy + Default + PartialOrd,
+{
+    type Value = ValLoc<T, I>;
+
+    fn combine(&self, lhs: Self::Value, rhs: Self::Value) -> Self::Value {
+        if rhs.val < lhs.val || (rhs.val == lhs.val && rhs.loc < lhs.loc) {
+            rhs
+        } else {
+            lhs
+        }
+    }
+
+    fn empty(&self) -> Self::Value {
+        ValLoc {
+            val: T::min_identity(),
+            loc: I::default(),
+        }
+    }
+}
+
+impl<T, I> Reducer for MaxLoc<T, I>
+where
+    T: ReductionIdentity + PartialOrd,
+    I: Copy + Default + PartialOrd,
+{
+    type Value = ValLoc<T, I>;
+
+    fn combine(&self, lhs: Self::Value, rhs: Self::Value) -> Self::Value {
+        if rhs.val > lhs.val || (rhs.val == lhs.val && rhs.loc < lhs.loc) {
+            rhs
+        } else {
+            lhs
+        }
+    }
+
+    fn empty(&self) -> Self::Value {
+        ValLoc {
+            val: T::max_identity(),
+            loc: I::default(),
+        }
+    }
+}
+
+impl<R: Reducer, const K: usize> Reducer for [R; K] {
+    type Value = [R::Value; K];
+
+    fn combine(&self, lhs: Self::Value, rhs: Self::Value) -> Self::Value {
+        std::array::from_fn(|i| self[i].combine(lhs[i], rhs[i]))
+    }
+
+    fn empty(&self) -> Self::Value {
+        std::array::from_fn(|i| self[i].empty())
+    }
+
+    fn finalize(&self, value: Self::Value) -> Self::Value {
+        std::array::from_fn(|i| self[i].finalize(value[i]))
+    }
+}
+
+impl<A: Reducer, B: Reducer> Reducer for (A, B) {
+    type Value = (A::Value, B::Value);
+
+    fn combine(&self, lhs: Self::Value, rhs: Self::Value) -> Self::Value {
+        (self.0.combine(lhs.0, rhs.0), self.1.combine(lhs.1, rhs.1))
+    }
+
+    fn empty(&self) -> Self::Value {
+        (self.0.empty(), self.1.empty())
+    }
+
+    fn finalize(&self, value: Self::Value) -> Self::Value {
+        (self.0.finalize(value.0), self.1.finalize(value.1))
+    }
+}
+
+/// Mode of a `parallel_scan` statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanMode {
+    /// The result at index `i` includes the value of index `i`.
+    Inclusive,
+    /// The result at index `i` only includes values of indices lower than `i`.
+    Exclusive,
+}
+
+/// Chunk-level predicate of an execution policy.
+///
+/// The predicate receives the index bounds of a chunk & returns `false` if the chunk is
+/// inactive, e.g. if the bounding box of its elements does not intersect a query. Inactive
+/// chunks are skipped by the dispatch, the kernel is left unchanged. See
+/// [ExecutionPolicy::chunk_predicate].
+#[derive(Clone)]
+pub struct ChunkPredicate(Arc<dyn Fn(Range<usize>) -> bool + Send + Sync>);
+
+impl ChunkPredicate {
+    /// Constructor.
+    pub fn new(predicate: impl Fn(Range<usize>) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(predicate))
+    }
+
+    /// Returns `true` if the chunk of bounds `bounds` should be executed.
+    pub fn test(&self, bounds: Range<usize>) -> bool {
+        (self.0)(bounds)
+    }
+}
+
+impl Debug for ChunkPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ChunkPredicate")
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Execution Policy enum. See Kokkos documentation for explanation on their model.
 ///
@@ -104,6 +911,8 @@ pub enum Schedule {
 ///         space: ExecutionSpace::DeviceCPU, // will try to parallelize code on CPU
 ///         range: RangePolicy::RangePolicy(0..length), // equivalent to "for i in 0..length"
 ///         schedule: Schedule::Static, // static division of workload
+///         chunk_size: None, // let the backend pick the chunk size
+///         chunk_predicate: None,
 ///     };
 /// ```
 pub struct ExecutionPolicy<const N: usize> {
@@ -111,6 +920,669 @@ pub struct ExecutionPolicy<const N: usize> {
     pub space: ExecutionSpace,
     /// Iteration pattern used to handle the workload.
     pub range: RangePolicy<N>,
-    /// Scheduling policy for the dispatch. CURRENTLY IGNORED.
+    /// Scheduling policy for the dispatch.
+    pub schedule: Schedule,
+    /// Number of indices (tiles for multi-dimensional policies) processed by a thread at
+    /// a time. Using `None`, the backend picks a size suited to the schedule. See
+    /// [Schedule].
+    pub chunk_size: Option<usize>,
+    /// Optional predicate evaluated on the bounds of each chunk before its execution;
+    /// chunks for which it returns `false` are skipped. Chunks are made of `chunk_size`
+    /// indices, [COOPERATIVE_CHUNK][crate::runtime::COOPERATIVE_CHUNK] if unspecified.
+    ///
+    /// Only honoured by `for` statements over a [RangePolicy::RangePolicy]; other
+    /// statements & policies return an error when a predicate is set.
+    pub chunk_predicate: Option<ChunkPredicate>,
+}
+
+impl<const N: usize> ExecutionPolicy<N> {
+    /// Build an execution policy iterating over the whole index space of a view, using
+    /// default execution space and scheduling. See [RangePolicy::from_dims].
+    ///
+    /// Other parameters can be adjusted using struct update syntax:
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::{
+    ///     routines::parameters::{ExecutionPolicy, ExecutionSpace},
+    ///     view::{parameters::Layout, ViewOwned},
+    /// };
+    ///
+    /// let view: ViewOwned<'_, 3, f64> = ViewOwned::new(Layout::Right, [8, 8, 8]);
+    /// let execp = ExecutionPolicy {
+    ///     space: ExecutionSpace::DeviceCPU,
+    ///     ..ExecutionPolicy::for_view(&view)
+    /// };
+    /// ```
+    pub fn for_view<T: DataTraits>(view: &ViewBase<'_, N, T>) -> Self {
+        Self {
+            space: ExecutionSpace::default(),
+            range: RangePolicy::from_dims(view.dims()),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        }
+    }
+
+    /// Build an execution policy iterating over the index space of a view minus a halo,
+    /// using default execution space and scheduling. See
+    /// [RangePolicy::from_dims_with_halo].
+    pub fn for_view_with_halo<T: DataTraits>(view: &ViewBase<'_, N, T>, halo: [usize; N]) -> Self {
+        Self {
+            space: ExecutionSpace::default(),
+            range: RangePolicy::from_dims_with_halo(view.dims(), halo),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        }
+    }
+}
+
+/// Policy partitioning a 1D index range by color.
+///
+/// Indices sharing a color are executed in parallel, colors are executed one after the
+/// other. If no two indices of the same color update a shared entity (e.g. a mesh node
+/// shared by two elements), updates do not need atomics nor locks.
+///
+/// Colors are given as a view mapping each index to its color; colors are expected to
+/// be small, consecutive integers.
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::{
+///     routines::parameters::ColoredPolicy,
+///     view::{parameters::Layout, ViewOwned},
+/// };
+///
+/// // elements 0 & 2 (resp. 1 & 3) do not share nodes
+/// let colors: ViewOwned<'_, 1, usize> =
+///     ViewOwned::new_from_data(vec![0, 1, 0, 1], Layout::Right, [4]);
+/// let colorp = ColoredPolicy::new(&colors);
+///
+/// assert_eq!(colorp.n_colors(), 2);
+/// assert_eq!(colorp.indices(1), &[1, 3]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ColoredPolicy {
+    /// Execution space targetted by the dispatch of each color.
+    pub space: ExecutionSpace,
+    /// Scheduling policy for the dispatch of each color.
     pub schedule: Schedule,
+    /// Number of indices processed by a thread at a time in the dispatch of each color.
+    /// See [ExecutionPolicy::chunk_size].
+    pub chunk_size: Option<usize>,
+    /// Indices of each color, in increasing order.
+    indices: Vec<Vec<usize>>,
+}
+
+impl ColoredPolicy {
+    /// Build a colored policy from a view of colors, using default execution space and
+    /// scheduling. Adjust public fields to change these.
+    pub fn new(colors: &ViewBase<'_, 1, usize>) -> Self {
+        let mut indices: Vec<Vec<usize>> = Vec::new();
+        for idx in 0..colors.dims()[0] {
+            let color = colors.get([idx]);
+            if color >= indices.len() {
+                indices.resize_with(color + 1, Vec::new);
+            }
+            indices[color].push(idx);
+        }
+        Self {
+            space: ExecutionSpace::default(),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            indices,
+        }
+    }
+
+    /// Returns the number of colors, unused colors included.
+    pub fn n_colors(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns the indices of a given color.
+    pub fn indices(&self, color: usize) -> &[usize] {
+        &self.indices[color]
+    }
+}
+
+// Rank-checked policies
+
+/// Rank-checked iteration policy.
+///
+/// Implementors are typed counterparts of [RangePolicy] variants: each policy fixes the
+/// dimensionality `N` of its dispatch as well as the argument type received by kernels.
+/// Statements of the [typed][super::typed] module use this to reject mismatched kernels
+/// at compile time, e.g. a [Range1D] can only be used with kernels taking a `usize`.
+pub trait Policy<const N: usize> {
+    /// Argument received by kernels executed using the policy.
+    type Arg;
+
+    /// Convert into the equivalent [RangePolicy], used for dispatch.
+    fn into_range(self) -> RangePolicy<N>;
+
+    /// Extract the kernel argument from its dispatch representation.
+    ///
+    /// Dispatch of [Policy::into_range] only yields a single variant of [KernelArgs],
+    /// hence this never fails.
+    fn arg(args: KernelArgs<N>) -> Self::Arg;
+}
+
+/// Rank-checked equivalent of [RangePolicy::RangePolicy]. Kernels receive a `usize`.
+#[derive(Debug, Clone)]
+pub struct Range1D(pub Range<usize>);
+
+impl Policy<1> for Range1D {
+    type Arg = usize;
+
+    fn into_range(self) -> RangePolicy<1> {
+        RangePolicy::RangePolicy(self.0)
+    }
+
+    fn arg(args: KernelArgs<1>) -> usize {
+        match args {
+            KernelArgs::Index1D(i) => i,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Rank-checked equivalent of [RangePolicy::MDRangePolicy]. Kernels receive a
+/// `[usize; N]`.
+#[derive(Debug, Clone)]
+pub struct MDRange<const N: usize>(pub [Range<usize>; N]);
+
+impl<const N: usize> MDRange<N> {
+    /// Build a policy covering the given extents, e.g. the dimensions of a view.
+    pub fn from_dims(dims: [usize; N]) -> Self {
+        Self(dims.map(|d| 0..d))
+    }
+}
+
+impl<const N: usize> Policy<N> for MDRange<N> {
+    type Arg = [usize; N];
+
+    fn into_range(self) -> RangePolicy<N> {
+        RangePolicy::MDRangePolicy(self.0)
+    }
+
+    fn arg(args: KernelArgs<N>) -> [usize; N] {
+        match args {
+            KernelArgs::IndexND(idx) => idx,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Rank-checked equivalent of [RangePolicy::TiledMDRangePolicy]. Kernels receive a
+/// `[usize; N]`.
+#[derive(Debug, Clone)]
+pub struct TiledMDRange<const N: usize> {
+    /// Iterated ranges.
+    pub ranges: [Range<usize>; N],
+    /// Tile size of each dimension. Sizes of 0 are treated as 1.
+    pub tile: [usize; N],
+    /// Iteration order of tiles.
+    pub outer: Iterate,
+    /// Iteration order of the indices of each tile.
+    pub inner: Iterate,
+}
+
+impl<const N: usize> Policy<N> for TiledMDRange<N> {
+    type Arg = [usize; N];
+
+    fn into_range(self) -> RangePolicy<N> {
+        RangePolicy::TiledMDRangePolicy {
+            ranges: self.ranges,
+            tile: self.tile,
+            outer: self.outer,
+            inner: self.inner,
+        }
+    }
+
+    fn arg(args: KernelArgs<N>) -> [usize; N] {
+        match args {
+            KernelArgs::IndexND(idx) => idx,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Policy iterating over the interior points of a structured grid, i.e. its indices
+/// minus a halo of width `halo[i]` on both sides of dimension `i`. Kernels receive a
+/// [StencilPoint], giving access to neighbors within the halo without computing their
+/// index by hand.
+///
+/// Dispatched as a [RangePolicy::MDRangePolicy], see [RangePolicy::from_dims_with_halo].
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::{
+///     routines::{
+///         parameters::{ExecutionSpace, Stencil, TypedExecutionPolicy},
+///         typed::parallel_for,
+///     },
+///     view::{parameters::Layout, ViewOwned},
+/// };
+///
+/// let u: ViewOwned<'_, 2, f64> = ViewOwned::new_from_data(vec![1.0; 64], Layout::Right, [8, 8]);
+/// #[allow(unused_mut)]
+/// let mut lap: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [8, 8]);
+///
+/// let execp = TypedExecutionPolicy {
+///     space: ExecutionSpace::DeviceCPU,
+///     ..TypedExecutionPolicy::new(Stencil::for_view(&u, [1, 1]))
+/// };
+/// // 5-point laplacian, boundary points are not updated
+/// parallel_for(execp, |p| {
+///     let neighbors: f64 = (0..2).flat_map(|axis| p.neighbors(&u, axis)).sum();
+///     lap.set(p.index(), neighbors - 4.0 * u.get(p.index()));
+/// })
+/// .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Stencil<const N: usize> {
+    /// Dimensions of the grid, halo included.
+    pub dims: [usize; N],
+    /// Halo width of each dimension.
+    pub halo: [usize; N],
+}
+
+impl<const N: usize> Stencil<N> {
+    /// Build a policy over the interior points of a view.
+    pub fn for_view<T: DataTraits>(view: &ViewBase<'_, N, T>, halo: [usize; N]) -> Self {
+        Self {
+            dims: view.dims(),
+            halo,
+        }
+    }
+}
+
+impl<const N: usize> Policy<N> for Stencil<N> {
+    type Arg = StencilPoint<N>;
+
+    fn into_range(self) -> RangePolicy<N> {
+        RangePolicy::from_dims_with_halo(self.dims, self.halo)
+    }
+
+    fn arg(args: KernelArgs<N>) -> StencilPoint<N> {
+        match args {
+            KernelArgs::IndexND(idx) => StencilPoint(idx),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Grid point received by kernels executed using a [Stencil] policy.
+///
+/// Offsets are signed & relative to the point. Accessing a neighbor outside of the grid
+/// panics; neighbors within the halo of the policy always exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilPoint<const N: usize>(pub [usize; N]);
+
+impl<const N: usize> StencilPoint<N> {
+    /// Returns the index of the point.
+    pub fn index(&self) -> [usize; N] {
+        self.0
+    }
+
+    /// Returns the index of the point shifted by `offset`.
+    pub fn offset(&self, offset: [isize; N]) -> [usize; N] {
+        let mut index = self.0;
+        index.iter_mut().zip(offset.iter()).for_each(|(i, o)| {
+            *i = i
+                .checked_add_signed(*o)
+                .expect("stencil offset should stay inside the grid");
+        });
+        index
+    }
+
+    /// Returns the index of the point shifted by `delta` along dimension `axis`.
+    pub fn neighbor(&self, axis: usize, delta: isize) -> [usize; N] {
+        let mut offset = [0; N];
+        offset[axis] = delta;
+        self.offset(offset)
+    }
+
+    /// Returns the element of `view` at the point shifted by `offset`.
+    pub fn get<T: DataTraits>(&self, view: &ViewBase<'_, N, T>, offset: [isize; N]) -> T {
+        view.get(self.offset(offset))
+    }
+
+    /// Returns the elements of `view` at the direct neighbors of the point along
+    /// dimension `axis`, i.e. at `-1` & `+1`.
+    pub fn neighbors<T: DataTraits>(&self, view: &ViewBase<'_, N, T>, axis: usize) -> [T; 2] {
+        [
+            view.get(self.neighbor(axis, -1)),
+            view.get(self.neighbor(axis, 1)),
+        ]
+    }
+}
+
+/// Rank-checked equivalent of [RangePolicy::TeamPolicy]. Kernels receive a [TeamHandle].
+#[derive(Debug, Clone)]
+pub struct Team {
+    /// Number of team.
+    pub league_size: usize,
+    /// Number of threads per team.
+    pub team_size: usize,
+    /// Number of vector
+    pub vector_size: usize,
+    /// Scratch memory requested per team at each level, in bytes, see
+    /// [TeamHandle::team_scratch].
+    pub scratch_size: [usize; SCRATCH_LEVELS],
+}
+
+impl Policy<1> for Team {
+    type Arg = TeamHandle;
+
+    fn into_range(self) -> RangePolicy<1> {
+        RangePolicy::TeamPolicy {
+            league_size: self.league_size,
+            team_size: self.team_size,
+            vector_size: self.vector_size,
+            scratch_size: self.scratch_size,
+        }
+    }
+
+    fn arg(args: KernelArgs<1>) -> TeamHandle {
+        match args {
+            KernelArgs::Handle(handle) => handle,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Rank-checked counterpart of [ExecutionPolicy], using a typed [Policy].
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::routines::parameters::{
+///     ExecutionSpace, MDRange, Schedule, TypedExecutionPolicy,
+/// };
+///
+/// let execp = TypedExecutionPolicy {
+///     space: ExecutionSpace::DeviceCPU,
+///     policy: MDRange([0..8, 0..4]), // kernels take a `[usize; 2]`
+///     schedule: Schedule::Static,
+///     chunk_size: None,
+///     chunk_predicate: None,
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct TypedExecutionPolicy<P> {
+    /// Execution space targetted by the dispatch.
+    pub space: ExecutionSpace,
+    /// Iteration pattern used to handle the workload.
+    pub policy: P,
+    /// Scheduling policy for the dispatch.
+    pub schedule: Schedule,
+    /// Number of indices (tiles for multi-dimensional policies) processed by a thread at
+    /// a time. See [ExecutionPolicy::chunk_size].
+    pub chunk_size: Option<usize>,
+    /// Optional chunk-level predicate. See [ExecutionPolicy::chunk_predicate].
+    pub chunk_predicate: Option<ChunkPredicate>,
+}
+
+impl<P> TypedExecutionPolicy<P> {
+    /// Build an execution policy using default execution space and scheduling.
+    pub fn new(policy: P) -> Self {
+        Self {
+            space: ExecutionSpace::default(),
+            policy,
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        }
+    }
+
+    /// Convert into the equivalent [ExecutionPolicy], used for dispatch.
+    pub fn untyped<const N: usize>(self) -> ExecutionPolicy<N>
+    where
+        P: Policy<N>,
+    {
+        ExecutionPolicy {
+            space: self.space,
+            range: self.policy.into_range(),
+            schedule: self.schedule,
+            chunk_size: self.chunk_size,
+            chunk_predicate: self.chunk_predicate,
+        }
+    }
+}
+
+/// Fluent builder of [TypedExecutionPolicy], optionally labelled.
+///
+/// Unset parameters keep their default value, see [TypedExecutionPolicy::new]. The
+/// built policy is retrieved using [PolicyBuilder::build], or the builder is directly
+/// used to execute a statement, e.g. [PolicyBuilder::parallel_for]; statements are
+/// then executed under the label of the builder, see
+/// [profiling::labeled][crate::profiling::labeled].
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::routines::parameters::{ExecutionSpace, PolicyBuilder, Schedule};
+///
+/// let n = 1024;
+/// let builder = PolicyBuilder::range(0..n)
+///     .schedule(Schedule::Dynamic)
+///     .space(ExecutionSpace::DeviceCPU)
+///     .chunk(128)
+///     .label("axpy");
+/// assert_eq!(builder.get_label(), Some("axpy"));
+///
+/// let execp = builder.build();
+/// assert_eq!(execp.chunk_size, Some(128));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PolicyBuilder<P> {
+    execp: TypedExecutionPolicy<P>,
+    label: Option<String>,
+    captures: Option<Captures>,
+}
+
+impl PolicyBuilder<Range1D> {
+    /// Start building a [Range1D] policy.
+    pub fn range(range: Range<usize>) -> Self {
+        Self::new(Range1D(range))
+    }
+}
+
+impl<const N: usize> PolicyBuilder<MDRange<N>> {
+    /// Start building a [MDRange] policy.
+    pub fn md_range(ranges: [Range<usize>; N]) -> Self {
+        Self::new(MDRange(ranges))
+    }
+}
+
+impl<const N: usize> PolicyBuilder<Stencil<N>> {
+    /// Start building a [Stencil] policy.
+    pub fn stencil(dims: [usize; N], halo: [usize; N]) -> Self {
+        Self::new(Stencil { dims, halo })
+    }
+}
+
+impl PolicyBuilder<Team> {
+    /// Start building a [Team] policy, without scratch memory.
+    pub fn team(league_size: usize, team_size: usize, vector_size: usize) -> Self {
+        Self::new(Team {
+            league_size,
+            team_size,
+            vector_size,
+            scratch_size: [0; SCRATCH_LEVELS],
+        })
+    }
+}
+
+impl<P> PolicyBuilder<P> {
+    /// Start building a policy iterating using `policy`.
+    pub fn new(policy: P) -> Self {
+        Self {
+            execp: TypedExecutionPolicy::new(policy),
+            label: None,
+            captures: None,
+        }
+    }
+
+    /// Set the execution space targetted by the dispatch.
+    pub fn space(mut self, space: ExecutionSpace) -> Self {
+        self.execp.space = space;
+        self
+    }
+
+    /// Set the scheduling policy of the dispatch.
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.execp.schedule = schedule;
+        self
+    }
+
+    /// Set the chunk size of the dispatch. See [ExecutionPolicy::chunk_size].
+    pub fn chunk(mut self, chunk_size: usize) -> Self {
+        self.execp.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Set the chunk-level predicate of the dispatch. See
+    /// [ExecutionPolicy::chunk_predicate].
+    pub fn chunk_predicate(mut self, predicate: ChunkPredicate) -> Self {
+        self.execp.chunk_predicate = Some(predicate);
+        self
+    }
+
+    /// Set the label under which statements of the builder are executed.
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_owned());
+        self
+    }
+
+    /// Declare the views captured by the kernel of statements of the builder. They are
+    /// checked before dispatching the statement in debug builds, see [aliasing].
+    ///
+    /// [aliasing]: super::aliasing
+    pub fn captures(mut self, captures: Captures) -> Self {
+        self.captures = Some(captures);
+        self
+    }
+
+    /// Returns the label of the builder, if set.
+    pub fn get_label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns the built policy. The label & captures are dropped.
+    pub fn build(self) -> TypedExecutionPolicy<P> {
+        self.execp
+    }
+
+    /// Execute `body` on the built policy, under the label of the builder if set. Declared
+    /// captures are checked first.
+    pub(crate) fn run<R>(
+        self,
+        body: impl FnOnce(TypedExecutionPolicy<P>) -> Result<R, StatementError>,
+    ) -> Result<R, StatementError> {
+        if let Some(captures) = &self.captures {
+            captures.enforce()?;
+        }
+        match self.label {
+            Some(label) => crate::profiling::labeled(&label, || body(self.execp)),
+            None => body(self.execp),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_reducers() {
+        let (min, max) = (MinLoc::<i32>::new(), MaxLoc::<i32>::new());
+        let values = [2, -1, 5, -1, 5].into_iter().enumerate();
+        let values: Vec<ValLoc<i32>> = values.map(|(loc, val)| ValLoc { val, loc }).collect();
+        assert_eq!(
+            min.reduce(values.iter().copied()),
+            Some(ValLoc { val: -1, loc: 1 })
+        );
+        assert_eq!(
+            max.reduce(values.iter().rev().copied()),
+            Some(ValLoc { val: 5, loc: 2 })
+        );
+        assert_eq!(
+            min.empty(),
+            ValLoc {
+                val: i32::MAX,
+                loc: 0
+            }
+        );
+        assert_eq!(max.empty().val, i32::MIN);
+
+        let prod = Prod::<f64>::new();
+        assert_eq!(prod.reduce([0.5, 4.0, 3.0].into_iter()), Some(6.0));
+        assert_eq!(prod.empty(), 1.0);
+    }
+
+    #[test]
+    fn policy_from_dims() {
+        let rangep = RangePolicy::from_dims([3, 5]);
+        assert!(
+            matches!(rangep, RangePolicy::MDRangePolicy([r0, r1]) if r0 == (0..3) && r1 == (0..5))
+        );
+    }
+
+    #[test]
+    fn policy_with_halo() {
+        let rangep = RangePolicy::from_dims_with_halo([10, 5, 3], [1, 2, 2]);
+        let RangePolicy::MDRangePolicy([r0, r1, r2]) = rangep else {
+            panic!("expected a MDRangePolicy")
+        };
+        assert_eq!(r0, 1..9);
+        assert_eq!(r1, 2..3);
+        assert!(r2.is_empty());
+    }
+
+    #[test]
+    fn stencil_policy() {
+        use crate::{
+            routines::typed::parallel_for,
+            view::{parameters::Layout, ViewOwned},
+        };
+
+        // the discrete laplacian of x^2 + y^2 is 4
+        let (nx, ny) = (9, 6);
+        let data = (0..ny).flat_map(|j| (0..nx).map(move |i| (i * i + j * j) as f64));
+        let u: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data(data.collect(), Layout::Left, [nx, ny]);
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            #[allow(unused_mut)]
+            let mut lap: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [nx, ny]);
+            let execp = TypedExecutionPolicy {
+                space,
+                ..TypedExecutionPolicy::new(Stencil::for_view(&u, [1, 1]))
+            };
+            parallel_for(execp, |p| {
+                let neighbors: f64 = (0..2).flat_map(|axis| p.neighbors(&u, axis)).sum();
+                lap.set(p.index(), neighbors - 4.0 * p.get(&u, [0, 0]));
+            })
+            .unwrap();
+            for (i, j) in (0..nx).flat_map(|i| (0..ny).map(move |j| (i, j))) {
+                let interior = (1..nx - 1).contains(&i) && (1..ny - 1).contains(&j);
+                assert_eq!(lap.get([i, j]), if interior { 4.0 } else { 0.0 });
+            }
+        }
+
+        let p = StencilPoint([2, 3]);
+        assert_eq!(p.offset([-2, 1]), [0, 4]);
+        assert_eq!(p.neighbor(1, -1), [2, 2]);
+        let RangePolicy::MDRangePolicy(ranges) = PolicyBuilder::stencil([4, 4], [2, 1])
+            .build()
+            .policy
+            .into_range()
+        else {
+            panic!("expected a MDRangePolicy")
+        };
+        assert!(ranges[0].is_empty());
+    }
 }
@@ -0,0 +1,317 @@
+//! team scratch memory related code
+//!
+//! This module contains the scratch memory of team-based statements, akin to Kokkos'
+//! `team_scratch`. A [TeamPolicy][super::parameters::RangePolicy::TeamPolicy] requests a
+//! number of bytes per team at each of the [SCRATCH_LEVELS] levels; the dispatcher
+//! allocates the scratch memory of all teams at once, before executing the kernel.
+//!
+//! Members access the memory of their team using
+//! [TeamHandle::team_scratch][crate::functor::TeamHandle::team_scratch], and carve
+//! [ScratchView]s out of it. Views are carved in order: members carving the same
+//! sequence of views get views of the same memory, which can be used to share data
+//! inside a team. Since members of a team may be executed by different threads, scratch
+//! views are written through a shared reference.
+//!
+//! Both levels currently use host memory. Scratch memory is zeroed when allocated, and
+//! is not reset between views of a team.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     routines::{
+//!         parameters::{Team, TypedExecutionPolicy},
+//!         typed::parallel_for,
+//!     },
+//!     view::parameters::Layout,
+//! };
+//!
+//! // 16 doubles per team at level 0
+//! let policy = Team {
+//!     league_size: 4,
+//!     team_size: 2,
+//!     vector_size: 1,
+//!     scratch_size: [16 * 8, 0],
+//! };
+//! parallel_for(TypedExecutionPolicy::new(policy), |handle| {
+//!     let mut scratch = handle.team_scratch(0);
+//!     let buffer = scratch.view::<f64, 1>(Layout::Right, [16]).unwrap();
+//!     buffer.set([handle.team_rank()], handle.league_rank() as f64);
+//! })
+//! .unwrap();
+//! ```
+
+use std::{
+    fmt::Debug,
+    mem::{align_of, size_of},
+    sync::Arc,
+};
+
+use crate::view::{
+    parameters::{compute_span, compute_stride, DataTraits, Layout},
+    ViewError,
+};
+
+/// Number of scratch memory levels, following Kokkos: level 0 is meant for small & fast
+/// memory, level 1 for larger allocations.
+pub const SCRATCH_LEVELS: usize = 2;
+
+/// Size of the words scratch memory is made of. Elements of scratch views cannot have a
+/// larger alignment.
+const WORD_SIZE: usize = size_of::<u64>();
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))] {
+        /// Storage cell of scratch memory. Depends on enabled feature(s).
+        ///
+        /// **Current version**: thread-safe
+        type ScratchCell<T> = atomic::Atomic<T>;
+
+        fn load<T: Copy>(cell: &ScratchCell<T>) -> T {
+            cell.load(atomic::Ordering::Relaxed)
+        }
+
+        fn store<T: Copy>(cell: &ScratchCell<T>, val: T) {
+            cell.store(val, atomic::Ordering::Relaxed)
+        }
+    } else {
+        /// Storage cell of scratch memory. Depends on enabled feature(s).
+        ///
+        /// **Current version**: no feature
+        type ScratchCell<T> = std::cell::Cell<T>;
+
+        fn load<T: Copy>(cell: &ScratchCell<T>) -> T {
+            cell.get()
+        }
+
+        fn store<T: Copy>(cell: &ScratchCell<T>, val: T) {
+            cell.set(val)
+        }
+    }
+}
+
+/// Element types of scratch views.
+///
+/// # Safety
+///
+/// Any bit pattern must be a valid value of the type, since the same scratch memory can
+/// be used by views of different types.
+pub unsafe trait ScratchElement: DataTraits {}
+
+macro_rules! impl_scratch_element {
+    ($($t: ty),+) => {
+        $(
+            unsafe impl ScratchElement for $t {}
+        )+
+    };
+}
+
+impl_scratch_element!(f64, f32, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Scratch memory of all teams of a statement.
+pub(crate) struct ScratchArena {
+    levels: [Box<[ScratchCell<u64>]>; SCRATCH_LEVELS],
+    team_words: [usize; SCRATCH_LEVELS],
+}
+
+impl ScratchArena {
+    /// Allocate `scratch_size` bytes per team at each level, for `league_size` teams.
+    /// Returns `None` if no scratch memory is requested.
+    pub(crate) fn new(
+        league_size: usize,
+        scratch_size: [usize; SCRATCH_LEVELS],
+    ) -> Option<Arc<Self>> {
+        if scratch_size.iter().all(|bytes| *bytes == 0) {
+            return None;
+        }
+        let team_words = scratch_size.map(|bytes| bytes.div_ceil(WORD_SIZE));
+        let levels = team_words.map(|words| {
+            (0..words * league_size)
+                .map(|_| ScratchCell::new(0))
+                .collect()
+        });
+        // without features, the arena is not shared between threads
+        #[allow(clippy::arc_with_non_send_sync)]
+        Some(Arc::new(Self { levels, team_words }))
+    }
+
+    /// Returns the scratch memory of team `league_rank` at `level`.
+    pub(crate) fn team(&self, level: usize, league_rank: usize) -> TeamScratch<'_> {
+        let words = self.team_words[level];
+        TeamScratch {
+            words: &self.levels[level][league_rank * words..(league_rank + 1) * words],
+            offset: 0,
+        }
+    }
+}
+
+/// Arenas are compared by identity: handles are equal if they use the same scratch
+/// memory.
+impl PartialEq for ScratchArena {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for ScratchArena {}
+
+impl Debug for ScratchArena {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScratchArena")
+            .field(
+                "team_bytes",
+                &self.team_words.map(|words| words * WORD_SIZE),
+            )
+            .finish()
+    }
+}
+
+/// Scratch memory of a team at a given level, out of which views are carved.
+#[derive(Debug)]
+pub struct TeamScratch<'a> {
+    words: &'a [ScratchCell<u64>],
+    /// Number of bytes used by previous views.
+    offset: usize,
+}
+
+impl<'a> TeamScratch<'a> {
+    /// Returns the empty scratch memory of statements that did not request any.
+    pub(crate) fn empty() -> Self {
+        Self {
+            words: &[],
+            offset: 0,
+        }
+    }
+
+    /// Returns the size of the scratch memory, in bytes. Requested sizes are rounded up
+    /// to a multiple of 8 bytes.
+    pub fn size(&self) -> usize {
+        self.words.len() * WORD_SIZE
+    }
+
+    /// Returns the number of bytes not used by views yet.
+    pub fn remaining(&self) -> usize {
+        self.size() - self.offset
+    }
+
+    /// Carve a view of the given layout & dimensions out of the remaining memory. The
+    /// view is aligned on its element type.
+    ///
+    /// Returns a [ViewError::ValueError] error if the alignment of `T` exceeds 8 bytes,
+    /// and a [ViewError::AllocationFailure] error if the remaining memory is too small.
+    pub fn view<T: ScratchElement, const N: usize>(
+        &mut self,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<ScratchView<'a, N, T>, ViewError<'static>> {
+        let align = align_of::<ScratchCell<T>>();
+        if !WORD_SIZE.is_multiple_of(align) {
+            return Err(ViewError::ValueError(
+                "Scratch elements cannot be aligned on more than 8 bytes",
+            ));
+        }
+        let span = compute_span(&dim, &layout);
+        let start = self.offset.next_multiple_of(align);
+        let end = span
+            .checked_mul(size_of::<ScratchCell<T>>())
+            .and_then(|bytes| bytes.checked_add(start))
+            .filter(|end| *end <= self.size())
+            .ok_or(ViewError::AllocationFailure(
+                "Team scratch memory is exhausted",
+            ))?;
+        self.offset = end;
+        // SAFETY: the span lies within the scratch memory, which is aligned on words; cells
+        // have interior mutability & any bit pattern is a valid element
+        let data = unsafe {
+            let ptr = self.words.as_ptr().cast::<u8>().add(start);
+            std::slice::from_raw_parts(ptr.cast::<ScratchCell<T>>(), span)
+        };
+        Ok(ScratchView {
+            data,
+            dim,
+            stride: compute_stride(&dim, &layout),
+        })
+    }
+}
+
+/// View carved out of team scratch memory.
+///
+/// Unlike other views, elements are written through a shared reference, whatever the
+/// enabled features: team members share scratch views.
+pub struct ScratchView<'a, const N: usize, T> {
+    data: &'a [ScratchCell<T>],
+    dim: [usize; N],
+    stride: [usize; N],
+}
+
+impl<const N: usize, T: ScratchElement> Debug for ScratchView<'_, N, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScratchView")
+            .field("dim", &self.dim)
+            .field("stride", &self.stride)
+            .finish()
+    }
+}
+
+impl<const N: usize, T: ScratchElement> ScratchView<'_, N, T> {
+    /// Returns the dimensions of the view.
+    pub fn dims(&self) -> [usize; N] {
+        self.dim
+    }
+
+    fn flat_idx(&self, index: [usize; N]) -> usize {
+        assert!(
+            index.iter().zip(self.dim.iter()).all(|(i, d)| i < d),
+            "index {index:?} is out of bounds"
+        );
+        index
+            .iter()
+            .zip(self.stride.iter())
+            .map(|(i, s)| i * s)
+            .sum()
+    }
+
+    /// Read the element at `index`.
+    pub fn get(&self, index: [usize; N]) -> T {
+        load(&self.data[self.flat_idx(index)])
+    }
+
+    /// Write `val` at `index`.
+    pub fn set(&self, index: [usize; N], val: T) {
+        store(&self.data[self.flat_idx(index)], val)
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carve_views() {
+        let arena = ScratchArena::new(2, [20, 0]).unwrap();
+        let mut scratch = arena.team(0, 1);
+        assert_eq!((scratch.size(), scratch.remaining()), (24, 24));
+
+        let a = scratch.view::<u8, 1>(Layout::Right, [3]).unwrap();
+        let b = scratch.view::<f64, 2>(Layout::Left, [1, 2]).unwrap();
+        assert_eq!(scratch.remaining(), 0);
+        assert!(scratch.view::<u8, 1>(Layout::Right, [1]).is_err());
+        a.set([2], 7);
+        b.set([0, 1], 1.5);
+        assert_eq!(b.get([0, 1]), 1.5);
+
+        // members carving the same views share memory; teams do not
+        let mut other = arena.team(0, 1);
+        assert_eq!(other.view::<u8, 1>(Layout::Right, [3]).unwrap().get([2]), 7);
+        let mut team0 = arena.team(0, 0);
+        assert_eq!(team0.view::<u8, 1>(Layout::Right, [3]).unwrap().get([2]), 0);
+        assert_eq!(arena.team(1, 0).size(), 0);
+
+        assert!(ScratchArena::new(4, [0; SCRATCH_LEVELS]).is_none());
+        let out_of_bounds = std::panic::AssertUnwindSafe(|| a.get([3]));
+        assert!(std::panic::catch_unwind(out_of_bounds).is_err());
+    }
+}
@@ -0,0 +1,307 @@
+//! task parallelism related code
+//!
+//! This module contains a minimal equivalent of Kokkos' tasking model. Tasks are
+//! spawned on a [TaskScheduler] and return a [TaskFuture], completing with the value
+//! of the task. Tasks can spawn other tasks & wait for them, which makes it possible
+//! to write recursive or irregular algorithms, e.g. tree traversals:
+//!
+//! - [TaskScheduler::spawn] queues a task & returns its future,
+//! - [TaskScheduler::when_all] aggregates futures, e.g. to express dependencies,
+//! - [TaskFuture::wait] blocks until the task is complete & returns its value.
+//!
+//! When a parallelization feature is enabled, tasks are executed by worker threads of
+//! the scheduler, as many as the threads used by CPU dispatch. Threads waiting for a
+//! future execute queued tasks in the meantime, newest first, so waiting from a task
+//! does not starve the scheduler. Without features, the scheduler has no worker: tasks
+//! are executed by the threads waiting for them.
+//!
+//! Unlike Kokkos, tasks are not respawned: they block until their dependencies are
+//! complete.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::routines::task::TaskScheduler;
+//!
+//! fn fib(scheduler: &TaskScheduler, n: u64) -> u64 {
+//!     if n < 2 {
+//!         return n;
+//!     }
+//!     let (s1, s2) = (scheduler.clone(), scheduler.clone());
+//!     let f1 = scheduler.spawn(move || fib(&s1, n - 1));
+//!     let f2 = scheduler.spawn(move || fib(&s2, n - 2));
+//!     f1.wait() + f2.wait()
+//! }
+//!
+//! let scheduler = TaskScheduler::new();
+//! assert_eq!(fib(&scheduler, 10), 55);
+//!
+//! let squares = (0..4).map(|i| scheduler.spawn(move || i * i)).collect();
+//! assert_eq!(scheduler.when_all(squares).wait(), vec![0, 1, 4, 9]);
+//! ```
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Queue of tasks shared by a scheduler & its workers.
+#[derive(Default)]
+struct Queue {
+    /// Queued jobs & shutdown flag.
+    state: Mutex<(VecDeque<Job>, bool)>,
+    available: Condvar,
+}
+
+impl Queue {
+    fn lock(&self) -> MutexGuard<'_, (VecDeque<Job>, bool)> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Pop the newest job, used by threads waiting for a future.
+    fn pop_newest(&self) -> Option<Job> {
+        self.lock().0.pop_back()
+    }
+
+    /// Execute jobs, oldest first, until the queue is shut down & empty.
+    fn work(&self) {
+        loop {
+            let job = {
+                let state = self.lock();
+                let mut state = self
+                    .available
+                    .wait_while(state, |(jobs, shutdown)| jobs.is_empty() && !*shutdown)
+                    .unwrap_or_else(|e| e.into_inner());
+                match state.0.pop_front() {
+                    Some(job) => job,
+                    None => return,
+                }
+            };
+            job();
+        }
+    }
+}
+
+/// State of a scheduler, shared by its handles.
+struct SchedulerState {
+    queue: Arc<Queue>,
+}
+
+impl Drop for SchedulerState {
+    fn drop(&mut self) {
+        // workers finish queued tasks & exit
+        self.queue.lock().1 = true;
+        self.queue.available.notify_all();
+    }
+}
+
+/// Scheduler executing tasks. Handles are cheap to clone & share the same queue.
+///
+/// Dropping the last handle does not cancel queued tasks.
+#[derive(Clone)]
+pub struct TaskScheduler {
+    state: Arc<SchedulerState>,
+}
+
+impl Default for TaskScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskScheduler {
+    /// Create a scheduler. When a parallelization feature is enabled, it uses as many
+    /// workers as the threads used by CPU dispatch, see
+    /// [num_threads][crate::runtime::num_threads]; otherwise, it has no worker.
+    pub fn new() -> Self {
+        let workers = if cfg!(any(feature = "threads", feature = "rayon")) {
+            crate::runtime::num_threads()
+        } else {
+            0
+        };
+        Self::with_workers(workers)
+    }
+
+    /// Create a scheduler using `workers` threads. With no worker, tasks are executed by
+    /// the threads waiting for them.
+    pub fn with_workers(workers: usize) -> Self {
+        let queue = Arc::new(Queue::default());
+        (0..workers).for_each(|_| {
+            let queue = queue.clone();
+            thread::spawn(move || queue.work());
+        });
+        Self {
+            state: Arc::new(SchedulerState { queue }),
+        }
+    }
+
+    /// Queue `task` & return its future. Panics of the task are propagated when its value
+    /// is retrieved.
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        task: impl FnOnce() -> T + Send + 'static,
+    ) -> TaskFuture<T> {
+        let shared = Arc::new(TaskShared {
+            state: Mutex::new((None, None)),
+            done: Condvar::new(),
+        });
+        let task_shared = shared.clone();
+        let job: Job = Box::new(move || {
+            let outcome = catch_unwind(AssertUnwindSafe(task));
+            let waker = {
+                let mut state = task_shared.lock();
+                state.0 = Some(outcome);
+                state.1.take()
+            };
+            task_shared.done.notify_all();
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        });
+        let queue = &self.state.queue;
+        queue.lock().0.push_back(job);
+        queue.available.notify_one();
+        TaskFuture {
+            shared,
+            queue: queue.clone(),
+        }
+    }
+
+    /// Returns a future completing with the values of `futures`, in the same order, once
+    /// all of them are complete.
+    pub fn when_all<T: Send + 'static>(&self, futures: Vec<TaskFuture<T>>) -> TaskFuture<Vec<T>> {
+        self.spawn(move || futures.into_iter().map(TaskFuture::wait).collect())
+    }
+}
+
+/// Completion state shared by a future & its task.
+struct TaskShared<T> {
+    state: Mutex<(Option<thread::Result<T>>, Option<Waker>)>,
+    done: Condvar,
+}
+
+impl<T> TaskShared<T> {
+    fn lock(&self) -> MutexGuard<'_, (Option<thread::Result<T>>, Option<Waker>)> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Handle to a task, completing with its value.
+///
+/// Dropping the handle does not cancel the task.
+pub struct TaskFuture<T> {
+    shared: Arc<TaskShared<T>>,
+    queue: Arc<Queue>,
+}
+
+impl<T> TaskFuture<T> {
+    /// Returns `true` if the task is complete, i.e. if [TaskFuture::wait] would not
+    /// block.
+    pub fn is_complete(&self) -> bool {
+        self.shared.lock().0.is_some()
+    }
+
+    /// Block until the task is complete and returns its value. Queued tasks are executed
+    /// by the calling thread in the meantime.
+    pub fn wait(self) -> T {
+        loop {
+            if let Some(outcome) = self.shared.lock().0.take() {
+                return outcome.unwrap_or_else(|payload| resume_unwind(payload));
+            }
+            match self.queue.pop_newest() {
+                Some(job) => job(),
+                None => {
+                    // the task is being executed; new tasks may be queued meanwhile
+                    let state = self.shared.lock();
+                    let _ = self.shared.done.wait_timeout_while(
+                        state,
+                        Duration::from_millis(1),
+                        |(outcome, _)| outcome.is_none(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Polling does not execute queued tasks: futures of schedulers without worker only
+/// complete when tasks are executed by [TaskFuture::wait].
+impl<T> Future for TaskFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.lock();
+        match state.0.take() {
+            Some(outcome) => Poll::Ready(outcome.unwrap_or_else(|payload| resume_unwind(payload))),
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Sum of the values of a binary tree of given depth, each node holding 1.
+    fn tree_sum(scheduler: &TaskScheduler, depth: usize) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+        let children: Vec<TaskFuture<usize>> = (0..2)
+            .map(|_| {
+                let s = scheduler.clone();
+                scheduler.spawn(move || tree_sum(&s, depth - 1))
+            })
+            .collect();
+        1 + scheduler.when_all(children).wait().iter().sum::<usize>()
+    }
+
+    #[test]
+    fn recursive_tasks() {
+        for workers in [0, 1, 4] {
+            let scheduler = TaskScheduler::with_workers(workers);
+            assert_eq!(tree_sum(&scheduler, 8), (1 << 9) - 1);
+        }
+        assert_eq!(tree_sum(&TaskScheduler::new(), 6), (1 << 7) - 1);
+    }
+
+    #[test]
+    fn detached_tasks() {
+        let scheduler = TaskScheduler::with_workers(2);
+        let count = Arc::new(AtomicUsize::new(0));
+        let futures: Vec<TaskFuture<()>> = (0..100)
+            .map(|_| {
+                let c = count.clone();
+                scheduler.spawn(move || {
+                    c.fetch_add(1, Ordering::Relaxed);
+                })
+            })
+            .collect();
+        drop(scheduler);
+        futures.into_iter().for_each(TaskFuture::wait);
+        assert_eq!(count.load(Ordering::Relaxed), 100);
+    }
+
+    #[test]
+    fn panicking_task() {
+        let scheduler = TaskScheduler::with_workers(0);
+        let fut = scheduler.spawn(|| -> usize { panic!("task failure") });
+        assert!(!fut.is_complete());
+        assert!(catch_unwind(AssertUnwindSafe(|| fut.wait())).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! team synchronization related code
+//!
+//! This module contains the synchronization state shared by the members of a team, used
+//! by the collective operations of [TeamHandle], akin to Kokkos' team-level collectives:
+//!
+//! - [TeamHandle::team_barrier] blocks until all members of the team reach it,
+//! - [TeamHandle::team_broadcast] returns the value of a given member to all members,
+//! - [TeamHandle::team_reduce] returns the reduction of the values of all members,
+//! - [TeamHandle::team_scan] returns the prefix sum of the values of members, in rank
+//!   order.
+//!
+//! Collectives require the members of a team to be executed concurrently. CPU dispatch
+//! routines of parallelization features group the threads of their pool by `team_size`,
+//! each thread of a group executing one member of the teams assigned to the group; a
+//! dispatch error is returned if `team_size` exceeds the number of threads, see
+//! [num_threads][crate::runtime::num_threads]. Teams of serial dispatch, of nested
+//! statements & of builds without features are executed member after member:
+//! collectives of these teams panic, unless they have a single member.
+//!
+//! All members of a team must call the same collectives, in the same order. If a member
+//! panics, members waiting for it panic as well instead of deadlocking.
+//!
+//! Blocks of a team kernel executed by a single member, akin to Kokkos' `single`, are
+//! defined using [`single`] & [`single_broadcast`] with a
+//! [PerTeam][RangePolicy::PerTeam] or [PerThread][RangePolicy::PerThread] policy.
+//!
+//! ### Example
+//!
+//! ```rust,no_run
+//! use poc_kokkos_rs::routines::{
+//!     parameters::{ExecutionSpace, ReduceOp, ScanMode, Schedule, Team, TypedExecutionPolicy},
+//!     typed::parallel_for,
+//! };
+//!
+//! let execp = TypedExecutionPolicy {
+//!     space: ExecutionSpace::DeviceCPU,
+//!     policy: Team {
+//!         league_size: 4,
+//!         team_size: 4,
+//!         vector_size: 1,
+//!         scratch_size: [0, 0],
+//!     },
+//!     schedule: Schedule::default(),
+//!     chunk_size: None,
+//!     chunk_predicate: None,
+//! };
+//! parallel_for(execp, |handle| {
+//!     let rank = handle.team_rank();
+//!     let total = handle.team_reduce(rank, &ReduceOp::Sum);
+//!     let offset = handle.team_scan(rank, ScanMode::Exclusive);
+//!     handle.team_barrier();
+//!     let root = handle.team_broadcast(handle.league_rank(), 0);
+//!     assert_eq!((total, offset, root), (6, (0..rank).sum(), handle.league_rank()));
+//! })
+//! .unwrap();
+//! ```
+//!
+//!
+//! ```rust
+//! use poc_kokkos_rs::routines::{
+//!     parameters::{RangePolicy, Team, TypedExecutionPolicy},
+//!     team::single,
+//!     typed::parallel_for,
+//! };
+//!
+//! let policy = Team {
+//!     league_size: 2,
+//!     team_size: 3,
+//!     vector_size: 1,
+//!     scratch_size: [0, 0],
+//! };
+//! parallel_for(TypedExecutionPolicy::new(policy), |handle| {
+//!     let team = handle.league_rank();
+//!     let res = single(RangePolicy::<1>::PerTeam(handle.clone()), || {
+//!         println!("Hello once from team {team}")
+//!     })
+//!     .unwrap();
+//!     assert_eq!(res.is_some(), handle.team_rank() == 0);
+//! })
+//! .unwrap();
+//! ```
+//!
+//! [TeamHandle]: crate::functor::TeamHandle
+//! [TeamHandle::team_barrier]: crate::functor::TeamHandle::team_barrier
+//! [TeamHandle::team_broadcast]: crate::functor::TeamHandle::team_broadcast
+//! [TeamHandle::team_reduce]: crate::functor::TeamHandle::team_reduce
+//! [TeamHandle::team_scan]: crate::functor::TeamHandle::team_scan
+
+use super::{
+    dispatch,
+    parameters::{ExecutionPolicy, ExecutionSpace, PolicyKind, RangePolicy, Schedule},
+    StatementError,
+};
+use crate::functor::KernelArgs;
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use std::sync::Arc;
+use std::{
+    any::Any,
+    fmt::Debug,
+    sync::{Condvar, Mutex, MutexGuard},
+};
+
+const POISONED: &str = "a member of the team panicked";
+
+/// Synchronization state of a team, shared by its members.
+struct SyncState {
+    /// Number of members waiting at the current barrier.
+    arrived: usize,
+    /// Number of completed barriers.
+    generation: usize,
+    /// Set when a member panics.
+    poisoned: bool,
+    /// Values exchanged by collectives, one per member.
+    slots: Vec<Option<Box<dyn Any + Send>>>,
+}
+
+/// Synchronization primitives of a team whose members are executed concurrently.
+pub(crate) struct TeamSync {
+    state: Mutex<SyncState>,
+    released: Condvar,
+    team_size: usize,
+}
+
+impl TeamSync {
+    /// Create the synchronization state of a team of `team_size` members. Returns `None`
+    /// for single-member teams, which do not need any.
+    #[cfg(any(feature = "threads", feature = "rayon"))]
+    pub(crate) fn new(team_size: usize) -> Option<Arc<Self>> {
+        (team_size > 1).then(|| {
+            Arc::new(Self {
+                state: Mutex::new(SyncState {
+                    arrived: 0,
+                    generation: 0,
+                    poisoned: false,
+                    slots: (0..team_size).map(|_| None).collect(),
+                }),
+                released: Condvar::new(),
+                team_size,
+            })
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SyncState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Block until all members of the team reach the barrier.
+    pub(crate) fn barrier(&self) {
+        let mut state = self.lock();
+        if state.poisoned {
+            drop(state);
+            panic!("{POISONED}");
+        }
+        state.arrived += 1;
+        if state.arrived == self.team_size {
+            state.arrived = 0;
+            state.generation += 1;
+            self.released.notify_all();
+            return;
+        }
+        let generation = state.generation;
+        let state = self
+            .released
+            .wait_while(state, |s| s.generation == generation && !s.poisoned)
+            .unwrap_or_else(|e| e.into_inner());
+        if state.poisoned {
+            drop(state);
+            panic!("{POISONED}");
+        }
+    }
+
+    /// Exchange `value` with the other members of the team. Returns the values of all
+    /// members, in rank order.
+    pub(crate) fn gather<T: Clone + Send + 'static>(&self, team_rank: usize, value: T) -> Vec<T> {
+        self.lock().slots[team_rank] = Some(Box::new(value));
+        self.barrier();
+        let values = self
+            .lock()
+            .slots
+            .iter()
+            .map(|slot| {
+                slot.as_ref()
+                    .and_then(|val| val.downcast_ref::<T>())
+                    .expect("members of a team must call the same collectives")
+                    .clone()
+            })
+            .collect();
+        // slots must not be overwritten before all members read them
+        self.barrier();
+        values
+    }
+
+    /// Returns a guard releasing members waiting for the calling member if it panics.
+    #[cfg(any(feature = "threads", feature = "rayon"))]
+    pub(crate) fn guard(&self) -> MemberGuard<'_> {
+        MemberGuard(self)
+    }
+}
+
+/// Teams are compared by identity: handles are equal if they belong to the same team.
+impl PartialEq for TeamSync {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for TeamSync {}
+
+impl Debug for TeamSync {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TeamSync")
+            .field("team_size", &self.team_size)
+            .finish()
+    }
+}
+
+/// Guard held by a member during the execution of its kernel. If the kernel panics,
+/// the team is poisoned & members waiting at a collective panic.
+#[cfg(any(feature = "threads", feature = "rayon"))]
+pub(crate) struct MemberGuard<'a>(&'a TeamSync);
+
+#[cfg(any(feature = "threads", feature = "rayon"))]
+impl Drop for MemberGuard<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.lock().poisoned = true;
+            self.0.released.notify_all();
+        }
+    }
+}
+
+// Single constructs
+
+/// Execute `func` once per team using a [PerTeam][RangePolicy::PerTeam] policy, or once
+/// per member using a [PerThread][RangePolicy::PerThread] policy. Called by all members
+/// of a team kernel, it returns the value of `func` to the members executing it, and
+/// `None` to the others.
+///
+/// A [StatementError::UnsupportedPolicy] error is returned for other policies.
+pub fn single<T, const N: usize>(
+    policy: RangePolicy<N>,
+    func: impl FnOnce() -> T,
+) -> Result<Option<T>, StatementError> {
+    match policy.kind() {
+        PolicyKind::PerTeam | PolicyKind::PerThread => {}
+        kind => return Err(StatementError::UnsupportedPolicy(kind)),
+    }
+    let execp = ExecutionPolicy {
+        space: ExecutionSpace::Serial,
+        range: policy,
+        schedule: Schedule::default(),
+        chunk_size: None,
+        chunk_predicate: None,
+    };
+    // the dispatch decides which member executes the block
+    let (mut func, mut res) = (Some(func), None);
+    dispatch::serial(
+        execp,
+        Box::new(|_: KernelArgs<N>| res = func.take().map(|f| f())),
+    )?;
+    Ok(res)
+}
+
+/// Variant of [`single`] returning the value of `func` to all members of the team. Using
+/// a [PerTeam][RangePolicy::PerTeam] policy, the value is broadcast from the member
+/// executing `func`, see
+/// [TeamHandle::team_broadcast][crate::functor::TeamHandle::team_broadcast]; members must then be executed
+/// concurrently, like for other collectives.
+pub fn single_broadcast<T, const N: usize>(
+    policy: RangePolicy<N>,
+    func: impl FnOnce() -> T,
+) -> Result<T, StatementError>
+where
+    T: Clone + Send + 'static,
+{
+    let handle = match &policy {
+        RangePolicy::PerTeam(handle) => handle.clone(),
+        _ => return single(policy, func).map(|res| res.expect("all members execute the block")),
+    };
+    let res = single(policy, func)?;
+    Ok(handle
+        .team_broadcast(res, 0)
+        .expect("the first member executes the block"))
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(all(test, any(feature = "threads", feature = "rayon")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gather_values() {
+        assert!(TeamSync::new(1).is_none());
+        let sync = TeamSync::new(3).unwrap();
+        let gathered: Vec<Vec<usize>> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..3)
+                .map(|rank| {
+                    let sync = &sync;
+                    s.spawn(move || {
+                        let first = sync.gather(rank, 10 * rank);
+                        sync.barrier();
+                        let second = sync.gather(rank, rank + 1);
+                        [first, second].concat()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert!(gathered.iter().all(|g| *g == vec![0, 10, 20, 1, 2, 3]));
+    }
+
+    #[test]
+    fn poisoned_team() {
+        let sync = TeamSync::new(2).unwrap();
+        let res = std::thread::scope(|s| {
+            let waiting = s.spawn(|| sync.barrier());
+            s.spawn(|| {
+                let _guard = sync.guard();
+                panic!("member failure");
+            })
+            .join()
+            .unwrap_err();
+            waiting.join()
+        });
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,368 @@
+//! parameter sweep code
+//!
+//! This module contains code used to measure a statement over a set of candidate
+//! configurations (tile sizes, chunk sizes, ...) and rank them. This is the reusable
+//! counterpart of the experiments encoded in the crate's benchmarks.
+//!
+//! The statement is provided as a closure taking a configuration as argument. Each
+//! candidate is measured [`SweepParams::repetitions`] times; candidates are ranked
+//! using the median of their measures.
+//!
+//! Scaling studies over thread counts can be done using [`scaling_study`], which
+//! reconfigures the [runtime][crate::runtime] for each count.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     functor::KernelArgs,
+//!     routines::{
+//!         parallel_for_skip_chunks,
+//!         parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+//!         tune::{sweep, SweepParams},
+//!     },
+//! };
+//!
+//! let results = sweep(&[16, 256, 4096], &SweepParams::default(), |chunk_size| {
+//!     let execp = ExecutionPolicy {
+//!         space: ExecutionSpace::DeviceCPU,
+//!         range: RangePolicy::RangePolicy(0..10_000),
+//!         schedule: Schedule::Static,
+//!         chunk_size: None,
+//!         chunk_predicate: None,
+//!     };
+//!     parallel_for_skip_chunks(execp, *chunk_size, |_| true, |_: KernelArgs<1>| {})
+//! })
+//! .unwrap();
+//!
+//! // results are sorted, fastest first
+//! assert_eq!(results.len(), 3);
+//! assert!(results[0].median() <= results[2].median());
+//! ```
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use super::StatementError;
+use crate::runtime::{self, RuntimeConfig, RuntimeError};
+
+// Enums
+
+/// Enum used to classify possible errors occuring during a scaling study.
+#[derive(Debug)]
+pub enum ScalingError {
+    /// The runtime could not be configured with the requested thread count.
+    Runtime(RuntimeError),
+    /// Error occured during one of the measured statements.
+    Statement(StatementError),
+}
+
+impl From<RuntimeError> for ScalingError {
+    fn from(e: RuntimeError) -> Self {
+        ScalingError::Runtime(e)
+    }
+}
+
+impl From<StatementError> for ScalingError {
+    fn from(e: StatementError) -> Self {
+        ScalingError::Statement(e)
+    }
+}
+
+impl Display for ScalingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScalingError::Runtime(e) => write!(f, "error during runtime setup: {e}"),
+            ScalingError::Statement(e) => write!(f, "error during measure: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ScalingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScalingError::Runtime(e) => Some(e),
+            ScalingError::Statement(e) => Some(e),
+        }
+    }
+}
+
+/// Kind of scaling study. Determines how efficiency is computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalingKind {
+    /// The problem size is fixed; ideal time decreases linearly with the thread count.
+    Strong,
+    /// The problem size grows with the thread count; ideal time is constant.
+    Weak,
+}
+
+// Parameters
+
+/// Sweep parameters.
+#[derive(Debug, Clone)]
+pub struct SweepParams {
+    /// Number of unmeasured runs of each candidate, done before measuring it.
+    pub warmup: usize,
+    /// Number of measured runs of each candidate.
+    pub repetitions: usize,
+}
+
+impl Default for SweepParams {
+    fn default() -> Self {
+        Self {
+            warmup: 1,
+            repetitions: 5,
+        }
+    }
+}
+
+// Results
+
+/// Measures of a single candidate configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepResult<C> {
+    /// Measured configuration.
+    pub config: C,
+    /// Execution time of each measured run.
+    pub times: Vec<Duration>,
+}
+
+impl<C> SweepResult<C> {
+    /// Returns the median execution time of the candidate.
+    pub fn median(&self) -> Duration {
+        let mut times = self.times.clone();
+        times.sort();
+        times.get(times.len() / 2).copied().unwrap_or_default()
+    }
+
+    /// Returns the minimal execution time of the candidate.
+    pub fn min(&self) -> Duration {
+        self.times.iter().min().copied().unwrap_or_default()
+    }
+}
+
+// Routines
+
+/// Measure `statement` for each configuration of `candidates`.
+///
+/// Returns the results sorted by ascending median execution time, i.e. the best
+/// configuration first. The sweep stops at the first error returned by the statement.
+pub fn sweep<C: Clone>(
+    candidates: &[C],
+    params: &SweepParams,
+    mut statement: impl FnMut(&C) -> Result<(), StatementError>,
+) -> Result<Vec<SweepResult<C>>, StatementError> {
+    let mut results = candidates
+        .iter()
+        .map(|config| {
+            for _ in 0..params.warmup {
+                statement(config)?;
+            }
+            let times = (0..params.repetitions)
+                .map(|_| {
+                    let start = Instant::now();
+                    statement(config).map(|_| start.elapsed())
+                })
+                .collect::<Result<Vec<Duration>, StatementError>>()?;
+            Ok(SweepResult {
+                config: config.clone(),
+                times,
+            })
+        })
+        .collect::<Result<Vec<SweepResult<C>>, StatementError>>()?;
+    results.sort_by_key(|res| res.median());
+    Ok(results)
+}
+
+/// Measures of a statement for a given thread count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalingRecord {
+    /// Number of threads used by CPU dispatch.
+    pub threads: usize,
+    /// Median execution time of the statement.
+    pub time: Duration,
+    /// Speedup relative to the first record of the study.
+    pub speedup: f64,
+    /// Parallel efficiency relative to the first record of the study. Ideal value is 1.
+    pub efficiency: f64,
+}
+
+/// Measure a statement for each thread count of `thread_counts`.
+///
+/// `builder` is called with the thread count and should execute the statement; it can
+/// use the thread count to scale the problem size in the case of a weak scaling study.
+/// The runtime is initialized with each thread count in turn, hence it should not be
+/// initialized when calling this function. Thread counts have no effect when no
+/// parallelization feature is enabled.
+///
+/// Speedup & efficiency are computed relative to the first thread count.
+pub fn scaling_study(
+    kind: ScalingKind,
+    thread_counts: &[usize],
+    params: &SweepParams,
+    mut builder: impl FnMut(usize) -> Result<(), StatementError>,
+) -> Result<Vec<ScalingRecord>, ScalingError> {
+    let mut times = Vec::with_capacity(thread_counts.len());
+    for &threads in thread_counts {
+        let config = RuntimeConfig {
+            num_threads: Some(threads),
+            ..Default::default()
+        };
+        let res = runtime::scoped(config, || sweep(&[threads], params, |n| builder(*n)))??;
+        times.push((threads, res[0].median()));
+    }
+
+    let Some(&(ref_threads, ref_time)) = times.first() else {
+        return Ok(Vec::new());
+    };
+    Ok(times
+        .into_iter()
+        .map(|(threads, time)| {
+            let speedup = ref_time.as_secs_f64() / time.as_secs_f64();
+            let efficiency = match kind {
+                ScalingKind::Strong => speedup * ref_threads as f64 / threads as f64,
+                ScalingKind::Weak => speedup,
+            };
+            ScalingRecord {
+                threads,
+                time,
+                speedup,
+                efficiency,
+            }
+        })
+        .collect())
+}
+
+// Exporters
+
+/// Header line of the CSV output.
+pub const CSV_HEADER: &str = "rank,config,median_ns,min_ns";
+
+/// Write sweep results as CSV into `out`, header included.
+///
+/// Configurations are written using their [Display] implementation, quoted.
+pub fn write_csv<W: Write, C: Display>(
+    mut out: W,
+    results: &[SweepResult<C>],
+) -> std::io::Result<()> {
+    writeln!(out, "{CSV_HEADER}")?;
+    for (rank, res) in results.iter().enumerate() {
+        writeln!(
+            out,
+            "{},\"{}\",{},{}",
+            rank,
+            res.config.to_string().replace('"', "\"\""),
+            res.median().as_nanos(),
+            res.min().as_nanos(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Header line of the scaling study CSV output.
+pub const SCALING_CSV_HEADER: &str = "threads,time_ns,speedup,efficiency";
+
+/// Write scaling study records as CSV into `out`, header included.
+pub fn write_scaling_csv<W: Write>(mut out: W, records: &[ScalingRecord]) -> std::io::Result<()> {
+    writeln!(out, "{SCALING_CSV_HEADER}")?;
+    for rec in records {
+        writeln!(
+            out,
+            "{},{},{},{}",
+            rec.threads,
+            rec.time.as_nanos(),
+            rec.speedup,
+            rec.efficiency,
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the best configuration of sorted `results` into the file at `path`, using its
+/// [Display] implementation. Nothing is written if `results` is empty.
+pub fn save_best<P: AsRef<Path>, C: Display>(
+    path: P,
+    results: &[SweepResult<C>],
+) -> std::io::Result<()> {
+    if let Some(best) = results.first() {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}", best.config)?;
+    }
+    Ok(())
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn ranking() {
+        let params = SweepParams {
+            warmup: 0,
+            repetitions: 3,
+        };
+        let mut calls = 0;
+        let results = sweep(&[3_u64, 1, 2], &params, |ms| {
+            calls += 1;
+            sleep(Duration::from_millis(*ms));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(calls, 9);
+        let configs: Vec<u64> = results.iter().map(|r| r.config).collect();
+        assert_eq!(configs, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn scaling() {
+        let params = SweepParams {
+            warmup: 0,
+            repetitions: 1,
+        };
+        let mut seen = Vec::new();
+        let records = scaling_study(ScalingKind::Weak, &[1, 2], &params, |_| {
+            seen.push(runtime::num_threads());
+            sleep(Duration::from_millis(5));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(seen, vec![1, 2]);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].efficiency, 1.0);
+        assert!(!runtime::is_initialized());
+
+        let mut out: Vec<u8> = Vec::new();
+        write_scaling_csv(&mut out, &records[..1]).unwrap();
+        let time = records[0].time.as_nanos();
+        let ref_out = format!("{SCALING_CSV_HEADER}\n1,{time},1,1\n");
+        assert_eq!(String::from_utf8(out).unwrap(), ref_out);
+    }
+
+    #[test]
+    fn csv_output() {
+        let results = [SweepResult {
+            config: "64x8",
+            times: vec![
+                Duration::from_nanos(30),
+                Duration::from_nanos(10),
+                Duration::from_nanos(20),
+            ],
+        }];
+        let mut out: Vec<u8> = Vec::new();
+        write_csv(&mut out, &results).unwrap();
+
+        let ref_out = format!("{CSV_HEADER}\n0,\"64x8\",20,10\n");
+        assert_eq!(String::from_utf8(out).unwrap(), ref_out);
+    }
+}
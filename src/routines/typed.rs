@@ -0,0 +1,481 @@
+//! rank-checked statement related code
+//!
+//! This module contains variants of parallel statements taking a
+//! [TypedExecutionPolicy]. The argument received by the kernel is determined by the
+//! [Policy] used, e.g. a `usize` for a [Range1D][super::parameters::Range1D] or a
+//! `[usize; N]` for a [MDRange][super::parameters::MDRange]:
+//!
+//! - mismatches between the policy and the kernel are rejected at compile time instead
+//!   of being reported by the dispatch,
+//! - kernels do not need to match on [KernelArgs] variants they never receive.
+//!
+//! Statements are dispatched using the same code as their counterparts of the
+//! [routines][super] module. These should be preferred over the [KernelArgs]-based
+//! statements, which are kept for compatibility.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::routines::{
+//!     parameters::{ExecutionSpace, MDRange, Range1D, Schedule, TypedExecutionPolicy},
+//!     typed::parallel_for,
+//! };
+//!
+//! let execp = TypedExecutionPolicy {
+//!     space: ExecutionSpace::DeviceCPU,
+//!     policy: Range1D(0..8),
+//!     schedule: Schedule::Static,
+//!     chunk_size: None,
+//!     chunk_predicate: None,
+//! };
+//! parallel_for(execp, |i: usize| println!("Hello from iteration {i}")).unwrap();
+//!
+//! let execp = TypedExecutionPolicy::new(MDRange([0..2, 0..3]));
+//! parallel_for(execp, |[i, j]| println!("Hello from iteration {i},{j}")).unwrap();
+//! ```
+//!
+//! Simple loops over `0..n`, or over the indices of an array of given dimensions, can
+//! skip the policy altogether using [parallel_for_n] & [parallel_for_md], akin to
+//! Kokkos' `parallel_for(N, functor)`. These use the [default_space]:
+//!
+//! ```rust
+//! use poc_kokkos_rs::routines::typed::{parallel_for_md, parallel_for_n};
+//!
+//! parallel_for_n(8, |i| println!("Hello from iteration {i}")).unwrap();
+//! parallel_for_md([2, 3], |[i, j]| println!("Hello from iteration {i},{j}")).unwrap();
+//! ```
+//!
+//! Pairing a policy with a kernel of another rank does not compile:
+//!
+//! ```rust,compile_fail
+//! use poc_kokkos_rs::routines::{
+//!     parameters::{Range1D, TypedExecutionPolicy},
+//!     typed::parallel_for,
+//! };
+//!
+//! let execp = TypedExecutionPolicy::new(Range1D(0..8));
+//! parallel_for(execp, |[i, j]: [usize; 2]| println!("{i},{j}")).unwrap();
+//! ```
+
+use std::ops::Add;
+
+use super::{
+    parameters::{
+        ExecutionSpace, MDRange, Policy, PolicyBuilder, Range1D, Reducer, ScanMode,
+        TypedExecutionPolicy,
+    },
+    KernelError, StatementError,
+};
+use crate::view::parameters::DataTraits;
+
+#[cfg(doc)]
+use crate::functor::KernelArgs;
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "threads", feature = "rayon"))] {
+        /// Rank-checked Parallel For statement. See [super::parallel_for].
+        ///
+        /// **Current version**: thread-safe
+        pub fn parallel_for<const N: usize, P>(
+            execp: TypedExecutionPolicy<P>,
+            func: impl Fn(P::Arg) + Send + Sync,
+        ) -> Result<(), StatementError>
+        where
+            P: Policy<N>,
+        {
+            super::parallel_for(execp.untyped(), |arg| func(P::arg(arg)))
+        }
+
+        /// Rank-checked Parallel Reduce statement. See [super::parallel_reduce].
+        ///
+        /// **Current version**: thread-safe
+        pub fn parallel_reduce<const N: usize, P, R>(
+            execp: TypedExecutionPolicy<P>,
+            op: R,
+            func: impl Fn(P::Arg) -> R::Value + Send + Sync,
+        ) -> Result<R::Value, StatementError>
+        where
+            P: Policy<N>,
+            R: Reducer + Sync,
+            R::Value: Send,
+        {
+            super::parallel_reduce(execp.untyped(), op, |arg| func(P::arg(arg)))
+        }
+
+        /// Rank-checked Fallible Parallel For statement. See [super::try_parallel_for].
+        ///
+        /// **Current version**: thread-safe
+        pub fn try_parallel_for<const N: usize, P, E>(
+            execp: TypedExecutionPolicy<P>,
+            func: impl Fn(P::Arg) -> Result<(), E> + Send + Sync,
+        ) -> Result<(), StatementError>
+        where
+            P: Policy<N>,
+            E: Into<KernelError>,
+        {
+            super::try_parallel_for(execp.untyped(), |arg| func(P::arg(arg)))
+        }
+
+        /// Rank-checked Parallel Scan statement. See [super::parallel_scan].
+        ///
+        /// **Current version**: thread-safe
+        pub fn parallel_scan<T>(
+            execp: TypedExecutionPolicy<Range1D>,
+            mode: ScanMode,
+            func: impl Fn(usize) -> T + Send + Sync,
+        ) -> Result<Vec<T>, StatementError>
+        where
+            T: DataTraits + Add<Output = T> + Send + Sync,
+        {
+            super::parallel_scan(execp.untyped(), mode, |arg| func(Range1D::arg(arg)))
+        }
+
+        /// Returns the execution space used by statements built without a policy, e.g.
+        /// [parallel_for_n]. Depends on enabled feature(s).
+        ///
+        /// **Current version**: thread-safe, [ExecutionSpace::DeviceCPU]
+        pub fn default_space() -> ExecutionSpace {
+            ExecutionSpace::DeviceCPU
+        }
+
+        /// Parallel For statement over `0..n`, using the [default_space] & scheduling.
+        ///
+        /// **Current version**: thread-safe
+        pub fn parallel_for_n(
+            n: usize,
+            func: impl Fn(usize) + Send + Sync,
+        ) -> Result<(), StatementError> {
+            parallel_for(default_execp(Range1D(0..n)), func)
+        }
+
+        /// Parallel For statement over the indices of an array of dimensions `dims`, using
+        /// the [default_space] & scheduling.
+        ///
+        /// **Current version**: thread-safe
+        pub fn parallel_for_md<const N: usize>(
+            dims: [usize; N],
+            func: impl Fn([usize; N]) + Send + Sync,
+        ) -> Result<(), StatementError> {
+            parallel_for(default_execp(MDRange::from_dims(dims)), func)
+        }
+
+        impl<P> PolicyBuilder<P> {
+            /// Rank-checked Parallel For statement using the built policy. See
+            /// [parallel_for].
+            ///
+            /// **Current version**: thread-safe
+            pub fn parallel_for<const N: usize>(
+                self,
+                func: impl Fn(P::Arg) + Send + Sync,
+            ) -> Result<(), StatementError>
+            where
+                P: Policy<N>,
+            {
+                self.run(|execp| parallel_for(execp, func))
+            }
+
+            /// Rank-checked Parallel Reduce statement using the built policy. See
+            /// [parallel_reduce].
+            ///
+            /// **Current version**: thread-safe
+            pub fn parallel_reduce<const N: usize, R>(
+                self,
+                op: R,
+                func: impl Fn(P::Arg) -> R::Value + Send + Sync,
+            ) -> Result<R::Value, StatementError>
+            where
+                P: Policy<N>,
+                R: Reducer + Sync,
+                R::Value: Send,
+            {
+                self.run(|execp| parallel_reduce(execp, op, func))
+            }
+        }
+    } else {
+        /// Rank-checked Parallel For statement. See [super::parallel_for].
+        ///
+        /// **Current version**: no feature
+        pub fn parallel_for<const N: usize, P>(
+            execp: TypedExecutionPolicy<P>,
+            mut func: impl FnMut(P::Arg),
+        ) -> Result<(), StatementError>
+        where
+            P: Policy<N>,
+        {
+            super::parallel_for(execp.untyped(), |arg| func(P::arg(arg)))
+        }
+
+        /// Rank-checked Parallel Reduce statement. See [super::parallel_reduce].
+        ///
+        /// **Current version**: no feature
+        pub fn parallel_reduce<const N: usize, P, R>(
+            execp: TypedExecutionPolicy<P>,
+            op: R,
+            mut func: impl FnMut(P::Arg) -> R::Value,
+        ) -> Result<R::Value, StatementError>
+        where
+            P: Policy<N>,
+            R: Reducer,
+        {
+            super::parallel_reduce(execp.untyped(), op, |arg| func(P::arg(arg)))
+        }
+
+        /// Rank-checked Fallible Parallel For statement. See [super::try_parallel_for].
+        ///
+        /// **Current version**: no feature
+        pub fn try_parallel_for<const N: usize, P, E>(
+            execp: TypedExecutionPolicy<P>,
+            mut func: impl FnMut(P::Arg) -> Result<(), E>,
+        ) -> Result<(), StatementError>
+        where
+            P: Policy<N>,
+            E: Into<KernelError>,
+        {
+            super::try_parallel_for(execp.untyped(), |arg| func(P::arg(arg)))
+        }
+
+        /// Rank-checked Parallel Scan statement. See [super::parallel_scan].
+        ///
+        /// **Current version**: no feature
+        pub fn parallel_scan<T>(
+            execp: TypedExecutionPolicy<Range1D>,
+            mode: ScanMode,
+            mut func: impl FnMut(usize) -> T,
+        ) -> Result<Vec<T>, StatementError>
+        where
+            T: DataTraits + Add<Output = T>,
+        {
+            super::parallel_scan(execp.untyped(), mode, |arg| func(Range1D::arg(arg)))
+        }
+
+        /// Returns the execution space used by statements built without a policy, e.g.
+        /// [parallel_for_n]. Depends on enabled feature(s).
+        ///
+        /// **Current version**: no feature, [ExecutionSpace::Serial]
+        pub fn default_space() -> ExecutionSpace {
+            ExecutionSpace::Serial
+        }
+
+        /// Parallel For statement over `0..n`, using the [default_space] & scheduling.
+        ///
+        /// **Current version**: no feature
+        pub fn parallel_for_n(n: usize, func: impl FnMut(usize)) -> Result<(), StatementError> {
+            parallel_for(default_execp(Range1D(0..n)), func)
+        }
+
+        /// Parallel For statement over the indices of an array of dimensions `dims`, using
+        /// the [default_space] & scheduling.
+        ///
+        /// **Current version**: no feature
+        pub fn parallel_for_md<const N: usize>(
+            dims: [usize; N],
+            func: impl FnMut([usize; N]),
+        ) -> Result<(), StatementError> {
+            parallel_for(default_execp(MDRange::from_dims(dims)), func)
+        }
+
+        impl<P> PolicyBuilder<P> {
+            /// Rank-checked Parallel For statement using the built policy. See
+            /// [parallel_for].
+            ///
+            /// **Current version**: no feature
+            pub fn parallel_for<const N: usize>(
+                self,
+                func: impl FnMut(P::Arg),
+            ) -> Result<(), StatementError>
+            where
+                P: Policy<N>,
+            {
+                self.run(|execp| parallel_for(execp, func))
+            }
+
+            /// Rank-checked Parallel Reduce statement using the built policy. See
+            /// [parallel_reduce].
+            ///
+            /// **Current version**: no feature
+            pub fn parallel_reduce<const N: usize, R>(
+                self,
+                op: R,
+                func: impl FnMut(P::Arg) -> R::Value,
+            ) -> Result<R::Value, StatementError>
+            where
+                P: Policy<N>,
+                R: Reducer,
+            {
+                self.run(|execp| parallel_reduce(execp, op, func))
+            }
+        }
+    }
+}
+
+fn default_execp<P>(policy: P) -> TypedExecutionPolicy<P> {
+    TypedExecutionPolicy {
+        space: default_space(),
+        ..TypedExecutionPolicy::new(policy)
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::{
+        instance::CpuInstance,
+        parameters::{
+            ChunkPredicate, ExecutionSpace, Iterate, MDRange, Range1D, ReduceOp, Schedule, Team,
+            TiledMDRange,
+        },
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn typed_policies() {
+        let instance = CpuInstance::new(4).unwrap();
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: Range1D(0..100),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let sum = parallel_reduce(execp, ReduceOp::Sum, |i| i).unwrap();
+            assert_eq!(sum, 4950);
+
+            let cells: Vec<AtomicUsize> = (0..6 * 7).map(|_| AtomicUsize::new(0)).collect();
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: TiledMDRange {
+                    ranges: [0..6, 0..7],
+                    tile: [4, 4],
+                    outer: Iterate::Left,
+                    inner: Iterate::Right,
+                },
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            parallel_for(execp, |[i, j]| {
+                cells[7 * i + j].store(10 * i + j, Ordering::Relaxed)
+            })
+            .unwrap();
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: MDRange::from_dims([6, 7]),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let max = parallel_reduce(execp, ReduceOp::Max, |[i, j]| {
+                cells[7 * i + j].load(Ordering::Relaxed)
+            })
+            .unwrap();
+            assert_eq!(max, 56);
+
+            // members of a team are executed by distinct threads
+            let team_space = match space {
+                ExecutionSpace::DeviceCPU => ExecutionSpace::DeviceCPUInstance(instance),
+                _ => space,
+            };
+            let execp = TypedExecutionPolicy {
+                space: team_space,
+                policy: Team {
+                    league_size: 3,
+                    team_size: 4,
+                    vector_size: 1,
+                    scratch_size: [0, 0],
+                },
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let ranks = AtomicUsize::new(0);
+            parallel_for(execp, |handle| {
+                let rank = handle.league_rank() * handle.team_size() + handle.team_rank();
+                ranks.fetch_add(rank, Ordering::Relaxed);
+            })
+            .unwrap();
+            assert_eq!(ranks.into_inner(), (0..12).sum());
+
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: Range1D(1..5),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let offsets = parallel_scan(execp.clone(), ScanMode::Exclusive, |i| i).unwrap();
+            assert_eq!(offsets, vec![0, 1, 3, 6]);
+            let res = try_parallel_for(execp, |i| if i % 2 == 0 { Err("even") } else { Ok(()) });
+            assert!(matches!(res, Err(StatementError::Kernel(errs)) if errs.len() == 2));
+        }
+        instance.release();
+    }
+
+    #[test]
+    fn policy_builder() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            let sum = PolicyBuilder::range(0..100)
+                .space(space)
+                .schedule(Schedule::Dynamic)
+                .chunk(16)
+                .label("typed::policy_builder")
+                .parallel_reduce(ReduceOp::Sum, |i| i)
+                .unwrap();
+            assert_eq!(sum, 4950);
+
+            let count = AtomicUsize::new(0);
+            PolicyBuilder::md_range([0..4, 1..3])
+                .space(space)
+                .parallel_for(|[i, j]| {
+                    count.fetch_add(i * j, Ordering::Relaxed);
+                })
+                .unwrap();
+            assert_eq!(count.swap(0, Ordering::Relaxed), 6 * 3);
+
+            PolicyBuilder::team(3, 1, 1)
+                .space(space)
+                .parallel_for(|handle| {
+                    count.fetch_add(handle.league_rank(), Ordering::Relaxed);
+                })
+                .unwrap();
+            assert_eq!(count.swap(0, Ordering::Relaxed), 3);
+
+            // only chunks starting below 50 are executed
+            PolicyBuilder::range(0..100)
+                .space(space)
+                .chunk(10)
+                .chunk_predicate(ChunkPredicate::new(|bounds| bounds.start < 50))
+                .parallel_for(|_| {
+                    count.fetch_add(1, Ordering::Relaxed);
+                })
+                .unwrap();
+            assert_eq!(count.into_inner(), 50);
+        }
+    }
+
+    #[test]
+    fn default_policies() {
+        let sum = AtomicUsize::new(0);
+        parallel_for_n(10, |i| {
+            sum.fetch_add(i, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert_eq!(sum.swap(0, Ordering::Relaxed), 45);
+
+        parallel_for_md([3, 4, 2], |[i, j, k]| {
+            sum.fetch_add(100 * i + 10 * j + k, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert_eq!(sum.into_inner(), 100 * 3 * 8 + 10 * 6 * 6 + 12);
+
+        let expected = if cfg!(any(feature = "threads", feature = "rayon")) {
+            ExecutionSpace::DeviceCPU
+        } else {
+            ExecutionSpace::Serial
+        };
+        assert_eq!(default_space(), expected);
+    }
+}
@@ -0,0 +1,760 @@
+//! runtime related code
+//!
+//! This module contains the global runtime context of the crate, akin to the
+//! `Kokkos::initialize` / `Kokkos::finalize` pair. The context holds the runtime
+//! configuration and the thread pool used by dispatch, when using the `threads` or
+//! `rayon` feature.
+//!
+//! Using the context is optional: statements executed while it is not initialized use
+//! default values (e.g. the global `rayon` pool). Unlike the global `rayon` pool, the
+//! context can be torn down using [`finalize`] and initialized again, which makes
+//! configuration changes possible between unit tests.
+//!
+//! The configuration can also be read from environment variables using
+//! [RuntimeConfig::from_env], e.g. to pin workers to cores when benchmarking, see
+//! [Pinning].
+//!
+//! Tests modifying the runtime should use [`scoped`], which serializes them with each
+//! other and restores a clean state even if the test panics.
+//!
+//! Applications embedding the crate can ask CPU workers to periodically hand their core
+//! back to the OS using [`enable_cooperative`]; this is independent of initialization.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::runtime::{self, RuntimeConfig};
+//!
+//! let config = RuntimeConfig {
+//!     num_threads: Some(2),
+//!     ..Default::default()
+//! };
+//! runtime::scoped(config, || {
+//!     assert!(runtime::is_initialized());
+//!     assert_eq!(runtime::num_threads(), 2);
+//! })
+//! .unwrap();
+//!
+//! assert!(!runtime::is_initialized());
+//! ```
+
+use std::{
+    fmt::Display,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex, MutexGuard, RwLock,
+    },
+    thread::available_parallelism,
+    time::Duration,
+};
+
+#[cfg(any(feature = "threads", feature = "rayon"))]
+use std::sync::Arc;
+#[cfg(feature = "threads")]
+use std::{
+    any::Any,
+    collections::VecDeque,
+    panic::AssertUnwindSafe,
+    sync::{Condvar, OnceLock},
+    thread::JoinHandle,
+};
+
+use crate::profiling;
+
+// Enums
+
+/// Enum used to classify possible errors occuring when managing the runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeError {
+    /// The runtime is already initialized.
+    AlreadyInitialized,
+    /// The runtime is not initialized.
+    NotInitialized,
+    /// The backend could not be set up according to the configuration.
+    Backend(&'static str),
+    /// An environment variable holds an invalid value.
+    Environment(&'static str),
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::AlreadyInitialized => write!(f, "runtime is already initialized"),
+            RuntimeError::NotInitialized => write!(f, "runtime is not initialized"),
+            RuntimeError::Backend(desc) => write!(f, "error during backend setup: {desc}"),
+            RuntimeError::Environment(desc) => write!(f, "invalid environment: {desc}"),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+// Context
+
+/// Thread pinning policy of CPU workers.
+///
+/// Policies can be parsed from their name, `unpinned`, `compact` or `spread`, or from a
+/// comma-separated list of core IDs for [Pinning::Explicit], e.g. `0,2,4,6`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Pinning {
+    /// Workers are scheduled freely by the OS.
+    #[default]
+    Unpinned,
+    /// Worker `i` is pinned to the `i`-th available core, wrapping around if there are
+    /// more workers than cores.
+    Compact,
+    /// Workers are spread evenly over the available cores, e.g. 4 workers on 16 cores
+    /// are pinned to the cores of index 0, 4, 8 & 12. This spreads workers over sockets
+    /// when cores of a socket are numbered consecutively.
+    Spread,
+    /// Worker `i` is pinned to the `i`-th core ID of the list, wrapping around if there
+    /// are more workers than IDs. Workers assigned to unavailable cores run unpinned.
+    Explicit(Vec<usize>),
+}
+
+impl FromStr for Pinning {
+    type Err = RuntimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "unpinned" => Ok(Pinning::Unpinned),
+            "compact" => Ok(Pinning::Compact),
+            "spread" => Ok(Pinning::Spread),
+            list => list
+                .split(',')
+                .map(|id| id.trim().parse::<usize>())
+                .collect::<Result<Vec<usize>, _>>()
+                .map(Pinning::Explicit)
+                .map_err(|_| {
+                    RuntimeError::Environment(
+                        "pinning must be unpinned, compact, spread or a list of core IDs",
+                    )
+                }),
+        }
+    }
+}
+
+/// Runtime configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    /// Number of threads used by CPU dispatch. Uses the available parallelism if `None`.
+    /// Ignored when no parallelization feature is enabled.
+    pub num_threads: Option<usize>,
+    /// Pinning policy of CPU workers. Ignored when no parallelization feature is enabled.
+    ///
+    /// Pinning is best-effort: workers that cannot be pinned run unpinned.
+    pub pinning: Pinning,
+}
+
+/// Name of the environment variable setting [RuntimeConfig::num_threads].
+pub const NUM_THREADS_VAR: &str = "KOKKOS_RS_NUM_THREADS";
+/// Name of the environment variable setting [RuntimeConfig::pinning], see [Pinning] for
+/// accepted values.
+pub const PINNING_VAR: &str = "KOKKOS_RS_PINNING";
+
+impl RuntimeConfig {
+    /// Build a configuration from the [NUM_THREADS_VAR] & [PINNING_VAR] environment
+    /// variables, unset variables keeping their default value.
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::runtime::{self, RuntimeConfig};
+    ///
+    /// // e.g. KOKKOS_RS_NUM_THREADS=8 KOKKOS_RS_PINNING=spread ./app
+    /// runtime::initialize(RuntimeConfig::from_env().unwrap()).unwrap();
+    /// # runtime::finalize().unwrap();
+    /// ```
+    pub fn from_env() -> Result<Self, RuntimeError> {
+        let num_threads = match std::env::var(NUM_THREADS_VAR) {
+            Ok(n) => Some(parse_num_threads(&n)?),
+            Err(_) => None,
+        };
+        let pinning = match std::env::var(PINNING_VAR) {
+            Ok(pinning) => pinning.parse()?,
+            Err(_) => Pinning::default(),
+        };
+        Ok(Self {
+            num_threads,
+            pinning,
+        })
+    }
+}
+
+/// Parse the value of [NUM_THREADS_VAR]. Zero threads is rejected, like any value that
+/// is not a positive integer.
+fn parse_num_threads(value: &str) -> Result<usize, RuntimeError> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or(RuntimeError::Environment(
+            "number of threads must be a positive integer",
+        ))
+}
+
+/// Global runtime context.
+struct Runtime {
+    config: RuntimeConfig,
+    #[cfg(feature = "rayon")]
+    pool: Arc<rayon::ThreadPool>,
+    #[cfg(feature = "threads")]
+    workers: Arc<WorkerPool>,
+}
+
+static RUNTIME: RwLock<Option<Runtime>> = RwLock::new(None);
+static SCOPE_LOCK: Mutex<()> = Mutex::new(());
+
+// The context stays consistent if a thread panics while holding the lock: all
+// writes are single assignments. Poisoning can therefore be ignored.
+fn read_runtime() -> std::sync::RwLockReadGuard<'static, Option<Runtime>> {
+    RUNTIME.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write_runtime() -> std::sync::RwLockWriteGuard<'static, Option<Runtime>> {
+    RUNTIME.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Initialize the runtime using the given configuration.
+pub fn initialize(config: RuntimeConfig) -> Result<(), RuntimeError> {
+    let mut runtime = write_runtime();
+    if runtime.is_some() {
+        return Err(RuntimeError::AlreadyInitialized);
+    }
+    #[cfg(feature = "rayon")]
+    let (pinning, n_workers) = (config.pinning.clone(), total_threads(&config));
+    #[cfg(feature = "rayon")]
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.num_threads.unwrap_or(0))
+        .start_handler(move |idx| pin_to_core(&pinning, idx, n_workers))
+        .build()
+        .map_err(|_| RuntimeError::Backend("could not build the thread pool"))?;
+    #[cfg(feature = "threads")]
+    let workers = {
+        let n_workers = total_threads(&config);
+        Arc::new(WorkerPool::new(n_workers, &config.pinning, 0, n_workers)?)
+    };
+    *runtime = Some(Runtime {
+        config,
+        #[cfg(feature = "rayon")]
+        pool: Arc::new(pool),
+        #[cfg(feature = "threads")]
+        workers,
+    });
+    Ok(())
+}
+
+/// Tear down the runtime.
+///
+/// Owned resources (e.g. the thread pool) are released, profiling is disabled,
+/// collected records are discarded and profiling tools are unregistered. The runtime can then be initialized again.
+pub fn finalize() -> Result<(), RuntimeError> {
+    write_runtime().take().ok_or(RuntimeError::NotInitialized)?;
+    profiling::disable();
+    profiling::take_records();
+    profiling::clear_tools();
+    disable_cooperative();
+    Ok(())
+}
+
+/// Returns `true` if the runtime is currently initialized.
+pub fn is_initialized() -> bool {
+    read_runtime().is_some()
+}
+
+/// Returns the configuration of the runtime, if it is initialized.
+pub fn config() -> Option<RuntimeConfig> {
+    read_runtime().as_ref().map(|rt| rt.config.clone())
+}
+
+/// Returns the number of threads used by CPU dispatch. When called from a statement
+/// dispatched on an [instance][crate::routines::instance], returns the number of
+/// threads of the instance.
+pub fn num_threads() -> usize {
+    if let Some(instance) = crate::routines::instance::current() {
+        return instance.num_threads();
+    }
+    total_threads(&config().unwrap_or_default())
+}
+
+/// Returns the number of threads used by CPU dispatch outside of instances, given the
+/// configuration of the runtime.
+fn total_threads(config: &RuntimeConfig) -> usize {
+    config
+        .num_threads
+        .unwrap_or_else(|| available_parallelism().map(|n| n.get()).unwrap_or(1))
+}
+
+/// Returns the core worker `idx` is pinned to according to `pinning`, out of `n_cores`
+/// available cores & `n_workers` workers. Returns an index for [Pinning::Compact] &
+/// [Pinning::Spread], a core ID for [Pinning::Explicit].
+#[cfg(any(feature = "threads", feature = "rayon"))]
+fn core_of(pinning: &Pinning, idx: usize, n_workers: usize, n_cores: usize) -> Option<usize> {
+    match pinning {
+        Pinning::Unpinned => None,
+        Pinning::Compact => Some(idx % n_cores),
+        Pinning::Spread => {
+            let n_workers = n_workers.max(1);
+            Some((idx % n_workers) * n_cores / n_workers % n_cores)
+        }
+        Pinning::Explicit(ids) => (!ids.is_empty()).then(|| ids[idx % ids.len()]),
+    }
+}
+
+/// Pin the calling thread according to `pinning`, `idx` being the index of the worker
+/// out of `n_workers`.
+#[cfg(any(feature = "threads", feature = "rayon"))]
+pub(crate) fn pin_to_core(pinning: &Pinning, idx: usize, n_workers: usize) {
+    if *pinning == Pinning::Unpinned {
+        return;
+    }
+    let Some(core_ids) = core_affinity::get_core_ids().filter(|ids| !ids.is_empty()) else {
+        return;
+    };
+    let core_id = match (pinning, core_of(pinning, idx, n_workers, core_ids.len())) {
+        (Pinning::Explicit(_), Some(id)) => core_ids.into_iter().find(|c| c.id == id),
+        (_, Some(k)) => Some(core_ids[k]),
+        (_, None) => None,
+    };
+    if let Some(core_id) = core_id {
+        // failures are ignored, see RuntimeConfig::pinning
+        let _ = core_affinity::set_for_current(core_id);
+    }
+}
+
+// Worker pool
+
+/// Work shared by the tasks of a [WorkerPool::broadcast] call.
+#[cfg(feature = "threads")]
+struct Job {
+    /// Lifetime-erased work, see [WorkerPool::broadcast].
+    work: &'static (dyn Fn(usize) + Sync),
+    /// Number of tasks not completed yet.
+    remaining: Mutex<usize>,
+    done: Condvar,
+    /// Payload of the first task that panicked.
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+/// Queue of tasks, each task being a job & the index passed to its work.
+#[cfg(feature = "threads")]
+struct TaskQueue {
+    tasks: VecDeque<(Arc<Job>, usize)>,
+    shutdown: bool,
+}
+
+/// State shared by a pool & its workers.
+#[cfg(feature = "threads")]
+struct PoolShared {
+    queue: Mutex<TaskQueue>,
+    available: Condvar,
+}
+
+/// Persistent workers of the `threads` backend.
+///
+/// Workers are spawned & pinned once, then park until tasks are queued. Pools are owned
+/// by the runtime & by instances; statements dispatched while the runtime is not
+/// initialized use a default pool, created at the first dispatch.
+#[cfg(feature = "threads")]
+pub(crate) struct WorkerPool {
+    shared: Arc<PoolShared>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+#[cfg(feature = "threads")]
+impl WorkerPool {
+    /// Spawn `n_workers` workers. Worker `i` is pinned according to `pinning` as worker
+    /// `first + i` out of `n_pinned`, see [Pinning].
+    pub(crate) fn new(
+        n_workers: usize,
+        pinning: &Pinning,
+        first: usize,
+        n_pinned: usize,
+    ) -> Result<Self, RuntimeError> {
+        let shared = Arc::new(PoolShared {
+            queue: Mutex::new(TaskQueue {
+                tasks: VecDeque::new(),
+                shutdown: false,
+            }),
+            available: Condvar::new(),
+        });
+        let mut pool = Self {
+            shared,
+            handles: Vec::with_capacity(n_workers),
+        };
+        for idx in 0..n_workers.max(1) {
+            let (shared, pinning) = (pool.shared.clone(), pinning.clone());
+            let handle = std::thread::Builder::new()
+                .name(format!("kokkos-rs-worker-{}", first + idx))
+                .spawn(move || {
+                    pin_to_core(&pinning, first + idx, n_pinned);
+                    work_loop(&shared)
+                })
+                // workers spawned so far are joined when the pool is dropped
+                .map_err(|_| RuntimeError::Backend("could not spawn worker threads"))?;
+            pool.handles.push(handle);
+        }
+        Ok(pool)
+    }
+
+    /// Returns the number of workers of the pool.
+    pub(crate) fn num_workers(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Execute `work` for each task index of `0..n_tasks` on the workers of the pool,
+    /// and block until all tasks are complete. Results are returned in task order.
+    ///
+    /// If a task panics, the panic is resumed on the calling thread once all tasks are
+    /// complete.
+    pub(crate) fn broadcast<O: Send>(
+        &self,
+        n_tasks: usize,
+        work: impl Fn(usize) -> O + Sync,
+    ) -> Vec<O> {
+        if n_tasks == 0 {
+            return Vec::new();
+        }
+        let results: Vec<Mutex<Option<O>>> = (0..n_tasks).map(|_| Mutex::new(None)).collect();
+        let run = |idx: usize| {
+            let res = work(idx);
+            *lock(&results[idx]) = Some(res);
+        };
+        let run: &(dyn Fn(usize) + Sync) = &run;
+        // SAFETY: the work is only called by tasks of this job, & this function does not
+        // return before all of them are complete
+        let run: &'static (dyn Fn(usize) + Sync) = unsafe { std::mem::transmute(run) };
+        let job = Arc::new(Job {
+            work: run,
+            remaining: Mutex::new(n_tasks),
+            done: Condvar::new(),
+            panic: Mutex::new(None),
+        });
+        lock(&self.shared.queue)
+            .tasks
+            .extend((0..n_tasks).map(|idx| (job.clone(), idx)));
+        self.shared.available.notify_all();
+
+        let remaining = lock(&job.remaining);
+        drop(
+            job.done
+                .wait_while(remaining, |n| *n > 0)
+                .unwrap_or_else(|e| e.into_inner()),
+        );
+        if let Some(payload) = lock(&job.panic).take() {
+            std::panic::resume_unwind(payload);
+        }
+        results
+            .into_iter()
+            .map(|res| {
+                res.into_inner()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .expect("all tasks are complete")
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "threads")]
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        lock(&self.shared.queue).shutdown = true;
+        self.shared.available.notify_all();
+        self.handles.drain(..).for_each(|handle| {
+            let _ = handle.join();
+        });
+    }
+}
+
+/// Lock `mutex`, ignoring poisoning: panics of tasks are caught before they can poison
+/// the locks of the pool.
+#[cfg(feature = "threads")]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Main loop of a worker: execute queued tasks until the pool shuts down.
+#[cfg(feature = "threads")]
+fn work_loop(shared: &PoolShared) {
+    loop {
+        let (job, idx) = {
+            let mut queue = lock(&shared.queue);
+            loop {
+                if let Some(task) = queue.tasks.pop_front() {
+                    break task;
+                }
+                if queue.shutdown {
+                    return;
+                }
+                queue = shared
+                    .available
+                    .wait(queue)
+                    .unwrap_or_else(|e| e.into_inner());
+            }
+        };
+        let res = std::panic::catch_unwind(AssertUnwindSafe(|| (job.work)(idx)));
+        if let Err(payload) = res {
+            lock(&job.panic).get_or_insert(payload);
+        }
+        let mut remaining = lock(&job.remaining);
+        *remaining -= 1;
+        if *remaining == 0 {
+            job.done.notify_all();
+        }
+    }
+}
+
+/// Returns the worker pool of the current instance or of the runtime, or the default
+/// pool if the runtime is not initialized.
+#[cfg(feature = "threads")]
+pub(crate) fn workers() -> Arc<WorkerPool> {
+    static DEFAULT: OnceLock<Arc<WorkerPool>> = OnceLock::new();
+
+    if let Some(pool) = crate::routines::instance::current_workers() {
+        return pool;
+    }
+    if let Some(rt) = read_runtime().as_ref() {
+        return rt.workers.clone();
+    }
+    DEFAULT
+        .get_or_init(|| {
+            let n_workers = total_threads(&RuntimeConfig::default());
+            let pool = WorkerPool::new(n_workers, &Pinning::Unpinned, 0, n_workers);
+            Arc::new(pool.expect("could not spawn the default worker pool"))
+        })
+        .clone()
+}
+
+/// Execute `op` in the thread pool of the current instance or of the runtime, or in the
+/// global `rayon` pool if the runtime is not initialized.
+#[cfg(feature = "rayon")]
+pub(crate) fn install<R: Send>(op: impl FnOnce() -> R + Send) -> R {
+    if let Some(pool) = crate::routines::instance::current_pool() {
+        return pool.install(op);
+    }
+    // release the lock before executing so that op can use the runtime
+    let pool = read_runtime().as_ref().map(|rt| rt.pool.clone());
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+// Cooperative execution
+
+static COOPERATIVE: AtomicBool = AtomicBool::new(false);
+static PAUSE_NS: AtomicU64 = AtomicU64::new(0);
+
+/// Number of indices processed by a CPU worker between two cooperative points.
+pub const COOPERATIVE_CHUNK: usize = 4096;
+
+/// Make CPU workers pause every [`COOPERATIVE_CHUNK`] indices, so that the machine
+/// stays responsive during long statements.
+///
+/// Workers sleep for `pause` if it is non-zero, and yield their time slice to the OS
+/// otherwise. The flag can be toggled from any thread, including while a statement is
+/// running. This only affects the `threads` & `rayon` backends.
+pub fn enable_cooperative(pause: Duration) {
+    PAUSE_NS.store(pause.as_nanos() as u64, Ordering::Relaxed);
+    COOPERATIVE.store(true, Ordering::Relaxed);
+}
+
+/// Stop pausing CPU workers between chunks.
+pub fn disable_cooperative() {
+    COOPERATIVE.store(false, Ordering::Relaxed);
+}
+
+/// Returns `true` if CPU workers currently pause between chunks.
+pub fn is_cooperative() -> bool {
+    COOPERATIVE.load(Ordering::Relaxed)
+}
+
+/// Pause the calling thread if cooperative execution is enabled. Called by dispatch
+/// routines between chunks; it can also be called from long-running kernels.
+#[inline(always)]
+pub fn cooperative_point() {
+    if is_cooperative() {
+        match PAUSE_NS.load(Ordering::Relaxed) {
+            0 => std::thread::yield_now(),
+            ns => std::thread::sleep(Duration::from_nanos(ns)),
+        }
+    }
+}
+
+/// Finalizes the runtime when dropped.
+struct ScopeGuard {
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        // body may have finalized the runtime itself
+        let _ = finalize();
+    }
+}
+
+/// Execute `body` with a runtime initialized using `config`, then finalize it.
+///
+/// Calls to this function are serialized, so that tests using it do not depend on
+/// execution order. The runtime is finalized even if `body` panics. Fails if the
+/// runtime was initialized outside of this function.
+pub fn scoped<R>(config: RuntimeConfig, body: impl FnOnce() -> R) -> Result<R, RuntimeError> {
+    let lock = SCOPE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    initialize(config)?;
+    let _guard = ScopeGuard { _lock: lock };
+    Ok(body())
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "threads")]
+    fn worker_pool() {
+        let pool = WorkerPool::new(3, &Pinning::Unpinned, 0, 3).unwrap();
+        assert_eq!(pool.num_workers(), 3);
+        // workers are reused by successive calls
+        let names: std::collections::HashSet<String> = (0..10)
+            .flat_map(|_| pool.broadcast(3, |_| std::thread::current().name().map(str::to_owned)))
+            .map(|name| name.unwrap())
+            .collect();
+        assert!(names.len() <= 3);
+        // results are returned in task order, more tasks than workers is fine
+        assert_eq!(
+            pool.broadcast(16, |i| i * i),
+            (0..16).map(|i| i * i).collect::<Vec<_>>()
+        );
+        assert!(pool.broadcast(0, |i| i).is_empty());
+
+        let res = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.broadcast(4, |i| assert_ne!(i, 2, "task failure"))
+        }));
+        assert!(res.is_err());
+        // the pool survives panicking tasks
+        assert_eq!(pool.broadcast(2, |i| i + 1), vec![1, 2]);
+    }
+
+    #[test]
+    fn reinitialize() {
+        scoped(RuntimeConfig::default(), || {
+            assert_eq!(
+                initialize(RuntimeConfig::default()),
+                Err(RuntimeError::AlreadyInitialized)
+            );
+            finalize().unwrap();
+            assert_eq!(finalize(), Err(RuntimeError::NotInitialized));
+            initialize(RuntimeConfig {
+                num_threads: Some(3),
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(num_threads(), 3);
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn pinned_workers() {
+        use crate::{
+            functor::KernelArgs,
+            routines::{
+                parallel_for,
+                parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+            },
+        };
+        use std::sync::atomic::AtomicUsize;
+
+        let count = AtomicUsize::new(0);
+        for pinning in [
+            Pinning::Compact,
+            Pinning::Spread,
+            Pinning::Explicit(vec![0]),
+        ] {
+            let config = RuntimeConfig {
+                num_threads: Some(3),
+                pinning,
+            };
+            scoped(config, || {
+                let execp = ExecutionPolicy {
+                    space: ExecutionSpace::DeviceCPU,
+                    range: RangePolicy::RangePolicy(0..1000),
+                    schedule: Schedule::Static,
+                    chunk_size: None,
+                    chunk_predicate: None,
+                };
+                let kernel = |_: KernelArgs<1>| {
+                    count.fetch_add(1, Ordering::Relaxed);
+                };
+                parallel_for(execp, kernel).unwrap();
+            })
+            .unwrap();
+        }
+        assert_eq!(count.load(Ordering::Relaxed), 3000);
+    }
+
+    #[test]
+    fn num_threads_variable() {
+        assert_eq!(parse_num_threads(" 8 "), Ok(8));
+        for value in ["0", "-2", "", "many"] {
+            assert!(matches!(
+                parse_num_threads(value),
+                Err(RuntimeError::Environment(_))
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "threads", feature = "rayon"))]
+    fn pinning_policies() {
+        assert_eq!("Spread".parse(), Ok(Pinning::Spread));
+        assert_eq!(" 0, 2,4".parse(), Ok(Pinning::Explicit(vec![0, 2, 4])));
+        assert!(matches!(
+            "scatter".parse::<Pinning>(),
+            Err(RuntimeError::Environment(_))
+        ));
+
+        let cores = |pinning: &Pinning, n_workers: usize| -> Vec<Option<usize>> {
+            (0..n_workers)
+                .map(|idx| core_of(pinning, idx, n_workers, 16))
+                .collect()
+        };
+        assert_eq!(cores(&Pinning::Unpinned, 2), [None, None]);
+        assert_eq!(cores(&Pinning::Compact, 3), [Some(0), Some(1), Some(2)]);
+        assert_eq!(
+            cores(&Pinning::Spread, 4),
+            [Some(0), Some(4), Some(8), Some(12)]
+        );
+        assert_eq!(
+            cores(&Pinning::Explicit(vec![3, 7]), 3),
+            [Some(3), Some(7), Some(3)]
+        );
+    }
+
+    #[test]
+    fn cooperative_flag() {
+        scoped(RuntimeConfig::default(), || {
+            enable_cooperative(Duration::from_micros(1));
+            assert!(is_cooperative());
+            cooperative_point();
+        })
+        .unwrap();
+        assert!(!is_cooperative());
+    }
+
+    #[test]
+    fn finalize_on_panic() {
+        let res = std::panic::catch_unwind(|| {
+            scoped(RuntimeConfig::default(), || panic!("test failure")).unwrap();
+        });
+        assert!(res.is_err());
+        scoped(RuntimeConfig::default(), || {
+            profiling::enable();
+        })
+        .unwrap();
+        assert!(!profiling::is_enabled());
+    }
+}
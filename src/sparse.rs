@@ -0,0 +1,263 @@
+//! sparse linear algebra related code
+//!
+//! This module contains sparse data structures stored in views, following Kokkos
+//! Kernels, and the kernels using them:
+//!
+//! - [StaticCrsGraph]: compressed row storage (CRS) of the structure of a sparse
+//!   matrix. The entries of row `i` are the column indices stored between offsets
+//!   `row_map[i]` & `row_map[i + 1]` of the `entries` view.
+//! - [CrsMatrix]: graph & values of a sparse matrix.
+//! - [`spmv`]: sparse matrix-vector multiply, `y = alpha * a * x + beta * y`.
+//!
+//! Like [blas][crate::blas] operations, kernels take the [ExecutionSpace] & [Schedule]
+//! of their dispatch. Rows are distributed over computational ressources; matrices with
+//! unbalanced rows benefit from a [Schedule::Dynamic] schedule.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     routines::parameters::{ExecutionSpace, Schedule},
+//!     sparse::{spmv, CrsMatrix},
+//!     view::{parameters::Layout, ViewOwned},
+//! };
+//!
+//! // (2 0 1)
+//! // (0 3 0)
+//! let a = CrsMatrix::try_new(3, vec![0, 2, 3], vec![0, 2, 1], vec![2.0, 1.0, 3.0]).unwrap();
+//! let x: ViewOwned<'_, 1, f64> = ViewOwned::new_from_data(vec![1.0; 3], Layout::Right, [3]);
+//! let mut y: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [2]);
+//!
+//! spmv(ExecutionSpace::DeviceCPU, Schedule::Dynamic, 1.0, &a, &x, 0.0, &mut y).unwrap();
+//! assert_eq!(y.get([0]), 3.0);
+//! assert_eq!(y.get([1]), 3.0);
+//! ```
+
+use std::ops::Mul;
+
+use crate::{
+    blas::BlasScalar,
+    routines::{
+        parameters::{ExecutionSpace, Range1D, Schedule, TypedExecutionPolicy},
+        typed::parallel_for,
+        StatementError,
+    },
+    view::{
+        parameters::{DataTraits, Layout},
+        ViewBase, ViewError, ViewOwned,
+    },
+};
+
+/// Structure of a sparse matrix, using compressed row storage.
+#[derive(Debug)]
+pub struct StaticCrsGraph {
+    row_map: ViewOwned<'static, 1, usize>,
+    entries: ViewOwned<'static, 1, usize>,
+}
+
+impl StaticCrsGraph {
+    /// Create a graph from its row offsets & column indices.
+    ///
+    /// An error is returned if `row_map` is empty, does not start at 0, is decreasing, or
+    /// does not end at the number of entries.
+    pub fn try_new(row_map: Vec<usize>, entries: Vec<usize>) -> Result<Self, ViewError<'static>> {
+        if row_map.first() != Some(&0) || row_map.last() != Some(&entries.len()) {
+            return Err(ViewError::DimensionMismatch(
+                "Row offsets must start at 0 & end at the number of entries",
+            ));
+        }
+        if row_map.windows(2).any(|w| w[0] > w[1]) {
+            return Err(ViewError::ValueError("Row offsets must not decrease"));
+        }
+        let (n_offsets, nnz) = (row_map.len(), entries.len());
+        Ok(Self {
+            row_map: ViewOwned::try_new_from_data(row_map, Layout::Right, [n_offsets])?,
+            entries: ViewOwned::try_new_from_data(entries, Layout::Right, [nnz])?,
+        })
+    }
+
+    /// Returns the number of rows of the graph.
+    pub fn num_rows(&self) -> usize {
+        self.row_map.dims()[0] - 1
+    }
+
+    /// Returns the number of entries of the graph.
+    pub fn nnz(&self) -> usize {
+        self.entries.dims()[0]
+    }
+
+    /// Returns the view of row offsets, of length `num_rows() + 1`.
+    pub fn row_map(&self) -> &ViewOwned<'static, 1, usize> {
+        &self.row_map
+    }
+
+    /// Returns the view of column indices.
+    pub fn entries(&self) -> &ViewOwned<'static, 1, usize> {
+        &self.entries
+    }
+
+    /// Returns the range of offsets of the entries of row `i`.
+    pub fn row_range(&self, i: usize) -> std::ops::Range<usize> {
+        self.row_map.get([i])..self.row_map.get([i + 1])
+    }
+}
+
+/// Sparse matrix, using compressed row storage.
+#[derive(Debug)]
+pub struct CrsMatrix<T>
+where
+    T: DataTraits + 'static,
+{
+    graph: StaticCrsGraph,
+    values: ViewOwned<'static, 1, T>,
+    num_cols: usize,
+}
+
+impl<T> CrsMatrix<T>
+where
+    T: DataTraits + 'static,
+{
+    /// Create a matrix of `num_cols` columns from its row offsets, column indices &
+    /// values.
+    ///
+    /// Returns the errors of [StaticCrsGraph::try_new], as well as an error if values do
+    /// not match entries, or if a column index is out of bounds.
+    pub fn try_new(
+        num_cols: usize,
+        row_map: Vec<usize>,
+        entries: Vec<usize>,
+        values: Vec<T>,
+    ) -> Result<Self, ViewError<'static>> {
+        if values.len() != entries.len() {
+            return Err(ViewError::DimensionMismatch(
+                "The number of values must match the number of entries",
+            ));
+        }
+        if entries.iter().any(|j| *j >= num_cols) {
+            return Err(ViewError::ValueError("Column index is out of bounds"));
+        }
+        let nnz = values.len();
+        Ok(Self {
+            graph: StaticCrsGraph::try_new(row_map, entries)?,
+            values: ViewOwned::try_new_from_data(values, Layout::Right, [nnz])?,
+            num_cols,
+        })
+    }
+
+    /// Returns the number of rows of the matrix.
+    pub fn num_rows(&self) -> usize {
+        self.graph.num_rows()
+    }
+
+    /// Returns the number of columns of the matrix.
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    /// Returns the number of stored entries of the matrix.
+    pub fn nnz(&self) -> usize {
+        self.graph.nnz()
+    }
+
+    /// Returns the structure of the matrix.
+    pub fn graph(&self) -> &StaticCrsGraph {
+        &self.graph
+    }
+
+    /// Returns the view of values, ordered like the entries of the graph.
+    pub fn values(&self) -> &ViewOwned<'static, 1, T> {
+        &self.values
+    }
+}
+
+// Statements
+
+/// Compute `y = alpha * a * x + beta * y`, `a` being a sparse matrix.
+///
+/// Rows of `a` are distributed over computational ressources. A
+/// [StatementError::DimensionMismatch] error is returned if dimensions of the operands
+/// are not compatible.
+pub fn spmv<T: BlasScalar + Mul<Output = T>>(
+    space: ExecutionSpace,
+    schedule: Schedule,
+    alpha: f64,
+    a: &CrsMatrix<T>,
+    x: &ViewBase<'_, 1, T>,
+    beta: f64,
+    y: &mut ViewBase<'_, 1, T>,
+) -> Result<(), StatementError> {
+    let (m, n) = (a.num_rows(), a.num_cols());
+    if x.dims() != [n] || y.dims() != [m] {
+        return Err(StatementError::DimensionMismatch);
+    }
+    let (entries, values) = (a.graph.entries(), a.values());
+    let execp = TypedExecutionPolicy {
+        space,
+        policy: Range1D(0..m),
+        schedule,
+        chunk_size: None,
+        chunk_predicate: None,
+    };
+    parallel_for(execp, |i| {
+        let ax_i = a.graph.row_range(i).fold(T::zero(), |acc, k| {
+            acc + values.get([k]) * x.get([entries.get([k])])
+        });
+        y.set([i], ax_i.scale(alpha) + y.get([i]).scale(beta))
+    })
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn construction() {
+        let a = CrsMatrix::try_new(4, vec![0, 1, 1, 3], vec![3, 0, 2], vec![1, 2, 3]).unwrap();
+        assert_eq!((a.num_rows(), a.num_cols(), a.nnz()), (3, 4, 3));
+        assert_eq!(a.graph().row_range(1), 1..1);
+        assert_eq!(a.graph().row_range(2), 1..3);
+
+        assert!(StaticCrsGraph::try_new(vec![], vec![]).is_err());
+        assert!(StaticCrsGraph::try_new(vec![1, 1], vec![0]).is_err());
+        assert!(StaticCrsGraph::try_new(vec![0, 2, 1, 2], vec![0, 0]).is_err());
+        assert!(CrsMatrix::try_new(2, vec![0, 1], vec![2], vec![1.0]).is_err());
+        assert!(CrsMatrix::<f64>::try_new(2, vec![0, 1], vec![1], vec![]).is_err());
+    }
+
+    #[test]
+    fn spmv_tridiagonal() {
+        // 1D laplacian
+        let n: usize = 100;
+        let (mut row_map, mut entries, mut values) = (vec![0], vec![], vec![]);
+        for i in 0..n {
+            for j in i.saturating_sub(1)..(i + 2).min(n) {
+                entries.push(j);
+                values.push(if i == j { 2.0 } else { -1.0 });
+            }
+            row_map.push(entries.len());
+        }
+        let a = CrsMatrix::try_new(n, row_map, entries, values).unwrap();
+        let x: ViewOwned<'_, 1, f64> =
+            ViewOwned::new_from_data((0..n).map(|i| i as f64).collect(), Layout::Right, [n]);
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            for schedule in [Schedule::Static, Schedule::Dynamic] {
+                let mut y: ViewOwned<'_, 1, f64> =
+                    ViewOwned::new_from_data(vec![1.0; n], Layout::Right, [n]);
+                spmv(space, schedule, 2.0, &a, &x, -1.0, &mut y).unwrap();
+                // the laplacian of a linear function vanishes, except at boundaries
+                assert_eq!(y.get([0]), -3.0);
+                assert!((1..n - 1).all(|i| y.get([i]) == -1.0));
+                assert_eq!(y.get([n - 1]), 2.0 * n as f64 - 1.0);
+
+                let mut z: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [n + 1]);
+                assert!(matches!(
+                    spmv(space, Schedule::Static, 1.0, &a, &x, 0.0, &mut z),
+                    Err(StatementError::DimensionMismatch)
+                ));
+            }
+        }
+    }
+}
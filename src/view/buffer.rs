@@ -0,0 +1,244 @@
+//! aligned buffer related code
+//!
+//! This module contains [AlignedBuffer], the storage of views owning their data. Unlike
+//! a `Vec`, whose allocation is only aligned on its element type, a buffer can be
+//! allocated using a larger alignment, e.g. 64 bytes for AVX-512 loads, or 2 MB so that
+//! the data starts on a huge page. Kernels & foreign code can then rely on the alignment
+//! of the data pointer, which is reported by
+//! [ViewBase::alignment][super::ViewBase::alignment].
+//!
+//! Buffers aligned on their element type use the same allocation as a `Vec`: they are
+//! converted from & into vectors without copy.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+//!
+//! let mut view: ViewOwned<'_, 2, f64> = ViewOwned::new_aligned(64, Layout::Right, [3, 5]);
+//! assert_eq!(view.alignment(), Some(64));
+//! assert_eq!(view.as_mut_slice().unwrap().as_ptr() as usize % 64, 0);
+//! ```
+
+use std::{
+    alloc::{self, Layout},
+    fmt::Debug,
+    mem::{align_of, size_of, ManuallyDrop},
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+use super::ViewError;
+
+/// Heap-allocated storage of elements of type `E`, aligned on a given number of bytes.
+///
+/// The buffer is accessed as a slice through [Deref] & [DerefMut].
+pub struct AlignedBuffer<E> {
+    ptr: NonNull<E>,
+    len: usize,
+    capacity: usize,
+    align: usize,
+}
+
+// SAFETY: the buffer owns its elements, like a `Vec`
+unsafe impl<E: Send> Send for AlignedBuffer<E> {}
+unsafe impl<E: Sync> Sync for AlignedBuffer<E> {}
+
+impl<E> AlignedBuffer<E> {
+    /// Allocate an empty buffer able to hold `capacity` elements, aligned on `align`
+    /// bytes, or on the alignment of `E` if it is larger.
+    ///
+    /// Returns a [ViewError::ValueError] error if `align` is not a power of two, and a
+    /// [ViewError::AllocationFailure] error if the storage cannot be allocated.
+    pub fn try_with_capacity(capacity: usize, align: usize) -> Result<Self, ViewError<'static>> {
+        if !align.is_power_of_two() {
+            return Err(ViewError::ValueError("Alignment must be a power of two"));
+        }
+        let align = align.max(align_of::<E>());
+        let layout = Self::layout(capacity, align).ok_or(ViewError::AllocationFailure(
+            "View size overflows the address space",
+        ))?;
+        let ptr = if layout.size() == 0 {
+            // nothing is allocated, the pointer only needs to be aligned
+            NonNull::new(std::ptr::without_provenance_mut(align))
+        } else {
+            // SAFETY: the layout has a non-zero size
+            NonNull::new(unsafe { alloc::alloc(layout) }.cast())
+        }
+        .ok_or(ViewError::AllocationFailure(
+            "Could not allocate the data of the View",
+        ))?;
+        Ok(Self {
+            ptr,
+            len: 0,
+            capacity,
+            align,
+        })
+    }
+
+    /// Layout of the allocation of `capacity` elements aligned on `align` bytes. It is
+    /// the layout of a `Vec` if `align` is the alignment of `E`.
+    fn layout(capacity: usize, align: usize) -> Option<Layout> {
+        Layout::from_size_align(capacity.checked_mul(size_of::<E>())?, align).ok()
+    }
+
+    /// Returns the alignment of the buffer, in bytes.
+    pub fn alignment(&self) -> usize {
+        self.align
+    }
+
+    /// Returns the number of elements the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Initialize the remaining capacity of the buffer using `f`.
+    pub fn fill_with(&mut self, mut f: impl FnMut() -> E) {
+        while self.len < self.capacity {
+            // SAFETY: the element lies within the allocation & is not initialized yet
+            unsafe { self.ptr.as_ptr().add(self.len).write(f()) };
+            self.len += 1;
+        }
+    }
+
+    /// Consumes the buffer to return a `Vec` holding its elements. Elements are copied
+    /// if the buffer is aligned on more than the alignment of `E`.
+    pub fn into_vec(self) -> Vec<E> {
+        let mut buffer = ManuallyDrop::new(self);
+        if buffer.align == align_of::<E>() {
+            // SAFETY: the buffer was allocated using the layout of a `Vec` of the same
+            // capacity; its ownership is transferred to the vector
+            unsafe { Vec::from_raw_parts(buffer.ptr.as_ptr(), buffer.len, buffer.capacity) }
+        } else {
+            let mut data = Vec::with_capacity(buffer.len);
+            // SAFETY: elements are moved into the vector, then the allocation is freed
+            // without dropping them
+            unsafe {
+                std::ptr::copy_nonoverlapping(buffer.ptr.as_ptr(), data.as_mut_ptr(), buffer.len);
+                data.set_len(buffer.len);
+                buffer.len = 0;
+                ManuallyDrop::drop(&mut buffer);
+            }
+            data
+        }
+    }
+
+    /// Reinterpret the buffer as a buffer of elements of type `U`, keeping its
+    /// allocation.
+    ///
+    /// # Safety
+    ///
+    /// `E` & `U` must have the same size & alignment, and values of `E` must be valid
+    /// values of `U`, e.g. `T` & `Atomic<T>`.
+    pub(crate) unsafe fn cast<U>(self) -> AlignedBuffer<U> {
+        let buffer = ManuallyDrop::new(self);
+        AlignedBuffer {
+            ptr: buffer.ptr.cast(),
+            len: buffer.len,
+            capacity: buffer.capacity,
+            align: buffer.align,
+        }
+    }
+}
+
+impl<E> Default for AlignedBuffer<E> {
+    fn default() -> Self {
+        Vec::new().into()
+    }
+}
+
+impl<E> From<Vec<E>> for AlignedBuffer<E> {
+    /// Take ownership of the allocation of `data`, aligned on the alignment of `E`.
+    fn from(data: Vec<E>) -> Self {
+        let mut data = ManuallyDrop::new(data);
+        Self {
+            // SAFETY: the pointer of a vector is never null
+            ptr: unsafe { NonNull::new_unchecked(data.as_mut_ptr()) },
+            len: data.len(),
+            capacity: data.capacity(),
+            align: align_of::<E>(),
+        }
+    }
+}
+
+impl<E> Deref for AlignedBuffer<E> {
+    type Target = [E];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the first `len` elements are initialized
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<E> DerefMut for AlignedBuffer<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: the first `len` elements are initialized & the buffer is exclusively
+        // borrowed
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<E: Debug> Debug for AlignedBuffer<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<E> Drop for AlignedBuffer<E> {
+    fn drop(&mut self) {
+        // SAFETY: the first `len` elements are initialized & dropped only once; the
+        // layout is the one used to allocate the buffer
+        unsafe {
+            std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(
+                self.ptr.as_ptr(),
+                self.len,
+            ));
+            let layout = Self::layout(self.capacity, self.align).expect("layout was checked");
+            if layout.size() != 0 {
+                alloc::dealloc(self.ptr.as_ptr().cast(), layout);
+            }
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alignment() {
+        for align in [1, 8, 64, 4096, 1 << 21] {
+            let mut buffer: AlignedBuffer<f64> =
+                AlignedBuffer::try_with_capacity(5, align).unwrap();
+            assert_eq!(buffer.len(), 0);
+            buffer.fill_with(|| 1.0);
+            assert_eq!(&*buffer, &[1.0; 5]);
+            assert_eq!(buffer.alignment(), align.max(8));
+            assert_eq!(buffer.as_ptr() as usize % buffer.alignment(), 0);
+            assert_eq!(buffer.into_vec(), vec![1.0; 5]);
+
+            let empty: AlignedBuffer<f64> = AlignedBuffer::try_with_capacity(0, align).unwrap();
+            assert_eq!(empty.as_ptr() as usize % empty.alignment(), 0);
+        }
+        assert!(AlignedBuffer::<f64>::try_with_capacity(4, 48).is_err());
+        assert!(AlignedBuffer::<f64>::try_with_capacity(usize::MAX, 64).is_err());
+    }
+
+    #[test]
+    fn vec_conversions() {
+        let data = vec![String::from("a"), String::from("b")];
+        let ptr = data.as_ptr();
+        let buffer = AlignedBuffer::from(data);
+        assert_eq!(buffer.as_ptr(), ptr);
+        let data = buffer.into_vec();
+        assert_eq!(data.as_ptr(), ptr);
+
+        // elements are moved, not dropped
+        let mut buffer: AlignedBuffer<String> = AlignedBuffer::try_with_capacity(2, 64).unwrap();
+        buffer.fill_with(|| String::from("c"));
+        assert_eq!(buffer.into_vec(), vec!["c", "c"]);
+    }
+}
@@ -0,0 +1,198 @@
+//! view comparison related code
+//!
+//! This module contains utilities used to validate the content of views, e.g. the output
+//! of a numerical kernel against a reference:
+//!
+//! - element-wise equality, see [ViewBase::elementwise_eq],
+//! - approximate comparison of floating point views using a [Tolerance], see
+//!   [ViewBase::approx_eq],
+//! - L1, L2 & Linf norms, computed using a `parallel_reduce` statement, see
+//!   [ViewBase::norm] & [ViewBase::distance].
+//!
+//! Views are compared index by index, hence views of different layouts holding the same
+//! values are equal. Like [ViewBase::iter], comparisons read values without updating
+//! access counters.
+//!
+//! Note that the [PartialEq] implementation of views compares data by reference, following
+//! Kokkos' `equal` algorithm, see [DataType][super::parameters::DataType].
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     routines::{audit::Tolerance, parameters::ExecutionSpace},
+//!     view::{compare::Norm, parameters::Layout, ViewOwned},
+//! };
+//!
+//! let a: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data(vec![1.0, 2.0, 3.0, 4.0], Layout::Right, [2, 2]);
+//! let b: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data(vec![1.0, 3.0, 2.0, 4.0], Layout::Left, [2, 2]);
+//! assert!(a.elementwise_eq(&b));
+//!
+//! let c: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data(vec![1.0, 2.0, 3.0, 4.001], Layout::Right, [2, 2]);
+//! assert!(!a.elementwise_eq(&c));
+//! assert!(a.approx_eq(&c, Tolerance::Absolute(1e-2)));
+//!
+//! let space = ExecutionSpace::DeviceCPU;
+//! assert_eq!(a.norm(space, Norm::L1).unwrap(), 10.0);
+//! assert!(a.distance(&c, space, Norm::Linf).unwrap() < 1e-2);
+//! ```
+
+use super::{parameters::DataTraits, ViewBase};
+use crate::routines::{
+    audit::Tolerance,
+    parameters::{ExecutionSpace, MDRange, ReduceOp, TypedExecutionPolicy},
+    typed::parallel_reduce,
+    StatementError,
+};
+
+/// Norms computed by [ViewBase::norm] & [ViewBase::distance].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Norm {
+    /// Sum of the absolute values.
+    L1,
+    /// Square root of the sum of the squared values.
+    L2,
+    /// Largest absolute value.
+    Linf,
+}
+
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits + PartialEq,
+{
+    /// Returns `true` if both views have the same dimensions & values, whatever their
+    /// layouts.
+    pub fn elementwise_eq(&self, other: &ViewBase<'_, N, T>) -> bool {
+        self.dims() == other.dims()
+            && self
+                .iter_indexed()
+                .all(|(idx, val)| other.load(other.flat_idx(idx)) == val)
+    }
+}
+
+impl<'a, const N: usize> ViewBase<'a, N, f64> {
+    /// Returns `true` if both views have the same dimensions & all their values are
+    /// considered equal according to `tolerance`. Relative tolerances are applied to the
+    /// values of `self`.
+    pub fn approx_eq(&self, other: &ViewBase<'_, N, f64>, tolerance: Tolerance) -> bool {
+        self.dims() == other.dims()
+            && self
+                .iter_indexed()
+                .all(|(idx, val)| tolerance.accepts(val, other.load(other.flat_idx(idx))))
+    }
+
+    /// Compute the norm of the view using a `parallel_reduce` statement dispatched on
+    /// `space`.
+    pub fn norm(&self, space: ExecutionSpace, norm: Norm) -> Result<f64, StatementError> {
+        reduce_norm(space, self.dims(), norm, |idx| {
+            self.load(self.flat_idx(idx))
+        })
+    }
+
+    /// Compute the norm of the difference between two views using a `parallel_reduce`
+    /// statement dispatched on `space`.
+    ///
+    /// A [StatementError::DimensionMismatch] error is returned if views have different
+    /// dimensions.
+    pub fn distance(
+        &self,
+        other: &ViewBase<'_, N, f64>,
+        space: ExecutionSpace,
+        norm: Norm,
+    ) -> Result<f64, StatementError> {
+        if self.dims() != other.dims() {
+            return Err(StatementError::DimensionMismatch);
+        }
+        reduce_norm(space, self.dims(), norm, |idx| {
+            self.load(self.flat_idx(idx)) - other.load(other.flat_idx(idx))
+        })
+    }
+}
+
+/// Compute the norm of the values returned by `value` over the index space `dims`.
+fn reduce_norm<const N: usize>(
+    space: ExecutionSpace,
+    dims: [usize; N],
+    norm: Norm,
+    value: impl Fn([usize; N]) -> f64 + Send + Sync,
+) -> Result<f64, StatementError> {
+    let execp = TypedExecutionPolicy {
+        space,
+        ..TypedExecutionPolicy::new(MDRange::from_dims(dims))
+    };
+    match norm {
+        Norm::L1 => parallel_reduce(execp, ReduceOp::Sum, |idx| value(idx).abs()),
+        Norm::L2 => parallel_reduce(execp, ReduceOp::Sum, |idx| value(idx).powi(2)).map(f64::sqrt),
+        // the identity of Max is used for empty views
+        Norm::Linf => {
+            parallel_reduce(execp, ReduceOp::Max, |idx| value(idx).abs()).map(|m| m.max(0.0))
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::{parameters::Layout, ViewOwned};
+
+    #[test]
+    fn equality() {
+        let a: ViewOwned<'_, 2, i32> =
+            ViewOwned::new_from_data((0..6).collect(), Layout::Right, [2, 3]);
+        let b: ViewOwned<'_, 2, i32> =
+            ViewOwned::new_from_data(vec![0, 3, 1, 4, 2, 5], Layout::Left, [2, 3]);
+        let c: ViewOwned<'_, 2, i32> =
+            ViewOwned::new_from_data((0..6).collect(), Layout::Right, [3, 2]);
+        assert!(a.elementwise_eq(&b));
+        assert!(!a.elementwise_eq(&c));
+
+        #[allow(unused_mut)]
+        let mut d: ViewOwned<'_, 2, i32> = ViewOwned::new(Layout::Right, [2, 3]);
+        (0..2).for_each(|i| (0..3).for_each(|j| d.set([i, j], (3 * i + j) as i32)));
+        assert!(a.elementwise_eq(&d));
+        d.set([1, 2], 0);
+        assert!(!a.elementwise_eq(&d));
+    }
+
+    #[test]
+    fn approx() {
+        let a: ViewOwned<'_, 1, f64> =
+            ViewOwned::new_from_data(vec![100.0, 200.0], Layout::Right, [2]);
+        let b: ViewOwned<'_, 1, f64> =
+            ViewOwned::new_from_data(vec![100.5, 200.0], Layout::Right, [2]);
+        assert!(!a.approx_eq(&b, Tolerance::Bitwise));
+        assert!(!a.approx_eq(&b, Tolerance::Absolute(0.1)));
+        assert!(a.approx_eq(&b, Tolerance::Absolute(0.5)));
+        assert!(a.approx_eq(&b, Tolerance::Relative(0.01)));
+        let c: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [3]);
+        assert!(!a.approx_eq(&c, Tolerance::Absolute(f64::MAX)));
+    }
+
+    #[test]
+    fn norms() {
+        let a: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data(vec![3.0, -4.0, 0.0, 0.0], Layout::Right, [2, 2]);
+        let zero: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Left, [2, 2]);
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            assert_eq!(a.norm(space, Norm::L1).unwrap(), 7.0);
+            assert_eq!(a.norm(space, Norm::L2).unwrap(), 5.0);
+            assert_eq!(a.norm(space, Norm::Linf).unwrap(), 4.0);
+            assert_eq!(a.distance(&zero, space, Norm::L2).unwrap(), 5.0);
+            assert_eq!(a.distance(&a, space, Norm::Linf).unwrap(), 0.0);
+
+            let empty: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [0]);
+            assert_eq!(empty.norm(space, Norm::Linf).unwrap(), 0.0);
+            let other: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [2, 3]);
+            assert!(matches!(
+                a.distance(&other, space, Norm::L1),
+                Err(StatementError::DimensionMismatch)
+            ));
+        }
+    }
+}
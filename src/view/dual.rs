@@ -0,0 +1,253 @@
+//! dual view related code
+//!
+//! This module contains [DualView], a pair of views holding the same data in two memory
+//! spaces, following Kokkos' `DualView` container. The view tracks which side was last
+//! modified, so that data is only copied when the other side needs it:
+//!
+//! 1. mark a side as modified using [DualView::modify] after writing to it,
+//! 2. call [DualView::sync] before reading the other side; the data is copied if it is
+//!    stale, otherwise nothing is done.
+//!
+//! Only CPU spaces are currently available: both sides are host allocations, and the
+//! "device" side is the one used by kernels dispatched on [ExecutionSpace::DeviceCPU].
+//! This lets codes follow the Kokkos porting pattern before device views can be
+//! used with it.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{
+//!     dual::{DualView, MemorySpace},
+//!     parameters::Layout,
+//! };
+//!
+//! let mut dv: DualView<1, f64> = DualView::new(Layout::Right, [4]);
+//!
+//! // fill on the host
+//! (0..4).for_each(|i| dv.view_mut(MemorySpace::Host).set([i], i as f64));
+//! dv.modify(MemorySpace::Host).unwrap();
+//!
+//! // the device side is stale until synced
+//! assert!(dv.need_sync(MemorySpace::Device));
+//! dv.sync(MemorySpace::Device).unwrap();
+//! assert_eq!(dv.view(MemorySpace::Device).get([3]), 3.0);
+//! ```
+
+use super::{deep_copy, parameters::DataTraits, parameters::Layout, ViewError, ViewOwned};
+use crate::routines::parameters::ExecutionSpace;
+
+/// Memory spaces of views, e.g. the sides of a [DualView].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemorySpace {
+    /// Memory accessed by host code & serial kernels.
+    Host,
+    /// Host memory backed by transparent huge pages, reducing TLB misses when accessing
+    /// large views. Only available on Linux, when transparent huge pages are enabled;
+    /// views fall back to [MemorySpace::Host] otherwise, see
+    /// [ViewBase::try_new_in][super::ViewBase::try_new_in]. Explicit huge pages
+    /// (`MAP_HUGETLB`) are not used, since view data is allocated by the global
+    /// allocator.
+    ///
+    /// [DualView] methods use it as an alias of [MemorySpace::Host].
+    HostHugePages,
+    /// Memory accessed by parallel kernels.
+    Device,
+}
+
+impl MemorySpace {
+    /// Returns the memory space accessed by kernels dispatched on `space`.
+    pub fn of(space: ExecutionSpace) -> Self {
+        match space {
+            ExecutionSpace::Serial => MemorySpace::Host,
+            _ => MemorySpace::Device,
+        }
+    }
+
+    /// Returns the side of a [DualView] matching the space.
+    fn side(self) -> Self {
+        match self {
+            MemorySpace::Host | MemorySpace::HostHugePages => MemorySpace::Host,
+            MemorySpace::Device => MemorySpace::Device,
+        }
+    }
+
+    fn other(self) -> Self {
+        match self.side() {
+            MemorySpace::Device => MemorySpace::Host,
+            _ => MemorySpace::Device,
+        }
+    }
+}
+
+/// Pair of views holding the same data in the host & device memory spaces.
+#[derive(Debug)]
+pub struct DualView<const N: usize, T>
+where
+    T: DataTraits + 'static,
+{
+    host: ViewOwned<'static, N, T>,
+    device: ViewOwned<'static, N, T>,
+    /// Side modified since the last synchronization, if any.
+    modified: Option<MemorySpace>,
+}
+
+impl<const N: usize, T> DualView<N, T>
+where
+    T: DataTraits + Send + Sync + 'static,
+{
+    /// Allocate both sides of the view. Both sides use the same layout & are up to date.
+    ///
+    /// Panics if the views cannot be created, see [DualView::try_new].
+    pub fn new(layout: Layout<N>, dim: [usize; N]) -> Self {
+        Self::try_new(layout, dim).unwrap_or_else(|e| panic!("could not create view: {e:?}"))
+    }
+
+    /// Fallible constructor. Returns the same errors as
+    /// [ViewBase::try_new][super::ViewBase::try_new].
+    ///
+    /// The device side is reported in the [MemorySpace::Device] space by the allocation
+    /// [registry][super::registry].
+    pub fn try_new(layout: Layout<N>, dim: [usize; N]) -> Result<Self, ViewError<'static>> {
+        Ok(Self {
+            host: ViewOwned::try_new(layout, dim)?,
+            device: ViewOwned::try_new_in(MemorySpace::Device, layout, dim)?,
+            modified: None,
+        })
+    }
+
+    /// Set the label of both sides of the view, see
+    /// [ViewBase::with_label][super::ViewBase::with_label].
+    pub fn with_label(self, label: &str) -> Self {
+        Self {
+            host: self.host.with_label(label),
+            device: self.device.with_label(label),
+            modified: self.modified,
+        }
+    }
+
+    /// Returns the dimensions of the view.
+    pub fn dims(&self) -> [usize; N] {
+        self.host.dims()
+    }
+
+    /// Returns the view of the given space. Its content may be stale, see
+    /// [DualView::need_sync].
+    pub fn view(&self, space: MemorySpace) -> &ViewOwned<'static, N, T> {
+        match space.side() {
+            MemorySpace::Device => &self.device,
+            _ => &self.host,
+        }
+    }
+
+    /// Returns the view of the given space, for writing. [DualView::modify] should be
+    /// called once writes are done.
+    pub fn view_mut(&mut self, space: MemorySpace) -> &mut ViewOwned<'static, N, T> {
+        match space.side() {
+            MemorySpace::Device => &mut self.device,
+            _ => &mut self.host,
+        }
+    }
+
+    /// Mark the view of the given space as modified.
+    ///
+    /// An error is returned if the other side was modified & not synced, since one of the
+    /// modifications would be lost.
+    pub fn modify(&mut self, space: MemorySpace) -> Result<(), ViewError<'static>> {
+        if self.modified == Some(space.other()) {
+            return Err(ViewError::ValueError(
+                "Concurrent modification of host & device views",
+            ));
+        }
+        self.modified = Some(space.side());
+        Ok(())
+    }
+
+    /// Returns `true` if the view of the given space is stale.
+    pub fn need_sync(&self, space: MemorySpace) -> bool {
+        self.modified == Some(space.other())
+    }
+
+    /// Bring the view of the given space up to date, copying data from the other side
+    /// if it was modified. Both sides are up to date afterwards.
+    pub fn sync(&mut self, space: MemorySpace) -> Result<(), ViewError<'static>> {
+        if self.need_sync(space) {
+            match space.side() {
+                MemorySpace::Device => deep_copy(&mut self.device, &self.host)?,
+                _ => deep_copy(&mut self.host, &self.device)?,
+            }
+            self.modified = None;
+        }
+        Ok(())
+    }
+
+    /// Discard modifications that have not been synced. Both sides are considered up to
+    /// date afterwards, even if their content differs.
+    pub fn clear_sync_state(&mut self) {
+        self.modified = None;
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::registry;
+
+    #[test]
+    fn modify_sync() {
+        let mut dv: DualView<2, i32> = DualView::new(Layout::Left, [2, 3]);
+        assert!(!dv.need_sync(MemorySpace::Host) && !dv.need_sync(MemorySpace::Device));
+
+        dv.view_mut(MemorySpace::Device).set([1, 2], 5);
+        dv.modify(MemorySpace::Device).unwrap();
+        assert!(dv.need_sync(MemorySpace::Host));
+        assert!(!dv.need_sync(MemorySpace::Device));
+        // host modifications would be lost
+        assert!(dv.modify(MemorySpace::Host).is_err());
+
+        // no-op on the up to date side
+        dv.sync(MemorySpace::Device).unwrap();
+        assert_eq!(dv.view(MemorySpace::Host).get([1, 2]), 0);
+        dv.sync(MemorySpace::Host).unwrap();
+        assert_eq!(dv.view(MemorySpace::Host).get([1, 2]), 5);
+        assert!(!dv.need_sync(MemorySpace::Host));
+
+        dv.view_mut(MemorySpace::Host).set([0, 0], 1);
+        dv.modify(MemorySpace::Host).unwrap();
+        dv.clear_sync_state();
+        dv.sync(MemorySpace::Device).unwrap();
+        assert_eq!(dv.view(MemorySpace::Device).get([0, 0]), 0);
+
+        // huge pages designate the host side
+        dv.modify(MemorySpace::HostHugePages).unwrap();
+        assert!(dv.need_sync(MemorySpace::Device));
+        assert!(!dv.need_sync(MemorySpace::Host));
+    }
+
+    #[test]
+    fn registered_spaces() {
+        let label = "dual::tests::registered_spaces";
+        let dv: DualView<1, f32> = DualView::new(Layout::Right, [10]).with_label(label);
+        let records: Vec<_> = registry::allocations()
+            .into_iter()
+            .filter(|rec| rec.label == label)
+            .map(|rec| (rec.space, rec.bytes))
+            .collect();
+        assert_eq!(
+            records,
+            [(MemorySpace::Host, 40), (MemorySpace::Device, 40)]
+        );
+        drop(dv);
+    }
+
+    #[test]
+    fn spaces() {
+        assert_eq!(MemorySpace::of(ExecutionSpace::Serial), MemorySpace::Host);
+        assert_eq!(
+            MemorySpace::of(ExecutionSpace::DeviceCPU),
+            MemorySpace::Device
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! lazy elementwise expression code
+//!
+//! This module contains code used to build elementwise expressions over views without
+//! evaluating them. Arithmetic operators applied to view references produce expression
+//! nodes instead of temporaries; the whole expression is then evaluated in a single
+//! parallel pass using [`ViewExpr::eval_into`].
+//!
+//! Supported operations are the ones defined by the [Scalar] trait: addition,
+//! subtraction, and scaling by a `f64` factor.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{expr::ViewExpr, parameters::Layout, ViewOwned};
+//!
+//! let a: ViewOwned<'_, 1, f64> = ViewOwned::new_from_data(vec![1.0, 2.0], Layout::Right, [2]);
+//! let b: ViewOwned<'_, 1, f64> = ViewOwned::new_from_data(vec![0.5, 0.5], Layout::Right, [2]);
+//! let mut c: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [2]);
+//!
+//! // c = 2a + b, without intermediate view
+//! (&a * 2.0 + &b).eval_into(&mut c).unwrap();
+//!
+//! assert_eq!(c.get([0]), 2.5);
+//! assert_eq!(c.get([1]), 4.5);
+//! ```
+
+use std::ops::{Add, Mul, Sub};
+
+use super::{
+    parameters::{DataTraits, Scalar},
+    ViewBase,
+};
+use crate::{
+    functor::KernelArgs,
+    routines::{
+        iter::MDIndexIter,
+        parallel_for,
+        parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+        StatementError,
+    },
+};
+
+/// Elementwise expression over views of dimension `N`.
+pub trait ViewExpr<const N: usize> {
+    /// Type of the elements resulting from the evaluation.
+    type Elem: Scalar;
+
+    /// Returns the dimensions of the expression, or `None` if operands have
+    /// inconsistent dimensions.
+    fn dims(&self) -> Option<[usize; N]>;
+
+    /// Evaluate the expression at a given index.
+    fn eval_at(&self, index: [usize; N]) -> Self::Elem;
+
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Evaluate the expression and write the result into `dst`.
+    ///
+    /// The evaluation is done in a single pass over the index space, dispatched on
+    /// [ExecutionSpace::DeviceCPU].
+    ///
+    /// **Current version**: no feature
+    fn eval_into(&self, dst: &mut ViewBase<'_, N, Self::Elem>) -> Result<(), StatementError>
+    where
+        Self: Sized,
+    {
+        let dims = self
+            .dims()
+            .filter(|d| *d == dst.dims())
+            .ok_or(StatementError::DimensionMismatch)?;
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::RangePolicy(0..dims[0]),
+            schedule: Schedule::Static,
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let kernel = |arg: KernelArgs<1>| match arg {
+            KernelArgs::Index1D(i) => {
+                let mut ranges = dims.map(|d| 0..d);
+                ranges[0] = i..i + 1;
+                MDIndexIter::new(ranges).for_each(|idx| dst.set(idx, self.eval_at(idx)));
+            }
+            KernelArgs::IndexND(_) => unimplemented!(),
+            KernelArgs::Handle(_) => unimplemented!(),
+        };
+        parallel_for(execp, kernel)
+    }
+
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Evaluate the expression and write the result into `dst`.
+    ///
+    /// The evaluation is done in a single pass over the index space, dispatched on
+    /// [ExecutionSpace::DeviceCPU].
+    ///
+    /// **Current version**: thread-safe
+    fn eval_into(&self, dst: &mut ViewBase<'_, N, Self::Elem>) -> Result<(), StatementError>
+    where
+        Self: Sized + Sync,
+        Self::Elem: Send,
+    {
+        let dims = self
+            .dims()
+            .filter(|d| *d == dst.dims())
+            .ok_or(StatementError::DimensionMismatch)?;
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::RangePolicy(0..dims[0]),
+            schedule: Schedule::Static,
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        let dst = &*dst;
+        let kernel = |arg: KernelArgs<1>| match arg {
+            KernelArgs::Index1D(i) => {
+                let mut ranges = dims.map(|d| 0..d);
+                ranges[0] = i..i + 1;
+                MDIndexIter::new(ranges).for_each(|idx| dst.set(idx, self.eval_at(idx)));
+            }
+            KernelArgs::IndexND(_) => unimplemented!(),
+            KernelArgs::Handle(_) => unimplemented!(),
+        };
+        parallel_for(execp, kernel)
+    }
+}
+
+// Nodes
+
+/// Expression node adding two sub-expressions.
+#[derive(Debug, Clone, Copy)]
+pub struct AddExpr<L, R> {
+    lhs: L,
+    rhs: R,
+}
+
+/// Expression node subtracting two sub-expressions.
+#[derive(Debug, Clone, Copy)]
+pub struct SubExpr<L, R> {
+    lhs: L,
+    rhs: R,
+}
+
+/// Expression node scaling a sub-expression by a factor.
+#[derive(Debug, Clone, Copy)]
+pub struct ScaleExpr<E> {
+    expr: E,
+    alpha: f64,
+}
+
+/// Returns the common dimensions of two operands, if they match.
+fn common_dims<const N: usize>(
+    lhs: Option<[usize; N]>,
+    rhs: Option<[usize; N]>,
+) -> Option<[usize; N]> {
+    match (lhs, rhs) {
+        (Some(l), Some(r)) if l == r => Some(l),
+        _ => None,
+    }
+}
+
+impl<'v, 'a, const N: usize, T> ViewExpr<N> for &'v ViewBase<'a, N, T>
+where
+    T: Scalar,
+{
+    type Elem = T;
+
+    fn dims(&self) -> Option<[usize; N]> {
+        Some(self.dim)
+    }
+
+    #[inline(always)]
+    fn eval_at(&self, index: [usize; N]) -> T {
+        self.get(index)
+    }
+}
+
+impl<const N: usize, L, R> ViewExpr<N> for AddExpr<L, R>
+where
+    L: ViewExpr<N>,
+    R: ViewExpr<N, Elem = L::Elem>,
+{
+    type Elem = L::Elem;
+
+    fn dims(&self) -> Option<[usize; N]> {
+        common_dims(self.lhs.dims(), self.rhs.dims())
+    }
+
+    #[inline(always)]
+    fn eval_at(&self, index: [usize; N]) -> Self::Elem {
+        self.lhs.eval_at(index) + self.rhs.eval_at(index)
+    }
+}
+
+impl<const N: usize, L, R> ViewExpr<N> for SubExpr<L, R>
+where
+    L: ViewExpr<N>,
+    R: ViewExpr<N, Elem = L::Elem>,
+{
+    type Elem = L::Elem;
+
+    fn dims(&self) -> Option<[usize; N]> {
+        common_dims(self.lhs.dims(), self.rhs.dims())
+    }
+
+    #[inline(always)]
+    fn eval_at(&self, index: [usize; N]) -> Self::Elem {
+        self.lhs.eval_at(index) - self.rhs.eval_at(index)
+    }
+}
+
+impl<const N: usize, E> ViewExpr<N> for ScaleExpr<E>
+where
+    E: ViewExpr<N>,
+{
+    type Elem = E::Elem;
+
+    fn dims(&self) -> Option<[usize; N]> {
+        self.expr.dims()
+    }
+
+    #[inline(always)]
+    fn eval_at(&self, index: [usize; N]) -> Self::Elem {
+        self.expr.eval_at(index).scale(self.alpha)
+    }
+}
+
+// Operators
+
+// Operators only build nodes; operand consistency is checked by the trait bounds
+// of ViewExpr when the expression is evaluated.
+macro_rules! impl_expr_ops {
+    ([$($gen: tt)*] $t: ty) => {
+        impl<$($gen)*, Rhs> Add<Rhs> for $t {
+            type Output = AddExpr<Self, Rhs>;
+
+            fn add(self, rhs: Rhs) -> Self::Output {
+                AddExpr { lhs: self, rhs }
+            }
+        }
+
+        impl<$($gen)*, Rhs> Sub<Rhs> for $t {
+            type Output = SubExpr<Self, Rhs>;
+
+            fn sub(self, rhs: Rhs) -> Self::Output {
+                SubExpr { lhs: self, rhs }
+            }
+        }
+
+        impl<$($gen)*> Mul<f64> for $t {
+            type Output = ScaleExpr<Self>;
+
+            fn mul(self, alpha: f64) -> Self::Output {
+                ScaleExpr { expr: self, alpha }
+            }
+        }
+    };
+}
+
+impl_expr_ops!(['v, 'a, const N: usize, T: DataTraits] &'v ViewBase<'a, N, T>);
+impl_expr_ops!([L, R] AddExpr<L, R>);
+impl_expr_ops!([L, R] SubExpr<L, R>);
+impl_expr_ops!([E] ScaleExpr<E>);
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::{parameters::Layout, ViewOwned};
+
+    #[test]
+    fn eval_2d() {
+        let a: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data((0..12).map(|x| x as f64).collect(), Layout::Right, [3, 4]);
+        let b: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data(vec![1.0; 12], Layout::Left, [3, 4]);
+        let mut c: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [3, 4]);
+
+        ((&a - &b) * 0.5 + &a).eval_into(&mut c).unwrap();
+
+        MDIndexIter::new([0..3, 0..4]).for_each(|idx| {
+            let ref_val = (a.get(idx) - 1.0) * 0.5 + a.get(idx);
+            assert_eq!(c.get(idx), ref_val);
+        });
+    }
+
+    #[test]
+    fn dims_mismatch() {
+        let a: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [3]);
+        let b: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [4]);
+        let mut c: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [3]);
+
+        assert!(matches!(
+            (&a + &b).eval_into(&mut c),
+            Err(StatementError::DimensionMismatch)
+        ));
+        assert!(matches!(
+            (&b * 2.0).eval_into(&mut c),
+            Err(StatementError::DimensionMismatch)
+        ));
+    }
+}
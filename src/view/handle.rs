@@ -0,0 +1,258 @@
+//! view handle related code
+//!
+//! This module contains [ViewHandle], an opaque handle used to share views with C++ code
+//! through the `cxx` bridge of the [ffi][crate::ffi] module. A handle owns the data of a
+//! view & exposes its description to C++: data pointer, extents, strides (in elements)
+//! and element type, which is enough to build an unmanaged `Kokkos::View` on the C++
+//! side.
+//!
+//! Handles are created from owned views, or from C++ using `new_view_handle`, and are
+//! destroyed when the `rust::Box` holding them is dropped. The data pointer stays valid
+//! as long as the handle is alive. Rust code can access the data of a handle using
+//! [ViewHandle::view_mut], or take it back using [ViewHandle::into_view].
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     ffi::ElementType,
+//!     view::{handle::ViewHandle, parameters::Layout, ViewOwned},
+//! };
+//!
+//! let view: ViewOwned<'static, 2, f64> = ViewOwned::new(Layout::Left, [2, 3]);
+//! let mut handle = ViewHandle::try_from(view).unwrap();
+//! assert_eq!(handle.element_type(), ElementType::F64);
+//! assert_eq!((handle.rank(), handle.extent(1), handle.stride(1)), (2, 3, 2));
+//!
+//! // handle.data() can be passed to C++ code, e.g. to build an unmanaged Kokkos view
+//! handle.view_mut::<2, f64>().unwrap().set([1, 2], 1.0);
+//!
+//! let view: ViewOwned<'static, 2, f64> = handle.into_view().unwrap();
+//! assert_eq!(view.get([1, 2]), 1.0);
+//! ```
+
+use super::{
+    buffer::AlignedBuffer,
+    parameters::{DataTraits, DataType, Layout, MAX_VIEW_DEPTH},
+    ViewError, ViewOwned, ViewRW,
+};
+pub use crate::ffi::ElementType;
+
+/// Element types of views that can be shared using a [ViewHandle].
+pub trait HandleElement: DataTraits {
+    /// Tag identifying the type on the C++ side.
+    const ELEMENT: ElementType;
+    /// Wrap data into the storage of a handle.
+    fn wrap(data: AlignedBuffer<Self>) -> HandleData;
+    /// Returns the data of a handle, if its elements are of this type.
+    fn unwrap(data: &mut HandleData) -> Option<&mut AlignedBuffer<Self>>;
+}
+
+/// Type-erased data of a [ViewHandle].
+#[derive(Debug)]
+pub enum HandleData {
+    /// `double` elements.
+    F64(AlignedBuffer<f64>),
+    /// `float` elements.
+    F32(AlignedBuffer<f32>),
+    /// `int32_t` elements.
+    I32(AlignedBuffer<i32>),
+    /// `int64_t` elements.
+    I64(AlignedBuffer<i64>),
+}
+
+macro_rules! impl_handle_element {
+    ($($t: ty => $variant: ident),*) => {
+        $(
+            impl HandleElement for $t {
+                const ELEMENT: ElementType = ElementType::$variant;
+
+                fn wrap(data: AlignedBuffer<Self>) -> HandleData {
+                    HandleData::$variant(data)
+                }
+
+                fn unwrap(data: &mut HandleData) -> Option<&mut AlignedBuffer<Self>> {
+                    match data {
+                        HandleData::$variant(v) => Some(v),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_handle_element!(f64 => F64, f32 => F32, i32 => I32, i64 => I64);
+
+/// Opaque handle to the data of a view, shared with C++.
+#[derive(Debug)]
+pub struct ViewHandle {
+    element: ElementType,
+    dims: Vec<usize>,
+    strides: Vec<usize>,
+    data: HandleData,
+}
+
+impl ViewHandle {
+    /// Returns the type of the elements of the view.
+    pub fn element_type(&self) -> ElementType {
+        self.element
+    }
+
+    /// Returns the number of dimensions of the view.
+    pub fn rank(&self) -> usize {
+        self.dims.len()
+    }
+
+    /// Returns the extent of dimension `dim`, or 0 if `dim` exceeds the rank.
+    pub fn extent(&self, dim: usize) -> usize {
+        self.dims.get(dim).copied().unwrap_or(0)
+    }
+
+    /// Returns the stride of dimension `dim` in elements, or 0 if `dim` exceeds the rank.
+    pub fn stride(&self, dim: usize) -> usize {
+        self.strides.get(dim).copied().unwrap_or(0)
+    }
+
+    /// Returns a pointer to the first element of the view. The pointer is valid as long
+    /// as the handle is alive, and keeps the alignment of the view the handle was created
+    /// from, see [ViewBase::alignment][super::ViewBase::alignment].
+    pub fn data(&mut self) -> *mut u8 {
+        match &mut self.data {
+            HandleData::F64(v) => v.as_mut_ptr() as *mut u8,
+            HandleData::F32(v) => v.as_mut_ptr() as *mut u8,
+            HandleData::I32(v) => v.as_mut_ptr() as *mut u8,
+            HandleData::I64(v) => v.as_mut_ptr() as *mut u8,
+        }
+    }
+
+    /// Returns the dimensions & strides of the view, checking its element type & rank.
+    fn geometry<const N: usize, T: HandleElement>(
+        &self,
+    ) -> Result<([usize; N], [usize; N]), ViewError<'static>> {
+        if self.element != T::ELEMENT {
+            return Err(ViewError::ValueError(
+                "Element type does not match the one of the handle",
+            ));
+        }
+        let dims = self.dims.as_slice().try_into();
+        let strides = self.strides.as_slice().try_into();
+        dims.and_then(|d| strides.map(|s| (d, s))).map_err(|_| {
+            ViewError::DimensionMismatch("View depth does not match the rank of the handle")
+        })
+    }
+
+    /// Borrow the data of the handle as a view.
+    pub fn view_mut<const N: usize, T: HandleElement>(
+        &mut self,
+    ) -> Result<ViewRW<'_, N, T>, ViewError<'static>> {
+        let (dims, s) = self.geometry::<N, T>()?;
+        let data = T::unwrap(&mut self.data).expect("element type was checked");
+        // SAFETY: the data is exclusively borrowed for the lifetime of the view & spans
+        // the elements described by the strides
+        unsafe { ViewRW::from_raw_parts(data.as_mut_ptr(), Layout::Stride { s }, dims) }
+    }
+
+    /// Take back the data of the handle as an owned view.
+    pub fn into_view<const N: usize, T: HandleElement>(
+        mut self,
+    ) -> Result<ViewOwned<'static, N, T>, ViewError<'static>> {
+        let (dims, s) = self.geometry::<N, T>()?;
+        let data = std::mem::take(T::unwrap(&mut self.data).expect("element type was checked"));
+        // SAFETY: InnerDataType<T> has the same memory layout as T
+        ViewOwned::try_new_from_buffer(unsafe { data.cast() }, Layout::Stride { s }, dims)
+    }
+}
+
+impl<const N: usize, T: HandleElement> TryFrom<ViewOwned<'static, N, T>> for ViewHandle {
+    type Error = ViewError<'static>;
+
+    /// Move the data of an owned view into a handle. Views borrowing their data, e.g.
+    /// mirrors, cannot be shared.
+    #[allow(clippy::unnecessary_cast)] // casts are no-ops in serial builds without `index-u32`
+    fn try_from(view: ViewOwned<'static, N, T>) -> Result<Self, Self::Error> {
+        let strides = view.stride.map(|s| s as usize).to_vec();
+        let DataType::Owned(data) = view.data else {
+            return Err(ViewError::ValueError(
+                "Only views owning their data can be shared",
+            ));
+        };
+        Ok(Self {
+            element: T::ELEMENT,
+            dims: view.dim.to_vec(),
+            strides,
+            // SAFETY: InnerDataType<T> has the same memory layout as T
+            data: T::wrap(unsafe { data.cast() }),
+        })
+    }
+}
+
+/// Create a handle to a zero-initialized view using [Layout::Right]. Used by C++ code.
+pub fn new_view_handle(
+    element: ElementType,
+    dims: &[usize],
+) -> Result<Box<ViewHandle>, ViewError<'static>> {
+    if dims.is_empty() || dims.len() > MAX_VIEW_DEPTH {
+        return Err(ViewError::ValueError(
+            "Handle rank must be between 1 & MAX_VIEW_DEPTH",
+        ));
+    }
+    let len = dims
+        .iter()
+        .try_fold(1usize, |acc, d| acc.checked_mul(*d))
+        .ok_or(ViewError::AllocationFailure(
+            "View size overflows the address space",
+        ))?;
+    let mut strides = vec![1; dims.len()];
+    for i in (0..dims.len() - 1).rev() {
+        strides[i] = strides[i + 1] * dims[i + 1];
+    }
+    let data = match element {
+        ElementType::F64 => HandleData::F64(vec![0.0; len].into()),
+        ElementType::F32 => HandleData::F32(vec![0.0; len].into()),
+        ElementType::I32 => HandleData::I32(vec![0; len].into()),
+        ElementType::I64 => HandleData::I64(vec![0; len].into()),
+        _ => return Err(ViewError::ValueError("Unknown element type")),
+    };
+    Ok(Box::new(ViewHandle {
+        element,
+        dims: dims.to_vec(),
+        strides,
+        data,
+    }))
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let view: ViewOwned<'static, 2, i32> =
+            ViewOwned::new_from_data((0..6).collect(), Layout::Right, [2, 3]);
+        let handle = ViewHandle::try_from(view).unwrap();
+        assert_eq!(handle.element_type(), ElementType::I32);
+        assert_eq!(handle.rank(), 2);
+        assert_eq!([handle.extent(0), handle.stride(0)], [2, 3]);
+        assert_eq!(handle.extent(2), 0);
+
+        assert!(handle.geometry::<2, f64>().is_err());
+        assert!(handle.geometry::<3, i32>().is_err());
+        let view: ViewOwned<'static, 2, i32> = handle.into_view().unwrap();
+        assert_eq!(view.get([1, 0]), 3);
+    }
+
+    #[test]
+    fn cpp_access() {
+        let mut handle = new_view_handle(ElementType::F64, &[3, 4]).unwrap();
+        assert_eq!(handle.stride(0), 4);
+        // written by C++ code, using the extents & strides of the handle
+        crate::ffi::fill_iota(&mut handle);
+        let view = handle.view_mut::<2, f64>().unwrap();
+        assert_eq!(view.get([2, 1]), 9.0);
+        assert!(new_view_handle(ElementType::F64, &[]).is_err());
+    }
+}
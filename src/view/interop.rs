@@ -0,0 +1,207 @@
+//! view interoperability related code
+//!
+//! This module contains constructors used to exchange data with other libraries:
+//!
+//! - [ViewBase::from_raw_parts] wraps a buffer allocated elsewhere, e.g. by C++/Kokkos
+//!   code crossing the `cxx` FFI boundary, without copying it.
+//! - When the `ndarray` feature is enabled, views can be converted from & into
+//!   `ndarray` arrays. Owned arrays & read-only array views are copied into
+//!   [Layout::Right] views; mutable array views are wrapped without copies, using their
+//!   strides.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{parameters::Layout, ViewRW};
+//!
+//! // buffer owned by foreign code
+//! let mut buffer = vec![0.0; 6];
+//! #[allow(unused_mut)]
+//! let mut view: ViewRW<'_, 2, f64> =
+//!     unsafe { ViewRW::from_raw_parts(buffer.as_mut_ptr(), Layout::Left, [2, 3]) }.unwrap();
+//! view.set([1, 0], 1.0);
+//! drop(view);
+//!
+//! assert_eq!(buffer[1], 1.0);
+//! ```
+
+use std::ptr::NonNull;
+
+#[cfg(feature = "access-stats")]
+use super::stats::AccessStats;
+use super::{
+    checked_geometry,
+    parameters::{DataTraits, DataType, InnerDataType, Layout},
+    ViewBase, ViewError, ViewRW,
+};
+
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits,
+{
+    /// Create a view of the buffer starting at `ptr`, using the given layout &
+    /// dimensions. The buffer is not copied, nor freed when the view is dropped.
+    ///
+    /// Returns the same errors as [ViewBase::try_new].
+    ///
+    /// # Safety
+    ///
+    /// For the lifetime `'a`, `ptr` must be non-null, aligned, and valid for reads &
+    /// writes of the whole span of the view, i.e. up to the element of largest offset.
+    /// The buffer must not be accessed through other pointers while the view is alive.
+    pub unsafe fn from_raw_parts(
+        ptr: *mut T,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<ViewRW<'a, N, T>, ViewError<'static>> {
+        let (stride, span) = checked_geometry::<N, T>(&dim, &layout)?;
+        // empty slices still require a non-null, aligned pointer
+        let ptr = if span == 0 {
+            NonNull::dangling().as_ptr()
+        } else {
+            ptr
+        };
+        // InnerDataType<T> has the same memory layout as T
+        let data = std::slice::from_raw_parts_mut(ptr as *mut InnerDataType<T>, span);
+        Ok(Self {
+            data: DataType::MutBorrowed(data),
+            layout,
+            dim,
+            stride,
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: None,
+        })
+    }
+}
+
+#[cfg(feature = "ndarray")]
+mod ndarray_conversions {
+    use ndarray::{ArrayD, ArrayViewD, ArrayViewMutD, IxDyn};
+
+    use super::{DataTraits, Layout, ViewBase, ViewError};
+    use crate::{routines::iter::MDIndexIter, view::ViewOwned};
+
+    /// Returns the shape of an array as view dimensions.
+    fn dims<const N: usize>(shape: &[usize]) -> Result<[usize; N], ViewError<'static>> {
+        shape.try_into().map_err(|_| {
+            ViewError::DimensionMismatch("Array dimensionality does not match the View depth")
+        })
+    }
+
+    impl<const N: usize, T> TryFrom<ArrayD<T>> for ViewOwned<'static, N, T>
+    where
+        T: DataTraits,
+    {
+        type Error = ViewError<'static>;
+
+        /// Copy the array into a [Layout::Right] view.
+        fn try_from(array: ArrayD<T>) -> Result<Self, Self::Error> {
+            ViewOwned::try_from(array.view())
+        }
+    }
+
+    impl<const N: usize, T> TryFrom<ArrayViewD<'_, T>> for ViewOwned<'static, N, T>
+    where
+        T: DataTraits,
+    {
+        type Error = ViewError<'static>;
+
+        /// Copy the array view into a [Layout::Right] view.
+        fn try_from(array: ArrayViewD<'_, T>) -> Result<Self, Self::Error> {
+            let dim = dims(array.shape())?;
+            // arrays iterate in logical order, i.e. row-major
+            ViewOwned::try_new_from_data(array.iter().copied().collect(), Layout::Right, dim)
+        }
+    }
+
+    impl<'a, const N: usize, T> TryFrom<ArrayViewMutD<'a, T>> for ViewBase<'a, N, T>
+    where
+        T: DataTraits,
+    {
+        type Error = ViewError<'static>;
+
+        /// Wrap the array view without copying it, using a [Layout::Stride] layout.
+        /// Negative strides are not supported.
+        fn try_from(mut array: ArrayViewMutD<'a, T>) -> Result<Self, Self::Error> {
+            let dim = dims(array.shape())?;
+            let s: [usize; N] = dims(
+                &array
+                    .strides()
+                    .iter()
+                    .map(|s| usize::try_from(*s))
+                    .collect::<Result<Vec<usize>, _>>()
+                    .map_err(|_| ViewError::ValueError("Negative strides are not supported"))?,
+            )?;
+            // SAFETY: the array view exclusively borrows its elements for 'a & its
+            // strides describe their location
+            unsafe { ViewBase::from_raw_parts(array.as_mut_ptr(), Layout::Stride { s }, dim) }
+        }
+    }
+
+    impl<const N: usize, T> From<&ViewBase<'_, N, T>> for ArrayD<T>
+    where
+        T: DataTraits,
+    {
+        /// Copy the view into an array in standard (row-major) layout.
+        fn from(view: &ViewBase<'_, N, T>) -> Self {
+            let data = MDIndexIter::new(view.dims().map(|d| 0..d))
+                .map(|idx| view.load(view.flat_idx(idx)))
+                .collect();
+            ArrayD::from_shape_vec(IxDyn(&view.dims()), data)
+                .expect("the number of elements matches the dimensions of the view")
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_parts() {
+        let mut buffer: Vec<i32> = (0..12).collect();
+        {
+            // 2x3 block in the upper left corner of a 3x4 row-major matrix
+            #[allow(unused_mut)]
+            let mut view: ViewRW<'_, 2, i32> = unsafe {
+                ViewRW::from_raw_parts(buffer.as_mut_ptr(), Layout::Stride { s: [4, 1] }, [2, 3])
+            }
+            .unwrap();
+            assert_eq!(view.get([1, 2]), 6);
+            view.set([1, 0], -1);
+        }
+        assert_eq!(buffer[4], -1);
+
+        let empty: ViewRW<'_, 1, i32> =
+            unsafe { ViewRW::from_raw_parts(std::ptr::null_mut(), Layout::Right, [0]) }.unwrap();
+        assert_eq!(empty.iter().count(), 0);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray_conversions() {
+        use crate::view::ViewOwned;
+        use ndarray::{ArrayD, IxDyn, ShapeBuilder};
+
+        let array = ArrayD::from_shape_vec(IxDyn(&[2, 3]), (0..6).collect()).unwrap();
+        let view: ViewOwned<'_, 2, i32> = array.view().try_into().unwrap();
+        assert_eq!(view.get([1, 0]), 3);
+        assert!(ViewOwned::<'_, 3, i32>::try_from(array.clone()).is_err());
+        assert_eq!(ArrayD::from(&view), array);
+
+        // column-major arrays are wrapped using their strides
+        let mut array = ArrayD::from_shape_vec(IxDyn(&[2, 3]).f(), vec![0, 3, 1, 4, 2, 5]).unwrap();
+        {
+            #[allow(unused_mut)]
+            let mut view: ViewBase<'_, 2, i32> = array.view_mut().try_into().unwrap();
+            assert_eq!(view.layout, Layout::Stride { s: [1, 2] });
+            assert_eq!(view.get([1, 0]), 3);
+            view.set([0, 2], -1);
+        }
+        assert_eq!(array[[0, 2]], -1);
+    }
+}
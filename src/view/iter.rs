@@ -0,0 +1,149 @@
+//! view iterator related code
+//!
+//! This module contains iterators over the elements of views, making it possible to use
+//! standard iterator adapters on view data instead of manual index loops:
+//!
+//! - [ViewBase::iter]: values, in layout order,
+//! - [ViewBase::iter_indexed]: indices & values, in layout order,
+//! - [ViewBase::rows] & [ViewBase::columns]: 1D subviews of a 2D view.
+//!
+//! Layout order is the order making memory accesses contiguous, e.g. the last index
+//! varies the fastest using [Layout::Right]. Elements located between the rows of
+//! strided views are not yielded. Like [ViewBase::sum], iterators read values without
+//! updating access counters.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+//!
+//! let mat: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data((0..6).map(|x| x as f64).collect(), Layout::Right, [2, 3]);
+//!
+//! let max = mat.iter().fold(f64::MIN, f64::max);
+//! assert_eq!(max, 5.0);
+//!
+//! let diag: Vec<f64> = mat
+//!     .iter_indexed()
+//!     .filter_map(|([i, j], val)| (i == j).then_some(val))
+//!     .collect();
+//! assert_eq!(diag, vec![0.0, 4.0]);
+//!
+//! let row_sums: Vec<f64> = mat.rows().map(|row| row.iter().sum()).collect();
+//! assert_eq!(row_sums, vec![3.0, 12.0]);
+//! ```
+
+#[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+use atomic::Ordering;
+
+#[cfg(doc)]
+use super::parameters::Layout;
+use super::{parameters::DataTraits, subview::SliceArg, ViewBase, ViewRO};
+use crate::routines::{iter::MDIndexIter, parameters::Iterate};
+
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits,
+{
+    /// Returns an iterator over the values of the view, in layout order.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.indices().map(|idx| self.load(self.flat_idx(idx)))
+    }
+
+    /// Returns an iterator over the indices & values of the view, in layout order.
+    pub fn iter_indexed(&self) -> impl Iterator<Item = ([usize; N], T)> + '_ {
+        self.indices()
+            .map(|idx| (idx, self.load(self.flat_idx(idx))))
+    }
+
+    /// Iterator over the indices of the view, in layout order.
+    fn indices(&self) -> MDIndexIter<N> {
+        MDIndexIter::with_iterate(self.dim.map(|d| 0..d), Iterate::from(&self.layout))
+    }
+
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Read the value stored at a given offset.
+    pub(crate) fn load(&self, offset: usize) -> T {
+        self.data_slice()[offset]
+    }
+
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Read the value stored at a given offset.
+    pub(crate) fn load(&self, offset: usize) -> T {
+        self.data_slice()[offset].load(Ordering::Relaxed)
+    }
+}
+
+impl<'a, T> ViewBase<'a, 2, T>
+where
+    T: DataTraits,
+{
+    /// Returns an iterator over the rows of the view, as read-only subviews.
+    pub fn rows(&self) -> impl Iterator<Item = ViewRO<'_, 1, T>> + '_ {
+        (0..self.dim[0]).map(|i| {
+            self.subview([SliceArg::Index(i), SliceArg::All])
+                .expect("row indices are in bounds")
+        })
+    }
+
+    /// Returns an iterator over the columns of the view, as read-only subviews.
+    pub fn columns(&self) -> impl Iterator<Item = ViewRO<'_, 1, T>> + '_ {
+        (0..self.dim[1]).map(|j| {
+            self.subview([SliceArg::All, SliceArg::Index(j)])
+                .expect("column indices are in bounds")
+        })
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::{parameters::Layout, ViewOwned};
+
+    #[test]
+    fn layout_order() {
+        let data: Vec<usize> = (0..2 * 3 * 4).collect();
+        for layout in [Layout::Right, Layout::Left] {
+            let view: ViewOwned<'_, 3, usize> =
+                ViewOwned::new_from_data(data.clone(), layout, [2, 3, 4]);
+            // values are yielded in memory order
+            assert_eq!(view.iter().collect::<Vec<_>>(), data);
+            view.iter_indexed()
+                .for_each(|(idx, val)| assert_eq!(view.get(idx), val));
+        }
+
+        // strided subview: the gaps are skipped
+        let mat: ViewOwned<'_, 2, usize> =
+            ViewOwned::new_from_data((0..12).collect(), Layout::Right, [3, 4]);
+        let block = mat
+            .subview::<2>([SliceArg::Range(1..3), SliceArg::Range(0..2)])
+            .unwrap();
+        assert_eq!(block.iter().collect::<Vec<_>>(), vec![4, 5, 8, 9]);
+        assert_eq!(block.iter_indexed().count(), 4);
+    }
+
+    #[test]
+    fn rows_columns() {
+        for layout in [Layout::Right, Layout::Left] {
+            #[allow(unused_mut)]
+            let mut mat: ViewOwned<'_, 2, usize> = ViewOwned::new(layout, [3, 4]);
+            MDIndexIter::new([0..3, 0..4]).for_each(|[i, j]| mat.set([i, j], 10 * i + j));
+            let rows: Vec<Vec<usize>> = mat.rows().map(|row| row.iter().collect()).collect();
+            assert_eq!(rows[1], vec![10, 11, 12, 13]);
+            assert_eq!(rows.len(), 3);
+            let cols: Vec<Vec<usize>> = mat.columns().map(|col| col.iter().collect()).collect();
+            assert_eq!(cols[2], vec![2, 12, 22]);
+            assert_eq!(cols.len(), 4);
+        }
+
+        let empty: ViewOwned<'_, 2, usize> = ViewOwned::new(Layout::Right, [0, 4]);
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(
+            empty.columns().map(|col| col.iter().count()).sum::<usize>(),
+            0
+        );
+    }
+}
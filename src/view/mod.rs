@@ -15,6 +15,21 @@
 //! API.
 //!
 //! Parameters of aforementionned views are defined in the [`parameters`] sub-module.
+//! Iterators over the elements of views are defined in the [`iter`] sub-module, and
+//! rayon parallel iterators in the `par_iter` sub-module when the `rayon` feature is
+//! enabled. Scatter-add patterns are supported by the wrapper of the [`scatter`]
+//! sub-module. Contiguous spans of view data can be accessed as slices using the methods
+//! of the [`span`] sub-module. Views can be compared & validated using the utilities of the
+//! [`compare`] sub-module. Views can wrap foreign buffers, or be converted from & into
+//! `ndarray` arrays, using the constructors of the [`interop`] sub-module, and be shared
+//! with C++ code using the handles of the [`handle`] sub-module. Data held in both host &
+//! device memory spaces is tracked by the [`dual`] sub-module. Allocations of owned views
+//! are reported, per label & memory space, by the [`registry`] sub-module, and stored in
+//! the aligned buffers of the [`buffer`] sub-module. Temporary views can be allocated out
+//! of the arenas of the [`pool`] sub-module. Views can be filled, scaled & combined in
+//! place using the operations of the [`ops`] sub-module, and contiguous views reshaped
+//! without copy using the methods of the [`reshape`] sub-module. Views can be transposed,
+//! or their axes permuted, using the routines of the [`transpose`] sub-module.
 //!
 //! ### Example
 //!
@@ -42,7 +57,26 @@
 //! // (2.0 2.0 2.0 2.0 2.0)
 //! ```
 
+pub mod buffer;
+pub mod compare;
+pub mod dual;
+pub mod expr;
+pub mod handle;
+pub mod interop;
+pub mod iter;
+pub mod ops;
+#[cfg(feature = "rayon")]
+pub mod par_iter;
 pub mod parameters;
+pub mod pool;
+pub mod registry;
+pub mod reshape;
+pub mod scatter;
+pub mod span;
+#[cfg(feature = "access-stats")]
+pub mod stats;
+pub mod subview;
+pub mod transpose;
 
 #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
 use atomic::{Atomic, Ordering};
@@ -50,8 +84,35 @@ use atomic::{Atomic, Ordering};
 #[cfg(any(doc, not(any(feature = "rayon", feature = "threads", feature = "gpu"))))]
 use std::ops::IndexMut;
 
-use self::parameters::{compute_stride, DataTraits, DataType, InnerDataType, Layout};
-use std::{fmt::Debug, ops::Index};
+use self::buffer::AlignedBuffer;
+use self::dual::MemorySpace;
+use self::parameters::{
+    compute_stride, index_stride, CastFrom, DataTraits, DataType, IndexType, InnerDataType,
+    IntegerTraits, Layout, Scalar, MAX_VIEW_DEPTH,
+};
+use self::registry::TrackedAllocation;
+#[cfg(feature = "access-stats")]
+use self::stats::{AccessCounts, AccessStats};
+use crate::routines::iter::MDIndexIter;
+use crate::routines::{
+    parameters::{ExecutionSpace, MDRange, OverflowSum, ReductionStrategy, TypedExecutionPolicy},
+    typed::parallel_reduce,
+};
+#[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+use crate::{
+    functor::KernelArgs,
+    routines::{
+        parallel_for,
+        parameters::{ExecutionPolicy, RangePolicy, Schedule},
+    },
+};
+use std::{
+    fmt::{Debug, Display},
+    hint::black_box,
+    mem::size_of,
+    ops::{Add, Index, Sub},
+    sync::{atomic::Ordering as StdOrdering, OnceLock},
+};
 
 #[derive(Debug)]
 /// Enum used to classify view-related errors.
@@ -60,8 +121,31 @@ use std::{fmt::Debug, ops::Index};
 pub enum ViewError<'a> {
     ValueError(&'a str),
     DoubleMirroring(&'a str),
+    /// An arithmetic overflow occured while computing a value from the view's content.
+    Overflow(&'a str),
+    /// The length of the data does not match the dimensions & layout of the view.
+    DimensionMismatch(&'a str),
+    /// The view has a depth of 0, or its elements have a size of 0.
+    ZeroSize(&'a str),
+    /// The data of the view could not be allocated, e.g. because its size overflows.
+    AllocationFailure(&'a str),
+}
+
+impl Display for ViewError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ViewError::ValueError(desc) => write!(f, "invalid value: {desc}"),
+            ViewError::DoubleMirroring(desc) => write!(f, "double mirroring: {desc}"),
+            ViewError::Overflow(desc) => write!(f, "arithmetic overflow: {desc}"),
+            ViewError::DimensionMismatch(desc) => write!(f, "dimension mismatch: {desc}"),
+            ViewError::ZeroSize(desc) => write!(f, "zero-sized view: {desc}"),
+            ViewError::AllocationFailure(desc) => write!(f, "allocation failure: {desc}"),
+        }
+    }
 }
 
+impl std::error::Error for ViewError<'_> {}
+
 #[derive(Debug, PartialEq)]
 /// Common structure used as the backend of all `View` types. The main differences between
 /// usable types is the type of the `data` field.
@@ -69,20 +153,120 @@ pub struct ViewBase<'a, const N: usize, T>
 where
     T: DataTraits,
 {
-    /// Data container. Depending on the type, it can be a vector (`Owned`), a reference
-    /// (`ReadOnly`) or a mutable reference (`ReadWrite`).
+    /// Data container. Depending on the type, it can be an aligned buffer (`Owned`), a
+    /// reference (`ReadOnly`) or a mutable reference (`ReadWrite`).
     pub data: DataType<'a, T>,
     /// Memory layout of the view. Refer to Kokkos documentation for more information.
     pub layout: Layout<N>,
     /// Dimensions of the data represented by the view. The view can:
     /// - be a vector (1 dimension)
     /// - be a multi-dimensionnal array (up to 8 dimensions)
+    ///
     /// The number of dimensions is referred to as the _depth_. Dimension 0, i.e. scalar,
     /// is not directly supported.
     pub dim: [usize; N],
     /// Stride between each element of a given dimension. Computed automatically for
-    /// [Layout::Left] and [Layout::Right].
-    pub stride: [usize; N],
+    /// [Layout::Left] and [Layout::Right]. Stored using [IndexType].
+    pub stride: [IndexType; N],
+    #[cfg(feature = "access-stats")]
+    /// Access counters of the view. Only defined when the `access-stats` feature is
+    /// enabled. Each view, mirrors included, counts its own accesses.
+    pub stats: AccessStats,
+    /// Registration of the data in the allocation [registry]. Only defined for views
+    /// owning their data.
+    pub allocation: Option<TrackedAllocation>,
+}
+
+/// Compute the strides & span of a view, checking that it can be allocated.
+fn checked_geometry<const N: usize, T>(
+    dim: &[usize; N],
+    layout: &Layout<N>,
+) -> Result<([IndexType; N], usize), ViewError<'static>> {
+    if N == 0 || std::mem::size_of::<T>() == 0 {
+        return Err(ViewError::ZeroSize(
+            "Views must have at least one dimension & non zero-sized elements",
+        ));
+    }
+    if N > MAX_VIEW_DEPTH {
+        return Err(ViewError::ValueError("View depth exceeds MAX_VIEW_DEPTH"));
+    }
+    const OVERFLOW: ViewError<'static> =
+        ViewError::AllocationFailure("View size overflows the address space");
+    // partial products of dimensions, i.e. strides of contiguous layouts, fit if the
+    // product of non-zero dimensions does
+    dim.iter()
+        .filter(|d| **d != 0)
+        .try_fold(1usize, |acc, d| acc.checked_mul(*d))
+        .ok_or(OVERFLOW)?;
+    let stride = compute_stride(dim, layout);
+    let max_offset = stride
+        .iter()
+        .zip(dim.iter())
+        .try_fold(0usize, |acc, (s, d)| {
+            s.checked_mul(d.saturating_sub(1))
+                .and_then(|o| acc.checked_add(o))
+        })
+        .ok_or(OVERFLOW)?;
+    let span = if dim.contains(&0) { 0 } else { max_offset + 1 };
+    if span
+        .checked_mul(std::mem::size_of::<T>())
+        .is_none_or(|bytes| bytes > isize::MAX as usize)
+    {
+        return Err(OVERFLOW);
+    }
+    if IndexType::try_from(max_offset).is_err() {
+        return Err(ViewError::AllocationFailure(
+            "View span does not fit in the index type",
+        ));
+    }
+    Ok((index_stride(stride, dim), span))
+}
+
+/// Reserve the storage of `capacity` elements aligned on `align` bytes in `space`,
+/// returning the space actually used. Memory is advised before being touched, so that
+/// pages are faulted as huge pages.
+fn reserve<E>(
+    capacity: usize,
+    align: usize,
+    space: MemorySpace,
+) -> Result<(AlignedBuffer<E>, MemorySpace), ViewError<'static>> {
+    let data: AlignedBuffer<E> = AlignedBuffer::try_with_capacity(capacity, align)?;
+    let space = match space {
+        MemorySpace::HostHugePages
+            if !advise_huge_pages(data.as_ptr().cast(), capacity * size_of::<E>()) =>
+        {
+            MemorySpace::Host
+        }
+        space => space,
+    };
+    Ok((data, space))
+}
+
+#[cfg(target_os = "linux")]
+/// Advise the kernel to back the pages of the given region with transparent huge pages.
+/// Returns `false` if they are disabled, or if the region does not span a full page.
+fn advise_huge_pages(ptr: *const u8, bytes: usize) -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    let enabled = *ENABLED.get_or_init(|| {
+        std::fs::read_to_string("/sys/kernel/mm/transparent_hugepage/enabled")
+            .is_ok_and(|mode| !mode.contains("[never]"))
+    });
+    // SAFETY: sysconf has no precondition
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = (ptr as usize).next_multiple_of(page);
+    let end = (ptr as usize + bytes) / page * page;
+    // SAFETY: the advised range lies within the allocation; advice does not change its
+    // content
+    enabled
+        && start < end
+        && unsafe { libc::madvise(start as *mut libc::c_void, end - start, libc::MADV_HUGEPAGE) }
+            == 0
+}
+
+#[cfg(not(target_os = "linux"))]
+/// Huge pages are only supported on Linux.
+fn advise_huge_pages(_ptr: *const u8, _bytes: usize) -> bool {
+    false
 }
 
 #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
@@ -92,36 +276,87 @@ where
     T: DataTraits, // fair assumption imo
 {
     /// Constructor used to create owned views. See dedicated methods for others.
+    ///
+    /// Panics if the view cannot be created, see [ViewBase::try_new].
     pub fn new(layout: Layout<N>, dim: [usize; N]) -> Self {
-        // compute stride & capacity
-        let stride = compute_stride(&dim, &layout);
-        let capacity: usize = dim.iter().product();
-
-        // build & return
-        Self {
-            data: DataType::Owned(vec![T::default(); capacity]), // should this be allocated though?
-            layout,
-            dim,
-            stride,
-        }
+        Self::try_new(layout, dim).unwrap_or_else(|e| panic!("could not create view: {e:?}"))
     }
 
     /// Constructor used to create owned views. See dedicated methods for others.
+    ///
+    /// Panics if the view cannot be created, see [ViewBase::try_new_from_data].
     pub fn new_from_data(data: Vec<T>, layout: Layout<N>, dim: [usize; N]) -> Self {
-        // compute stride if necessary
-        let stride = compute_stride(&dim, &layout);
+        Self::try_new_from_data(data, layout, dim)
+            .unwrap_or_else(|e| panic!("could not create view: {e:?}"))
+    }
+
+    /// Fallible constructor used to create owned views, initialized using
+    /// `T::default()`.
+    ///
+    /// Returns a [ViewError::ZeroSize] error for views of depth 0 or zero-sized
+    /// elements, and a [ViewError::AllocationFailure] error if the data cannot be
+    /// allocated.
+    pub fn try_new(layout: Layout<N>, dim: [usize; N]) -> Result<Self, ViewError<'static>> {
+        Self::try_new_in(MemorySpace::Host, layout, dim)
+    }
 
-        // checks
-        let capacity: usize = dim.iter().product();
-        assert_eq!(capacity, data.len());
+    /// Fallible constructor used to create owned views in a given memory space. Views
+    /// requesting [MemorySpace::HostHugePages] fall back to [MemorySpace::Host] if huge
+    /// pages are not available; the space actually used is returned by
+    /// [ViewBase::memory_space].
+    ///
+    /// Returns the same errors as [ViewBase::try_new].
+    pub fn try_new_in(
+        space: MemorySpace,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<Self, ViewError<'static>> {
+        Self::try_new_aligned_in(space, 1, layout, dim)
+    }
+
+    /// Fallible constructor used to create owned views in a given memory space, whose
+    /// data is aligned on `align` bytes, or on the alignment of `T` if it is larger. See
+    /// [ViewBase::try_new_in] & [ViewBase::alignment].
+    ///
+    /// Returns a [ViewError::ValueError] error if `align` is not a power of two, in
+    /// addition to errors of [ViewBase::try_new].
+    pub fn try_new_aligned_in(
+        space: MemorySpace,
+        align: usize,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<Self, ViewError<'static>> {
+        let (stride, capacity) = checked_geometry::<N, T>(&dim, &layout)?;
+        let (mut data, space) = reserve(capacity, align, space)?;
+        data.fill_with(T::default);
+        let allocation = TrackedAllocation::new(
+            capacity * size_of::<InnerDataType<T>>(),
+            data.alignment(),
+            space,
+        );
 
         // build & return
-        Self {
+        Ok(Self {
             data: DataType::Owned(data),
             layout,
             dim,
             stride,
-        }
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: Some(allocation),
+        })
+    }
+
+    /// Fallible constructor used to create owned views from existing data.
+    ///
+    /// Returns a [ViewError::DimensionMismatch] error if the length of `data` does not
+    /// match the span of the view, in addition to errors of [ViewBase::try_new].
+    pub fn try_new_from_data(
+        data: Vec<T>,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<Self, ViewError<'static>> {
+        Self::try_new_from_buffer(data.into(), layout, dim)
     }
 }
 
@@ -132,35 +367,213 @@ where
     T: DataTraits, // fair assumption imo
 {
     /// Constructor used to create owned views. See dedicated methods for others.
+    ///
+    /// Panics if the view cannot be created, see [ViewBase::try_new].
     pub fn new(layout: Layout<N>, dim: [usize; N]) -> Self {
-        // compute stride & capacity
-        let stride = compute_stride(&dim, &layout);
-        let capacity: usize = dim.iter().product();
+        Self::try_new(layout, dim).unwrap_or_else(|e| panic!("could not create view: {e:?}"))
+    }
+
+    /// Constructor used to create owned views. See dedicated methods for others.
+    ///
+    /// Panics if the view cannot be created, see [ViewBase::try_new_from_data].
+    pub fn new_from_data(data: Vec<T>, layout: Layout<N>, dim: [usize; N]) -> Self {
+        Self::try_new_from_data(data, layout, dim)
+            .unwrap_or_else(|e| panic!("could not create view: {e:?}"))
+    }
+
+    /// Fallible constructor used to create owned views, initialized using
+    /// `T::default()`.
+    ///
+    /// Returns a [ViewError::ZeroSize] error for views of depth 0 or zero-sized
+    /// elements, and a [ViewError::AllocationFailure] error if the data cannot be
+    /// allocated.
+    pub fn try_new(layout: Layout<N>, dim: [usize; N]) -> Result<Self, ViewError<'static>> {
+        Self::try_new_in(MemorySpace::Host, layout, dim)
+    }
+
+    /// Fallible constructor used to create owned views in a given memory space. Views
+    /// requesting [MemorySpace::HostHugePages] fall back to [MemorySpace::Host] if huge
+    /// pages are not available; the space actually used is returned by
+    /// [ViewBase::memory_space].
+    ///
+    /// Returns the same errors as [ViewBase::try_new].
+    pub fn try_new_in(
+        space: MemorySpace,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<Self, ViewError<'static>> {
+        Self::try_new_aligned_in(space, 1, layout, dim)
+    }
+
+    /// Fallible constructor used to create owned views in a given memory space, whose
+    /// data is aligned on `align` bytes, or on the alignment of `T` if it is larger. See
+    /// [ViewBase::try_new_in] & [ViewBase::alignment].
+    ///
+    /// Returns a [ViewError::ValueError] error if `align` is not a power of two, in
+    /// addition to errors of [ViewBase::try_new].
+    pub fn try_new_aligned_in(
+        space: MemorySpace,
+        align: usize,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<Self, ViewError<'static>> {
+        let (stride, capacity) = checked_geometry::<N, T>(&dim, &layout)?;
+        let (mut data, space) = reserve(capacity, align, space)?;
+        data.fill_with(|| Atomic::new(T::default()));
+        let allocation = TrackedAllocation::new(
+            capacity * size_of::<InnerDataType<T>>(),
+            data.alignment(),
+            space,
+        );
 
         // build & return
-        Self {
-            data: DataType::Owned((0..capacity).map(|_| Atomic::new(T::default())).collect()), // should this be allocated though?
+        Ok(Self {
+            data: DataType::Owned(data),
             layout,
             dim,
             stride,
-        }
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: Some(allocation),
+        })
     }
 
-    /// Constructor used to create owned views. See dedicated methods for others.
-    pub fn new_from_data(data: Vec<T>, layout: Layout<N>, dim: [usize; N]) -> Self {
-        // compute stride if necessary
-        let stride = compute_stride(&dim, &layout);
+    /// Fallible constructor used to create owned views from existing data.
+    ///
+    /// Returns a [ViewError::DimensionMismatch] error if the length of `data` does not
+    /// match the span of the view, in addition to errors of [ViewBase::try_new].
+    pub fn try_new_from_data(
+        data: Vec<T>,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<Self, ViewError<'static>> {
+        let data: Vec<InnerDataType<T>> = data.into_iter().map(|elem| Atomic::new(elem)).collect();
+        Self::try_new_from_buffer(data.into(), layout, dim)
+    }
+}
+
+// ~~~~~~~~ Padded & memory space constructors
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits,
+{
+    /// Constructor used to create owned views in a given memory space.
+    ///
+    /// Panics if the view cannot be created, see [ViewBase::try_new_in].
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::view::{dual::MemorySpace, parameters::Layout, ViewOwned};
+    ///
+    /// // huge pages are used if available
+    /// let view: ViewOwned<'_, 2, f64> =
+    ///     ViewOwned::new_in(MemorySpace::HostHugePages, Layout::Right, [1024, 1024]);
+    /// assert!(matches!(
+    ///     view.memory_space(),
+    ///     Some(MemorySpace::HostHugePages | MemorySpace::Host)
+    /// ));
+    /// ```
+    pub fn new_in(space: MemorySpace, layout: Layout<N>, dim: [usize; N]) -> Self {
+        Self::try_new_in(space, layout, dim)
+            .unwrap_or_else(|e| panic!("could not create view: {e:?}"))
+    }
 
-        // checks
-        let capacity: usize = dim.iter().product();
-        assert_eq!(capacity, data.len());
+    /// Constructor used to create owned views whose data is aligned on `align` bytes,
+    /// e.g. 64 for AVX-512 loads.
+    ///
+    /// Panics if the view cannot be created, see [ViewBase::try_new_aligned].
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+    ///
+    /// let mut view: ViewOwned<'_, 1, f32> = ViewOwned::new_aligned(64, Layout::Right, [100]);
+    /// assert_eq!(view.alignment(), Some(64));
+    /// assert_eq!(view.as_mut_slice().unwrap().as_ptr() as usize % 64, 0);
+    /// ```
+    pub fn new_aligned(align: usize, layout: Layout<N>, dim: [usize; N]) -> Self {
+        Self::try_new_aligned(align, layout, dim)
+            .unwrap_or_else(|e| panic!("could not create view: {e:?}"))
+    }
+
+    /// Fallible constructor used to create owned views whose data is aligned on `align`
+    /// bytes. See [ViewBase::try_new_aligned_in].
+    pub fn try_new_aligned(
+        align: usize,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<Self, ViewError<'static>> {
+        Self::try_new_aligned_in(MemorySpace::Host, align, layout, dim)
+    }
+
+    /// Fallible constructor used to create owned views from an existing buffer, keeping
+    /// its alignment. Returns the same errors as [ViewBase::try_new_from_data].
+    pub(crate) fn try_new_from_buffer(
+        data: AlignedBuffer<InnerDataType<T>>,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<Self, ViewError<'static>> {
+        let (stride, capacity) = checked_geometry::<N, T>(&dim, &layout)?;
+        if capacity != data.len() {
+            return Err(ViewError::DimensionMismatch(
+                "Data length does not match the dimensions & layout of the View",
+            ));
+        }
+
+        let allocation = TrackedAllocation::new(
+            capacity * size_of::<InnerDataType<T>>(),
+            data.alignment(),
+            MemorySpace::Host,
+        );
 
         // build & return
-        Self {
-            data: DataType::Owned(data.into_iter().map(|elem| Atomic::new(elem)).collect()),
+        Ok(Self {
+            data: DataType::Owned(data),
             layout,
             dim,
             stride,
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: Some(allocation),
+        })
+    }
+
+    /// Constructor used to create owned views whose fastest-varying dimension is padded,
+    /// akin to Kokkos' `AllowPadding`.
+    ///
+    /// Using [Layout::Right] (resp. [Layout::Left]), rows (resp. columns) are padded so
+    /// that each spans a multiple of `align` bytes, e.g. [CACHE_LINE_SIZE][parameters::CACHE_LINE_SIZE]. Rows of a
+    /// matrix then do not share cache lines, which prevents false sharing when they are
+    /// updated by different threads. The resulting layout is a [Layout::Stride], see
+    /// [Layout::padded_right]. [Layout::Stride] layouts are used as is.
+    ///
+    /// If `align` is a power of two, the allocation itself is aligned on `align` bytes, so
+    /// that padded rows are aligned in memory. Otherwise, offsets are only aligned
+    /// relatively to the start of the allocation.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::view::{
+    ///     parameters::{Layout, CACHE_LINE_SIZE},
+    ///     ViewOwned,
+    /// };
+    ///
+    /// let view: ViewOwned<'_, 2, f64> = ViewOwned::new_padded(Layout::Right, [3, 5], CACHE_LINE_SIZE);
+    /// assert_eq!(view.flat_idx([1, 0]), 8);
+    /// ```
+    pub fn new_padded(layout: Layout<N>, dim: [usize; N], align: usize) -> Self {
+        let layout = match layout {
+            Layout::Right => Layout::padded_right::<T>(dim, align),
+            Layout::Left => Layout::padded_left::<T>(dim, align),
+            Layout::Stride { .. } => layout,
+        };
+        if align.is_power_of_two() {
+            Self::new_aligned(align, layout, dim)
+        } else {
+            Self::new(layout, dim)
         }
     }
 }
@@ -179,8 +592,8 @@ where
     /// consistent user API:
     ///
     /// - any feature enabled: implictly use an atomic store operation on top of the
-    /// regular [Index] trait implementation to prevent a mutable borrow. The store
-    /// currently uses relaxed ordering, this may change.
+    ///   regular [Index] trait implementation to prevent a mutable borrow. The store
+    ///   currently uses relaxed ordering, this may change.
     /// - no feature enabled: uses a regular [IndexMut] trait implementation.
     ///
     /// Note that [Index] is always implemented while [IndexMut] only is when no
@@ -188,6 +601,8 @@ where
     ///
     /// **Current version**: no feature
     pub fn set(&mut self, index: [usize; N], val: T) {
+        #[cfg(feature = "access-stats")]
+        self.stats.record_write();
         self[index] = val;
     }
 
@@ -200,8 +615,8 @@ where
     /// consistent user API:
     ///
     /// - any feature enabled: implictly use an atomic store operation on top of the
-    /// regular [Index] trait implementation to prevent a mutable borrow. The store
-    /// currently uses relaxed ordering, this may change.
+    ///   regular [Index] trait implementation to prevent a mutable borrow. The store
+    ///   currently uses relaxed ordering, this may change.
     /// - no feature enabled: uses a regular [IndexMut] trait implementation.
     ///
     /// Note that [Index] is always implemented while [IndexMut] only is when no
@@ -209,6 +624,8 @@ where
     ///
     /// **Current version**: thread-safe
     pub fn set(&self, index: [usize; N], val: T) {
+        #[cfg(feature = "access-stats")]
+        self.stats.record_write();
         self[index].store(val, Ordering::Relaxed);
     }
 
@@ -220,8 +637,8 @@ where
     /// consistent user API across features:
     ///
     /// - any feature enabled: implictly use an atomic load operation on top of the
-    /// regular [Index] trait implementation. The load currently uses relaxed ordering,
-    /// this may change.
+    ///   regular [Index] trait implementation. The load currently uses relaxed ordering,
+    ///   this may change.
     /// - no feature enabled: uses the regular [Index] trait implementation.
     ///
     /// Note that [Index] is always implemented while [IndexMut] only is when no
@@ -229,6 +646,8 @@ where
     ///
     /// **Current version**: no feature
     pub fn get(&self, index: [usize; N]) -> T {
+        #[cfg(feature = "access-stats")]
+        self.stats.record_read();
         self[index]
     }
 
@@ -240,8 +659,8 @@ where
     /// consistent user API across features:
     ///
     /// - any feature enabled: implictly use an atomic load operation on top of the
-    /// regular [Index] trait implementation. The load currently uses relaxed ordering,
-    /// this may change.
+    ///   regular [Index] trait implementation. The load currently uses relaxed ordering,
+    ///   this may change.
     /// - no feature enabled: uses the regular [Index] trait implementation.
     ///
     /// Note that [Index] is always implemented while [IndexMut] only is when no
@@ -249,68 +668,182 @@ where
     ///
     /// **Current version**: thread-safe
     pub fn get(&self, index: [usize; N]) -> T {
+        #[cfg(feature = "access-stats")]
+        self.stats.record_read();
         self[index].load(atomic::Ordering::Relaxed)
     }
 
+    #[inline(always)]
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Writing interface without bounds checking. See [ViewBase::set].
+    ///
+    /// The bounds check is kept in debug builds.
+    ///
+    /// **Current version**: no feature
+    ///
+    /// # Safety
+    ///
+    /// The flat offset of `index` must be in the bounds of the data of the view, e.g.
+    /// each index must be smaller than the corresponding dimension.
+    pub unsafe fn set_unchecked(&mut self, index: [usize; N], val: T) {
+        #[cfg(feature = "access-stats")]
+        self.stats.record_write();
+        let flat_idx = self.flat_idx(index);
+        let data: &mut [InnerDataType<T>] = match &mut self.data {
+            DataType::Owned(v) => v,
+            DataType::Borrowed(_) => unimplemented!("Cannot mutably access a read-only view!"),
+            DataType::MutBorrowed(mut_slice) => mut_slice,
+        };
+        debug_assert!(flat_idx < data.len());
+        *data.get_unchecked_mut(flat_idx) = val;
+    }
+
+    #[inline(always)]
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Writing interface without bounds checking. See [ViewBase::set].
+    ///
+    /// The bounds check is kept in debug builds.
+    ///
+    /// **Current version**: thread-safe
+    ///
+    /// # Safety
+    ///
+    /// The flat offset of `index` must be in the bounds of the data of the view, e.g.
+    /// each index must be smaller than the corresponding dimension.
+    pub unsafe fn set_unchecked(&self, index: [usize; N], val: T) {
+        #[cfg(feature = "access-stats")]
+        self.stats.record_write();
+        let flat_idx = self.flat_idx(index);
+        let data = self.data_slice();
+        debug_assert!(flat_idx < data.len());
+        data.get_unchecked(flat_idx).store(val, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Reading interface without bounds checking. See [ViewBase::get].
+    ///
+    /// The bounds check is kept in debug builds.
+    ///
+    /// **Current version**: no feature
+    ///
+    /// # Safety
+    ///
+    /// The flat offset of `index` must be in the bounds of the data of the view, e.g.
+    /// each index must be smaller than the corresponding dimension.
+    pub unsafe fn get_unchecked(&self, index: [usize; N]) -> T {
+        #[cfg(feature = "access-stats")]
+        self.stats.record_read();
+        let flat_idx = self.flat_idx(index);
+        let data = self.data_slice();
+        debug_assert!(flat_idx < data.len());
+        *data.get_unchecked(flat_idx)
+    }
+
+    #[inline(always)]
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Reading interface without bounds checking. See [ViewBase::get].
+    ///
+    /// The bounds check is kept in debug builds.
+    ///
+    /// **Current version**: thread-safe
+    ///
+    /// # Safety
+    ///
+    /// The flat offset of `index` must be in the bounds of the data of the view, e.g.
+    /// each index must be smaller than the corresponding dimension.
+    pub unsafe fn get_unchecked(&self, index: [usize; N]) -> T {
+        #[cfg(feature = "access-stats")]
+        self.stats.record_read();
+        let flat_idx = self.flat_idx(index);
+        let data = self.data_slice();
+        debug_assert!(flat_idx < data.len());
+        data.get_unchecked(flat_idx).load(Ordering::Relaxed)
+    }
+
     // ~~~~~~~~ Mirrors
 
     /// Create a new View mirroring `self`, i.e. referencing the same data. This mirror
     /// is always immutable, but it inner values might still be writable if they are
     /// atomic types.
     ///
-    /// Note that mirrors currently can only be created from the "original" view,
-    /// i.e. the view owning the data.
-    pub fn create_mirror<'b>(&'a self) -> Result<ViewRO<'b, N, T>, ViewError>
-    where
-        'a: 'b, // 'a outlives 'b
-    {
-        let inner = if let DataType::Owned(v) = &self.data {
-            v
-        } else {
-            return Err(ViewError::DoubleMirroring(
-                "Cannot create a mirror from a non-data-owning View",
-            ));
-        };
-
-        Ok(Self {
-            data: DataType::Borrowed(inner),
+    /// Mirrors can be created from any view, including other mirrors. They are tracked
+    /// by the borrow checker: the data cannot be moved or dropped while a mirror is alive.
+    pub fn create_mirror<'b>(&'b self) -> Result<ViewRO<'b, N, T>, ViewError<'b>> {
+        Ok(ViewBase {
+            data: DataType::Borrowed(self.data_slice()),
             layout: self.layout,
             dim: self.dim,
             stride: self.stride,
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: None,
         })
     }
 
-    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
     /// Create a new View mirroring `self`, i.e. referencing the same data. This mirror
-    /// uses a mutable reference, hence the serial-only definition
+    /// uses a mutable reference, meaning `self` cannot be accessed while it is alive.
     ///
-    /// Note that mirrors currently can only be created from the "original" view,
-    /// i.e. the view owning the data.
-    ///
-    /// Only defined when no feature are enabled since all interfaces should be immutable
-    /// otherwise.
-    pub fn create_mutable_mirror<'b>(&'a mut self) -> Result<ViewRW<'b, N, T>, ViewError>
-    where
-        'a: 'b, // 'a outlives 'b
-    {
-        let inner = if let DataType::Owned(v) = &mut self.data {
-            v
-        } else {
-            return Err(ViewError::DoubleMirroring(
-                "Cannot create a mirror from a non-data-owning View",
-            ));
+    /// Mirrors can be created from any view able to write its data, i.e. any view except
+    /// read-only mirrors. Using parallelization features, the mirror can be shared
+    /// between threads like any other view.
+    pub fn create_mutable_mirror<'b>(&'b mut self) -> Result<ViewRW<'b, N, T>, ViewError<'b>> {
+        let inner: &mut [InnerDataType<T>] = match &mut self.data {
+            DataType::Owned(v) => v,
+            DataType::MutBorrowed(mut_slice) => mut_slice,
+            DataType::Borrowed(_) => {
+                return Err(ViewError::DoubleMirroring(
+                    "Cannot create a mutable mirror from a read-only View",
+                ))
+            }
         };
 
-        Ok(Self {
+        Ok(ViewBase {
             data: DataType::MutBorrowed(inner),
             layout: self.layout,
             dim: self.dim,
             stride: self.stride,
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: None,
         })
     }
 
     // ~~~~~~~~ Convenience
 
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Write every element of the view with its current value.
+    ///
+    /// This is used to make sure the memory of the view is mapped (i.e. page faults
+    /// already happened) before measuring performances of a kernel. Read-only mirrors
+    /// are only read.
+    ///
+    /// **Current version**: no feature
+    pub fn touch(&mut self) {
+        match &mut self.data {
+            DataType::Owned(v) => v.iter_mut().for_each(|elem| *elem = black_box(*elem)),
+            DataType::Borrowed(slice) => slice.iter().for_each(|elem| {
+                black_box(*elem);
+            }),
+            DataType::MutBorrowed(mut_slice) => mut_slice
+                .iter_mut()
+                .for_each(|elem| *elem = black_box(*elem)),
+        }
+    }
+
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Write every element of the view with its current value.
+    ///
+    /// This is used to make sure the memory of the view is mapped (i.e. page faults
+    /// already happened) before measuring performances of a kernel.
+    ///
+    /// **Current version**: thread-safe
+    pub fn touch(&self) {
+        self.data_slice().iter().for_each(|elem| {
+            elem.store(black_box(elem.load(Ordering::Relaxed)), Ordering::Relaxed)
+        });
+    }
+
     #[cfg(all(
         test,
         not(any(feature = "rayon", feature = "threads", feature = "gpu"))
@@ -320,7 +853,7 @@ where
     /// This method is meant to be used in tests
     pub fn raw_val<'b>(self) -> Result<Vec<T>, ViewError<'b>> {
         if let DataType::Owned(v) = self.data {
-            Ok(v)
+            Ok(v.into_vec())
         } else {
             Err(ViewError::ValueError(
                 "Cannot fetch raw values of a non-data-owning views",
@@ -344,14 +877,593 @@ where
         }
     }
 
+    #[cfg(feature = "access-stats")]
+    /// Returns the number of reads and writes performed through the `get` and `set`
+    /// methods since the creation of the view (or the last reset of its counters).
+    ///
+    /// Only defined when the `access-stats` feature is enabled.
+    pub fn access_stats(&self) -> AccessCounts {
+        self.stats.snapshot()
+    }
+
+    /// Set the label of the view, reported by the allocation [registry]. Labels of views
+    /// that do not own their data are not stored.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+    ///
+    /// let view: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [8]).with_label("x");
+    /// assert_eq!(view.label(), "x");
+    /// assert_eq!(view.create_mirror().unwrap().label(), "");
+    /// ```
+    pub fn with_label(mut self, label: &str) -> Self {
+        if let Some(alloc) = &mut self.allocation {
+            alloc.set_label(label);
+        }
+        self
+    }
+
+    /// Returns the label of the view, empty if the view was not labelled.
+    pub fn label(&self) -> &str {
+        self.allocation.as_ref().map_or("", |alloc| alloc.label())
+    }
+
+    /// Returns the memory space of the data, or `None` if the view does not own it.
+    pub fn memory_space(&self) -> Option<MemorySpace> {
+        self.allocation.as_ref().map(|alloc| alloc.space())
+    }
+
+    /// Returns the alignment of the data in bytes, or `None` if the view does not own
+    /// it. The pointer to the data of the view is a multiple of the alignment.
+    pub fn alignment(&self) -> Option<usize> {
+        match &self.data {
+            DataType::Owned(v) => Some(v.alignment()),
+            _ => None,
+        }
+    }
+
+    /// Returns the dimensions of the view.
+    pub fn dims(&self) -> [usize; N] {
+        self.dim
+    }
+
     #[inline(always)]
     /// Mapping function between N-indices and the flat offset.
+    ///
+    /// Panics if the offset does not fit in [IndexType]; such an index is out of bounds,
+    /// since all offsets of the view fit in the type.
+    #[allow(clippy::unnecessary_cast)] // cast is a no-op unless using `index-u32`
     pub fn flat_idx(&self, index: [usize; N]) -> usize {
         index
             .iter()
             .zip(self.stride.iter())
-            .map(|(i, s_i)| *i * *s_i)
-            .sum()
+            .try_fold(0 as IndexType, |offset, (i, s_i)| {
+                IndexType::try_from(*i)
+                    .ok()?
+                    .checked_mul(*s_i)?
+                    .checked_add(offset)
+            })
+            .expect("index offset does not fit in the index type") as usize
+    }
+
+    /// Convert the memory layout of the view without allocating a second buffer.
+    ///
+    /// Only conversions between [Layout::Left] and [Layout::Right] are supported, and
+    /// the view must be able to write its data, i.e. not be a read-only mirror.
+    ///
+    /// Square 2D views are transposed in place using blocked swaps. Other shapes are
+    /// converted by following the cycles of the index permutation, which requires a
+    /// single bit of extra memory per element.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+    ///
+    /// let mut view: ViewOwned<'_, 2, f64> =
+    ///     ViewOwned::new_from_data(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0], Layout::Right, [2, 3]);
+    /// view.convert_layout_in_place(Layout::Left).unwrap();
+    ///
+    /// assert_eq!(view.layout, Layout::Left);
+    /// assert_eq!(view.get([1, 0]), 3.0);
+    /// assert_eq!(view.flat_idx([1, 0]), 1);
+    /// ```
+    pub fn convert_layout_in_place(&mut self, layout: Layout<N>) -> Result<(), ViewError<'static>> {
+        if layout == self.layout {
+            return Ok(());
+        }
+        if !matches!(
+            (self.layout, layout),
+            (Layout::Left, Layout::Right) | (Layout::Right, Layout::Left)
+        ) {
+            return Err(ViewError::ValueError(
+                "Only conversions between Left and Right layouts are supported",
+            ));
+        }
+        let old_stride = compute_stride(&self.dim, &self.layout);
+        let new_stride = compute_stride(&self.dim, &layout);
+        let dim = self.dim;
+        let data: &mut [InnerDataType<T>] = match &mut self.data {
+            DataType::Owned(v) => v,
+            DataType::MutBorrowed(mut_slice) => mut_slice,
+            DataType::Borrowed(_) => {
+                return Err(ViewError::ValueError(
+                    "Cannot convert the layout of a read-only View",
+                ))
+            }
+        };
+
+        if N == 2 && dim[0] == dim[1] {
+            // square matrix: Left <-> Right is a transposition
+            const BLOCK: usize = 32;
+            let n = dim[0];
+            for bi in (0..n).step_by(BLOCK) {
+                for bj in (bi..n).step_by(BLOCK) {
+                    for i in bi..(bi + BLOCK).min(n) {
+                        for j in bj.max(i + 1)..(bj + BLOCK).min(n) {
+                            data.swap(i * n + j, j * n + i);
+                        }
+                    }
+                }
+            }
+        } else {
+            // destination offset of the element stored at offset `src`
+            let dest = |src: usize| {
+                let mut dst = 0;
+                for k in 0..N {
+                    dst += (src / old_stride[k]) % dim[k] * new_stride[k];
+                }
+                dst
+            };
+            let mut visited = vec![0_u64; data.len().div_ceil(64)];
+            for start in 0..data.len() {
+                if visited[start / 64] & (1 << (start % 64)) != 0 {
+                    continue;
+                }
+                let mut cur = dest(start);
+                while cur != start {
+                    data.swap(start, cur);
+                    visited[cur / 64] |= 1 << (cur % 64);
+                    cur = dest(cur);
+                }
+            }
+        }
+
+        self.layout = layout;
+        self.stride = index_stride(new_stride, &self.dim);
+        Ok(())
+    }
+
+    /// Change the dimensions of the view, preserving its content.
+    ///
+    /// Elements whose index is valid for both the old & new dimensions keep their value;
+    /// other elements are default-initialized. Data is reallocated and strides are
+    /// recomputed accordingly, using the current layout.
+    ///
+    /// Only views owning their data can be resized, and the layout must be either
+    /// [Layout::Left] or [Layout::Right]. Use [ViewBase::realloc] if the content of the
+    /// view does not need to be preserved.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+    ///
+    /// let mut view: ViewOwned<'_, 2, f64> =
+    ///     ViewOwned::new_from_data(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0], Layout::Right, [2, 3]);
+    /// view.resize([3, 2]).unwrap();
+    ///
+    /// assert_eq!(view.dims(), [3, 2]);
+    /// assert_eq!(view.get([1, 1]), 4.0);
+    /// assert_eq!(view.get([2, 0]), 0.0);
+    /// ```
+    pub fn resize(&mut self, dim: [usize; N]) -> Result<(), ViewError<'static>> {
+        self.check_resizable()?;
+        #[allow(unused_mut)]
+        let mut new = self.reallocated(dim)?;
+        let overlap: [_; N] = std::array::from_fn(|k| 0..self.dim[k].min(dim[k]));
+        MDIndexIter::new(overlap).for_each(|idx| new.set(idx, self.load(self.flat_idx(idx))));
+        self.replace_data(new);
+        Ok(())
+    }
+
+    /// Change the dimensions of the view, discarding its content.
+    ///
+    /// This is a cheaper version of [ViewBase::resize]: all elements of the reallocated
+    /// view are default-initialized. The same restrictions apply.
+    pub fn realloc(&mut self, dim: [usize; N]) -> Result<(), ViewError<'static>> {
+        self.check_resizable()?;
+        let new = self.reallocated(dim)?;
+        self.replace_data(new);
+        Ok(())
+    }
+
+    /// Checks that the view can be reallocated.
+    fn check_resizable(&self) -> Result<(), ViewError<'static>> {
+        if !matches!(self.data, DataType::Owned(_)) {
+            return Err(ViewError::ValueError(
+                "Cannot reallocate a View that does not own its data",
+            ));
+        }
+        if matches!(self.layout, Layout::Stride { .. }) {
+            return Err(ViewError::ValueError(
+                "Cannot reallocate a View using a custom stride",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Create an owned view of dimensions `dim` using the layout, memory space &
+    /// alignment of the view.
+    fn reallocated(&self, dim: [usize; N]) -> Result<Self, ViewError<'static>> {
+        Self::try_new_aligned_in(
+            self.memory_space().unwrap_or(MemorySpace::Host),
+            self.alignment().unwrap_or(1),
+            self.layout,
+            dim,
+        )
+    }
+
+    /// Replace the data & dimensions of the view by the ones of `new`. Access counters
+    /// & the label are kept.
+    fn replace_data(&mut self, new: Self) {
+        if let Some(alloc) = &self.allocation {
+            alloc.set_bytes(size_of_val(new.data_slice()));
+        }
+        self.data = new.data;
+        self.dim = new.dim;
+        self.stride = new.stride;
+    }
+
+    /// Returns the underlying data as a slice, independently of ownership.
+    pub(crate) fn data_slice(&self) -> &[InnerDataType<T>] {
+        match &self.data {
+            DataType::Owned(v) => v,
+            DataType::Borrowed(slice) => slice,
+            DataType::MutBorrowed(mut_slice) => mut_slice,
+        }
+    }
+
+    /// Iterator over the values of the view, in layout order. Elements that are not part
+    /// of the view (e.g. padding, or the rows skipped by a subview) are not yielded.
+    pub(crate) fn values(&self) -> impl Iterator<Item = T> + '_ {
+        self.iter()
+    }
+}
+
+// ~~~~~~~~ Atomic read-modify-write interface across all features
+//
+// Operations are implemented using compare-exchange loops so that they are available
+// for all element types, floating-point numbers included.
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits,
+{
+    #[inline(always)]
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Apply `op` to the element at `index`, returning its previous value.
+    fn fetch_op(&mut self, index: [usize; N], _order: StdOrdering, op: impl Fn(T) -> T) -> T {
+        #[cfg(feature = "access-stats")]
+        {
+            self.stats.record_read();
+            self.stats.record_write();
+        }
+        let prev = self[index];
+        self[index] = op(prev);
+        prev
+    }
+
+    #[inline(always)]
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Apply `op` to the element at `index` atomically, returning its previous value.
+    fn fetch_op(&self, index: [usize; N], order: StdOrdering, op: impl Fn(T) -> T) -> T {
+        #[cfg(feature = "access-stats")]
+        {
+            self.stats.record_read();
+            self.stats.record_write();
+        }
+        // failed exchanges are loads, which cannot use release semantics
+        let fetch_order = match order {
+            Ordering::Release => Ordering::Relaxed,
+            Ordering::AcqRel => Ordering::Acquire,
+            o => o,
+        };
+        match self[index].fetch_update(order, fetch_order, |prev| Some(op(prev))) {
+            Ok(prev) | Err(prev) => prev,
+        }
+    }
+
+    #[inline(always)]
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Add `val` to the element at `index`, returning its previous value. Mirrors Kokkos' `atomic_fetch_add`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory ordering.
+    /// - no feature enabled: the ordering is ignored, exclusivity being guaranteed by
+    ///   the mutable borrow.
+    ///
+    /// **Current version**: no feature
+    pub fn fetch_add(&mut self, index: [usize; N], val: T, order: StdOrdering) -> T
+    where
+        T: Add<Output = T>,
+    {
+        self.fetch_op(index, order, |prev| prev + val)
+    }
+
+    #[inline(always)]
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Add `val` to the element at `index`, returning its previous value. Mirrors Kokkos' `atomic_fetch_add`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory ordering.
+    /// - no feature enabled: the ordering is ignored, exclusivity being guaranteed by
+    ///   the mutable borrow.
+    ///
+    /// **Current version**: thread-safe
+    pub fn fetch_add(&self, index: [usize; N], val: T, order: StdOrdering) -> T
+    where
+        T: Add<Output = T>,
+    {
+        self.fetch_op(index, order, |prev| prev + val)
+    }
+
+    #[inline(always)]
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Subtract `val` from the element at `index`, returning its previous value. Mirrors Kokkos' `atomic_fetch_sub`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory ordering.
+    /// - no feature enabled: the ordering is ignored, exclusivity being guaranteed by
+    ///   the mutable borrow.
+    ///
+    /// **Current version**: no feature
+    pub fn fetch_sub(&mut self, index: [usize; N], val: T, order: StdOrdering) -> T
+    where
+        T: Sub<Output = T>,
+    {
+        self.fetch_op(index, order, |prev| prev - val)
+    }
+
+    #[inline(always)]
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Subtract `val` from the element at `index`, returning its previous value. Mirrors Kokkos' `atomic_fetch_sub`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory ordering.
+    /// - no feature enabled: the ordering is ignored, exclusivity being guaranteed by
+    ///   the mutable borrow.
+    ///
+    /// **Current version**: thread-safe
+    pub fn fetch_sub(&self, index: [usize; N], val: T, order: StdOrdering) -> T
+    where
+        T: Sub<Output = T>,
+    {
+        self.fetch_op(index, order, |prev| prev - val)
+    }
+
+    #[inline(always)]
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Replace the element at `index` by the minimum between itself and `val`, returning its
+    /// previous value. Mirrors Kokkos' `atomic_fetch_min`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory ordering.
+    /// - no feature enabled: the ordering is ignored, exclusivity being guaranteed by
+    ///   the mutable borrow.
+    ///
+    /// **Current version**: no feature
+    pub fn fetch_min(&mut self, index: [usize; N], val: T, order: StdOrdering) -> T
+    where
+        T: PartialOrd,
+    {
+        self.fetch_op(index, order, |prev| if val < prev { val } else { prev })
+    }
+
+    #[inline(always)]
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Replace the element at `index` by the minimum between itself and `val`, returning its
+    /// previous value. Mirrors Kokkos' `atomic_fetch_min`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory ordering.
+    /// - no feature enabled: the ordering is ignored, exclusivity being guaranteed by
+    ///   the mutable borrow.
+    ///
+    /// **Current version**: thread-safe
+    pub fn fetch_min(&self, index: [usize; N], val: T, order: StdOrdering) -> T
+    where
+        T: PartialOrd,
+    {
+        self.fetch_op(index, order, |prev| if val < prev { val } else { prev })
+    }
+
+    #[inline(always)]
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Replace the element at `index` by the maximum between itself and `val`, returning its
+    /// previous value. Mirrors Kokkos' `atomic_fetch_max`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory ordering.
+    /// - no feature enabled: the ordering is ignored, exclusivity being guaranteed by
+    ///   the mutable borrow.
+    ///
+    /// **Current version**: no feature
+    pub fn fetch_max(&mut self, index: [usize; N], val: T, order: StdOrdering) -> T
+    where
+        T: PartialOrd,
+    {
+        self.fetch_op(index, order, |prev| if val > prev { val } else { prev })
+    }
+
+    #[inline(always)]
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Replace the element at `index` by the maximum between itself and `val`, returning its
+    /// previous value. Mirrors Kokkos' `atomic_fetch_max`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory ordering.
+    /// - no feature enabled: the ordering is ignored, exclusivity being guaranteed by
+    ///   the mutable borrow.
+    ///
+    /// **Current version**: thread-safe
+    pub fn fetch_max(&self, index: [usize; N], val: T, order: StdOrdering) -> T
+    where
+        T: PartialOrd,
+    {
+        self.fetch_op(index, order, |prev| if val > prev { val } else { prev })
+    }
+
+    #[inline(always)]
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Store `new` at `index` if the element is equal to `current`. Returns the previous
+    /// value, wrapped in `Ok` if it was replaced and in `Err` otherwise. Mirrors Kokkos'
+    /// `atomic_compare_exchange`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory orderings.
+    ///   Values are compared bitwise, e.g. `0.0` and `-0.0` are considered different.
+    /// - no feature enabled: orderings are ignored and values are compared using
+    ///   [PartialEq].
+    ///
+    /// **Current version**: no feature
+    pub fn compare_exchange(
+        &mut self,
+        index: [usize; N],
+        current: T,
+        new: T,
+        _success: StdOrdering,
+        _failure: StdOrdering,
+    ) -> Result<T, T>
+    where
+        T: PartialEq,
+    {
+        #[cfg(feature = "access-stats")]
+        self.stats.record_read();
+        let prev = self[index];
+        if prev != current {
+            return Err(prev);
+        }
+        #[cfg(feature = "access-stats")]
+        self.stats.record_write();
+        self[index] = new;
+        Ok(prev)
+    }
+
+    #[inline(always)]
+    #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+    /// Store `new` at `index` if the element is equal to `current`. Returns the previous
+    /// value, wrapped in `Ok` if it was replaced and in `Err` otherwise. Mirrors Kokkos'
+    /// `atomic_compare_exchange`.
+    ///
+    /// - any feature enabled: the operation is atomic and uses the given memory orderings.
+    ///   Values are compared bitwise, e.g. `0.0` and `-0.0` are considered different.
+    /// - no feature enabled: orderings are ignored and values are compared using
+    ///   [PartialEq].
+    ///
+    /// **Current version**: thread-safe
+    pub fn compare_exchange(
+        &self,
+        index: [usize; N],
+        current: T,
+        new: T,
+        success: StdOrdering,
+        failure: StdOrdering,
+    ) -> Result<T, T>
+    where
+        T: PartialEq,
+    {
+        #[cfg(feature = "access-stats")]
+        {
+            self.stats.record_read();
+            self.stats.record_write();
+        }
+        self[index].compare_exchange(current, new, success, failure)
+    }
+}
+
+// ~~~~~~~~ Host reductions
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits + std::ops::Add<Output = T>,
+{
+    /// Sum all elements of the view into a host scalar.
+    ///
+    /// The reduction is done on the host and does not depend on enabled features. Note
+    /// that for integer types, overflows panic in debug builds and wrap silently in
+    /// release builds; use [`sum_checked`][Self::sum_checked] or
+    /// [`sum_saturating`][Self::sum_saturating] to handle them explicitly.
+    pub fn sum(&self) -> T {
+        self.values().fold(T::default(), |acc, val| acc + val)
+    }
+}
+
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: Scalar,
+{
+    /// Sum all elements of the view into a host scalar using the given combine strategy.
+    ///
+    /// See [ReductionStrategy] for the error bound of each strategy.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::{
+    ///     routines::parameters::ReductionStrategy,
+    ///     view::{parameters::Layout, ViewOwned},
+    /// };
+    ///
+    /// let mut data = vec![1.0e-16; 1001];
+    /// data[0] = 1.0;
+    /// let view: ViewOwned<'_, 1, f64> = ViewOwned::new_from_data(data, Layout::Right, [1001]);
+    ///
+    /// // small values are absorbed one by one when accumulating sequentially
+    /// assert_eq!(view.sum_with(ReductionStrategy::Sequential), 1.0);
+    /// assert!(view.sum_with(ReductionStrategy::Kahan) > 1.0);
+    /// ```
+    pub fn sum_with(&self, strategy: ReductionStrategy) -> T {
+        strategy.sum(self.values())
+    }
+}
+
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: IntegerTraits + Send + Sync,
+{
+    /// Sum all elements of an integer view into a host scalar, reporting overflows.
+    ///
+    /// An overflow is only reported if the exact sum does not fit in the type: partial
+    /// sums may overflow, e.g. when adding `[i8::MAX, 1, -1]`. The sum is computed using
+    /// a `parallel_reduce` statement on the CPU, see [OverflowSum].
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+    ///
+    /// let counts: ViewOwned<'_, 1, u8> = ViewOwned::new_from_data(vec![200, 100], Layout::Right, [2]);
+    /// assert!(counts.sum_checked().is_err());
+    /// assert_eq!(counts.sum_saturating(), u8::MAX);
+    /// ```
+    pub fn sum_checked(&self) -> Result<T, ViewError<'static>> {
+        OverflowSum::checked(self.sum_overflowing()).ok_or(ViewError::Overflow(
+            "Integer overflow occured during view reduction",
+        ))
+    }
+
+    /// Sum all elements of an integer view into a host scalar, saturating at the
+    /// numeric bounds of the type instead of overflowing.
+    ///
+    /// The exact sum is clamped once, i.e. the result does not depend on the order of
+    /// the elements. The sum is computed using a `parallel_reduce` statement on the CPU,
+    /// see [OverflowSum].
+    pub fn sum_saturating(&self) -> T {
+        OverflowSum::saturating(self.sum_overflowing())
+    }
+
+    /// Sum all elements using an [OverflowSum] reducer.
+    fn sum_overflowing(&self) -> (T, i64) {
+        let execp = TypedExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            ..TypedExecutionPolicy::new(MDRange::from_dims(self.dims()))
+        };
+        parallel_reduce(execp, OverflowSum::new(), |idx| {
+            (self.load(self.flat_idx(idx)), 0)
+        })
+        .expect("MDRange reductions are supported by all CPU backends")
     }
 }
 
@@ -403,6 +1515,243 @@ where
     }
 }
 
+// ~~~~~~~~ Copies
+
+/// Returns the N-index of the `k`-th element of a view of dimensions `dim`, in
+/// row-major order.
+fn unflatten_idx<const N: usize>(dim: &[usize; N], mut k: usize) -> [usize; N] {
+    let mut index = [0; N];
+    for (i, d) in index.iter_mut().zip(dim.iter()).rev() {
+        *i = k % d;
+        k /= d;
+    }
+    index
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))] {
+        /// Copy the content of `src` into `dst`, converting the memory layout if needed.
+        ///
+        /// Both views must have the same dimensions; their layouts can differ, including
+        /// [Layout::Stride]. Views sharing the same strides are copied in memory order,
+        /// other views are copied element by element. The copy is done using a
+        /// `parallel_for` statement on the CPU.
+        ///
+        /// **Current version**: thread-safe
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::view::{deep_copy, parameters::Layout, ViewOwned};
+        ///
+        /// let src: ViewOwned<'_, 2, f64> =
+        ///     ViewOwned::new_from_data(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0], Layout::Right, [2, 3]);
+        /// let mut dst: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Left, [2, 3]);
+        /// deep_copy(&mut dst, &src).unwrap();
+        ///
+        /// assert_eq!(dst.get([1, 0]), 3.0);
+        /// ```
+        pub fn deep_copy<const N: usize, T>(
+            dst: &mut ViewBase<'_, N, T>,
+            src: &ViewBase<'_, N, T>,
+        ) -> Result<(), ViewError<'static>>
+        where
+            T: DataTraits + Send + Sync,
+        {
+            // writes are atomic, a shared borrow is enough for the kernel
+            let dst = &*dst;
+            if dst.dim != src.dim {
+                return Err(ViewError::ValueError("Views must have identical dimensions"));
+            }
+            let (dst_data, src_data) = (dst.data_slice(), src.data_slice());
+            // subviews may not be contiguous
+            let same_stride = dst.stride == src.stride
+                && dst_data.len() == src_data.len()
+                && dst_data.len() == dst.dim.iter().product();
+            let execp = ExecutionPolicy {
+                space: ExecutionSpace::DeviceCPU,
+                range: RangePolicy::RangePolicy(0..dst.dim.iter().product()),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let kernel = |arg: KernelArgs<1>| {
+                if let KernelArgs::Index1D(k) = arg {
+                    if same_stride {
+                        dst_data[k].store(src_data[k].load(Ordering::Relaxed), Ordering::Relaxed);
+                    } else {
+                        let index = unflatten_idx(&dst.dim, k);
+                        dst.set(index, src.get(index));
+                    }
+                }
+            };
+            parallel_for(execp, kernel).map_err(|_| ViewError::ValueError("Copy statement failed"))
+        }
+        /// Copy the content of `src` into `dst`, converting elements to the element type
+        /// of `dst`, e.g. from `f64` to `f32`. See [CastFrom] for conversion rules.
+        ///
+        /// Layouts are handled like [deep_copy]. This makes it possible to store data using
+        /// a lower precision, while accumulating using a higher one.
+        ///
+        /// **Current version**: thread-safe
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::view::{deep_copy_convert, parameters::Layout, ViewOwned};
+        ///
+        /// let src: ViewOwned<'_, 1, f64> =
+        ///     ViewOwned::new_from_data(vec![0.1, 1.0e40], Layout::Right, [2]);
+        /// let mut dst: ViewOwned<'_, 1, f32> = ViewOwned::new(Layout::Right, [2]);
+        /// deep_copy_convert(&mut dst, &src).unwrap();
+        ///
+        /// assert_eq!(dst.get([0]), 0.1_f32);
+        /// assert_eq!(dst.get([1]), f32::INFINITY);
+        /// ```
+        pub fn deep_copy_convert<const N: usize, D, S>(
+            dst: &mut ViewBase<'_, N, D>,
+            src: &ViewBase<'_, N, S>,
+        ) -> Result<(), ViewError<'static>>
+        where
+            D: CastFrom<S> + Send + Sync,
+            S: DataTraits + Send + Sync,
+        {
+            // writes are atomic, a shared borrow is enough for the kernel
+            let dst = &*dst;
+            if dst.dim != src.dim {
+                return Err(ViewError::ValueError("Views must have identical dimensions"));
+            }
+            let (dst_data, src_data) = (dst.data_slice(), src.data_slice());
+            // subviews may not be contiguous
+            let same_stride = dst.stride == src.stride
+                && dst_data.len() == src_data.len()
+                && dst_data.len() == dst.dim.iter().product();
+            let execp = ExecutionPolicy {
+                space: ExecutionSpace::DeviceCPU,
+                range: RangePolicy::RangePolicy(0..dst.dim.iter().product()),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            let kernel = |arg: KernelArgs<1>| {
+                if let KernelArgs::Index1D(k) = arg {
+                    if same_stride {
+                        let val = D::cast_from(src_data[k].load(Ordering::Relaxed));
+                        dst_data[k].store(val, Ordering::Relaxed);
+                    } else {
+                        let index = unflatten_idx(&dst.dim, k);
+                        dst.set(index, D::cast_from(src.get(index)));
+                    }
+                }
+            };
+            parallel_for(execp, kernel).map_err(|_| ViewError::ValueError("Copy statement failed"))
+        }
+    } else {
+        /// Copy the content of `src` into `dst`, converting the memory layout if needed.
+        ///
+        /// Both views must have the same dimensions; their layouts can differ, including
+        /// [Layout::Stride]. Views sharing the same strides are copied in memory order,
+        /// other views are copied element by element. `dst` must not be a read-only
+        /// mirror.
+        ///
+        /// **Current version**: no feature
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::view::{deep_copy, parameters::Layout, ViewOwned};
+        ///
+        /// let src: ViewOwned<'_, 2, f64> =
+        ///     ViewOwned::new_from_data(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0], Layout::Right, [2, 3]);
+        /// let mut dst: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Left, [2, 3]);
+        /// deep_copy(&mut dst, &src).unwrap();
+        ///
+        /// assert_eq!(dst.get([1, 0]), 3.0);
+        /// ```
+        pub fn deep_copy<const N: usize, T>(
+            dst: &mut ViewBase<'_, N, T>,
+            src: &ViewBase<'_, N, T>,
+        ) -> Result<(), ViewError<'static>>
+        where
+            T: DataTraits,
+        {
+            if dst.dim != src.dim {
+                return Err(ViewError::ValueError("Views must have identical dimensions"));
+            }
+            // subviews may not be contiguous
+            let contiguous = src.data_slice().len() == src.dim.iter().product();
+            if dst.stride == src.stride && contiguous && dst.data_slice().len() == src.data_slice().len() {
+                let dst_data: &mut [T] = match &mut dst.data {
+                    DataType::Owned(v) => v,
+                    DataType::MutBorrowed(mut_slice) => mut_slice,
+                    DataType::Borrowed(_) => {
+                        return Err(ViewError::ValueError("Cannot copy into a read-only View"))
+                    }
+                };
+                dst_data.copy_from_slice(src.data_slice());
+            } else {
+                if let DataType::Borrowed(_) = dst.data {
+                    return Err(ViewError::ValueError("Cannot copy into a read-only View"));
+                }
+                MDIndexIter::new(dst.dim.map(|d| 0..d)).for_each(|index| dst.set(index, src.get(index)));
+            }
+            Ok(())
+        }
+        /// Copy the content of `src` into `dst`, converting elements to the element type
+        /// of `dst`, e.g. from `f64` to `f32`. See [CastFrom] for conversion rules.
+        ///
+        /// Layouts are handled like [deep_copy]. This makes it possible to store data using
+        /// a lower precision, while accumulating using a higher one.
+        ///
+        /// **Current version**: no feature
+        ///
+        /// ### Example
+        ///
+        /// ```rust
+        /// use poc_kokkos_rs::view::{deep_copy_convert, parameters::Layout, ViewOwned};
+        ///
+        /// let src: ViewOwned<'_, 1, f64> =
+        ///     ViewOwned::new_from_data(vec![0.1, 1.0e40], Layout::Right, [2]);
+        /// let mut dst: ViewOwned<'_, 1, f32> = ViewOwned::new(Layout::Right, [2]);
+        /// deep_copy_convert(&mut dst, &src).unwrap();
+        ///
+        /// assert_eq!(dst.get([0]), 0.1_f32);
+        /// assert_eq!(dst.get([1]), f32::INFINITY);
+        /// ```
+        pub fn deep_copy_convert<const N: usize, D, S>(
+            dst: &mut ViewBase<'_, N, D>,
+            src: &ViewBase<'_, N, S>,
+        ) -> Result<(), ViewError<'static>>
+        where
+            D: CastFrom<S>,
+            S: DataTraits,
+        {
+            if dst.dim != src.dim {
+                return Err(ViewError::ValueError("Views must have identical dimensions"));
+            }
+            // subviews may not be contiguous
+            let contiguous = src.data_slice().len() == src.dim.iter().product();
+            if dst.stride == src.stride && contiguous && dst.data_slice().len() == src.data_slice().len() {
+                let dst_data: &mut [D] = match &mut dst.data {
+                    DataType::Owned(v) => v,
+                    DataType::MutBorrowed(mut_slice) => mut_slice,
+                    DataType::Borrowed(_) => {
+                        return Err(ViewError::ValueError("Cannot copy into a read-only View"))
+                    }
+                };
+                dst_data.iter_mut().zip(src.data_slice()).for_each(|(d, s)| *d = D::cast_from(*s));
+            } else {
+                if let DataType::Borrowed(_) = dst.data {
+                    return Err(ViewError::ValueError("Cannot copy into a read-only View"));
+                }
+                MDIndexIter::new(dst.dim.map(|d| 0..d))
+                    .for_each(|index| dst.set(index, D::cast_from(src.get(index))));
+            }
+            Ok(())
+        }
+    }
+}
+
 /// View type owning the data it yields access to, i.e. "original" view.
 pub type ViewOwned<'a, const N: usize, T> = ViewBase<'a, N, T>;
 
@@ -413,3 +1762,477 @@ pub type ViewRO<'a, const N: usize, T> = ViewBase<'a, N, T>;
 /// View type owning a mutable borrow to the data it yields access to, i.e. a
 /// read-write mirror.
 pub type ViewRW<'a, const N: usize, T> = ViewBase<'a, N, T>;
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::{
+        iter::MDIndexIter,
+        parameters::{ExecutionSpace, MDRange, Schedule, TypedExecutionPolicy},
+        typed,
+    };
+    use subview::SliceArg;
+
+    #[test]
+    fn sum_float() {
+        let view: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], Layout::Left, [2, 3]);
+        assert_eq!(view.sum(), 21.0);
+    }
+
+    #[test]
+    fn sum_strategies() {
+        // 1 followed by many small values, absorbed by sequential accumulation
+        let mut data = vec![1.0e-16; 4097];
+        data[0] = 1.0;
+        let view: ViewOwned<'_, 1, f64> = ViewOwned::new_from_data(data, Layout::Right, [4097]);
+        let exact = 1.0 + 4096.0e-16;
+
+        assert_eq!(view.sum_with(ReductionStrategy::Sequential), 1.0);
+        let is_accurate = |sum: f64| (sum - exact).abs() <= 4.0 * f64::EPSILON;
+        assert!(is_accurate(view.sum_with(ReductionStrategy::Kahan)));
+        assert!(is_accurate(view.sum_with(ReductionStrategy::Pairwise)));
+        // values of the first chunk are still absorbed, within the documented bound
+        let chunked = view.sum_with(ReductionStrategy::default());
+        assert!(chunked > 1.0);
+        assert!((chunked - exact).abs() <= (128.0 + 5.0) * f64::EPSILON * exact);
+
+        // odd-sized chunks & lengths
+        let view: ViewOwned<'_, 1, f64> = ViewOwned::new_from_data(
+            (1..=1000).map(|x| x as f64).collect(),
+            Layout::Right,
+            [1000],
+        );
+        assert_eq!(
+            view.sum_with(ReductionStrategy::Chunked { chunk: 7 }),
+            500500.0
+        );
+    }
+
+    #[test]
+    fn convert_layout() {
+        // permutation cycles path
+        let dim = [3, 5, 7];
+        let data: Vec<f64> = (0..3 * 5 * 7).map(|x| x as f64).collect();
+        let ref_view: ViewOwned<'_, 3, f64> =
+            ViewOwned::new_from_data(data.clone(), Layout::Right, dim);
+        let mut view: ViewOwned<'_, 3, f64> = ViewOwned::new_from_data(data, Layout::Right, dim);
+
+        view.convert_layout_in_place(Layout::Left).unwrap();
+        assert_eq!(
+            view.stride,
+            ViewOwned::<'_, 3, f64>::new(Layout::Left, dim).stride
+        );
+        MDIndexIter::new(dim.map(|d| 0..d))
+            .for_each(|idx| assert_eq!(view.get(idx), ref_view.get(idx)));
+
+        // square transposition path
+        let data: Vec<f64> = (0..40 * 40).map(|x| x as f64).collect();
+        let mut view: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data(data.clone(), Layout::Left, [40, 40]);
+        view.convert_layout_in_place(Layout::Right).unwrap();
+        MDIndexIter::new([0..40, 0..40])
+            .for_each(|[i, j]| assert_eq!(view.get([i, j]), data[i + 40 * j]));
+        view.convert_layout_in_place(Layout::Left).unwrap();
+        assert_eq!(view.raw_val().unwrap(), data);
+
+        let mut view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [4, 4]);
+        assert!(view
+            .convert_layout_in_place(Layout::Stride { s: [1, 4] })
+            .is_err());
+    }
+
+    #[test]
+    fn resize_realloc() {
+        let data: Vec<f64> = (0..3 * 4).map(|x| x as f64).collect();
+        for layout in [Layout::Right, Layout::Left] {
+            let ref_view: ViewOwned<'_, 2, f64> =
+                ViewOwned::new_from_data(data.clone(), layout, [3, 4]);
+            let mut view: ViewOwned<'_, 2, f64> =
+                ViewOwned::new_from_data(data.clone(), layout, [3, 4]);
+
+            // grow along one dimension, shrink along the other
+            view.resize([5, 2]).unwrap();
+            assert_eq!(view.dims(), [5, 2]);
+            assert_eq!(
+                view.stride,
+                ViewOwned::<'_, 2, f64>::new(layout, [5, 2]).stride
+            );
+            MDIndexIter::new([0..5, 0..2]).for_each(|[i, j]| {
+                let expected = if i < 3 { ref_view.get([i, j]) } else { 0.0 };
+                assert_eq!(view.get([i, j]), expected);
+            });
+
+            view.resize([0, 2]).unwrap();
+            assert_eq!(view.raw_val().unwrap(), Vec::<f64>::new());
+        }
+
+        let mut view: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data(data.clone(), Layout::Right, [3, 4]);
+        view.realloc([2, 2]).unwrap();
+        assert_eq!(view.raw_val().unwrap(), vec![0.0; 4]);
+
+        // unsupported views
+        let mut view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Stride { s: [1, 3] }, [3, 4]);
+        assert!(view.resize([4, 4]).is_err());
+        let view: ViewOwned<'_, 2, f64> = ViewOwned::new_from_data(data, Layout::Right, [3, 4]);
+        let mut mirror = view.create_mirror().unwrap();
+        assert!(mirror.realloc([4, 4]).is_err());
+    }
+
+    #[test]
+    fn unchecked_access() {
+        #[allow(unused_mut)]
+        let mut view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Left, [3, 4]);
+        for i in 0..3 {
+            for j in 0..4 {
+                // SAFETY: indices are in the bounds of the view
+                unsafe { view.set_unchecked([i, j], (4 * i + j) as f64) };
+            }
+        }
+        for i in 0..3 {
+            for j in 0..4 {
+                assert_eq!(view.get([i, j]), (4 * i + j) as f64);
+                // SAFETY: indices are in the bounds of the view
+                assert_eq!(unsafe { view.get_unchecked([i, j]) }, view.get([i, j]));
+            }
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic]
+    fn unchecked_access_debug() {
+        let view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [3, 4]);
+        // out of bounds: still caught in debug builds
+        let _ = unsafe { view.get_unchecked([3, 0]) };
+    }
+
+    #[cfg(feature = "index-u32")]
+    #[test]
+    #[should_panic(expected = "index offset does not fit in the index type")]
+    fn out_of_bounds_u32_offset() {
+        let view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [3, 4]);
+        // 4 * 2^30 wraps to 0 using u32 arithmetic
+        let _ = view.get([1 << 30, 0]);
+    }
+
+    #[test]
+    fn fallible_constructors() {
+        let view = ViewOwned::<'_, 2, f64>::try_new(Layout::Left, [3, 4]).unwrap();
+        assert_eq!(view.dims(), [3, 4]);
+        assert_eq!(view.raw_val().unwrap(), vec![0.0; 12]);
+        let view = ViewOwned::<'_, 2, f64>::try_new_from_data(vec![1.0; 12], Layout::Right, [3, 4]);
+        assert!(view.is_ok());
+
+        assert!(matches!(
+            ViewOwned::<'_, 2, f64>::try_new_from_data(vec![1.0; 11], Layout::Right, [3, 4]),
+            Err(ViewError::DimensionMismatch(_))
+        ));
+        assert!(matches!(
+            ViewOwned::<'_, 0, f64>::try_new(Layout::Right, []),
+            Err(ViewError::ZeroSize(_))
+        ));
+        assert!(matches!(
+            ViewOwned::<'_, 2, f64>::try_new(Layout::Right, [usize::MAX, 2]),
+            Err(ViewError::AllocationFailure(_))
+        ));
+        assert!(matches!(
+            ViewOwned::<'_, 2, f64>::try_new(Layout::Stride { s: [usize::MAX, 1] }, [2, 2]),
+            Err(ViewError::AllocationFailure(_))
+        ));
+        // zero-length views are valid
+        let view = ViewOwned::<'_, 2, f64>::try_new(Layout::Right, [0, 8]).unwrap();
+        assert_eq!(view.raw_val().unwrap(), Vec::<f64>::new());
+
+        // failed reallocations leave the view untouched
+        let mut view: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data(vec![1.0; 12], Layout::Right, [3, 4]);
+        assert!(view.resize([usize::MAX / 2, 4]).is_err());
+        assert!(view.realloc([1 << 40, 1 << 40]).is_err());
+        assert_eq!(view.dims(), [3, 4]);
+        assert_eq!(view.get([2, 3]), 1.0);
+    }
+
+    #[test]
+    fn mirrors() {
+        let mut view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [3, 4]);
+        {
+            #[allow(unused_mut)]
+            let mut mirror = view.create_mutable_mirror().unwrap();
+            mirror.set([1, 2], 1.0);
+            // mirror of a mirror
+            #[allow(unused_mut)]
+            let mut inner = mirror.create_mutable_mirror().unwrap();
+            inner.set([2, 3], 2.0);
+            let ro = inner.create_mirror().unwrap();
+            assert_eq!(ro.get([1, 2]), 1.0);
+            let ro_ro = ro.create_mirror().unwrap();
+            assert_eq!(ro_ro.get([2, 3]), 2.0);
+        }
+        assert_eq!(view.get([1, 2]), 1.0);
+        assert_eq!(view.get([2, 3]), 2.0);
+
+        // mutable mirrors can be written from parallel kernels
+        {
+            #[allow(unused_mut)]
+            let mut mirror = view.create_mutable_mirror().unwrap();
+            let execp = TypedExecutionPolicy {
+                space: ExecutionSpace::DeviceCPU,
+                policy: MDRange::from_dims([3, 4]),
+                schedule: Schedule::default(),
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            typed::parallel_for(execp, |[i, j]| mirror.set([i, j], (4 * i + j) as f64)).unwrap();
+        }
+        assert_eq!(view.sum(), (0..12).sum::<usize>() as f64);
+
+        let ro = view.create_mirror().unwrap();
+        let mut ro_copy = ro.create_mirror().unwrap();
+        assert!(matches!(
+            ro_copy.create_mutable_mirror(),
+            Err(ViewError::DoubleMirroring(_))
+        ));
+    }
+
+    #[test]
+    fn padded_views() {
+        let dim = [5, 3];
+        for layout in [Layout::Right, Layout::Left] {
+            #[allow(unused_mut)]
+            let mut view: ViewOwned<'_, 2, f64> =
+                ViewOwned::new_padded(layout, dim, parameters::CACHE_LINE_SIZE);
+            // each leading index starts on a cache line
+            let lead = match layout {
+                Layout::Right => view.flat_idx([1, 0]),
+                _ => view.flat_idx([0, 1]),
+            };
+            assert_eq!(lead % 8, 0);
+            assert!(lead > 0);
+            MDIndexIter::new([0..5, 0..3]).for_each(|[i, j]| view.set([i, j], (3 * i + j) as f64));
+            MDIndexIter::new([0..5, 0..3])
+                .for_each(|[i, j]| assert_eq!(view.get([i, j]), (3 * i + j) as f64));
+        }
+
+        // strided layouts are kept as is
+        let stride = Layout::Stride { s: [1, 6] };
+        let view: ViewOwned<'_, 2, f64> = ViewOwned::new_padded(stride, dim, 64);
+        assert_eq!(view.layout, stride);
+    }
+
+    #[test]
+    fn padded_host_reductions() {
+        for layout in [Layout::Right, Layout::Left] {
+            #[allow(unused_mut)]
+            let mut view: ViewOwned<'_, 2, f64> =
+                ViewOwned::new_padded(layout, [5, 3], parameters::CACHE_LINE_SIZE);
+            MDIndexIter::new([0..5, 0..3]).for_each(|idx| view.set(idx, 2.0));
+            // padding elements are not part of the view
+            assert_eq!(view.values().count(), 15);
+            assert_eq!(view.values().fold(f64::INFINITY, f64::min), 2.0);
+            assert_eq!(view.sum(), 30.0);
+            assert_eq!(view.sum_with(ReductionStrategy::Pairwise), 30.0);
+        }
+    }
+
+    #[test]
+    fn deep_copy_layouts() {
+        let dim = [3, 4, 5];
+        let data: Vec<f64> = (0..3 * 4 * 5).map(|x| x as f64).collect();
+        let src: ViewOwned<'_, 3, f64> = ViewOwned::new_from_data(data.clone(), Layout::Right, dim);
+        for layout in [
+            Layout::Right,
+            Layout::Left,
+            Layout::Stride { s: [1, 15, 3] },
+        ] {
+            let mut dst: ViewOwned<'_, 3, f64> = ViewOwned::new(layout, dim);
+            deep_copy(&mut dst, &src).unwrap();
+            MDIndexIter::new(dim.map(|d| 0..d))
+                .for_each(|idx| assert_eq!(dst.get(idx), src.get(idx)));
+        }
+
+        let mut dst: ViewOwned<'_, 3, f64> = ViewOwned::new(Layout::Right, [3, 4, 4]);
+        assert!(deep_copy(&mut dst, &src).is_err());
+
+        // non-contiguous source
+        let block = src
+            .subview::<2>([
+                SliceArg::Index(1),
+                SliceArg::Range(1..3),
+                SliceArg::Range(0..3),
+            ])
+            .unwrap();
+        let mut dst: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [2, 3]);
+        deep_copy(&mut dst, &block).unwrap();
+        MDIndexIter::new([0..2, 0..3])
+            .for_each(|[j, k]| assert_eq!(dst.get([j, k]), src.get([1, j + 1, k])));
+    }
+
+    #[test]
+    fn deep_copy_precision() {
+        let dim = [4, 5];
+        let data: Vec<f64> = (0..4 * 5).map(|x| 1.0 + x as f64 / 3.0).collect();
+        let src: ViewOwned<'_, 2, f64> = ViewOwned::new_from_data(data, Layout::Right, dim);
+        for layout in [Layout::Right, Layout::Left] {
+            let mut low: ViewOwned<'_, 2, f32> = ViewOwned::new(layout, dim);
+            deep_copy_convert(&mut low, &src).unwrap();
+            let mut high: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, dim);
+            deep_copy_convert(&mut high, &low).unwrap();
+            MDIndexIter::new(dim.map(|d| 0..d)).for_each(|idx| {
+                assert_eq!(low.get(idx), src.get(idx) as f32);
+                assert!((high.get(idx) - src.get(idx)).abs() <= f32::EPSILON as f64 * 8.0);
+            });
+        }
+
+        let mut low: ViewOwned<'_, 2, f32> = ViewOwned::new(Layout::Right, [5, 4]);
+        assert!(deep_copy_convert(&mut low, &src).is_err());
+    }
+
+    #[test]
+    fn fetch_ops() {
+        use crate::{
+            functor::KernelArgs,
+            routines::{
+                parallel_for,
+                parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+            },
+        };
+        use std::sync::atomic::Ordering;
+
+        // scatter-add into a histogram
+        #[allow(unused_mut)]
+        let mut hist: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [4]);
+        let execp = ExecutionPolicy {
+            space: ExecutionSpace::DeviceCPU,
+            range: RangePolicy::RangePolicy(0..10_000),
+            schedule: Schedule::default(),
+            chunk_size: None,
+            chunk_predicate: None,
+        };
+        parallel_for(execp, |arg: KernelArgs<1>| {
+            if let KernelArgs::Index1D(i) = arg {
+                hist.fetch_add([i % 4], 0.5, Ordering::Relaxed);
+            }
+        })
+        .unwrap();
+        assert!((0..4).all(|i| hist.get([i]) == 1250.0));
+
+        #[allow(unused_mut)]
+        let mut view: ViewOwned<'_, 2, i32> = ViewOwned::new(Layout::Left, [2, 2]);
+        assert_eq!(view.fetch_sub([1, 0], 3, Ordering::AcqRel), 0);
+        assert_eq!(view.fetch_min([1, 0], -5, Ordering::SeqCst), -3);
+        assert_eq!(view.fetch_max([1, 0], 7, Ordering::Release), -5);
+        assert_eq!(view.fetch_max([1, 0], 2, Ordering::Acquire), 7);
+        assert_eq!(view.get([1, 0]), 7);
+
+        let (ok, fail) = (Ordering::AcqRel, Ordering::Acquire);
+        assert_eq!(view.compare_exchange([1, 0], 7, 1, ok, fail), Ok(7));
+        assert_eq!(view.compare_exchange([1, 0], 7, 2, ok, fail), Err(1));
+        assert_eq!(view.get([1, 0]), 1);
+    }
+
+    #[test]
+    fn sum_checked_overflow() {
+        let view: ViewOwned<'_, 1, i32> =
+            ViewOwned::new_from_data(vec![i32::MAX, 1, 5], Layout::Right, [3]);
+        assert!(matches!(view.sum_checked(), Err(ViewError::Overflow(_))));
+        // partial sums overflow, the exact sum does not
+        let view: ViewOwned<'_, 1, i32> =
+            ViewOwned::new_from_data(vec![i32::MAX, 1, -5], Layout::Right, [3]);
+        assert_eq!(view.sum_checked().unwrap(), i32::MAX - 4);
+
+        let view: ViewOwned<'_, 1, i64> =
+            ViewOwned::new_from_data(vec![i32::MAX as i64, 1, -5], Layout::Right, [3]);
+        assert_eq!(view.sum_checked().unwrap(), i32::MAX as i64 - 4);
+    }
+
+    #[test]
+    fn sum_saturating_bounds() {
+        let view: ViewOwned<'_, 1, i8> =
+            ViewOwned::new_from_data(vec![-100, -100, 50], Layout::Right, [3]);
+        // the exact sum, -150, is clamped
+        assert_eq!(view.sum_saturating(), -128);
+        let view: ViewOwned<'_, 1, i8> =
+            ViewOwned::new_from_data(vec![100, 100, -100], Layout::Right, [3]);
+        assert_eq!(view.sum_saturating(), 100);
+        let view: ViewOwned<'_, 1, u16> =
+            ViewOwned::new_from_data(vec![u16::MAX, 10], Layout::Right, [2]);
+        assert_eq!(view.sum_saturating(), u16::MAX);
+    }
+
+    #[test]
+    fn labelled_allocations() {
+        let label = "view::tests::labelled_allocations";
+        let bytes = || registry::bytes_per_label().get(label).copied();
+        let mut view: ViewOwned<'_, 2, i64> =
+            ViewOwned::new(Layout::Right, [4, 4]).with_label(label);
+        assert_eq!(bytes(), Some(16 * 8));
+        view.resize([8, 4]).unwrap();
+        assert_eq!(view.label(), label);
+        assert_eq!(bytes(), Some(32 * 8));
+        drop(view);
+        assert_eq!(bytes(), None);
+    }
+
+    #[test]
+    fn memory_spaces() {
+        // large enough to span huge pages
+        let dim = [1 << 20];
+        for space in [
+            MemorySpace::Host,
+            MemorySpace::HostHugePages,
+            MemorySpace::Device,
+        ] {
+            #[allow(unused_mut)]
+            let mut view: ViewOwned<'_, 1, f64> = ViewOwned::new_in(space, Layout::Right, dim);
+            let used = view.memory_space().unwrap();
+            assert!(
+                used == space || (space, used) == (MemorySpace::HostHugePages, MemorySpace::Host)
+            );
+            view.set([dim[0] - 1], 1.0);
+            assert_eq!(view.sum(), 1.0);
+            assert_eq!(view.create_mirror().unwrap().memory_space(), None);
+        }
+    }
+
+    #[test]
+    fn aligned_views() {
+        let aligned = |view: &mut ViewOwned<'_, 2, f32>, align: usize| {
+            assert_eq!(view.alignment(), Some(align));
+            assert_eq!(view.as_mut_slice().unwrap().as_ptr() as usize % align, 0);
+        };
+        // huge pages are aligned on 2 MB
+        let mut view: ViewOwned<'_, 2, f32> = ViewOwned::try_new_aligned_in(
+            MemorySpace::HostHugePages,
+            1 << 21,
+            Layout::Right,
+            [512, 1024],
+        )
+        .unwrap();
+        aligned(&mut view, 1 << 21);
+        assert!(registry::allocations()
+            .iter()
+            .any(|rec| rec.align == 1 << 21));
+        drop(view);
+
+        let mut view: ViewOwned<'_, 2, f32> = ViewOwned::new_aligned(64, Layout::Left, [3, 5]);
+        aligned(&mut view, 64);
+        view.set([2, 4], 1.0);
+        // reallocations keep the alignment
+        view.resize([7, 5]).unwrap();
+        aligned(&mut view, 64);
+        assert_eq!(view.get([2, 4]), 1.0);
+        assert_eq!(view.raw_val().unwrap().len(), 35);
+
+        // smaller alignments are raised to the alignment of the element type
+        let mut view: ViewOwned<'_, 2, f32> = ViewOwned::new_aligned(1, Layout::Right, [2, 2]);
+        aligned(&mut view, 4);
+        assert!(ViewOwned::<'_, 2, f32>::try_new_aligned(24, Layout::Right, [2, 2]).is_err());
+        let view: ViewOwned<'_, 2, f32> = ViewOwned::new_padded(Layout::Right, [3, 5], 128);
+        assert_eq!(view.alignment(), Some(128));
+    }
+}
@@ -0,0 +1,281 @@
+//! element-wise update related code
+//!
+//! This module contains element-wise operations updating a view in place, covering
+//! common initialization & update patterns without writing a kernel:
+//!
+//! - [ViewBase::fill] sets all elements to a value,
+//! - [ViewBase::assign_from] copies the elements of another view, see [deep_copy],
+//! - [ViewBase::scale] multiplies all elements by a factor,
+//! - [ViewBase::axpby] computes `alpha * x + beta * self`, like the BLAS routine.
+//!
+//! Operations are done using a `parallel_for` statement on the CPU. Views of any layout
+//! can be used, including subviews: operands sharing the same strides are updated in
+//! memory order, other operands element by element.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+//!
+//! let x: ViewOwned<'_, 2, f64> = ViewOwned::new_from_data(vec![1.0; 6], Layout::Left, [2, 3]);
+//! let mut y: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [2, 3]);
+//!
+//! y.fill(2.0).unwrap();
+//! y.scale(0.5).unwrap();
+//! // y = 3x + 2y
+//! y.axpby(3.0, &x, 2.0).unwrap();
+//!
+//! assert!(y.iter().all(|val| val == 5.0));
+//! ```
+
+#[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+use super::parameters::DataType;
+use super::{
+    deep_copy,
+    parameters::{DataTraits, Scalar},
+    unflatten_idx, ViewBase, ViewError,
+};
+use crate::{
+    functor::KernelArgs,
+    routines::{
+        parallel_for,
+        parameters::{ExecutionPolicy, ExecutionSpace, RangePolicy, Schedule},
+    },
+};
+#[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
+use atomic::Ordering;
+
+/// Returns the policy of update statements over `n_elems` elements.
+fn update_policy(n_elems: usize) -> ExecutionPolicy<1> {
+    ExecutionPolicy {
+        space: ExecutionSpace::DeviceCPU,
+        range: RangePolicy::RangePolicy(0..n_elems),
+        schedule: Schedule::default(),
+        chunk_size: None,
+        chunk_predicate: None,
+    }
+}
+
+/// Returns `true` if `dst` & `src` can be updated in memory order, i.e. both are
+/// contiguous & share the same strides.
+fn in_memory_order<const N: usize, T>(
+    dst: &ViewBase<'_, N, T>,
+    src: Option<&ViewBase<'_, N, T>>,
+) -> bool
+where
+    T: DataTraits,
+{
+    // subviews may not be contiguous
+    let n_elems: usize = dst.dim.iter().product();
+    dst.data_slice().len() == n_elems
+        && src.is_none_or(|src| src.stride == dst.stride && src.data_slice().len() == n_elems)
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))] {
+        /// Set each element of `dst` to `op(dst_val, src_val)`, `src_val` being
+        /// `T::default()` if there is no `src`.
+        ///
+        /// **Current version**: thread-safe
+        fn update<const N: usize, T>(
+            dst: &ViewBase<'_, N, T>,
+            src: Option<&ViewBase<'_, N, T>>,
+            op: impl Fn(T, T) -> T + Send + Sync,
+        ) -> Result<(), ViewError<'static>>
+        where
+            T: DataTraits + Send + Sync,
+        {
+            if src.is_some_and(|src| src.dim != dst.dim) {
+                return Err(ViewError::ValueError("Views must have identical dimensions"));
+            }
+            let memory_order = in_memory_order(dst, src);
+            let dst_data = dst.data_slice();
+            let src_data = src.map(|src| src.data_slice());
+            let kernel = |arg: KernelArgs<1>| {
+                if let KernelArgs::Index1D(k) = arg {
+                    if memory_order {
+                        let other = src_data.map_or(T::default(), |d| d[k].load(Ordering::Relaxed));
+                        let val = op(dst_data[k].load(Ordering::Relaxed), other);
+                        dst_data[k].store(val, Ordering::Relaxed);
+                    } else {
+                        let index = unflatten_idx(&dst.dim, k);
+                        let other = src.map_or(T::default(), |src| src.get(index));
+                        dst.set(index, op(dst.get(index), other));
+                    }
+                }
+            };
+            parallel_for(update_policy(dst.dim.iter().product()), kernel)
+                .map_err(|_| ViewError::ValueError("Update statement failed"))
+        }
+
+        impl<const N: usize, T> ViewBase<'_, N, T>
+        where
+            T: DataTraits + Send + Sync,
+        {
+            /// Set all elements of the view to `value`.
+            ///
+            /// **Current version**: thread-safe
+            pub fn fill(&mut self, value: T) -> Result<(), ViewError<'static>> {
+                update(self, None, |_, _| value)
+            }
+
+            /// Copy the elements of `other` into the view, converting the memory layout if
+            /// needed. See [deep_copy].
+            ///
+            /// **Current version**: thread-safe
+            pub fn assign_from(&mut self, other: &ViewBase<'_, N, T>) -> Result<(), ViewError<'static>> {
+                deep_copy(self, other)
+            }
+        }
+
+        impl<const N: usize, T> ViewBase<'_, N, T>
+        where
+            T: Scalar + Send + Sync,
+        {
+            /// Multiply all elements of the view by `alpha`.
+            ///
+            /// **Current version**: thread-safe
+            pub fn scale(&mut self, alpha: f64) -> Result<(), ViewError<'static>> {
+                update(self, None, |val, _| val.scale(alpha))
+            }
+
+            /// Set the view to `alpha * x + beta * self`, element-wise. Both views must have
+            /// the same dimensions; their layouts can differ.
+            ///
+            /// **Current version**: thread-safe
+            pub fn axpby(
+                &mut self,
+                alpha: f64,
+                x: &ViewBase<'_, N, T>,
+                beta: f64,
+            ) -> Result<(), ViewError<'static>> {
+                update(self, Some(x), |y, x| x.scale(alpha) + y.scale(beta))
+            }
+        }
+    } else {
+        /// Set each element of `dst` to `op(dst_val, src_val)`, `src_val` being
+        /// `T::default()` if there is no `src`. `dst` must not be a read-only mirror.
+        ///
+        /// **Current version**: no feature
+        fn update<const N: usize, T>(
+            dst: &mut ViewBase<'_, N, T>,
+            src: Option<&ViewBase<'_, N, T>>,
+            mut op: impl FnMut(T, T) -> T,
+        ) -> Result<(), ViewError<'static>>
+        where
+            T: DataTraits,
+        {
+            if src.is_some_and(|src| src.dim != dst.dim) {
+                return Err(ViewError::ValueError("Views must have identical dimensions"));
+            }
+            let memory_order = in_memory_order(dst, src);
+            let (dim, execp) = (dst.dim, update_policy(dst.dim.iter().product()));
+            let res = if memory_order {
+                let dst_data: &mut [T] = match &mut dst.data {
+                    DataType::Owned(v) => v,
+                    DataType::MutBorrowed(mut_slice) => mut_slice,
+                    DataType::Borrowed(_) => {
+                        return Err(ViewError::ValueError("Cannot update a read-only View"))
+                    }
+                };
+                let src_data = src.map(|src| src.data_slice());
+                parallel_for(execp, |arg: KernelArgs<1>| {
+                    if let KernelArgs::Index1D(k) = arg {
+                        let other = src_data.map_or(T::default(), |d| d[k]);
+                        dst_data[k] = op(dst_data[k], other);
+                    }
+                })
+            } else {
+                if let DataType::Borrowed(_) = dst.data {
+                    return Err(ViewError::ValueError("Cannot update a read-only View"));
+                }
+                parallel_for(execp, |arg: KernelArgs<1>| {
+                    if let KernelArgs::Index1D(k) = arg {
+                        let index = unflatten_idx(&dim, k);
+                        let other = src.map_or(T::default(), |src| src.get(index));
+                        dst.set(index, op(dst.get(index), other));
+                    }
+                })
+            };
+            res.map_err(|_| ViewError::ValueError("Update statement failed"))
+        }
+
+        impl<const N: usize, T> ViewBase<'_, N, T>
+        where
+            T: DataTraits,
+        {
+            /// Set all elements of the view to `value`.
+            ///
+            /// **Current version**: no feature
+            pub fn fill(&mut self, value: T) -> Result<(), ViewError<'static>> {
+                update(self, None, |_, _| value)
+            }
+
+            /// Copy the elements of `other` into the view, converting the memory layout if
+            /// needed. See [deep_copy].
+            ///
+            /// **Current version**: no feature
+            pub fn assign_from(&mut self, other: &ViewBase<'_, N, T>) -> Result<(), ViewError<'static>> {
+                deep_copy(self, other)
+            }
+        }
+
+        impl<const N: usize, T> ViewBase<'_, N, T>
+        where
+            T: Scalar,
+        {
+            /// Multiply all elements of the view by `alpha`.
+            ///
+            /// **Current version**: no feature
+            pub fn scale(&mut self, alpha: f64) -> Result<(), ViewError<'static>> {
+                update(self, None, |val, _| val.scale(alpha))
+            }
+
+            /// Set the view to `alpha * x + beta * self`, element-wise. Both views must have
+            /// the same dimensions; their layouts can differ.
+            ///
+            /// **Current version**: no feature
+            pub fn axpby(
+                &mut self,
+                alpha: f64,
+                x: &ViewBase<'_, N, T>,
+                beta: f64,
+            ) -> Result<(), ViewError<'static>> {
+                update(self, Some(x), |y, x| x.scale(alpha) + y.scale(beta))
+            }
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use crate::view::{parameters::Layout, subview::SliceArg, ViewOwned};
+
+    #[test]
+    fn update_views() {
+        let x: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data((0..6).map(|v| v as f64).collect(), Layout::Right, [2, 3]);
+        for layout in [Layout::Right, Layout::Left] {
+            let mut y: ViewOwned<'_, 2, f64> = ViewOwned::new(layout, [2, 3]);
+            y.fill(1.0).unwrap();
+            y.scale(4.0).unwrap();
+            assert!(y.iter().all(|val| val == 4.0));
+            y.axpby(2.0, &x, 0.5).unwrap();
+            assert_eq!(y.get([1, 2]), 12.0);
+            y.assign_from(&x).unwrap();
+            assert_eq!(y.get([1, 0]), 3.0);
+
+            let z: ViewOwned<'_, 2, f64> = ViewOwned::new(layout, [3, 2]);
+            assert!(y.axpby(1.0, &z, 1.0).is_err());
+        }
+
+        // non-contiguous operand
+        let mut y: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [2]);
+        let col = x.subview::<1>([SliceArg::All, SliceArg::Index(1)]).unwrap();
+        y.axpby(1.0, &col, 0.0).unwrap();
+        assert_eq!((y.get([0]), y.get([1])), (1.0, 4.0));
+    }
+}
@@ -0,0 +1,218 @@
+//! view parallel iterator related code
+//!
+//! This module contains the integration of views with [`rayon`] parallel iterators. It
+//! is only defined when the `rayon` feature is enabled. Views can be consumed by rayon
+//! pipelines (`map`, `reduce`, `collect`, ...) outside of the statements of the
+//! [routines][crate::routines] module:
+//!
+//! - [ViewBase::par_iter] (or `into_par_iter` on a reference) yields values in layout
+//!   order, like [ViewBase::iter],
+//! - [ViewBase::par_chunks] yields subviews of consecutive indices along the leading
+//!   dimension.
+//!
+//! Iterators are executed by the current rayon pool, i.e. the global pool unless called
+//! from another pool; the thread count of the [runtime][crate::runtime] does not apply.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+//! use rayon::prelude::*;
+//!
+//! let mat: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data((0..12).map(|x| x as f64).collect(), Layout::Right, [4, 3]);
+//!
+//! let sum_sq: f64 = mat.par_iter().map(|x| x * x).sum();
+//! assert_eq!(sum_sq, 506.0);
+//!
+//! let doubled: Vec<f64> = (&mat).into_par_iter().map(|x| 2.0 * x).collect();
+//! assert_eq!(doubled[11], 22.0);
+//!
+//! // blocks of (at most) 3 rows
+//! let block_max: Vec<f64> = mat
+//!     .par_chunks(3)
+//!     .map(|block| block.iter().fold(f64::MIN, f64::max))
+//!     .collect();
+//! assert_eq!(block_max, vec![8.0, 11.0]);
+//! ```
+
+use rayon::iter::{
+    plumbing::{Consumer, ProducerCallback, UnindexedConsumer},
+    IndexedParallelIterator, IntoParallelIterator, ParallelIterator,
+};
+
+use super::{parameters::DataTraits, subview::SliceArg, ViewBase, ViewRO};
+use crate::routines::parameters::Iterate;
+
+/// Parallel iterator over the values of a view, in layout order. See
+/// [ViewBase::par_iter].
+pub struct ParIter<'b, 'a, const N: usize, T>
+where
+    T: DataTraits,
+{
+    view: &'b ViewBase<'a, N, T>,
+}
+
+impl<'b, 'a, const N: usize, T> ParIter<'b, 'a, N, T>
+where
+    T: DataTraits + Send + Sync,
+{
+    /// Returns the equivalent iterator over positions in layout order.
+    fn positions(self) -> impl IndexedParallelIterator<Item = T> + 'b {
+        let view = self.view;
+        let iterate = Iterate::from(&view.layout);
+        (0..view.dim.iter().product())
+            .into_par_iter()
+            .map(move |pos| view.load(view.flat_idx(index_at(view.dim, iterate, pos))))
+    }
+}
+
+impl<'b, 'a, const N: usize, T> ParallelIterator for ParIter<'b, 'a, N, T>
+where
+    T: DataTraits + Send + Sync,
+{
+    type Item = T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<T>,
+    {
+        self.positions().drive_unindexed(consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<'b, 'a, const N: usize, T> IndexedParallelIterator for ParIter<'b, 'a, N, T>
+where
+    T: DataTraits + Send + Sync,
+{
+    fn len(&self) -> usize {
+        self.view.dim.iter().product()
+    }
+
+    fn drive<C: Consumer<T>>(self, consumer: C) -> C::Result {
+        self.positions().drive(consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<T>>(self, callback: CB) -> CB::Output {
+        self.positions().with_producer(callback)
+    }
+}
+
+impl<'b, 'a, const N: usize, T> IntoParallelIterator for &'b ViewBase<'a, N, T>
+where
+    T: DataTraits + Send + Sync,
+{
+    type Iter = ParIter<'b, 'a, N, T>;
+    type Item = T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIter { view: self }
+    }
+}
+
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits + Send + Sync,
+{
+    /// Returns a parallel iterator over the values of the view, in layout order.
+    ///
+    /// Only defined when the `rayon` feature is enabled.
+    pub fn par_iter(&self) -> ParIter<'_, 'a, N, T> {
+        self.into_par_iter()
+    }
+
+    /// Returns a parallel iterator over subviews of `chunk_size` consecutive indices
+    /// along the leading dimension; the last subview may be smaller. A size of 0 is
+    /// treated as 1.
+    ///
+    /// Only defined when the `rayon` feature is enabled.
+    pub fn par_chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl IndexedParallelIterator<Item = ViewRO<'_, N, T>> + '_ {
+        let chunk_size = chunk_size.max(1);
+        let len = self.dim[0];
+        (0..len.div_ceil(chunk_size)).into_par_iter().map(move |k| {
+            let rows = k * chunk_size..((k + 1) * chunk_size).min(len);
+            let args = std::array::from_fn(|d| match d {
+                0 => SliceArg::Range(rows.clone()),
+                _ => SliceArg::All,
+            });
+            self.subview(args).expect("chunk bounds are in range")
+        })
+    }
+}
+
+/// Returns the index at position `pos` of the index space `dim`, iterated using
+/// `iterate`.
+fn index_at<const N: usize>(dim: [usize; N], iterate: Iterate, mut pos: usize) -> [usize; N] {
+    let mut idx = [0; N];
+    let mut place = |k: usize| {
+        idx[k] = pos % dim[k];
+        pos /= dim[k];
+    };
+    match iterate {
+        Iterate::Right => (0..N).rev().for_each(&mut place),
+        Iterate::Left => (0..N).for_each(&mut place),
+    }
+    idx
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::{parameters::Layout, ViewOwned};
+
+    #[test]
+    fn par_iter_order() {
+        let data: Vec<usize> = (0..3 * 5 * 7).collect();
+        for layout in [Layout::Right, Layout::Left] {
+            let view: ViewOwned<'_, 3, usize> =
+                ViewOwned::new_from_data(data.clone(), layout, [3, 5, 7]);
+            assert_eq!(view.par_iter().collect::<Vec<_>>(), data);
+            assert_eq!(view.par_iter().len(), data.len());
+            assert_eq!(
+                (&view).into_par_iter().sum::<usize>(),
+                data.iter().sum::<usize>()
+            );
+            // consistent with the serial iterator
+            assert!(view
+                .par_iter()
+                .zip(view.iter().collect::<Vec<_>>())
+                .all(|(a, b)| a == b));
+        }
+
+        // strided subview
+        let mat: ViewOwned<'_, 2, usize> =
+            ViewOwned::new_from_data((0..12).collect(), Layout::Right, [3, 4]);
+        let block = mat
+            .subview::<2>([SliceArg::Range(1..3), SliceArg::Range(0..2)])
+            .unwrap();
+        assert_eq!(block.par_iter().collect::<Vec<_>>(), vec![4, 5, 8, 9]);
+    }
+
+    #[test]
+    fn par_chunks_rows() {
+        let mat: ViewOwned<'_, 2, usize> =
+            ViewOwned::new_from_data((0..7 * 3).collect(), Layout::Left, [7, 3]);
+        let chunks: Vec<_> = mat.par_chunks(3).collect();
+        assert_eq!(
+            chunks.iter().map(|c| c.dims()).collect::<Vec<_>>(),
+            vec![[3, 3], [3, 3], [1, 3]]
+        );
+        assert_eq!(chunks[2].get([0, 1]), mat.get([6, 1]));
+        let total: usize = mat.par_chunks(0).map(|c| c.iter().sum::<usize>()).sum();
+        assert_eq!(total, (0..21).sum());
+
+        let empty: ViewOwned<'_, 2, usize> = ViewOwned::new(Layout::Right, [0, 3]);
+        assert_eq!(empty.par_chunks(4).count(), 0);
+        assert_eq!(empty.par_iter().count(), 0);
+    }
+}
@@ -12,19 +12,284 @@
 //! - Memory space
 //! - Memory traits
 
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    ops::{Add, Sub},
+};
 
 #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))]
 use atomic::Atomic;
 
+use super::{buffer::AlignedBuffer, ViewError};
+
 /// Maximum possible depth (i.e. number of dimensions) for a view.
 pub const MAX_VIEW_DEPTH: usize = 8;
 
+/// Size of a cache line in bytes, usable as the padding alignment of views. See
+/// [ViewBase::new_padded][super::ViewBase::new_padded].
+pub const CACHE_LINE_SIZE: usize = 64;
+
 /// Supertrait with common trait that elements of a View should implement.
 pub trait DataTraits: Debug + Clone + Copy + Default {}
 
 impl DataTraits for f64 {}
 impl DataTraits for f32 {}
+impl DataTraits for bool {}
+
+/// Complex element types, available using the `complex` feature.
+///
+/// When using parallelization features, complex elements are stored in `Atomic` cells like
+/// other types. They are not aligned for native atomic operations, hence accesses use the
+/// lock-based fallback of the `atomic` crate, which is slower than accesses to real views.
+#[cfg(feature = "complex")]
+pub use num_complex::Complex;
+
+#[cfg(feature = "complex")]
+impl DataTraits for Complex<f64> {}
+#[cfg(feature = "complex")]
+impl DataTraits for Complex<f32> {}
+
+/// Conversion between element types, used to copy views of different element types,
+/// see [deep_copy_convert][super::deep_copy_convert].
+///
+/// Unlike [From], conversions may lose precision: values are converted like `as` casts,
+/// e.g. from `f64` to `f32` the nearest value is used & out-of-range values become
+/// infinite.
+pub trait CastFrom<S>: DataTraits {
+    /// Convert `src` into an element of type `Self`.
+    fn cast_from(src: S) -> Self;
+}
+
+impl<T: DataTraits> CastFrom<T> for T {
+    #[inline(always)]
+    fn cast_from(src: T) -> Self {
+        src
+    }
+}
+
+impl CastFrom<f64> for f32 {
+    #[inline(always)]
+    fn cast_from(src: f64) -> Self {
+        src as f32
+    }
+}
+
+impl CastFrom<f32> for f64 {
+    #[inline(always)]
+    fn cast_from(src: f32) -> Self {
+        src as f64
+    }
+}
+
+#[cfg(feature = "complex")]
+impl CastFrom<Complex<f64>> for Complex<f32> {
+    #[inline(always)]
+    fn cast_from(src: Complex<f64>) -> Self {
+        Complex::new(src.re as f32, src.im as f32)
+    }
+}
+
+#[cfg(feature = "complex")]
+impl CastFrom<Complex<f32>> for Complex<f64> {
+    #[inline(always)]
+    fn cast_from(src: Complex<f32>) -> Self {
+        Complex::new(src.re as f64, src.im as f64)
+    }
+}
+
+/// Numeric element types.
+///
+/// This trait is used to bound numerical routines instead of hard-coding floating point
+/// types. Operations are restricted to the ones preserving the unit of a quantity
+/// (addition, subtraction, scaling by a dimensionless factor), so that it can be
+/// implemented by thin wrappers carrying physical units. The
+/// [`impl_scalar_newtype`][crate::impl_scalar_newtype] macro can be used to implement it
+/// for newtypes over `f64`.
+pub trait Scalar: DataTraits + PartialOrd + Add<Output = Self> + Sub<Output = Self> {
+    /// Additive identity.
+    fn zero() -> Self;
+    /// Multiply the value by a dimensionless factor.
+    fn scale(self, alpha: f64) -> Self;
+}
+
+impl Scalar for f64 {
+    #[inline(always)]
+    fn zero() -> Self {
+        0.0
+    }
+
+    #[inline(always)]
+    fn scale(self, alpha: f64) -> Self {
+        self * alpha
+    }
+}
+
+impl Scalar for f32 {
+    #[inline(always)]
+    fn zero() -> Self {
+        0.0
+    }
+
+    #[inline(always)]
+    fn scale(self, alpha: f64) -> Self {
+        self * alpha as f32
+    }
+}
+
+/// Implement [DataTraits], [ReductionIdentity] and [Scalar] for a newtype over `f64`.
+///
+/// The type must be a tuple struct with a single `f64` field, deriving [Debug], [Clone],
+/// [Copy], [Default] and [PartialOrd]. The macro also implements [Add] and [Sub].
+///
+/// ### Example
+///
+/// ```rust
+/// use poc_kokkos_rs::{
+///     impl_scalar_newtype,
+///     view::{parameters::Layout, ViewOwned},
+/// };
+///
+/// #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+/// struct Meters(f64);
+///
+/// impl_scalar_newtype!(Meters);
+///
+/// let lengths: ViewOwned<'_, 1, Meters> =
+///     ViewOwned::new_from_data(vec![Meters(1.0), Meters(2.5)], Layout::Right, [2]);
+/// assert_eq!(lengths.sum(), Meters(3.5));
+/// ```
+#[macro_export]
+macro_rules! impl_scalar_newtype {
+    ($t: ty) => {
+        impl $crate::view::parameters::DataTraits for $t {}
+
+        impl ::std::ops::Add for $t {
+            type Output = Self;
+
+            #[inline(always)]
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl ::std::ops::Sub for $t {
+            type Output = Self;
+
+            #[inline(always)]
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl $crate::view::parameters::ReductionIdentity for $t {
+            fn min_identity() -> Self {
+                Self(f64::MAX)
+            }
+
+            fn max_identity() -> Self {
+                Self(f64::MIN)
+            }
+        }
+
+        impl $crate::view::parameters::Scalar for $t {
+            #[inline(always)]
+            fn zero() -> Self {
+                Self(0.0)
+            }
+
+            #[inline(always)]
+            fn scale(self, alpha: f64) -> Self {
+                Self(self.0 * alpha)
+            }
+        }
+    };
+}
+
+/// Integer-specific operations used by view reductions.
+///
+/// This is used to detect (or saturate instead of wrapping on) overflows when
+/// reducing integer views, see
+/// [OverflowSum][crate::routines::parameters::OverflowSum].
+pub trait IntegerTraits: DataTraits + PartialOrd {
+    /// Smallest value of the type.
+    const MIN: Self;
+    /// Largest value of the type.
+    const MAX: Self;
+    /// Wrapping addition, also returning whether an overflow occured.
+    fn overflowing_add(self, rhs: Self) -> (Self, bool);
+}
+
+/// Identity elements of minimum & maximum reductions, akin to
+/// `Kokkos::reduction_identity`.
+///
+/// This is used to define the result of minimum & maximum reductions over empty ranges,
+/// see [ReduceOp][crate::routines::parameters::ReduceOp].
+pub trait ReductionIdentity: DataTraits {
+    /// Identity of the minimum, i.e. the largest value of the type.
+    fn min_identity() -> Self;
+    /// Identity of the maximum, i.e. the lowest value of the type.
+    fn max_identity() -> Self;
+}
+
+impl ReductionIdentity for f64 {
+    fn min_identity() -> Self {
+        f64::MAX
+    }
+
+    fn max_identity() -> Self {
+        f64::MIN
+    }
+}
+
+impl ReductionIdentity for f32 {
+    fn min_identity() -> Self {
+        f32::MAX
+    }
+
+    fn max_identity() -> Self {
+        f32::MIN
+    }
+}
+
+impl ReductionIdentity for bool {
+    fn min_identity() -> Self {
+        true
+    }
+
+    fn max_identity() -> Self {
+        false
+    }
+}
+
+macro_rules! impl_integer_traits {
+    ($($t: ty),*) => {
+        $(
+            impl DataTraits for $t {}
+
+            impl ReductionIdentity for $t {
+                fn min_identity() -> Self {
+                    <$t>::MAX
+                }
+
+                fn max_identity() -> Self {
+                    <$t>::MIN
+                }
+            }
+
+            impl IntegerTraits for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                #[inline(always)]
+                fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+                    <$t>::overflowing_add(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_traits!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
 
 #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
 /// Generic alias for elements of type `T` of a View.
@@ -33,9 +298,14 @@ impl DataTraits for f32 {}
 /// of Views. There are two possible values:
 ///
 /// - any feature enabled: `InnerDataType<T> = Atomic<T>`. By adding the atomic wrapping,
-/// operations on views can be implemented using thread-safe methods.
+///   operations on views can be implemented using thread-safe methods.
 /// - no feature enabled: `InnerDataType<T> = T`.
 ///
+/// `Atomic<T>` uses native atomic instructions when the size & alignment of `T` match a
+/// native atomic type, i.e. for `bool`, floats and integers of up to 64 bits on most
+/// platforms. Other types (e.g. `i128`, `u128`) fall back to a spinlock, which stays
+/// correct but is significantly slower under contention.
+///
 /// **Current version**: no feature
 pub type InnerDataType<T> = T;
 
@@ -46,9 +316,14 @@ pub type InnerDataType<T> = T;
 /// of Views. There are two possible values:
 ///
 /// - any feature enabled: `InnerDataType<T> = Atomic<T>`. By adding the atomic wrapping,
-/// operations on views can be implemented using thread-safe methods.
+///   operations on views can be implemented using thread-safe methods.
 /// - no feature enabled: `InnerDataType<T> = T`.
 ///
+/// `Atomic<T>` uses native atomic instructions when the size & alignment of `T` match a
+/// native atomic type, i.e. for `bool`, floats and integers of up to 64 bits on most
+/// platforms. Other types (e.g. `i128`, `u128`) fall back to a spinlock, which stays
+/// correct but is significantly slower under contention.
+///
 /// **Current version**: thread-safe
 pub type InnerDataType<T> = Atomic<T>;
 
@@ -65,8 +340,8 @@ pub enum DataType<'a, T>
 where
     T: DataTraits,
 {
-    /// The view owns the data.
-    Owned(Vec<InnerDataType<T>>),
+    /// The view owns the data, see [AlignedBuffer].
+    Owned(AlignedBuffer<InnerDataType<T>>),
     /// The view borrows the data and can only read it.
     Borrowed(&'a [InnerDataType<T>]),
     /// The view borrows the data and can both read and modify it.
@@ -108,10 +383,162 @@ pub enum Layout<const N: usize> {
     /// Lowest stride for the first index, increasing stride as index decreases.
     /// Exact stride for each index can be computed from dimensions at view initialization.
     Left,
-    /// Custom stride for each index. Must be compatible with dimensions.
+    /// Custom stride for each index. Must be compatible with dimensions, i.e. two
+    /// indices must not map to the same element; use [Layout::stride] to build a checked
+    /// layout. Strides may leave gaps between elements, e.g. to pad rows, see
+    /// [Layout::padded_right] & [Layout::padded_left].
     Stride { s: [usize; N] },
 }
 
+impl<const N: usize> Layout<N> {
+    /// Build a [Layout::Stride] layout, checking that strides are compatible with
+    /// dimensions `dim`: distinct indices must map to distinct elements.
+    ///
+    /// Strides are checked by ordering dimensions by increasing stride: each stride must
+    /// be greater than or equal to the span of the previous dimensions. Dimensions of size
+    /// 0 or 1 are ignored.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::view::parameters::Layout;
+    ///
+    /// // 3x4 matrix, rows padded to 6 elements
+    /// assert!(Layout::stride([6, 1], [3, 4]).is_ok());
+    /// // overlapping rows
+    /// assert!(Layout::stride([3, 1], [3, 4]).is_err());
+    /// ```
+    pub fn stride(s: [usize; N], dim: [usize; N]) -> Result<Self, ViewError<'static>> {
+        let mut extents: Vec<(usize, usize)> = s
+            .iter()
+            .zip(dim.iter())
+            .filter(|(_, d)| **d > 1)
+            .map(|(s, d)| (*s, *d))
+            .collect();
+        extents.sort_unstable();
+        let mut span = 1;
+        for (s, d) in extents {
+            if s < span {
+                return Err(ViewError::ValueError(
+                    "Strides map distinct indices to the same element",
+                ));
+            }
+            span += s * (d - 1);
+        }
+        Ok(Layout::Stride { s })
+    }
+
+    /// Build a [Layout::Stride] layout equivalent to [Layout::Right], the stride of the
+    /// second to last dimension being padded so that it spans a multiple of `align`
+    /// bytes, e.g. 64 for cache lines. Elements of the last dimension remain contiguous.
+    ///
+    /// Offsets are aligned relatively to the start of the allocation.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+    ///
+    /// // rows of 5 `f64` are padded to 8 elements, i.e. 64 bytes
+    /// let layout = Layout::padded_right::<f64>([3, 5], 64);
+    /// assert_eq!(layout, Layout::Stride { s: [8, 1] });
+    ///
+    /// let view: ViewOwned<'_, 2, f64> = ViewOwned::new(layout, [3, 5]);
+    /// assert_eq!(view.flat_idx([1, 0]), 8);
+    /// ```
+    pub fn padded_right<T>(dim: [usize; N], align: usize) -> Self {
+        let mut padded = dim;
+        padded[N - 1] = padded_len::<T>(dim[N - 1], align);
+        Layout::Stride {
+            s: compute_stride(&padded, &Layout::Right),
+        }
+    }
+
+    /// Build a [Layout::Stride] layout equivalent to [Layout::Left], the stride of the
+    /// second dimension being padded so that it spans a multiple of `align` bytes. See
+    /// [Layout::padded_right].
+    pub fn padded_left<T>(dim: [usize; N], align: usize) -> Self {
+        let mut padded = dim;
+        padded[0] = padded_len::<T>(dim[0], align);
+        Layout::Stride {
+            s: compute_stride(&padded, &Layout::Left),
+        }
+    }
+}
+
+/// Returns `len` rounded up to the closest number of elements of type `T` spanning a
+/// multiple of `align` bytes.
+pub(crate) fn padded_len<T>(len: usize, align: usize) -> usize {
+    let size = std::mem::size_of::<T>().max(1);
+    // smallest element count spanning a multiple of `align` bytes
+    let step = align.max(1) / gcd(align.max(1), size);
+    len.next_multiple_of(step)
+}
+
+/// Greatest common divisor.
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "index-u32")] {
+        /// Integer type used by views to store strides & compute flat offsets. Depends on
+        /// enabled feature(s).
+        ///
+        /// Using a narrower type reduces register pressure and helps vectorization of
+        /// N-D accesses. Views whose span does not fit in this type cannot be created.
+        ///
+        /// **Current version**: `index-u32`
+        pub type IndexType = u32;
+    } else {
+        /// Integer type used by views to store strides & compute flat offsets. Depends on
+        /// enabled feature(s).
+        ///
+        /// Using a narrower type reduces register pressure and helps vectorization of
+        /// N-D accesses. Views whose span does not fit in this type cannot be created.
+        ///
+        /// **Current version**: no feature
+        pub type IndexType = usize;
+    }
+}
+
+/// Convert strides to [IndexType], checking that all offsets of a view of dimensions
+/// `dim` can be represented.
+///
+/// Panics if the largest offset does not fit in [IndexType].
+pub fn index_stride<const N: usize>(stride: [usize; N], dim: &[usize; N]) -> [IndexType; N] {
+    let max_offset = stride
+        .iter()
+        .zip(dim.iter())
+        .map(|(s, d)| s * d.saturating_sub(1))
+        .sum::<usize>();
+    assert!(
+        IndexType::try_from(max_offset).is_ok(),
+        "view span does not fit in the index type"
+    );
+    // strides of non-empty dimensions are bounded by the max offset
+    stride.map(|s| IndexType::try_from(s).unwrap_or(IndexType::MAX))
+}
+
+/// Compute the number of elements spanned by a view of dimensions `dim` using the
+/// specified layout, i.e. the size of its allocation. It is equal to the product of
+/// dimensions, unless the layout leaves gaps between elements.
+pub fn compute_span<const N: usize>(dim: &[usize; N], layout: &Layout<N>) -> usize {
+    if dim.contains(&0) {
+        return 0;
+    }
+    compute_stride(dim, layout)
+        .iter()
+        .zip(dim.iter())
+        .map(|(s, d)| s * (d - 1))
+        .sum::<usize>()
+        + 1
+}
+
 /// Compute correct strides of each index using dimensions and specified layout.
 pub fn compute_stride<const N: usize>(dim: &[usize; N], layout: &Layout<N>) -> [usize; N] {
     assert_eq!(N.clamp(1, MAX_VIEW_DEPTH), N); // 1 <= N <= MAX_N
@@ -147,6 +574,23 @@ pub fn compute_stride<const N: usize>(dim: &[usize; N], layout: &Layout<N>) -> [
 mod tests {
     use super::*;
 
+    #[test]
+    fn non_float_views() {
+        use crate::view::ViewOwned;
+
+        #[allow(unused_mut)]
+        let mut mask: ViewOwned<'_, 2, bool> = ViewOwned::new(Layout::Right, [2, 2]);
+        mask.set([1, 0], true);
+        assert!(mask.get([1, 0]));
+        assert!(!mask.get([0, 1]));
+
+        #[allow(unused_mut)]
+        let mut counters: ViewOwned<'_, 1, u128> = ViewOwned::new(Layout::Right, [2]);
+        counters.set([1], u128::MAX);
+        assert_eq!(counters.get([1]), u128::MAX);
+        assert_eq!(counters.get([0]), 0);
+    }
+
     #[test]
     fn stride_right() {
         // dim = [n0, n1, n2, n3]
@@ -171,6 +615,82 @@ mod tests {
         assert_eq!(cmp_stride, ref_stride);
     }
 
+    #[test]
+    fn scalar_newtype() {
+        #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+        struct Seconds(f64);
+        crate::impl_scalar_newtype!(Seconds);
+
+        assert_eq!(Seconds::zero(), Seconds(0.0));
+        assert_eq!(Seconds(1.5) + Seconds(2.0), Seconds(3.5));
+        assert_eq!((Seconds(1.5) - Seconds(2.0)).scale(2.0), Seconds(-1.0));
+    }
+
+    #[cfg(feature = "index-u32")]
+    #[test]
+    #[should_panic(expected = "view span does not fit in the index type")]
+    fn index_stride_overflow() {
+        let dim = [1 << 16, 1 << 16, 2];
+        let _ = index_stride(compute_stride(&dim, &Layout::Right), &dim);
+    }
+
+    #[test]
+    fn index_stride_span() {
+        let dim = [3, 4, 5];
+        let stride = compute_stride(&dim, &Layout::Left);
+        let ref_stride: [IndexType; 3] = [1, 3, 12];
+        assert_eq!(index_stride(stride, &dim), ref_stride);
+    }
+
+    #[test]
+    fn checked_stride() {
+        // permutations of Left/Right strides
+        assert!(Layout::stride([1, 15, 3], [3, 4, 5]).is_ok());
+        assert!(Layout::stride([20, 5, 1], [3, 4, 5]).is_ok());
+        // overlaps
+        assert!(Layout::stride([1, 2, 3], [3, 4, 5]).is_err());
+        assert!(Layout::stride([0, 1], [2, 2]).is_err());
+        // size 0 & 1 dimensions are ignored
+        assert!(Layout::stride([0, 1], [1, 4]).is_ok());
+        assert!(Layout::stride([1, 1], [0, 4]).is_ok());
+
+        // gaps increase the allocation span
+        let layout = Layout::stride([8, 1], [3, 5]).unwrap();
+        assert_eq!(compute_span(&[3, 5], &layout), 2 * 8 + 5);
+        assert_eq!(compute_span(&[3, 5], &Layout::Right), 15);
+        assert_eq!(compute_span(&[3, 0], &layout), 0);
+    }
+
+    #[test]
+    fn padded_layouts() {
+        use crate::{routines::iter::MDIndexIter, view::ViewOwned};
+
+        let dim = [3, 5, 7];
+        let right = Layout::padded_right::<f64>(dim, 64);
+        assert_eq!(right, Layout::Stride { s: [5 * 8, 8, 1] });
+        let left = Layout::padded_left::<f32>(dim, 64);
+        assert_eq!(left, Layout::Stride { s: [1, 16, 16 * 5] });
+        // alignments that are not multiples of the element size
+        assert_eq!(
+            Layout::padded_right::<[u8; 3]>([2, 5], 4),
+            Layout::Stride { s: [8, 1] }
+        );
+
+        for layout in [right, left] {
+            let Layout::Stride { s } = layout else {
+                unreachable!()
+            };
+            assert!(Layout::stride(s, dim).is_ok());
+            #[allow(unused_mut)]
+            let mut view: ViewOwned<'_, 3, f64> = ViewOwned::new(layout, dim);
+            MDIndexIter::new(dim.map(|d| 0..d))
+                .for_each(|[i, j, k]| view.set([i, j, k], (100 * i + 10 * j + k) as f64));
+            MDIndexIter::new(dim.map(|d| 0..d)).for_each(|[i, j, k]| {
+                assert_eq!(view.get([i, j, k]), (100 * i + 10 * j + k) as f64)
+            });
+        }
+    }
+
     #[test]
     fn one_d_stride() {
         // 1d view (vector) of length 1
@@ -0,0 +1,364 @@
+//! memory pool related code
+//!
+//! This module contains [MemoryPool], akin to Kokkos' `MemoryPool`: an arena allocated
+//! once in a given memory space, out of which temporary views are carved. Kernels
+//! needing scratch views at each iteration can then allocate them without going
+//! through the global allocator.
+//!
+//! The arena is divided into blocks of fixed size. Each view allocated from the pool
+//! uses a single block, claimed & released in constant time; the block is released
+//! when the [PoolView] is dropped. Usage of the pool is reported by
+//! [MemoryPool::stats], and the arena itself by the allocation
+//! [registry][super::registry].
+//!
+//! Pools created using [MemoryPool::new_aligned] align their arena & pad their blocks,
+//! so that the data of each view starts on the requested alignment.
+//!
+//! The content of a pool view is not reset: it holds the values left by the previous
+//! view using the same block, or `T::default()` for fresh blocks.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     routines::{
+//!         parameters::{Range1D, TypedExecutionPolicy},
+//!         typed::parallel_for,
+//!     },
+//!     view::{dual::MemorySpace, parameters::Layout, pool::MemoryPool},
+//! };
+//!
+//! // 1024 blocks of 256 elements, at least one per thread
+//! let pool: MemoryPool<f64> = MemoryPool::new(MemorySpace::Device, 256, 1024);
+//!
+//! let execp = TypedExecutionPolicy::new(Range1D(0..1000));
+//! parallel_for(execp, |i| {
+//!     // per-iteration scratch view
+//!     #[allow(unused_mut)]
+//!     let mut scratch = pool.allocate(Layout::Right, [16, 16]).unwrap();
+//!     scratch.set([0, 0], i as f64);
+//! })
+//! .unwrap();
+//!
+//! let stats = pool.stats();
+//! assert_eq!(stats.used_blocks, 0);
+//! assert!(stats.peak_used_blocks >= 1);
+//! ```
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use super::{
+    checked_geometry,
+    dual::MemorySpace,
+    parameters::{padded_len, DataTraits, DataType, InnerDataType, Layout},
+    ViewError, ViewOwned, ViewRW,
+};
+
+/// Snapshot of the usage of a [MemoryPool].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Number of elements of a block.
+    pub block_size: usize,
+    /// Number of blocks of the pool.
+    pub num_blocks: usize,
+    /// Number of blocks currently used by views.
+    pub used_blocks: usize,
+    /// Largest number of blocks used at the same time.
+    pub peak_used_blocks: usize,
+    /// Number of allocations that failed because the pool was exhausted.
+    pub failed_allocations: usize,
+}
+
+/// Arena of fixed-size blocks, out of which temporary views are allocated.
+#[derive(Debug)]
+pub struct MemoryPool<T>
+where
+    T: DataTraits + 'static,
+{
+    /// Storage of all blocks. Only accessed through `base` once the pool is built.
+    arena: ViewOwned<'static, 1, T>,
+    base: *mut InnerDataType<T>,
+    block_size: usize,
+    /// Number of elements between the starts of consecutive blocks.
+    block_stride: usize,
+    num_blocks: usize,
+    /// Indices of free blocks.
+    free: Mutex<Vec<usize>>,
+    peak_used: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+// SAFETY: blocks are handed out to a single view at a time, so the arena is never
+// accessed through aliasing mutable references
+unsafe impl<T: DataTraits + Send + 'static> Send for MemoryPool<T> {}
+unsafe impl<T: DataTraits + Send + 'static> Sync for MemoryPool<T> {}
+
+impl<T> MemoryPool<T>
+where
+    T: DataTraits + 'static,
+{
+    /// Create a pool of `num_blocks` blocks of `block_size` elements, allocated in
+    /// `space`.
+    ///
+    /// Panics if the arena cannot be created, see [MemoryPool::try_new].
+    pub fn new(space: MemorySpace, block_size: usize, num_blocks: usize) -> Self {
+        Self::try_new(space, block_size, num_blocks)
+            .unwrap_or_else(|e| panic!("could not create memory pool: {e:?}"))
+    }
+
+    /// Create a pool of `num_blocks` blocks of `block_size` elements, allocated in
+    /// `space`, each block starting on a multiple of `align` bytes.
+    ///
+    /// Panics if the arena cannot be created, see [MemoryPool::try_new_aligned].
+    pub fn new_aligned(
+        space: MemorySpace,
+        align: usize,
+        block_size: usize,
+        num_blocks: usize,
+    ) -> Self {
+        Self::try_new_aligned(space, align, block_size, num_blocks)
+            .unwrap_or_else(|e| panic!("could not create memory pool: {e:?}"))
+    }
+
+    /// Fallible constructor. Returns a [ViewError::ValueError] error if the size of the
+    /// arena overflows, as well as the errors of
+    /// [ViewBase::try_new_in][super::ViewBase::try_new_in].
+    pub fn try_new(
+        space: MemorySpace,
+        block_size: usize,
+        num_blocks: usize,
+    ) -> Result<Self, ViewError<'static>> {
+        Self::try_new_aligned(space, 1, block_size, num_blocks)
+    }
+
+    /// Fallible constructor of aligned pools. Blocks are padded to span a multiple of
+    /// `align` bytes. Returns the same errors as [MemoryPool::try_new], as well as the
+    /// errors of [ViewBase::try_new_aligned_in][super::ViewBase::try_new_aligned_in].
+    pub fn try_new_aligned(
+        space: MemorySpace,
+        align: usize,
+        block_size: usize,
+        num_blocks: usize,
+    ) -> Result<Self, ViewError<'static>> {
+        let block_stride = padded_len::<InnerDataType<T>>(block_size, align);
+        let size = block_stride
+            .checked_mul(num_blocks)
+            .ok_or(ViewError::ValueError("Memory pool size overflows"))?;
+        let mut arena =
+            ViewOwned::try_new_aligned_in(space, align, Layout::Right, [size])?.with_label("pool");
+        let DataType::Owned(data) = &mut arena.data else {
+            unreachable!("views created by constructors own their data")
+        };
+        let base = data.as_mut_ptr();
+        Ok(Self {
+            arena,
+            base,
+            block_size,
+            block_stride,
+            num_blocks,
+            // blocks of lowest index are claimed first
+            free: Mutex::new((0..num_blocks).rev().collect()),
+            peak_used: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the memory space of the arena.
+    pub fn memory_space(&self) -> MemorySpace {
+        self.arena.memory_space().expect("the arena owns its data")
+    }
+
+    /// Returns the alignment of the arena, in bytes. The data of all pool views starts
+    /// on a multiple of it.
+    pub fn alignment(&self) -> usize {
+        self.arena.alignment().expect("the arena owns its data")
+    }
+
+    /// Allocate a view of the given layout & dimensions out of a free block.
+    ///
+    /// Returns a [ViewError::AllocationFailure] error if the span of the view exceeds the
+    /// size of a block, or if all blocks are used, as well as the errors of
+    /// [ViewBase::try_new][super::ViewBase::try_new].
+    pub fn allocate<const N: usize>(
+        &self,
+        layout: Layout<N>,
+        dim: [usize; N],
+    ) -> Result<PoolView<'_, N, T>, ViewError<'static>> {
+        let (_, span) = checked_geometry::<N, T>(&dim, &layout)?;
+        if span > self.block_size {
+            return Err(ViewError::AllocationFailure(
+                "View span exceeds the block size of the pool",
+            ));
+        }
+        let block = {
+            let mut free = self.free.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(block) = free.pop() else {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                return Err(ViewError::AllocationFailure("Memory pool is exhausted"));
+            };
+            self.peak_used
+                .fetch_max(self.num_blocks - free.len(), Ordering::Relaxed);
+            block
+        };
+        // SAFETY: the block lies within the arena & is claimed by this view only until
+        // it is released; InnerDataType<T> has the same memory layout as T
+        let view = unsafe {
+            let ptr: *mut T = self.base.add(block * self.block_stride).cast();
+            ViewRW::from_raw_parts(ptr, layout, dim)
+        };
+        match view {
+            Ok(view) => Ok(PoolView {
+                view,
+                pool: self,
+                block,
+            }),
+            Err(e) => {
+                self.release(block);
+                Err(e)
+            }
+        }
+    }
+
+    fn release(&self, block: usize) {
+        self.free
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(block);
+    }
+
+    /// Returns the current usage of the pool.
+    pub fn stats(&self) -> PoolStats {
+        let free = self.free.lock().unwrap_or_else(|e| e.into_inner()).len();
+        PoolStats {
+            block_size: self.block_size,
+            num_blocks: self.num_blocks,
+            used_blocks: self.num_blocks - free,
+            peak_used_blocks: self.peak_used.load(Ordering::Relaxed),
+            failed_allocations: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// View allocated out of a [MemoryPool]. Its block is released when it is dropped.
+///
+/// The view is accessed through [Deref] & [DerefMut].
+#[derive(Debug)]
+pub struct PoolView<'p, const N: usize, T>
+where
+    T: DataTraits + 'static,
+{
+    view: ViewRW<'p, N, T>,
+    pool: &'p MemoryPool<T>,
+    block: usize,
+}
+
+impl<'p, const N: usize, T> Deref for PoolView<'p, N, T>
+where
+    T: DataTraits + 'static,
+{
+    type Target = ViewRW<'p, N, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.view
+    }
+}
+
+impl<const N: usize, T> DerefMut for PoolView<'_, N, T>
+where
+    T: DataTraits + 'static,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.view
+    }
+}
+
+impl<const N: usize, T> Drop for PoolView<'_, N, T>
+where
+    T: DataTraits + 'static,
+{
+    fn drop(&mut self) {
+        self.pool.release(self.block);
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routines::{
+        parameters::{ExecutionSpace, Range1D, Schedule, TypedExecutionPolicy},
+        typed::parallel_for,
+    };
+
+    #[test]
+    fn allocate_release() {
+        let pool: MemoryPool<i32> = MemoryPool::new(MemorySpace::Host, 8, 2);
+        assert_eq!(pool.memory_space(), MemorySpace::Host);
+        #[allow(unused_mut)]
+        let mut a = pool.allocate(Layout::Left, [2, 4]).unwrap();
+        let b = pool.allocate(Layout::Right, [3]).unwrap();
+        a.set([1, 3], 7);
+        assert!(pool.allocate(Layout::Right, [1]).is_err());
+        assert!(pool.allocate(Layout::Right, [9]).is_err());
+        assert_eq!(
+            pool.stats(),
+            PoolStats {
+                block_size: 8,
+                num_blocks: 2,
+                used_blocks: 2,
+                peak_used_blocks: 2,
+                failed_allocations: 1,
+            }
+        );
+
+        // the last released block is reused, its content is kept
+        drop(b);
+        drop(a);
+        let c = pool.allocate(Layout::Right, [8]).unwrap();
+        assert_eq!(c.get([7]), 7);
+        assert_eq!(pool.stats().used_blocks, 1);
+    }
+
+    #[test]
+    fn scratch_views() {
+        for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+            let pool: MemoryPool<usize> = MemoryPool::new(MemorySpace::of(space), 16, 256);
+            let execp = TypedExecutionPolicy {
+                space,
+                policy: Range1D(0..512),
+                schedule: Schedule::Dynamic,
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            parallel_for(execp, |i| {
+                #[allow(unused_mut)]
+                let mut scratch = pool.allocate(Layout::Right, [4, 4]).unwrap();
+                (0..4).for_each(|k| scratch.set([k, k], i));
+                assert!((0..4).all(|k| scratch.get([k, k]) == i));
+            })
+            .unwrap();
+            assert_eq!(pool.stats().used_blocks, 0);
+        }
+    }
+
+    #[test]
+    fn aligned_blocks() {
+        let pool: MemoryPool<f32> = MemoryPool::new_aligned(MemorySpace::Host, 64, 10, 4);
+        assert_eq!(pool.alignment(), 64);
+        let mut views: Vec<_> = (0..4)
+            .map(|_| pool.allocate(Layout::Right, [10]).unwrap())
+            .collect();
+        for view in &mut views {
+            assert_eq!(view.as_mut_slice().unwrap().as_ptr() as usize % 64, 0);
+        }
+        assert!(MemoryPool::<f32>::try_new_aligned(MemorySpace::Host, 48, 10, 4).is_err());
+    }
+}
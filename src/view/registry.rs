@@ -0,0 +1,225 @@
+//! allocation registry related code
+//!
+//! This module contains a global registry of the data allocated by views, akin to the
+//! memory tracking of the Kokkos runtime. Each owned view registers its allocation
+//! when created and unregisters it when dropped; mirrors & views wrapping foreign
+//! buffers are not registered since they do not own their data.
+//!
+//! Allocations carry the label of their view, set using
+//! [`with_label`][crate::view::ViewBase::with_label], their [MemorySpace] & their
+//! alignment. Live allocations can be listed using [`allocations`], aggregated using
+//! [`bytes_in_space`] & [`bytes_per_label`], or dumped as a table using [`dump`]. This
+//! helps finding which views dominate the memory footprint of a run.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{parameters::Layout, registry, ViewOwned};
+//!
+//! let view: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new(Layout::Right, [64, 64]).with_label("temperature");
+//! assert_eq!(view.label(), "temperature");
+//! assert_eq!(registry::bytes_per_label()["temperature"], 64 * 64 * 8);
+//!
+//! let mut out: Vec<u8> = Vec::new();
+//! registry::dump(&mut out).unwrap();
+//!
+//! drop(view);
+//! assert!(!registry::bytes_per_label().contains_key("temperature"));
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use super::dual::MemorySpace;
+
+/// Snapshot of a live allocation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllocationRecord {
+    /// Label of the view owning the allocation. Empty if the view is not labelled.
+    pub label: String,
+    /// Memory space of the allocation.
+    pub space: MemorySpace,
+    /// Size of the allocation, in bytes.
+    pub bytes: usize,
+    /// Alignment of the allocation, in bytes.
+    pub align: usize,
+}
+
+// Global store
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static ALLOCATIONS: Mutex<BTreeMap<u64, AllocationRecord>> = Mutex::new(BTreeMap::new());
+
+fn lock() -> MutexGuard<'static, BTreeMap<u64, AllocationRecord>> {
+    ALLOCATIONS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Registration of an allocation, held by the view owning it. The allocation is
+/// unregistered when the handle is dropped.
+#[derive(Debug)]
+pub struct TrackedAllocation {
+    id: u64,
+    label: String,
+    space: MemorySpace,
+}
+
+impl TrackedAllocation {
+    /// Register an unlabelled allocation of `bytes` bytes aligned on `align` bytes in
+    /// `space`.
+    pub(crate) fn new(bytes: usize, align: usize, space: MemorySpace) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        lock().insert(
+            id,
+            AllocationRecord {
+                label: String::new(),
+                space,
+                bytes,
+                align,
+            },
+        );
+        Self {
+            id,
+            label: String::new(),
+            space,
+        }
+    }
+
+    /// Returns the label of the allocation.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the memory space of the allocation.
+    pub fn space(&self) -> MemorySpace {
+        self.space
+    }
+
+    /// Change the label of the allocation.
+    pub(crate) fn set_label(&mut self, label: &str) {
+        label.clone_into(&mut self.label);
+        self.update(|rec| label.clone_into(&mut rec.label));
+    }
+
+    /// Change the size of the allocation, e.g. after a reallocation.
+    pub(crate) fn set_bytes(&self, bytes: usize) {
+        self.update(|rec| rec.bytes = bytes);
+    }
+
+    fn update(&self, op: impl FnOnce(&mut AllocationRecord)) {
+        if let Some(rec) = lock().get_mut(&self.id) {
+            op(rec)
+        }
+    }
+}
+
+impl Drop for TrackedAllocation {
+    fn drop(&mut self) {
+        lock().remove(&self.id);
+    }
+}
+
+/// Registrations do not take part in view comparison: two views are equal if they
+/// reference the same data, independently of their label.
+impl PartialEq for TrackedAllocation {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+// Queries
+
+/// Returns a snapshot of live allocations, in creation order.
+pub fn allocations() -> Vec<AllocationRecord> {
+    lock().values().cloned().collect()
+}
+
+/// Returns the number of bytes currently allocated in `space`.
+pub fn bytes_in_space(space: MemorySpace) -> usize {
+    lock()
+        .values()
+        .filter(|rec| rec.space == space)
+        .map(|rec| rec.bytes)
+        .sum()
+}
+
+/// Returns the number of bytes currently allocated per label, all spaces included.
+/// Unlabelled allocations are aggregated under the empty label.
+pub fn bytes_per_label() -> BTreeMap<String, usize> {
+    lock().values().fold(BTreeMap::new(), |mut acc, rec| {
+        *acc.entry(rec.label.clone()).or_default() += rec.bytes;
+        acc
+    })
+}
+
+/// Write live allocations into `out`, as a table sorted by decreasing size, followed by
+/// the total of each memory space.
+pub fn dump<W: Write>(mut out: W) -> std::io::Result<()> {
+    let mut records = allocations();
+    records.sort_by_key(|rec| std::cmp::Reverse(rec.bytes));
+    let mut row = |label: &str, space: &str, bytes: &dyn Display| {
+        writeln!(out, "{label:<32} {space:<8} {bytes:>16}")
+    };
+    row("label", "space", &"bytes")?;
+    for rec in &records {
+        let label = if rec.label.is_empty() {
+            "<unlabelled>"
+        } else {
+            &rec.label
+        };
+        row(label, &format!("{:?}", rec.space), &rec.bytes)?;
+    }
+    for space in [
+        MemorySpace::Host,
+        MemorySpace::HostHugePages,
+        MemorySpace::Device,
+    ] {
+        let total: usize = records
+            .iter()
+            .filter(|rec| rec.space == space)
+            .map(|rec| rec.bytes)
+            .sum();
+        row("total", &format!("{space:?}"), &total)?;
+    }
+    Ok(())
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_and_drop() {
+        let mut alloc = TrackedAllocation::new(128, 64, MemorySpace::Device);
+        alloc.set_label("registry::register_and_drop");
+        alloc.set_bytes(256);
+        assert_eq!(alloc.label(), "registry::register_and_drop");
+        assert!(allocations().contains(&AllocationRecord {
+            label: "registry::register_and_drop".to_string(),
+            space: MemorySpace::Device,
+            bytes: 256,
+            align: 64,
+        }));
+        assert!(bytes_in_space(MemorySpace::Device) >= 256);
+
+        let mut out: Vec<u8> = Vec::new();
+        dump(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out
+            .lines()
+            .any(|l| l.starts_with("registry::register_and_drop")));
+
+        drop(alloc);
+        assert!(!bytes_per_label().contains_key("registry::register_and_drop"));
+    }
+}
@@ -0,0 +1,181 @@
+//! reshape related code
+//!
+//! This module contains code used to reinterpret the dimensions of a view without
+//! copying its data, akin to reshaping a contiguous `Kokkos::View`: a 2D view can be
+//! flattened into a 1D view, and a 1D view can be split into a 2D view.
+//!
+//! Like subviews, reshaped views share the data of the original view. Elements keep
+//! their position in memory: the reshaped view uses the layout of the original view,
+//! i.e. elements of a [Layout::Right] view are reinterpreted in row-major order, and
+//! elements of a [Layout::Left] view in column-major order.
+//!
+//! Reshaping requires contiguous storage. An error is returned for views whose strides
+//! leave gaps between elements, e.g. padded views or most subviews.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+//!
+//! let vec: ViewOwned<'_, 1, f64> =
+//!     ViewOwned::new_from_data((0..6).map(|x| x as f64).collect(), Layout::Right, [6]);
+//!
+//! let mat = vec.reshape([2, 3]).unwrap();
+//! assert_eq!(mat.get([1, 0]), 3.0);
+//! assert_eq!(mat.flatten().unwrap().get([4]), 4.0);
+//! assert!(vec.reshape([4, 2]).is_err());
+//! ```
+
+#[cfg(feature = "access-stats")]
+use super::stats::AccessStats;
+#[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+use super::{parameters::InnerDataType, ViewRW};
+use super::{
+    parameters::{compute_stride, index_stride, DataTraits, DataType, Layout},
+    ViewBase, ViewError, ViewRO,
+};
+
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits,
+{
+    /// Returns the layout of the view reshaped to dimensions `dim`, i.e. [Layout::Right]
+    /// or [Layout::Left] depending on the storage order of the view.
+    fn reshaped_layout<const M: usize>(
+        &self,
+        dim: &[usize; M],
+    ) -> Result<Layout<M>, ViewError<'static>> {
+        if dim.iter().product::<usize>() != self.dim.iter().product() {
+            return Err(ViewError::DimensionMismatch(
+                "Reshaped view must have the same number of elements",
+            ));
+        }
+        #[allow(clippy::unnecessary_cast)] // cast is a no-op unless using `index-u32`
+        let stride = self.stride.map(|s| s as usize);
+        let contiguous = self.data_slice().len() == self.dim.iter().product();
+        let (right, left) = (
+            contiguous && stride == compute_stride(&self.dim, &Layout::Right),
+            contiguous && stride == compute_stride(&self.dim, &Layout::Left),
+        );
+        // strides of some views match both orders, e.g. 1D views
+        match (self.layout, right, left) {
+            (Layout::Left, _, true) | (_, false, true) => Ok(Layout::Left),
+            (_, true, _) => Ok(Layout::Right),
+            _ => Err(ViewError::ValueError(
+                "View storage is not contiguous, it cannot be reshaped",
+            )),
+        }
+    }
+
+    /// Create a read-only view of `self`'s data using dimensions `dim`.
+    ///
+    /// `dim` must have the same number of elements as `self`, otherwise a
+    /// [ViewError::DimensionMismatch] error is returned. A [ViewError::ValueError] error is
+    /// returned if the storage of `self` is not contiguous.
+    ///
+    /// When using parallelization features, the reshaped view can still be written to
+    /// since its inner values are atomic types.
+    pub fn reshape<const M: usize>(
+        &self,
+        dim: [usize; M],
+    ) -> Result<ViewRO<'_, M, T>, ViewError<'static>> {
+        let layout = self.reshaped_layout(&dim)?;
+        Ok(ViewBase {
+            data: DataType::Borrowed(self.data_slice()),
+            layout,
+            dim,
+            stride: index_stride(compute_stride(&dim, &layout), &dim),
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: None,
+        })
+    }
+
+    /// Create a read-only 1D view of `self`'s data. See [ViewBase::reshape].
+    pub fn flatten(&self) -> Result<ViewRO<'_, 1, T>, ViewError<'static>> {
+        self.reshape([self.dim.iter().product()])
+    }
+
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Create a mutable view of `self`'s data using dimensions `dim`. See
+    /// [ViewBase::reshape].
+    ///
+    /// Only defined when no feature are enabled since all interfaces should be immutable
+    /// otherwise.
+    pub fn reshape_mut<const M: usize>(
+        &mut self,
+        dim: [usize; M],
+    ) -> Result<ViewRW<'_, M, T>, ViewError<'static>> {
+        let layout = self.reshaped_layout(&dim)?;
+        let data: &mut [InnerDataType<T>] = match &mut self.data {
+            DataType::Owned(v) => v,
+            DataType::MutBorrowed(mut_slice) => mut_slice,
+            DataType::Borrowed(_) => {
+                return Err(ViewError::ValueError(
+                    "Cannot create a mutable reshape of a read-only View",
+                ))
+            }
+        };
+        Ok(ViewBase {
+            data: DataType::MutBorrowed(data),
+            layout,
+            dim,
+            stride: index_stride(compute_stride(&dim, &layout), &dim),
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: None,
+        })
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        routines::iter::MDIndexIter,
+        view::{parameters::Layout, subview::SliceArg, ViewError, ViewOwned},
+    };
+
+    #[test]
+    fn reshape_views() {
+        let data: Vec<f64> = (0..12).map(|x| x as f64).collect();
+        for layout in [Layout::Right, Layout::Left] {
+            let mat: ViewOwned<'_, 2, f64> = ViewOwned::new_from_data(data.clone(), layout, [3, 4]);
+            // flattening follows the storage order
+            let flat = mat.flatten().unwrap();
+            assert_eq!(flat.dims(), [12]);
+            assert!((0..12).all(|k| flat.get([k]) == k as f64));
+
+            let cube = flat.reshape([2, 3, 2]).unwrap();
+            let back = cube.reshape([3, 4]).unwrap();
+            MDIndexIter::new([0..3, 0..4]).for_each(|idx| assert_eq!(back.get(idx), mat.get(idx)));
+            assert!(matches!(
+                mat.reshape([5, 2]),
+                Err(ViewError::DimensionMismatch(_))
+            ));
+        }
+
+        let mat: ViewOwned<'_, 2, f64> = ViewOwned::new_from_data(data, Layout::Right, [3, 4]);
+        // contiguous rows can be reshaped, columns & padded views cannot
+        let rows = mat
+            .subview::<2>([SliceArg::Range(1..3), SliceArg::All])
+            .unwrap();
+        assert_eq!(rows.reshape([4, 2]).unwrap().get([0, 0]), 4.0);
+        let col = mat
+            .subview::<1>([SliceArg::All, SliceArg::Index(1)])
+            .unwrap();
+        assert!(matches!(col.reshape([3, 1]), Err(ViewError::ValueError(_))));
+        let padded: ViewOwned<'_, 2, f64> = ViewOwned::new_padded(Layout::Right, [3, 3], 64);
+        assert!(padded.flatten().is_err());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    fn reshape_mut() {
+        let mut vec: ViewOwned<'_, 1, i32> = ViewOwned::new(Layout::Right, [6]);
+        vec.reshape_mut([3, 2]).unwrap().set([2, 1], 7);
+        assert_eq!(vec.get([5]), 7);
+    }
+}
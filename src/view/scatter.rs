@@ -0,0 +1,271 @@
+//! scatter view related code
+//!
+//! This module contains [ScatterView], the equivalent of Kokkos' `ScatterView`. It wraps
+//! a view in order to accumulate contributions to arbitrary elements from a parallel
+//! kernel, e.g. histograms or assembly of finite-element matrices, where several
+//! iterations may update the same element:
+//!
+//! - [ScatterMode::Atomic]: contributions are applied to the view using atomic
+//!   read-modify-write operations,
+//! - [ScatterMode::Duplicated]: each thread contributes to a private replica of the
+//!   view, lowering contention. Replicas are merged into the view using
+//!   [ScatterView::contribute].
+//!
+//! Replicas are only allocated when a parallelization feature is enabled; otherwise,
+//! both modes update the view directly.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     routines::{
+//!         parameters::{ReduceOp, TypedExecutionPolicy, Range1D},
+//!         typed::parallel_for,
+//!     },
+//!     view::{
+//!         parameters::Layout,
+//!         scatter::{ScatterMode, ScatterView},
+//!         ViewOwned,
+//!     },
+//! };
+//!
+//! let mut histogram: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [4]);
+//! #[allow(unused_mut)]
+//! let mut scatter = ScatterView::new(&mut histogram, ReduceOp::Sum, ScatterMode::Duplicated);
+//!
+//! let execp = TypedExecutionPolicy::new(Range1D(0..100));
+//! parallel_for(execp, |i| scatter.update([i % 4], 1.0)).unwrap();
+//! scatter.contribute();
+//! drop(scatter);
+//!
+//! assert_eq!(histogram.get([3]), 25.0);
+//! ```
+
+use std::{ops::Add, sync::atomic::Ordering};
+
+use super::{parameters::DataTraits, ViewBase, ViewOwned};
+use crate::routines::{iter::MDIndexIter, parameters::ReduceOp};
+
+/// Strategy used by a [ScatterView] to apply contributions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScatterMode {
+    /// Contributions are applied atomically to the wrapped view.
+    #[default]
+    Atomic,
+    /// Contributions are applied to thread-private replicas, merged using
+    /// [ScatterView::contribute].
+    Duplicated,
+}
+
+/// View wrapper accumulating contributions using a reduction operator.
+///
+/// The wrapped view is mutably borrowed for the lifetime of the scatter view; it can be
+/// read again once the latter is dropped. Contributions applied to replicas are only
+/// visible in the wrapped view after a call to [ScatterView::contribute].
+pub struct ScatterView<'v, 'a, const N: usize, T>
+where
+    T: DataTraits,
+{
+    target: &'v mut ViewBase<'a, N, T>,
+    op: ReduceOp<T>,
+    replicas: Vec<ViewOwned<'a, N, T>>,
+}
+
+impl<'v, 'a, const N: usize, T> ScatterView<'v, 'a, N, T>
+where
+    T: DataTraits + Add<Output = T> + PartialOrd,
+{
+    /// Wrap `target`, combining contributions with its elements using `op`.
+    ///
+    /// [ReduceOp::Custom] operators have no known identity element, hence always use
+    /// [ScatterMode::Atomic].
+    pub fn new(target: &'v mut ViewBase<'a, N, T>, op: ReduceOp<T>, mode: ScatterMode) -> Self {
+        let n_replicas = match (mode, op) {
+            (_, ReduceOp::Custom(_)) | (ScatterMode::Atomic, _) => 0,
+            (ScatterMode::Duplicated, _) => replica_count(),
+        };
+        let replicas = (0..n_replicas)
+            .map(|_| ViewOwned::new(target.layout, target.dim))
+            .collect();
+        let mut scatter = Self {
+            target,
+            op,
+            replicas,
+        };
+        scatter.reset();
+        scatter
+    }
+
+    /// Returns the mode effectively used by the scatter view.
+    pub fn mode(&self) -> ScatterMode {
+        if self.replicas.is_empty() {
+            ScatterMode::Atomic
+        } else {
+            ScatterMode::Duplicated
+        }
+    }
+
+    /// Merge the content of replicas into the wrapped view.
+    ///
+    /// Replicas are left untouched: [ScatterView::reset] must be called before
+    /// contributing again, otherwise values are merged twice.
+    pub fn contribute(&mut self) {
+        let op = self.op;
+        let target = &mut *self.target;
+        self.replicas.iter().for_each(|replica| {
+            MDIndexIter::new(target.dim.map(|d| 0..d)).for_each(|idx| {
+                let val = replica.load(replica.flat_idx(idx));
+                target.fetch_op(idx, Ordering::Relaxed, |prev| op.combine(prev, val));
+            })
+        });
+    }
+
+    /// Reset replicas to the identity of the operator, i.e. zero for sums & the current
+    /// values of the wrapped view for minimums & maximums.
+    pub fn reset(&mut self) {
+        let (op, target) = (self.op, &*self.target);
+        self.replicas.iter_mut().for_each(|replica| {
+            MDIndexIter::new(target.dim.map(|d| 0..d)).for_each(|idx| {
+                let init = match op {
+                    ReduceOp::Sum => T::default(),
+                    _ => target.load(target.flat_idx(idx)),
+                };
+                replica.set(idx, init);
+            })
+        });
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))] {
+        impl<'v, 'a, const N: usize, T> ScatterView<'v, 'a, N, T>
+        where
+            T: DataTraits + Add<Output = T> + PartialOrd,
+        {
+            /// Combine `val` with the element at `index`.
+            ///
+            /// **Current version**: thread-safe
+            pub fn update(&self, index: [usize; N], val: T) {
+                let op = self.op;
+                let view: &ViewBase<'_, N, T> = if self.replicas.is_empty() {
+                    self.target
+                } else {
+                    &self.replicas[thread_slot() % self.replicas.len()]
+                };
+                view.fetch_op(index, Ordering::Relaxed, |prev| op.combine(prev, val));
+            }
+        }
+
+        /// Returns the number of replicas allocated by duplicated scatter views.
+        fn replica_count() -> usize {
+            crate::runtime::num_threads().max(1)
+        }
+
+        static NEXT_SLOT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+        thread_local! {
+            static SLOT: usize = NEXT_SLOT.fetch_add(1, Ordering::Relaxed);
+        }
+
+        /// Returns an index unique to the calling thread, used to select its replica.
+        /// Threads sharing a replica remain correct since updates are atomic.
+        fn thread_slot() -> usize {
+            SLOT.with(|slot| *slot)
+        }
+    } else {
+        impl<'v, 'a, const N: usize, T> ScatterView<'v, 'a, N, T>
+        where
+            T: DataTraits + Add<Output = T> + PartialOrd,
+        {
+            /// Combine `val` with the element at `index`.
+            ///
+            /// **Current version**: no feature
+            pub fn update(&mut self, index: [usize; N], val: T) {
+                let op = self.op;
+                self.target
+                    .fetch_op(index, Ordering::Relaxed, |prev| op.combine(prev, val));
+            }
+        }
+
+        /// Returns the number of replicas allocated by duplicated scatter views.
+        fn replica_count() -> usize {
+            0
+        }
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        routines::{
+            parameters::{ExecutionSpace, Range1D, Schedule, TypedExecutionPolicy},
+            typed::parallel_for,
+        },
+        view::parameters::Layout,
+    };
+
+    #[test]
+    fn scatter_sum() {
+        for mode in [ScatterMode::Atomic, ScatterMode::Duplicated] {
+            for space in [ExecutionSpace::Serial, ExecutionSpace::DeviceCPU] {
+                let mut view: ViewOwned<'_, 2, f64> =
+                    ViewOwned::new_from_data(vec![1.0; 6], Layout::Left, [2, 3]);
+                {
+                    #[allow(unused_mut)]
+                    let mut scatter = ScatterView::new(&mut view, ReduceOp::Sum, mode);
+                    let execp = TypedExecutionPolicy {
+                        space,
+                        policy: Range1D(0..600),
+                        schedule: Schedule::Dynamic,
+                        chunk_size: None,
+                        chunk_predicate: None,
+                    };
+                    parallel_for(execp, |i| scatter.update([i % 2, i % 3], 0.5)).unwrap();
+                    scatter.contribute();
+                    // contributing after a reset does not merge values twice
+                    scatter.reset();
+                    scatter.contribute();
+                }
+                assert!(view.iter().all(|val| val == 51.0));
+            }
+        }
+    }
+
+    #[test]
+    fn scatter_min_max() {
+        let data = vec![5, 5, 5, 5];
+        for mode in [ScatterMode::Atomic, ScatterMode::Duplicated] {
+            let mut view: ViewOwned<'_, 1, usize> =
+                ViewOwned::new_from_data(data.clone(), Layout::Right, [4]);
+            {
+                #[allow(unused_mut)]
+                let mut scatter = ScatterView::new(&mut view, ReduceOp::Max, mode);
+                let execp = TypedExecutionPolicy::new(Range1D(0..40));
+                parallel_for(execp, |i| scatter.update([i % 4], i)).unwrap();
+                scatter.contribute();
+            }
+            assert_eq!(view.iter().collect::<Vec<_>>(), vec![36, 37, 38, 39]);
+
+            {
+                #[allow(unused_mut)]
+                let mut scatter = ScatterView::new(&mut view, ReduceOp::Min, mode);
+                let execp = TypedExecutionPolicy::new(Range1D(0..3));
+                parallel_for(execp, |i| scatter.update([i], 10 * i)).unwrap();
+                scatter.contribute();
+            }
+            assert_eq!(view.iter().collect::<Vec<_>>(), vec![0, 10, 20, 39]);
+        }
+
+        let mut view: ViewOwned<'_, 1, usize> = ViewOwned::new(Layout::Right, [2]);
+        let scatter = ScatterView::new(
+            &mut view,
+            ReduceOp::Custom(|a, b| a | b),
+            ScatterMode::Duplicated,
+        );
+        assert_eq!(scatter.mode(), ScatterMode::Atomic);
+    }
+}
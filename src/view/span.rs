@@ -0,0 +1,358 @@
+//! contiguous span related code
+//!
+//! This module contains methods yielding the data of views as regular slices of `T`,
+//! making it possible to write kernels that the compiler can auto-vectorize (or that
+//! use explicit SIMD) instead of going through `get` & `set` element by element:
+//!
+//! - [ViewBase::as_slice] & [ViewBase::as_mut_slice]: the whole data of views without
+//!   padding, i.e. using [Layout::Right] or [Layout::Left],
+//! - [ViewBase::as_chunks_mut]: the contiguous spans of the fastest-varying dimension,
+//!   e.g. the rows of a [Layout::Right] matrix, padded or not.
+//!
+//! Using parallelization features, the data of views is atomic & may be written through
+//! shared references. Plain slices are therefore only handed out from exclusive
+//! borrows of the view. Parallel kernels access spans through [Chunks::claim], which
+//! grants exclusive access to a single span at a time.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::{
+//!     routines::{
+//!         parameters::{Range1D, TypedExecutionPolicy},
+//!         typed::parallel_for,
+//!     },
+//!     view::{parameters::Layout, ViewOwned},
+//! };
+//!
+//! let mut mat: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new(Layout::padded_right::<f64>([4, 5], 64), [4, 5]);
+//! let chunks = mat.as_chunks_mut().unwrap();
+//! assert_eq!(chunks.span_len(), 5);
+//!
+//! let execp = TypedExecutionPolicy::new(Range1D(0..chunks.len()));
+//! parallel_for(execp, |k| {
+//!     let [row, _] = chunks.origin(k);
+//!     let mut span = chunks.claim(k).unwrap();
+//!     span.iter_mut().enumerate().for_each(|(col, x)| *x = (row * col) as f64);
+//! })
+//! .unwrap();
+//! drop(chunks);
+//!
+//! assert_eq!(mat.get([3, 4]), 12.0);
+//! ```
+
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use super::{
+    parameters::{compute_stride, index_stride, DataTraits, DataType, InnerDataType, Layout},
+    ViewBase, ViewError,
+};
+use crate::routines::{iter::MDIndexIter, parameters::Iterate};
+
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits,
+{
+    /// Returns the data of the view as a mutable slice, in memory order.
+    ///
+    /// The view must not contain padding, i.e. its stride must match [Layout::Right] or
+    /// [Layout::Left], and must be able to write its data.
+    pub fn as_mut_slice(&mut self) -> Result<&mut [T], ViewError<'static>> {
+        let len = self.contiguous_len()?;
+        let data = self.data_slice_mut()?;
+        // SAFETY: `InnerDataType<T>` is either `T` or `Atomic<T>`, which has the same
+        // in-memory representation. The data is exclusively borrowed.
+        Ok(unsafe { std::slice::from_raw_parts_mut(data.as_mut_ptr().cast::<T>(), len) })
+    }
+
+    /// Returns the contiguous spans of the fastest-varying dimension of the view, i.e.
+    /// the dimension with a stride of 1.
+    ///
+    /// Spans are ordered in layout order & are disjoint: elements located between
+    /// the spans of padded views are not accessible. The view must be able to write its
+    /// data.
+    pub fn as_chunks_mut(&mut self) -> Result<Chunks<'_, N, T>, ViewError<'static>> {
+        let axis = self
+            .contiguous_axis()
+            .ok_or(ViewError::ValueError("View has no contiguous dimension"))?;
+        let span_len = self.dim[axis];
+        let total: usize = self.dim.iter().product();
+
+        let mut outer = self.dim.map(|d| 0..d);
+        outer[axis] = 0..1;
+        let (origins, offsets): (Vec<[usize; N]>, Vec<usize>) = if total == 0 {
+            (Vec::new(), Vec::new())
+        } else {
+            MDIndexIter::with_iterate(outer, Iterate::from(&self.layout))
+                .map(|idx| (idx, self.flat_idx(idx)))
+                .unzip()
+        };
+
+        // custom strides may alias elements of different spans
+        let mut sorted = offsets.clone();
+        sorted.sort_unstable();
+        let data = self.data_slice_mut()?;
+        let in_bounds = sorted
+            .last()
+            .is_none_or(|last| last + span_len <= data.len());
+        if !in_bounds || sorted.windows(2).any(|w| w[1] - w[0] < span_len) {
+            return Err(ViewError::ValueError("Spans of the View overlap"));
+        }
+
+        Ok(Chunks {
+            ptr: data.as_mut_ptr().cast::<T>(),
+            span_len,
+            origins,
+            claimed: offsets.iter().map(|_| AtomicBool::new(false)).collect(),
+            offsets,
+            _borrow: PhantomData,
+        })
+    }
+
+    /// Returns the number of elements of the view if its data is contiguous.
+    fn contiguous_len(&self) -> Result<usize, ViewError<'static>> {
+        let contiguous = [Layout::Right, Layout::Left].iter().any(|layout| {
+            index_stride(compute_stride(&self.dim, layout), &self.dim) == self.stride
+        });
+        if contiguous {
+            Ok(self.dim.iter().product())
+        } else {
+            Err(ViewError::ValueError("View data is not contiguous"))
+        }
+    }
+
+    /// Returns the fastest-varying dimension if its stride is 1.
+    fn contiguous_axis(&self) -> Option<usize> {
+        let axis = match self.layout {
+            Layout::Right => N - 1,
+            Layout::Left => 0,
+            // largest dimension among the ones with unit stride
+            Layout::Stride { s } => (0..N).filter(|k| s[*k] == 1).max_by_key(|k| self.dim[*k])?,
+        };
+        #[allow(clippy::unnecessary_cast)] // casts are no-ops unless using `index-u32`
+        (self.stride[axis] as usize == 1 || self.dim[axis] <= 1).then_some(axis)
+    }
+
+    /// Returns the underlying data as a mutable slice, unless the view is read-only.
+    fn data_slice_mut(&mut self) -> Result<&mut [InnerDataType<T>], ViewError<'static>> {
+        match &mut self.data {
+            DataType::Owned(v) => Ok(v),
+            DataType::MutBorrowed(mut_slice) => Ok(mut_slice),
+            DataType::Borrowed(_) => Err(ViewError::ValueError(
+                "Cannot mutably access the data of a read-only View",
+            )),
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))] {
+        impl<'a, const N: usize, T> ViewBase<'a, N, T>
+        where
+            T: DataTraits,
+        {
+            /// Returns the data of the view as a slice, in memory order.
+            ///
+            /// The view must not contain padding, i.e. its stride must match
+            /// [Layout::Right] or [Layout::Left]. Read-only mirrors are rejected since
+            /// the data they refer to may still be written atomically.
+            ///
+            /// **Current version**: thread-safe
+            pub fn as_slice(&mut self) -> Result<&[T], ViewError<'static>> {
+                self.as_mut_slice().map(|slice| &*slice)
+            }
+        }
+    } else {
+        impl<'a, const N: usize, T> ViewBase<'a, N, T>
+        where
+            T: DataTraits,
+        {
+            /// Returns the data of the view as a slice, in memory order.
+            ///
+            /// The view must not contain padding, i.e. its stride must match
+            /// [Layout::Right] or [Layout::Left].
+            ///
+            /// **Current version**: no feature
+            pub fn as_slice(&self) -> Result<&[T], ViewError<'static>> {
+                let len = self.contiguous_len()?;
+                Ok(&self.data_slice()[..len])
+            }
+        }
+    }
+}
+
+/// Contiguous spans of a view, obtained using [ViewBase::as_chunks_mut].
+///
+/// The view is mutably borrowed for the lifetime of the chunks. Spans can be accessed
+/// sequentially using [Chunks::iter_mut], or from a parallel kernel using
+/// [Chunks::claim].
+pub struct Chunks<'v, const N: usize, T> {
+    ptr: *mut T,
+    span_len: usize,
+    origins: Vec<[usize; N]>,
+    offsets: Vec<usize>,
+    claimed: Vec<AtomicBool>,
+    _borrow: PhantomData<&'v mut [T]>,
+}
+
+// SAFETY: spans are disjoint & each one is accessed by at most one thread at a time,
+// either through an exclusive borrow of the chunks or through a claim.
+unsafe impl<const N: usize, T: Send> Send for Chunks<'_, N, T> {}
+unsafe impl<const N: usize, T: Send> Sync for Chunks<'_, N, T> {}
+
+impl<'v, const N: usize, T> Chunks<'v, N, T> {
+    /// Returns the number of spans.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns `true` if there are no spans, i.e. if the view is empty.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Returns the number of elements of each span.
+    pub fn span_len(&self) -> usize {
+        self.span_len
+    }
+
+    /// Returns the N-index of the first element of the `k`-th span.
+    pub fn origin(&self, k: usize) -> [usize; N] {
+        self.origins[k]
+    }
+
+    /// Grant exclusive access to the `k`-th span until the returned guard is dropped.
+    ///
+    /// Returns `None` if the span is already claimed.
+    pub fn claim(&self, k: usize) -> Option<SpanGuard<'_, T>> {
+        let flag = &self.claimed[k];
+        flag.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()?;
+        // SAFETY: the span is in bounds & disjoint from the others; the flag prevents
+        // other claims until the guard is dropped.
+        let span =
+            unsafe { std::slice::from_raw_parts_mut(self.ptr.add(self.offsets[k]), self.span_len) };
+        Some(SpanGuard { span, flag })
+    }
+
+    /// Returns an iterator over the spans, in layout order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut [T]> + '_ {
+        let (ptr, span_len) = (self.ptr, self.span_len);
+        // SAFETY: spans are in bounds & disjoint; the chunks are exclusively borrowed
+        // for the lifetime of the iterator.
+        self.offsets.iter().map(move |offset| unsafe {
+            std::slice::from_raw_parts_mut(ptr.add(*offset), span_len)
+        })
+    }
+}
+
+/// Exclusive access to a span, released when dropped. See [Chunks::claim].
+pub struct SpanGuard<'c, T> {
+    span: &'c mut [T],
+    flag: &'c AtomicBool,
+}
+
+impl<T> Deref for SpanGuard<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.span
+    }
+}
+
+impl<T> DerefMut for SpanGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.span
+    }
+}
+
+impl<T> Drop for SpanGuard<'_, T> {
+    fn drop(&mut self) {
+        self.flag.store(false, Ordering::Release);
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        routines::{
+            parameters::{ExecutionSpace, Range1D, Schedule, TypedExecutionPolicy},
+            typed::parallel_for,
+        },
+        view::ViewOwned,
+    };
+
+    #[test]
+    fn contiguous_slices() {
+        let mut view: ViewOwned<'_, 2, f64> =
+            ViewOwned::new_from_data((0..6).map(|x| x as f64).collect(), Layout::Left, [2, 3]);
+        view.as_mut_slice()
+            .unwrap()
+            .iter_mut()
+            .for_each(|x| *x *= 2.0);
+        assert_eq!(view.as_slice().unwrap(), &[0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(view.get([1, 2]), 10.0);
+
+        let mut padded: ViewOwned<'_, 2, f64> =
+            ViewOwned::new(Layout::padded_right::<f64>([2, 3], 64), [2, 3]);
+        assert!(padded.as_slice().is_err());
+        assert!(padded.as_mut_slice().is_err());
+
+        let mut mirror = view.create_mirror().unwrap();
+        assert!(mirror.as_mut_slice().is_err());
+        assert!(mirror.as_chunks_mut().is_err());
+    }
+
+    #[test]
+    fn chunks() {
+        for layout in [
+            Layout::Right,
+            Layout::Left,
+            Layout::padded_right::<f64>([5, 3], 64),
+            Layout::padded_left::<f64>([5, 3], 64),
+        ] {
+            let mut view: ViewOwned<'_, 2, f64> = ViewOwned::new(layout, [5, 3]);
+            let mut chunks = view.as_chunks_mut().unwrap();
+            let (n_spans, span_len) = match layout {
+                Layout::Left => (3, 5),
+                Layout::Stride { s } if s[0] == 1 => (3, 5),
+                _ => (5, 3),
+            };
+            assert_eq!((chunks.len(), chunks.span_len()), (n_spans, span_len));
+            chunks
+                .iter_mut()
+                .for_each(|span| span.iter_mut().for_each(|x| *x += 1.0));
+
+            let execp = TypedExecutionPolicy {
+                space: ExecutionSpace::DeviceCPU,
+                policy: Range1D(0..chunks.len()),
+                schedule: Schedule::Dynamic,
+                chunk_size: None,
+                chunk_predicate: None,
+            };
+            parallel_for(execp, |k| {
+                let mut span = chunks.claim(k).unwrap();
+                // a span cannot be claimed twice
+                assert!(chunks.claim(k).is_none());
+                span.iter_mut().for_each(|x| *x *= 3.0);
+            })
+            .unwrap();
+            assert!(chunks.claim(0).is_some());
+            drop(chunks);
+            assert!(view.iter().all(|x| x == 3.0));
+        }
+
+        // aliasing strides
+        let mut view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Stride { s: [1, 2] }, [3, 2]);
+        assert!(view.as_chunks_mut().is_err());
+    }
+}
@@ -0,0 +1,117 @@
+//! view access statistics code
+//!
+//! This module contains code used to count accesses made to views during a run. It is
+//! only compiled when the `access-stats` feature is enabled, in which case each view
+//! carries its own counters, updated by the [`get`][crate::view::ViewBase::get] and
+//! [`set`][crate::view::ViewBase::set] methods.
+//!
+//! The counters are meant to produce quantitative data about access patterns in order
+//! to inform layout and tiling decisions. Accesses made directly through the [Index]
+//! trait implementation are not counted.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{parameters::Layout, ViewOwned};
+//!
+//! let mut view: ViewOwned<'_, 1, f64> = ViewOwned::new(Layout::Right, [4]);
+//! view.set([0], 1.0);
+//! let _ = view.get([0]);
+//! let _ = view.get([1]);
+//!
+//! let counts = view.access_stats();
+//! assert_eq!(counts.reads, 2);
+//! assert_eq!(counts.writes, 1);
+//! ```
+
+#[cfg(doc)]
+use std::ops::Index;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Snapshot of the access counters of a view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccessCounts {
+    /// Number of elements read through the `get` method.
+    pub reads: usize,
+    /// Number of elements written through the `set` method.
+    pub writes: usize,
+}
+
+impl AccessCounts {
+    /// Total number of accesses.
+    pub fn total(&self) -> usize {
+        self.reads + self.writes
+    }
+}
+
+/// Access counters of a view.
+///
+/// Counters are atomics updated using relaxed ordering so that they can be shared
+/// between threads when using parallelization features. This means values should only
+/// be read once the parallel statements using the view are over.
+#[derive(Debug, Default)]
+pub struct AccessStats {
+    reads: AtomicUsize,
+    writes: AtomicUsize,
+}
+
+impl AccessStats {
+    #[inline(always)]
+    /// Record a read access.
+    pub fn record_read(&self) {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    /// Record a write access.
+    pub fn record_write(&self) {
+        self.writes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the current values of the counters.
+    pub fn snapshot(&self) -> AccessCounts {
+        AccessCounts {
+            reads: self.reads.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Set all counters back to zero.
+    pub fn reset(&self) {
+        self.reads.store(0, Ordering::Relaxed);
+        self.writes.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Statistics do not take part in view comparison: two views are equal if they
+/// reference the same data, independently of how it was accessed.
+impl PartialEq for AccessStats {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_reset() {
+        let stats = AccessStats::default();
+        stats.record_read();
+        stats.record_read();
+        stats.record_write();
+        assert_eq!(
+            stats.snapshot(),
+            AccessCounts {
+                reads: 2,
+                writes: 1
+            }
+        );
+        assert_eq!(stats.snapshot().total(), 3);
+
+        stats.reset();
+        assert_eq!(stats.snapshot(), AccessCounts::default());
+    }
+}
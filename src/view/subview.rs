@@ -0,0 +1,284 @@
+//! subview related code
+//!
+//! This module contains code used to extract views of a subset of another view's data,
+//! akin to `Kokkos::subview`. A subview shares the allocation of the original view and
+//! carries its own dimensions & strides, hence it is accessed through the usual `get` &
+//! `set` interface.
+//!
+//! Each dimension of the original view is sliced using a [SliceArg]. Dimensions sliced
+//! using [SliceArg::Index] are dropped, which makes it possible to extract lower-rank
+//! views, e.g. a row or a column of a matrix.
+//!
+//! Subviews of non-contiguous blocks use a [Layout::Stride] layout.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{parameters::Layout, subview::SliceArg, ViewOwned};
+//!
+//! let mat: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data((0..12).map(|x| x as f64).collect(), Layout::Right, [3, 4]);
+//!
+//! // second row
+//! let row = mat.subview::<1>([SliceArg::Index(1), SliceArg::All]).unwrap();
+//! assert_eq!(row.dims(), [4]);
+//! assert_eq!(row.get([2]), 6.0);
+//!
+//! // 2x2 block in the upper right corner
+//! let block = mat
+//!     .subview::<2>([SliceArg::Range(0..2), SliceArg::Range(2..4)])
+//!     .unwrap();
+//! assert_eq!(block.get([1, 0]), 6.0);
+//! ```
+
+use std::ops::Range;
+
+#[cfg(feature = "access-stats")]
+use super::stats::AccessStats;
+#[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+use super::{parameters::InnerDataType, ViewRW};
+use super::{
+    parameters::{compute_stride, index_stride, DataTraits, DataType, Layout},
+    ViewBase, ViewError, ViewRO,
+};
+
+/// Slicing argument of a single dimension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SliceArg {
+    /// Keep the whole dimension.
+    All,
+    /// Keep a single index of the dimension. The dimension is dropped in the subview.
+    Index(usize),
+    /// Keep a range of indices of the dimension.
+    Range(Range<usize>),
+}
+
+/// Geometry of a subview: offset of its first element in the original data, dimensions
+/// and strides.
+struct SubviewGeometry<const M: usize> {
+    offset: usize,
+    span: usize,
+    dim: [usize; M],
+    stride: [usize; M],
+}
+
+impl<const M: usize> SubviewGeometry<M> {
+    /// Returns the layout matching the strides of the subview.
+    fn layout(&self) -> Layout<M> {
+        if self.stride == compute_stride(&self.dim, &Layout::Right) {
+            Layout::Right
+        } else if self.stride == compute_stride(&self.dim, &Layout::Left) {
+            Layout::Left
+        } else {
+            Layout::Stride { s: self.stride }
+        }
+    }
+}
+
+impl<'a, const N: usize, T> ViewBase<'a, N, T>
+where
+    T: DataTraits,
+{
+    /// Compute the geometry of the subview described by `args`.
+    fn subview_geometry<const M: usize>(
+        &self,
+        args: &[SliceArg; N],
+    ) -> Result<SubviewGeometry<M>, ViewError<'static>> {
+        let kept = args
+            .iter()
+            .filter(|arg| !matches!(arg, SliceArg::Index(_)))
+            .count();
+        if kept != M {
+            return Err(ViewError::ValueError(
+                "Subview rank does not match the number of kept dimensions",
+            ));
+        }
+        let mut geometry = SubviewGeometry {
+            offset: 0,
+            span: 0,
+            dim: [0; M],
+            stride: [0; M],
+        };
+        let mut m = 0;
+        for (k, arg) in args.iter().enumerate() {
+            let range = match arg {
+                SliceArg::All => 0..self.dim[k],
+                SliceArg::Index(i) => *i..*i + 1,
+                SliceArg::Range(r) => r.clone(),
+            };
+            if range.start > range.end || range.end > self.dim[k] {
+                return Err(ViewError::ValueError("Subview is out of the view's bounds"));
+            }
+            #[allow(clippy::unnecessary_cast)] // cast is a no-op unless using `index-u32`
+            let stride = self.stride[k] as usize;
+            geometry.offset += range.start * stride;
+            if !matches!(arg, SliceArg::Index(_)) {
+                geometry.dim[m] = range.len();
+                geometry.stride[m] = stride;
+                m += 1;
+            }
+        }
+        geometry.span = if geometry.dim.contains(&0) {
+            geometry.offset = 0;
+            0
+        } else {
+            // offset of the last element + 1
+            geometry
+                .dim
+                .iter()
+                .zip(geometry.stride.iter())
+                .map(|(d, s)| (d - 1) * s)
+                .sum::<usize>()
+                + 1
+        };
+        Ok(geometry)
+    }
+
+    /// Create a read-only view of a subset of `self`'s data.
+    ///
+    /// `M` is the rank of the subview, i.e. the number of arguments that are not
+    /// [SliceArg::Index]. An error is returned if it does not match, or if an argument is
+    /// out of bounds. Unlike mirrors, subviews can be created from any view.
+    ///
+    /// When using parallelization features, the subview can still be written to since
+    /// its inner values are atomic types.
+    pub fn subview<'b, const M: usize>(
+        &'b self,
+        args: [SliceArg; N],
+    ) -> Result<ViewRO<'b, M, T>, ViewError<'static>> {
+        let geometry = self.subview_geometry::<M>(&args)?;
+        let data = &self.data_slice()[geometry.offset..geometry.offset + geometry.span];
+        Ok(ViewBase {
+            data: DataType::Borrowed(data),
+            layout: geometry.layout(),
+            dim: geometry.dim,
+            stride: index_stride(geometry.stride, &geometry.dim),
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: None,
+        })
+    }
+
+    #[cfg(not(any(feature = "rayon", feature = "threads", feature = "gpu")))]
+    /// Create a mutable view of a subset of `self`'s data. See [ViewBase::subview].
+    ///
+    /// Only defined when no feature are enabled since all interfaces should be immutable
+    /// otherwise.
+    pub fn subview_mut<'b, const M: usize>(
+        &'b mut self,
+        args: [SliceArg; N],
+    ) -> Result<ViewRW<'b, M, T>, ViewError<'static>> {
+        let geometry = self.subview_geometry::<M>(&args)?;
+        let data: &mut [InnerDataType<T>] = match &mut self.data {
+            DataType::Owned(v) => v,
+            DataType::MutBorrowed(mut_slice) => mut_slice,
+            DataType::Borrowed(_) => {
+                return Err(ViewError::ValueError(
+                    "Cannot create a mutable subview of a read-only View",
+                ))
+            }
+        };
+        let layout = geometry.layout();
+        Ok(ViewBase {
+            data: DataType::MutBorrowed(
+                &mut data[geometry.offset..geometry.offset + geometry.span],
+            ),
+            layout,
+            dim: geometry.dim,
+            stride: index_stride(geometry.stride, &geometry.dim),
+            #[cfg(feature = "access-stats")]
+            stats: AccessStats::default(),
+            allocation: None,
+        })
+    }
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routines::iter::MDIndexIter, view::ViewOwned};
+
+    #[test]
+    fn geometry() {
+        let dim = [4, 5, 6];
+        let data: Vec<f64> = (0..4 * 5 * 6).map(|x| x as f64).collect();
+        for layout in [Layout::Right, Layout::Left] {
+            let view: ViewOwned<'_, 3, f64> = ViewOwned::new_from_data(data.clone(), layout, dim);
+
+            // plane j = 2, block of the other two dimensions
+            let sub = view
+                .subview::<2>([SliceArg::Range(1..3), SliceArg::Index(2), SliceArg::All])
+                .unwrap();
+            assert_eq!(sub.dims(), [2, 6]);
+            MDIndexIter::new([0..2, 0..6])
+                .for_each(|[i, k]| assert_eq!(sub.get([i, k]), view.get([i + 1, 2, k])));
+
+            // contiguous subviews keep their layout
+            let slab = match layout {
+                Layout::Right => {
+                    view.subview::<3>([SliceArg::Range(1..3), SliceArg::All, SliceArg::All])
+                }
+                _ => view.subview::<3>([SliceArg::All, SliceArg::All, SliceArg::Range(1..3)]),
+            }
+            .unwrap();
+            assert_eq!(slab.layout, layout);
+            assert_eq!(slab.data_slice().len(), slab.dims().iter().product());
+        }
+    }
+
+    #[test]
+    fn invalid_args() {
+        let view: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [3, 4]);
+        assert!(view
+            .subview::<2>([SliceArg::Index(0), SliceArg::All])
+            .is_err());
+        assert!(view
+            .subview::<1>([SliceArg::Index(3), SliceArg::All])
+            .is_err());
+        assert!(view
+            .subview::<2>([SliceArg::All, SliceArg::Range(2..5)])
+            .is_err());
+        let empty = view
+            .subview::<2>([SliceArg::Range(3..3), SliceArg::All])
+            .unwrap();
+        assert_eq!(empty.dims(), [0, 4]);
+    }
+
+    #[test]
+    fn host_reductions() {
+        let mat: ViewOwned<'_, 2, i32> =
+            ViewOwned::new_from_data((0..12).collect(), Layout::Right, [3, 4]);
+
+        // elements located between the rows of the column are not summed
+        let col = mat
+            .subview::<1>([SliceArg::All, SliceArg::Index(1)])
+            .unwrap();
+        assert_eq!(col.sum(), 1 + 5 + 9);
+        assert_eq!(col.sum_checked().unwrap(), 15);
+        let block = mat
+            .subview::<2>([SliceArg::Range(1..3), SliceArg::Range(0..2)])
+            .unwrap();
+        assert_eq!(block.sum(), 4 + 5 + 8 + 9);
+    }
+
+    #[test]
+    fn write_through() {
+        #[allow(unused_mut)]
+        let mut mat: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Left, [3, 3]);
+        {
+            cfg_if::cfg_if! {
+                if #[cfg(any(feature = "rayon", feature = "threads", feature = "gpu"))] {
+                    let col = mat.subview::<1>([SliceArg::All, SliceArg::Index(1)]).unwrap();
+                } else {
+                    let mut col = mat.subview_mut::<1>([SliceArg::All, SliceArg::Index(1)]).unwrap();
+                }
+            }
+            (0..3).for_each(|i| col.set([i], 1.0 + i as f64));
+        }
+        assert_eq!(mat.get([2, 1]), 3.0);
+        assert_eq!(mat.get([2, 2]), 0.0);
+    }
+}
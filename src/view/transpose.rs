@@ -0,0 +1,177 @@
+//! transpose related code
+//!
+//! This module contains routines copying the content of a view into another view whose
+//! axes are permuted:
+//!
+//! - [`transpose`] copies a 2D view into its transpose,
+//! - [`permute`] generalizes the transpose to views of any rank.
+//!
+//! Like [deep_copy][super::deep_copy], views of any layout can be used, and the copy is
+//! done using a `parallel_for` statement on the CPU. A naive transpose reads or writes
+//! one of the views with a large stride, whatever their layouts; [`transpose`] instead
+//! processes the views by square tiles of [TRANSPOSE_TILE] elements per side, small
+//! enough for both tiles to stay in cache.
+//!
+//! ### Example
+//!
+//! ```rust
+//! use poc_kokkos_rs::view::{
+//!     parameters::Layout,
+//!     transpose::{permute, transpose},
+//!     ViewOwned,
+//! };
+//!
+//! let a: ViewOwned<'_, 2, f64> =
+//!     ViewOwned::new_from_data(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0], Layout::Right, [2, 3]);
+//! let mut at: ViewOwned<'_, 2, f64> = ViewOwned::new(Layout::Right, [3, 2]);
+//! transpose(&mut at, &a).unwrap();
+//! assert_eq!(at.get([2, 1]), 5.0);
+//!
+//! // move the first axis last
+//! let cube: ViewOwned<'_, 3, f64> = ViewOwned::new(Layout::Right, [2, 3, 4]);
+//! let mut perm: ViewOwned<'_, 3, f64> = ViewOwned::new(Layout::Left, [3, 4, 2]);
+//! permute(&mut perm, &cube, [1, 2, 0]).unwrap();
+//! ```
+
+use super::{parameters::DataTraits, unflatten_idx, ViewBase, ViewError};
+use crate::routines::{
+    parameters::{ExecutionSpace, MDRange, Range1D, TypedExecutionPolicy},
+    typed::parallel_for,
+};
+
+/// Number of elements per side of the tiles processed by [`transpose`].
+pub const TRANSPOSE_TILE: usize = 32;
+
+/// Copy the transpose of `src` into `dst`, i.e. `dst[j, i] = src[i, j]`.
+///
+/// Dimensions of `dst` must be the ones of `src`, swapped; layouts can differ. Tiles of
+/// the views are distributed over computational ressources.
+pub fn transpose<T>(
+    dst: &mut ViewBase<'_, 2, T>,
+    src: &ViewBase<'_, 2, T>,
+) -> Result<(), ViewError<'static>>
+where
+    T: DataTraits + Send + Sync,
+{
+    let [m, n] = src.dims();
+    if dst.dims() != [n, m] {
+        return Err(ViewError::ValueError(
+            "Transposed view must have swapped dimensions",
+        ));
+    }
+    let execp = TypedExecutionPolicy {
+        space: ExecutionSpace::DeviceCPU,
+        ..TypedExecutionPolicy::new(MDRange([
+            0..m.div_ceil(TRANSPOSE_TILE),
+            0..n.div_ceil(TRANSPOSE_TILE),
+        ]))
+    };
+    parallel_for(execp, |[ti, tj]| {
+        let rows = ti * TRANSPOSE_TILE..((ti + 1) * TRANSPOSE_TILE).min(m);
+        let cols = tj * TRANSPOSE_TILE..((tj + 1) * TRANSPOSE_TILE).min(n);
+        for i in rows {
+            cols.clone().for_each(|j| dst.set([j, i], src.get([i, j])));
+        }
+    })
+    .map_err(|_| ViewError::ValueError("Transpose statement failed"))
+}
+
+/// Copy `src` into `dst`, permuting its axes: axis `k` of `dst` is axis `axes[k]` of
+/// `src`. For example, [`transpose`] is equivalent to a permutation using `[1, 0]`.
+///
+/// `axes` must be a permutation of `0..N`, and dimensions of `dst` must be the ones of
+/// `src`, permuted; layouts can differ. Elements of `dst` are distributed over
+/// computational ressources.
+pub fn permute<const N: usize, T>(
+    dst: &mut ViewBase<'_, N, T>,
+    src: &ViewBase<'_, N, T>,
+    axes: [usize; N],
+) -> Result<(), ViewError<'static>>
+where
+    T: DataTraits + Send + Sync,
+{
+    let mut seen = [false; N];
+    for &axis in &axes {
+        if axis >= N || std::mem::replace(&mut seen[axis], true) {
+            return Err(ViewError::ValueError("Axes must be a permutation of 0..N"));
+        }
+    }
+    let src_dim = src.dims();
+    let dim = dst.dims();
+    if dim != axes.map(|axis| src_dim[axis]) {
+        return Err(ViewError::ValueError(
+            "Permuted view must have permuted dimensions",
+        ));
+    }
+    let execp = TypedExecutionPolicy {
+        space: ExecutionSpace::DeviceCPU,
+        ..TypedExecutionPolicy::new(Range1D(0..dim.iter().product()))
+    };
+    parallel_for(execp, |k| {
+        let index = unflatten_idx(&dim, k);
+        let mut src_index = [0; N];
+        axes.iter()
+            .zip(index.iter())
+            .for_each(|(axis, i)| src_index[*axis] = *i);
+        dst.set(index, src.get(src_index));
+    })
+    .map_err(|_| ViewError::ValueError("Permute statement failed"))
+}
+
+// ~~~~~~
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        routines::iter::MDIndexIter,
+        view::{parameters::Layout, subview::SliceArg, ViewOwned},
+    };
+
+    #[test]
+    fn transpose_layouts() {
+        // several tiles, partial ones included
+        let (m, n) = (TRANSPOSE_TILE * 2 + 5, TRANSPOSE_TILE + 1);
+        let data: Vec<usize> = (0..m * n).collect();
+        for src_layout in [Layout::Right, Layout::Left] {
+            let src: ViewOwned<'_, 2, usize> =
+                ViewOwned::new_from_data(data.clone(), src_layout, [m, n]);
+            for dst_layout in [Layout::Right, Layout::Left] {
+                let mut dst: ViewOwned<'_, 2, usize> = ViewOwned::new(dst_layout, [n, m]);
+                transpose(&mut dst, &src).unwrap();
+                MDIndexIter::new([0..m, 0..n])
+                    .for_each(|[i, j]| assert_eq!(dst.get([j, i]), src.get([i, j])));
+
+                let mut perm: ViewOwned<'_, 2, usize> = ViewOwned::new(dst_layout, [n, m]);
+                permute(&mut perm, &src, [1, 0]).unwrap();
+                MDIndexIter::new([0..n, 0..m])
+                    .for_each(|idx| assert_eq!(perm.get(idx), dst.get(idx)));
+            }
+        }
+        // strided source
+        let src: ViewOwned<'_, 2, usize> = ViewOwned::new_from_data(data, Layout::Right, [m, n]);
+        let sub = src
+            .subview::<2>([SliceArg::Range(3..m), SliceArg::Range(1..n)])
+            .unwrap();
+        let mut dst: ViewOwned<'_, 2, usize> = ViewOwned::new(Layout::Left, [n - 1, m - 3]);
+        transpose(&mut dst, &sub).unwrap();
+        MDIndexIter::new([0..m - 3, 0..n - 1])
+            .for_each(|[i, j]| assert_eq!(dst.get([j, i]), src.get([i + 3, j + 1])));
+        assert!(transpose(&mut dst, &src).is_err());
+    }
+
+    #[test]
+    fn permute_axes() {
+        let dim = [2, 3, 4];
+        let src: ViewOwned<'_, 3, usize> =
+            ViewOwned::new_from_data((0..24).collect(), Layout::Left, dim);
+        let mut dst: ViewOwned<'_, 3, usize> = ViewOwned::new(Layout::Right, [4, 2, 3]);
+        permute(&mut dst, &src, [2, 0, 1]).unwrap();
+        MDIndexIter::new(dim.map(|d| 0..d))
+            .for_each(|[i, j, k]| assert_eq!(dst.get([k, i, j]), src.get([i, j, k])));
+
+        assert!(permute(&mut dst, &src, [2, 0, 0]).is_err());
+        assert!(permute(&mut dst, &src, [2, 1, 0]).is_err());
+    }
+}